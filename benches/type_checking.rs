@@ -3,7 +3,10 @@ use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criteri
 use luanext_parser::{Lexer, Parser, StringInterner};
 use std::sync::Arc;
 
-use luanext_typechecker::{cli::diagnostics::CollectingDiagnosticHandler, TypeChecker};
+use luanext_typechecker::{
+    cli::{config::LuaVersion, diagnostics::CollectingDiagnosticHandler},
+    StdlibSnapshot, TypeChecker,
+};
 
 fn parse_and_check(code: &str) {
     let arena = Bump::new();
@@ -20,6 +23,21 @@ fn parse_and_check(code: &str) {
     black_box(());
 }
 
+fn parse_and_check_with_snapshot(code: &str, snapshot: &StdlibSnapshot) {
+    let arena = Bump::new();
+    let handler = Arc::new(CollectingDiagnosticHandler::new());
+    let (interner, common) = snapshot.interner();
+    let mut lexer = Lexer::new(code, handler.clone(), &interner);
+    let tokens = lexer.tokenize().expect("Lexing failed");
+    let mut parser = Parser::new(tokens, handler.clone(), &interner, &common, &arena);
+    let program = parser.parse().expect("Parsing failed");
+    let mut checker =
+        TypeChecker::new_with_snapshot(handler.clone(), &interner, &common, &arena, snapshot)
+            .unwrap();
+    checker.check_program(&program).unwrap();
+    black_box(());
+}
+
 fn generate_synthetic_exprs(count: usize) -> String {
     let mut code = String::new();
     for i in 0..count {
@@ -297,6 +315,23 @@ fn benchmark_class_heavy(c: &mut Criterion) {
     group.finish();
 }
 
+fn benchmark_stdlib_loading(c: &mut Criterion) {
+    let mut group = c.benchmark_group("stdlib_loading");
+    let snapshot = StdlibSnapshot::for_target(LuaVersion::Lua54).unwrap();
+
+    for count in [1, 10, 100] {
+        let code = generate_synthetic_exprs(count);
+        group.bench_with_input(BenchmarkId::new("parsed", count), &code, |b, code| {
+            b.iter(|| parse_and_check(black_box(code)))
+        });
+        group.bench_with_input(BenchmarkId::new("snapshot", count), &code, |b, code| {
+            b.iter(|| parse_and_check_with_snapshot(black_box(code), &snapshot))
+        });
+    }
+
+    group.finish();
+}
+
 criterion_group!(
     benches,
     benchmark_synthetic_exprs,
@@ -309,6 +344,7 @@ criterion_group!(
     benchmark_table_literals,
     benchmark_interface_heavy,
     benchmark_class_heavy,
+    benchmark_stdlib_loading,
 );
 
 criterion_main!(benches);
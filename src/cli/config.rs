@@ -63,6 +63,45 @@ impl LuaVersion {
             other => other,
         }
     }
+    /// Whether the version distinguishes the `integer` subtype of `number`
+    /// (Lua 5.3+). On Lua 5.1 and LuaJIT, `integer` is an alias for `number`.
+    /// `Auto` is treated like the latest version, as `detect()` falls back to it.
+    pub fn has_integer_subtype(self) -> bool {
+        !matches!(
+            self,
+            LuaVersion::Lua51 | LuaVersion::Lua52 | LuaVersion::LuaJIT
+        )
+    }
+
+    /// Whether bitwise operators and floor division `//` are part of the
+    /// language. Both arrived together with integers in Lua 5.3.
+    pub fn supports_integer_operators(self) -> bool {
+        self.has_integer_subtype()
+    }
+
+    /// Whether `goto` and `::labels::` are supported (Lua 5.2+ and LuaJIT).
+    pub fn supports_goto(self) -> bool {
+        !matches!(self, LuaVersion::Lua51)
+    }
+
+    /// Whether the Lua 5.1 globals removed in 5.2 (`setfenv`, `getfenv`,
+    /// `unpack`, `loadstring`) still exist.
+    pub fn has_lua51_globals(self) -> bool {
+        matches!(self, LuaVersion::Lua51 | LuaVersion::LuaJIT)
+    }
+
+    /// Name of the version as shown in diagnostics
+    pub fn display_name(self) -> &'static str {
+        match self {
+            LuaVersion::Lua51 => "Lua 5.1",
+            LuaVersion::Lua52 => "Lua 5.2",
+            LuaVersion::Lua53 => "Lua 5.3",
+            LuaVersion::Lua54 => "Lua 5.4",
+            LuaVersion::Lua55 => "Lua 5.5",
+            LuaVersion::LuaJIT => "LuaJIT",
+            LuaVersion::Auto => "the detected Lua version",
+        }
+    }
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
@@ -157,6 +196,16 @@ pub struct CompilerOptions {
     #[serde(default)]
     pub no_explicit_unknown: bool,
 
+    /// Require class properties to be initialized and derived constructors
+    /// to call `super(...)` before using `self` (default: false)
+    #[serde(default)]
+    pub strict_property_initialization: bool,
+
+    /// Require the `override` modifier on methods that redeclare an
+    /// inherited member (default: false)
+    #[serde(default)]
+    pub no_implicit_override: bool,
+
     /// Target Lua version (default: 5.4)
     #[serde(default)]
     pub target: LuaVersion,
@@ -223,6 +272,16 @@ pub struct CompilerOptions {
     /// Example: `"@/*": ["src/*"]` maps `@/components/Button` to `src/components/Button`
     #[serde(default)]
     pub paths: HashMap<String, Vec<String>>,
+
+    /// Bundled environment definition packs loaded alongside the standard
+    /// library: `love2d`, `nvim`, `openresty` and `defold` (default: none)
+    #[serde(default)]
+    pub libraries: Vec<String>,
+
+    /// Directories of `.d.luax` definition files loaded alongside the
+    /// standard library (default: none)
+    #[serde(default)]
+    pub type_roots: Vec<String>,
 }
 
 fn default_true() -> bool {
@@ -244,6 +303,8 @@ impl Default for CompilerOptions {
             strict_naming: StrictLevel::Error,
             no_implicit_unknown: false,
             no_explicit_unknown: false,
+            strict_property_initialization: false,
+            no_implicit_override: false,
             target: LuaVersion::Lua54,
             enable_decorators: true,
             allow_non_typed_lua: true,
@@ -260,6 +321,8 @@ impl Default for CompilerOptions {
             optimization_level: OptimizationLevel::Auto,
             base_url: None,
             paths: HashMap::new(),
+            libraries: Vec::new(),
+            type_roots: Vec::new(),
         }
     }
 }
@@ -296,11 +359,17 @@ impl Default for CompilerConfig {
 }
 
 impl CompilerConfig {
-    /// Load configuration from a YAML file (luanext.config.yaml)
+    /// Load configuration from a YAML file (luanext.config.yaml). Relative
+    /// `typeRoots` are resolved against the file's directory.
     pub fn from_file(path: &Path) -> Result<Self, crate::cli::errors::CompilationError> {
         let content = std::fs::read_to_string(path)?;
-        let config: CompilerConfig = serde_yaml::from_str(&content)
+        let mut config: CompilerConfig = serde_yaml::from_str(&content)
             .map_err(|e| crate::cli::errors::CompilationError::ConfigError(e.to_string()))?;
+        if let Some(dir) = path.parent() {
+            for root in &mut config.compiler_options.type_roots {
+                *root = dir.join(&*root).display().to_string();
+            }
+        }
         Ok(config)
     }
 
@@ -329,6 +398,12 @@ impl CompilerConfig {
         if let Some(no_explicit_unknown) = overrides.no_explicit_unknown {
             self.compiler_options.no_explicit_unknown = no_explicit_unknown;
         }
+        if let Some(strict_property_initialization) = overrides.strict_property_initialization {
+            self.compiler_options.strict_property_initialization = strict_property_initialization;
+        }
+        if let Some(no_implicit_override) = overrides.no_implicit_override {
+            self.compiler_options.no_implicit_override = no_implicit_override;
+        }
         if let Some(target) = overrides.target {
             self.compiler_options.target = target;
         }
@@ -382,6 +457,8 @@ pub struct CliOverrides {
     pub strict_naming: Option<StrictLevel>,
     pub no_implicit_unknown: Option<bool>,
     pub no_explicit_unknown: Option<bool>,
+    pub strict_property_initialization: Option<bool>,
+    pub no_implicit_override: Option<bool>,
     pub target: Option<LuaVersion>,
     pub enable_decorators: Option<bool>,
     pub allow_non_typed_lua: Option<bool>,
@@ -608,4 +685,64 @@ compilerOptions:
     fn test_luajit_effective() {
         assert_eq!(LuaVersion::LuaJIT.effective(), LuaVersion::LuaJIT);
     }
+
+    #[test]
+    fn test_integer_subtype_versions() {
+        assert!(!LuaVersion::Lua51.has_integer_subtype());
+        assert!(!LuaVersion::Lua52.has_integer_subtype());
+        assert!(!LuaVersion::LuaJIT.has_integer_subtype());
+        assert!(LuaVersion::Lua53.has_integer_subtype());
+        assert!(LuaVersion::Lua54.has_integer_subtype());
+        assert!(LuaVersion::Lua55.has_integer_subtype());
+    }
+
+    #[test]
+    fn test_version_feature_gates() {
+        assert!(!LuaVersion::Lua51.supports_goto());
+        assert!(LuaVersion::Lua52.supports_goto());
+        assert!(LuaVersion::LuaJIT.supports_goto());
+        assert!(!LuaVersion::LuaJIT.supports_integer_operators());
+        assert!(LuaVersion::Lua53.supports_integer_operators());
+        assert!(LuaVersion::LuaJIT.has_lua51_globals());
+        assert!(!LuaVersion::Lua52.has_lua51_globals());
+        assert_eq!(LuaVersion::Lua54.display_name(), "Lua 5.4");
+    }
+
+    #[test]
+    fn test_deserialize_options() {
+        // Each option is read from its camelCase key and differs from its default
+        let cases: &[(&str, fn(&CompilerOptions) -> bool)] = &[
+            ("enableDecorators: false", |o| !o.enable_decorators),
+            ("noImplicitUnknown: true", |o| o.no_implicit_unknown),
+            ("strictPropertyInitialization: true", |o| {
+                o.strict_property_initialization
+            }),
+            ("noImplicitOverride: true", |o| o.no_implicit_override),
+            (r#"libraries: ["love2d", "nvim"]"#, |o| {
+                o.libraries == ["love2d", "nvim"]
+            }),
+            (r#"typeRoots: ["./types"]"#, |o| o.type_roots == ["./types"]),
+        ];
+        for (option, is_set) in cases {
+            let yaml = format!("compilerOptions:\n  {}\n", option);
+            let config: CompilerConfig = serde_yaml::from_str(&yaml).unwrap();
+            assert!(is_set(&config.compiler_options), "{}", option);
+            assert!(!is_set(&CompilerOptions::default()), "{}", option);
+        }
+    }
+
+    #[test]
+    fn test_type_roots_relative_to_config_file() {
+        let dir = std::env::temp_dir().join(format!("luanext-config-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("luanext.config.yaml");
+        std::fs::write(&path, "compilerOptions:\n  typeRoots: [\"types\"]\n").unwrap();
+        let config = CompilerConfig::from_file(&path);
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(
+            config.unwrap().compiler_options.type_roots,
+            [dir.join("types").display().to_string()]
+        );
+    }
 }
@@ -449,6 +449,37 @@ pub mod error_codes {
     /// Or-pattern has no alternatives
     pub const EMPTY_OR_PATTERN: DiagnosticCode = DiagnosticCode::new('E', 3028);
 
+    /// Derived class constructor does not call `super(...)`
+    pub const MISSING_SUPER_CALL: DiagnosticCode = DiagnosticCode::new('E', 3029);
+
+    /// `self` used before `super(...)` in a derived class constructor
+    pub const SELF_BEFORE_SUPER: DiagnosticCode = DiagnosticCode::new('E', 3030);
+
+    /// Class property is not definitely initialized
+    pub const PROPERTY_NOT_INITIALIZED: DiagnosticCode = DiagnosticCode::new('E', 3031);
+
+    /// Method redeclares an inherited member without `override`
+    pub const MISSING_OVERRIDE_MODIFIER: DiagnosticCode = DiagnosticCode::new('E', 3032);
+
+    /// Method is marked `override` but no ancestor declares it
+    pub const OVERRIDE_WITHOUT_BASE: DiagnosticCode = DiagnosticCode::new('E', 3033);
+
+    /// Method overrides a `final` member
+    pub const OVERRIDE_OF_FINAL_MEMBER: DiagnosticCode = DiagnosticCode::new('E', 3034);
+
+    /// Class extends a `final` class
+    pub const EXTENDS_FINAL_CLASS: DiagnosticCode = DiagnosticCode::new('E', 3035);
+
+    /// Literal `string.format` format string is malformed or unsupported by the target
+    pub const INVALID_FORMAT_STRING: DiagnosticCode = DiagnosticCode::new('E', 3036);
+
+    /// Literal Lua pattern or `gsub` replacement is malformed
+    pub const INVALID_PATTERN: DiagnosticCode = DiagnosticCode::new('E', 3037);
+
+    /// `require` loads a plain Lua file that has no `.d.luax` declaration or
+    /// that `allowNonTypedLua` blocks
+    pub const UNTYPED_LUA_MODULE: DiagnosticCode = DiagnosticCode::new('E', 3038);
+
     // ========================================
     // Code Generator Errors (E4000-E4999)
     // ========================================
@@ -939,6 +970,16 @@ mod tests {
             INCONSISTENT_OR_PATTERN_BINDINGS,
             INCOMPATIBLE_OR_PATTERN_TYPES,
             EMPTY_OR_PATTERN,
+            MISSING_SUPER_CALL,
+            SELF_BEFORE_SUPER,
+            PROPERTY_NOT_INITIALIZED,
+            MISSING_OVERRIDE_MODIFIER,
+            OVERRIDE_WITHOUT_BASE,
+            OVERRIDE_OF_FINAL_MEMBER,
+            EXTENDS_FINAL_CLASS,
+            INVALID_FORMAT_STRING,
+            INVALID_PATTERN,
+            UNTYPED_LUA_MODULE,
             // Code generator
             UNSUPPORTED_FEATURE,
             SOURCE_MAP_ERROR,
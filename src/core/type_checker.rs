@@ -1,12 +1,17 @@
-use crate::cli::config::CompilerOptions;
+use crate::cli::config::{CompilerOptions, LuaVersion};
 use crate::cli::diagnostics::DiagnosticHandler;
 use crate::core::type_compat::TypeCompatibility;
 use crate::core::type_environment::TypeEnvironment;
-use crate::helpers::{control_flow, type_utilities};
+use crate::helpers::{control_flow, target_features, type_utilities};
 use crate::incremental::DeclarationHash;
 use crate::phases;
 use crate::phases::declaration_checking_phase;
+use crate::state::stdlib_snapshot::{StdlibSnapshot, StdlibState};
 use crate::type_relations::TypeRelationCache;
+use crate::types::coroutine::{self, CoroutineSignature};
+use crate::types::ffi::{self, CDeclarations};
+use crate::types::reflection::RuntimeTypeInfo;
+use crate::utils::doc_comment::{self, Deprecation, DocComment};
 use crate::utils::symbol_table::{Symbol, SymbolKind, SymbolTable};
 use crate::visitors::{
     AccessControl, AccessControlVisitor, ClassContext, ClassMemberInfo, ClassMemberKind,
@@ -23,6 +28,88 @@ use rustc_hash::FxHashMap;
 use std::sync::Arc;
 use tracing::{debug, error, info, instrument, span, Level};
 
+/// The stdlib namespace declaring the built-in decorators
+const BUILTIN_DECORATORS: &str = "Decorators";
+
+/// Check whether a type contains a `typeof` query anywhere in its tree
+fn contains_type_query(typ: &Type<'_>) -> bool {
+    match &typ.kind {
+        TypeKind::TypeQuery(_) => true,
+        TypeKind::Union(types) | TypeKind::Intersection(types) | TypeKind::Tuple(types) => {
+            types.iter().any(contains_type_query)
+        }
+        TypeKind::Array(inner)
+        | TypeKind::Nullable(inner)
+        | TypeKind::Parenthesized(inner)
+        | TypeKind::KeyOf(inner) => contains_type_query(inner),
+        TypeKind::IndexAccess(object, index) => {
+            contains_type_query(object) || contains_type_query(index)
+        }
+        TypeKind::Reference(type_ref) => type_ref
+            .type_arguments
+            .is_some_and(|args| args.iter().any(contains_type_query)),
+        TypeKind::Object(obj) => obj.members.iter().any(|member| match member {
+            ObjectTypeMember::Property(prop) => contains_type_query(&prop.type_annotation),
+            ObjectTypeMember::Method(method) => {
+                parameters_contain_type_query(method.parameters)
+                    || contains_type_query(&method.return_type)
+            }
+            ObjectTypeMember::Index(index) => contains_type_query(&index.value_type),
+        }),
+        TypeKind::Function(func) => {
+            parameters_contain_type_query(func.parameters) || contains_type_query(func.return_type)
+        }
+        TypeKind::Conditional(conditional) => [
+            conditional.check_type,
+            conditional.extends_type,
+            conditional.true_type,
+            conditional.false_type,
+        ]
+        .into_iter()
+        .any(contains_type_query),
+        TypeKind::Mapped(mapped) => {
+            contains_type_query(mapped.in_type) || contains_type_query(mapped.value_type)
+        }
+        _ => false,
+    }
+}
+
+fn parameters_contain_type_query(parameters: &[Parameter<'_>]) -> bool {
+    parameters
+        .iter()
+        .filter_map(|param| param.type_annotation.as_ref())
+        .any(contains_type_query)
+}
+
+/// The variable at the root of a `typeof` operand (`a` in `typeof a.b:c()`)
+fn query_root_name(
+    expr: &Expression<'_>,
+    interner: &luanext_parser::string_interner::StringInterner,
+) -> Option<String> {
+    match &expr.kind {
+        ExpressionKind::Identifier(name) => Some(interner.resolve(*name).to_string()),
+        ExpressionKind::Member(object, _)
+        | ExpressionKind::OptionalMember(object, _)
+        | ExpressionKind::Index(object, _)
+        | ExpressionKind::Call(object, _, _)
+        | ExpressionKind::MethodCall(object, _, _, _)
+        | ExpressionKind::Parenthesized(object) => query_root_name(object, interner),
+        _ => None,
+    }
+}
+
+/// A key of an indexed access type `T[K]`, after evaluating `K`
+enum IndexKey {
+    /// A string literal key (`T["name"]`)
+    Name(String),
+    /// A non-negative numeric literal key (`T[1]`); tuple positions start at 1
+    Position(usize),
+    /// `T[string]`
+    AnyString,
+    /// `T[number]`
+    AnyNumber,
+}
+
 /// Type checker for TypedLua programs
 pub struct TypeChecker<'a, 'arena> {
     symbol_table: SymbolTable<'arena>,
@@ -39,6 +126,8 @@ pub struct TypeChecker<'a, 'arena> {
     current_module_id: Option<crate::module_resolver::ModuleId>,
     /// Module resolver for imports
     module_resolver: Option<Arc<crate::module_resolver::ModuleResolver>>,
+    /// Type-checks a dependency that is imported or required before it was checked
+    lazy_callback: Option<Arc<dyn phases::module_phase::LazyTypeCheckCallback>>,
     /// Track module dependencies for cache invalidation
     module_dependencies: Vec<crate::module_resolver::TypedDependency>,
     /// Stack of whether we're inside a catch block (for rethrow validation)
@@ -61,6 +150,26 @@ pub struct TypeChecker<'a, 'arena> {
     type_relation_cache: TypeRelationCache,
     /// Cycle detection for recursive type alias expansion
     resolving_types: std::cell::RefCell<std::collections::HashSet<String>>,
+    /// Declarations whose annotations are being evaluated (cycle detection for `typeof`)
+    declaring_names: Vec<String>,
+    /// `options.target` with `Auto` resolved, computed on first use
+    effective_target: std::cell::OnceCell<LuaVersion>,
+    /// C declarations registered by `ffi.cdef` calls (LuaJIT)
+    c_declarations: CDeclarations,
+    /// Signatures of the coroutine bodies being checked, innermost last
+    coroutine_signatures: Vec<CoroutineSignature<'arena>>,
+    /// Types raised with `error(...)` or `throw` by the function bodies being
+    /// checked, innermost last
+    error_channels: Vec<Vec<Type<'arena>>>,
+    /// Source text of the program being checked, used to read doc comments
+    source: Option<&'a str>,
+    /// Spans of the references to deprecated APIs already warned about
+    reported_deprecations: std::cell::RefCell<rustc_hash::FxHashSet<(usize, usize)>>,
+    /// Dependencies of the `require` calls of the last inferred expression,
+    /// not yet added to `module_dependencies`
+    required_modules: std::cell::RefCell<Vec<crate::module_resolver::TypedDependency>>,
+    /// Spans of the `require` calls of plain Lua files already reported
+    reported_plain_lua_requires: std::cell::RefCell<rustc_hash::FxHashSet<(usize, usize)>>,
 }
 
 /// Extract a function declaration from a statement, handling both
@@ -112,6 +221,7 @@ impl<'a, 'arena> TypeChecker<'a, 'arena> {
             module_registry: None,
             current_module_id: None,
             module_resolver: None,
+            lazy_callback: None,
             module_dependencies: Vec::new(),
             in_catch_block: Vec::new(),
             current_namespace: None,
@@ -124,6 +234,15 @@ impl<'a, 'arena> TypeChecker<'a, 'arena> {
             arena,
             type_relation_cache: TypeRelationCache::new(),
             resolving_types: std::cell::RefCell::new(std::collections::HashSet::new()),
+            declaring_names: Vec::new(),
+            effective_target: std::cell::OnceCell::new(),
+            c_declarations: CDeclarations::new(),
+            coroutine_signatures: Vec::new(),
+            error_channels: Vec::new(),
+            source: None,
+            reported_deprecations: Default::default(),
+            required_modules: Default::default(),
+            reported_plain_lua_requires: Default::default(),
         }
     }
 
@@ -165,6 +284,7 @@ impl<'a, 'arena> TypeChecker<'a, 'arena> {
             module_registry: None,
             current_module_id: None,
             module_resolver: None,
+            lazy_callback: None,
             module_dependencies: Vec::new(),
             in_catch_block: Vec::new(),
             current_namespace: None,
@@ -177,6 +297,15 @@ impl<'a, 'arena> TypeChecker<'a, 'arena> {
             arena,
             type_relation_cache: TypeRelationCache::new(),
             resolving_types: std::cell::RefCell::new(std::collections::HashSet::new()),
+            declaring_names: Vec::new(),
+            effective_target: std::cell::OnceCell::new(),
+            c_declarations: CDeclarations::new(),
+            coroutine_signatures: Vec::new(),
+            error_channels: Vec::new(),
+            source: None,
+            reported_deprecations: Default::default(),
+            required_modules: Default::default(),
+            reported_plain_lua_requires: Default::default(),
         }
     }
 
@@ -202,6 +331,67 @@ impl<'a, 'arena> TypeChecker<'a, 'arena> {
         Ok(checker)
     }
 
+    /// Create a new TypeChecker starting from a shared standard library
+    /// snapshot, which copies the globals and types of the stdlib instead of
+    /// parsing and checking its definition files again.
+    ///
+    /// The program must be parsed with an interner from
+    /// [`StdlibSnapshot::interner`], so the names in the snapshot's types
+    /// resolve to the same strings. The checker targets the snapshot's Lua
+    /// version and borrows the snapshot, whose arena holds the stdlib types.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `interner` does not start with the snapshot's strings.
+    ///
+    /// # Example
+    ///
+    /// ```rust,ignore
+    /// let snapshot = StdlibSnapshot::for_target(LuaVersion::Lua54)?;
+    /// let (interner, common) = snapshot.interner();
+    /// let checker = TypeChecker::new_with_snapshot(handler, &interner, &common, &arena, &snapshot)?;
+    /// ```
+    pub fn new_with_snapshot(
+        diagnostic_handler: Arc<dyn DiagnosticHandler>,
+        interner: &'a luanext_parser::string_interner::StringInterner,
+        common: &'a luanext_parser::string_interner::CommonIdentifiers,
+        arena: &'arena bumpalo::Bump,
+        snapshot: &'arena StdlibSnapshot,
+    ) -> Result<Self, String> {
+        if !snapshot.is_compatible(interner) {
+            return Err(
+                "The interner does not match the stdlib snapshot; create it with StdlibSnapshot::interner"
+                    .to_string(),
+            );
+        }
+
+        let mut checker = Self::new(diagnostic_handler, interner, common, arena);
+        checker.options.target = snapshot.target();
+        let state = snapshot.state();
+        checker.symbol_table = state.symbol_table;
+        checker.type_env = state.type_env;
+        checker.access_control = state.access_control;
+        checker.class_type_params = state.class_type_params;
+        Ok(checker)
+    }
+
+    /// Load the standard library for `options` and hand back the state it
+    /// populated, to build a [`StdlibSnapshot`]
+    pub(crate) fn into_stdlib_state(
+        mut self,
+        options: CompilerOptions,
+    ) -> Result<StdlibState<'arena>, String> {
+        self.options = options;
+        self.load_stdlib()?;
+        self.register_minimal_stdlib();
+        Ok(StdlibState {
+            symbol_table: self.symbol_table,
+            type_env: self.type_env,
+            access_control: self.access_control,
+            class_type_params: self.class_type_params,
+        })
+    }
+
     /// Load the standard library into this type checker.
     ///
     /// This method loads the standard library for the configured Lua version.
@@ -217,9 +407,17 @@ impl<'a, 'arena> TypeChecker<'a, 'arena> {
     }
 
     pub fn with_options(mut self, options: CompilerOptions) -> Self {
-        // Check if target version changed
-        let version_changed = self.options.target != options.target;
+        use crate::cli::diagnostics::{error_codes, Diagnostic};
+
+        // Check if the target version or the loaded definition packs changed.
+        // `Auto` is compared by the version it resolves to, so a checker built
+        // from a snapshot keeps it under the default options.
+        let version_changed = (self.options.target != options.target
+            && self.options.target.effective() != options.target.effective())
+            || self.options.libraries != options.libraries
+            || self.options.type_roots != options.type_roots;
         self.options = options;
+        self.effective_target = std::cell::OnceCell::new();
 
         // Only reload stdlib if the target version changed
         if version_changed {
@@ -232,13 +430,39 @@ impl<'a, 'arena> TypeChecker<'a, 'arena> {
 
             // Reload stdlib with the new target version
             if let Err(e) = self.load_stdlib() {
-                eprintln!("Warning: Failed to load stdlib: {}", e);
+                self.diagnostic_handler.report(Diagnostic::error_with_code(
+                    Span::new(0, 0, 0, 0),
+                    error_codes::INVALID_CONFIG,
+                    format!("Failed to load the standard library: {}", e),
+                ));
             }
         }
 
         self
     }
 
+    /// Provide the source text of the program to check, so the doc comments
+    /// of its declarations are recorded
+    pub fn with_source(mut self, source: &'a str) -> Self {
+        self.source = Some(source);
+        self
+    }
+
+    /// The doc comment of a global declaration (`print`, `Account`) or of a
+    /// member (`string.format`, `Account.deposit`), for hover and signature
+    /// help in editors
+    pub fn doc_comment(&self, name: &str) -> Option<Arc<DocComment>> {
+        if let Some(symbol) = self.symbol_table.lookup(name) {
+            return symbol.doc.clone();
+        }
+        let (owner, member) = name.rsplit_once('.')?;
+        self.symbol_table
+            .lookup(owner)?
+            .member_docs
+            .get(member)
+            .cloned()
+    }
+
     /// Create a TypeChecker with module support for multi-module compilation
     pub fn new_with_module_support(
         diagnostic_handler: Arc<dyn DiagnosticHandler>,
@@ -256,30 +480,333 @@ impl<'a, 'arena> TypeChecker<'a, 'arena> {
         checker
     }
 
+    /// Type-check dependencies that are imported or required before they
+    /// were checked through `callback`, instead of failing the import or
+    /// typing the `require` as `unknown`
+    pub fn with_lazy_callback(
+        mut self,
+        callback: Arc<dyn phases::module_phase::LazyTypeCheckCallback>,
+    ) -> Self {
+        self.lazy_callback = Some(callback);
+        self
+    }
+
     /// Load the standard library for the configured Lua version
     ///
-    /// This method parses the stdlib definition files and processes their
-    /// statements to populate the type checker's symbol table and type environment.
+    /// This method parses the stdlib definition files, the selected `libraries`
+    /// and the files under `type_roots`, and processes their statements to
+    /// populate the type checker's symbol table and type environment. Relative
+    /// type roots are resolved against the module resolver's project
+    /// directory. An unknown library or a type root that cannot be read is
+    /// reported, and the stdlib and the other packs are loaded regardless.
+    /// A namespace declared by several packs gets the members of all of them;
+    /// any other global declared by two different packs is reported and keeps
+    /// its first declaration.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a standard library file fails to parse.
     pub fn load_stdlib(&mut self) -> Result<(), String> {
+        use crate::cli::diagnostics::{error_codes, Diagnostic};
         use crate::state::stdlib_loader;
 
-        let programs = stdlib_loader::parse_stdlib_files(
-            self.options.target,
+        let base_dir = self.module_resolver.as_ref().map_or_else(
+            || std::path::PathBuf::from("."),
+            |r| r.base_dir().to_path_buf(),
+        );
+        let parsed = stdlib_loader::parse_stdlib_files(
+            &self.options,
+            &base_dir,
             self.interner,
             self.common,
             self.arena,
         )?;
+        for error in parsed.errors {
+            self.diagnostic_handler.report(Diagnostic::error_with_code(
+                Span::new(0, 0, 0, 0),
+                error_codes::INVALID_CONFIG,
+                error,
+            ));
+        }
 
-        for program in programs {
-            for statement in program.statements.iter() {
-                // Ignore errors from stdlib - best-effort population
-                let _ = self.check_statement(statement);
+        let describe = |pack: &Option<String>| match pack {
+            Some(pack) => format!("'{}'", pack),
+            None => "the standard library".to_string(),
+        };
+        // The pack that first declared each global value and type
+        let mut declared_by: FxHashMap<
+            (luanext_parser::string_interner::StringId, bool),
+            Option<String>,
+        > = FxHashMap::default();
+
+        for file in parsed.files {
+            for statement in file.program.statements.iter() {
+                if let Some((name, span)) = Self::definition_name(statement) {
+                    let key = (
+                        name.node,
+                        matches!(
+                            statement,
+                            Statement::DeclareType(_) | Statement::DeclareInterface(_)
+                        ),
+                    );
+                    match declared_by.get(&key) {
+                        Some(first) if *first != file.pack => {
+                            if let Statement::DeclareNamespace(ns) = statement {
+                                if self.augment_declared_namespace(ns, first, &file.pack) {
+                                    self.record_doc_comments(statement, Some(file.source), None);
+                                    continue;
+                                }
+                            }
+                            self.diagnostic_handler.report(Diagnostic::error_with_code(
+                                span,
+                                error_codes::DUPLICATE_DECLARATION,
+                                format!(
+                                    "'{}' is declared by both {} and {}",
+                                    self.interner.resolve(name.node),
+                                    describe(first),
+                                    describe(&file.pack)
+                                ),
+                            ));
+                            continue;
+                        }
+                        Some(_) => {}
+                        None => {
+                            declared_by.insert(key, file.pack.clone());
+                        }
+                    }
+                }
+                // Ignore errors from stdlib - best-effort population. Its
+                // `declare type` aliases are ambient, not opaque.
+                let _ = match statement {
+                    Statement::DeclareType(alias) => self.check_type_alias(alias),
+                    _ => self.check_statement(statement),
+                };
+                self.record_doc_comments(statement, Some(file.source), None);
             }
         }
 
         Ok(())
     }
 
+    /// Add the members of a namespace that another pack declared first to that
+    /// namespace, reporting members both declare. Returns `false` when the
+    /// existing global is not a namespace.
+    fn augment_declared_namespace(
+        &mut self,
+        ns: &DeclareNamespaceStatement<'arena>,
+        first: &Option<String>,
+        pack: &Option<String>,
+    ) -> bool {
+        use crate::cli::diagnostics::{error_codes, Diagnostic};
+
+        let name = self.interner.resolve(ns.name.node);
+        let Some(TypeKind::Object(existing)) = self
+            .symbol_table
+            .lookup(&name)
+            .map(|symbol| &symbol.typ.kind)
+        else {
+            return false;
+        };
+        let member_name = |member: &ObjectTypeMember<'arena>| match member {
+            ObjectTypeMember::Method(method) => Some((method.name.node, method.span)),
+            ObjectTypeMember::Property(prop) => Some((prop.name.node, prop.span)),
+            _ => None,
+        };
+
+        let mut members = existing.members.to_vec();
+        for member in phases::declaration_phase::namespace_members(ns) {
+            // Overloads within the augmenting namespace are not conflicts
+            if let Some((added, span)) = member_name(&member).filter(|(added, _)| {
+                existing
+                    .members
+                    .iter()
+                    .any(|m| member_name(m).is_some_and(|(name, _)| name == *added))
+            }) {
+                let describe = |pack: &Option<String>| match pack {
+                    Some(pack) => format!("'{}'", pack),
+                    None => "the standard library".to_string(),
+                };
+                self.diagnostic_handler.report(Diagnostic::error_with_code(
+                    span,
+                    error_codes::DUPLICATE_DECLARATION,
+                    format!(
+                        "'{}.{}' is declared by both {} and {}",
+                        name,
+                        self.interner.resolve(added),
+                        describe(first),
+                        describe(pack)
+                    ),
+                ));
+                continue;
+            }
+            members.push(member);
+        }
+
+        let merged = Type::new(
+            TypeKind::Object(ObjectType {
+                members: self.arena.alloc_slice_fill_iter(members),
+                span: existing.span,
+            }),
+            existing.span,
+        );
+        self.symbol_table.set_type(&name, merged);
+        true
+    }
+
+    /// Attach the doc comments written above a declaration, and above the
+    /// members of a class, interface, enum or declared namespace, so API
+    /// consumers can look them up with [`TypeChecker::doc_comment`].
+    /// `doc_span` overrides where the comment is looked for, e.g. above the
+    /// `export` keyword of an exported declaration.
+    ///
+    /// A `@deprecated` decorator on a class or class member is recorded as
+    /// the `@deprecated` tag of its doc, so references to it are reported
+    /// whether or not the source text is available.
+    fn record_doc_comments(
+        &mut self,
+        statement: &Statement<'arena>,
+        source: Option<&str>,
+        doc_span: Option<Span>,
+    ) {
+        let (name, span, decorators) = match statement {
+            Statement::Export(export) => {
+                if let ExportKind::Declaration(decl) = &export.kind {
+                    self.record_doc_comments(decl, source, Some(export.span));
+                }
+                return;
+            }
+            Statement::Function(decl) => (decl.name.node, decl.span, &[][..]),
+            Statement::Variable(decl) => match &decl.pattern {
+                Pattern::Identifier(ident) => (ident.node, decl.span, &[][..]),
+                _ => return,
+            },
+            Statement::Class(class_decl) => {
+                (class_decl.name.node, class_decl.span, class_decl.decorators)
+            }
+            Statement::Interface(iface) | Statement::DeclareInterface(iface) => {
+                (iface.name.node, iface.span, &[][..])
+            }
+            Statement::TypeAlias(alias) | Statement::DeclareType(alias) => {
+                (alias.name.node, alias.span, &[][..])
+            }
+            Statement::Enum(enum_decl) => (enum_decl.name.node, enum_decl.span, &[][..]),
+            Statement::DeclareFunction(func) => (func.name.node, func.span, &[][..]),
+            Statement::DeclareNamespace(ns) => (ns.name.node, ns.span, &[][..]),
+            Statement::DeclareConst(const_decl) => (const_decl.name.node, const_decl.span, &[][..]),
+            _ => return,
+        };
+        let owner = self.interner.resolve(name);
+        if let Some(doc) = self.declaration_doc(source, doc_span.unwrap_or(span), decorators) {
+            self.symbol_table.set_doc(&owner, doc);
+        }
+
+        let members: Vec<(_, _, &[Decorator<'arena>])> = match statement {
+            Statement::Class(class_decl) => class_decl
+                .members
+                .iter()
+                .filter_map(|member| match member {
+                    ClassMember::Property(prop) => {
+                        Some((prop.name.node, prop.span, prop.decorators))
+                    }
+                    ClassMember::Method(method) => {
+                        Some((method.name.node, method.span, method.decorators))
+                    }
+                    ClassMember::Getter(getter) => {
+                        Some((getter.name.node, getter.span, getter.decorators))
+                    }
+                    ClassMember::Setter(setter) => {
+                        Some((setter.name.node, setter.span, setter.decorators))
+                    }
+                    _ => None,
+                })
+                .collect(),
+            Statement::Interface(iface) | Statement::DeclareInterface(iface) => iface
+                .members
+                .iter()
+                .filter_map(|member| match member {
+                    InterfaceMember::Property(prop) => Some((prop.name.node, prop.span, &[][..])),
+                    InterfaceMember::Method(method) => {
+                        Some((method.name.node, method.span, &[][..]))
+                    }
+                    InterfaceMember::Index(_) => None,
+                })
+                .collect(),
+            Statement::Enum(enum_decl) => enum_decl
+                .members
+                .iter()
+                .map(|member| (member.name.node, member.span, &[][..]))
+                .collect(),
+            Statement::DeclareNamespace(ns) => ns
+                .members
+                .iter()
+                .filter_map(|member| match member {
+                    Statement::DeclareFunction(func) => Some((func.name.node, func.span, &[][..])),
+                    Statement::DeclareConst(const_decl) => {
+                        Some((const_decl.name.node, const_decl.span, &[][..]))
+                    }
+                    _ => None,
+                })
+                .collect(),
+            _ => Vec::new(),
+        };
+        for (member, span, decorators) in members {
+            if let Some(doc) = self.declaration_doc(source, span, decorators) {
+                self.symbol_table
+                    .set_member_doc(&owner, &self.interner.resolve(member), doc);
+            }
+        }
+    }
+
+    /// The doc comment above the declaration at `span`, with the deprecation
+    /// of its `@deprecated` decorator
+    fn declaration_doc(
+        &self,
+        source: Option<&str>,
+        span: Span,
+        decorators: &[Decorator<'arena>],
+    ) -> Option<Arc<DocComment>> {
+        let mut doc =
+            source.and_then(|source| doc_comment::doc_comment_before(source, span.start as usize));
+        if let Some(deprecation) = self.decorator_deprecation(decorators) {
+            doc.get_or_insert_with(DocComment::default).deprecated = Some(deprecation);
+        }
+        doc.map(Arc::new)
+    }
+
+    /// The deprecation a `@deprecated` or `@deprecated("message")` decorator
+    /// declares, if one is applied
+    fn decorator_deprecation(&self, decorators: &[Decorator<'arena>]) -> Option<Deprecation> {
+        let decorator = decorators
+            .iter()
+            .find(|decorator| self.decorator_has_name(&decorator.expression, "deprecated"))?;
+        let message = match &decorator.expression {
+            DecoratorExpression::Call { arguments, .. } => {
+                match arguments.first().map(|arg| &arg.kind) {
+                    Some(ExpressionKind::Literal(Literal::String(message))) => {
+                        Some(message.to_string())
+                    }
+                    _ => None,
+                }
+            }
+            _ => None,
+        };
+        Some(Deprecation { message })
+    }
+
+    /// The global a definition file statement declares
+    fn definition_name<'s>(
+        statement: &'s Statement<'arena>,
+    ) -> Option<(&'s luanext_parser::ast::Ident, Span)> {
+        match statement {
+            Statement::DeclareFunction(func) => Some((&func.name, func.span)),
+            Statement::DeclareNamespace(ns) => Some((&ns.name, ns.span)),
+            Statement::DeclareConst(const_decl) => Some((&const_decl.name, const_decl.span)),
+            Statement::DeclareType(alias) => Some((&alias.name, alias.span)),
+            Statement::DeclareInterface(iface) => Some((&iface.name, iface.span)),
+            _ => None,
+        }
+    }
+
     /// Type check a program
     #[instrument(skip(self, program))]
     pub fn check_program(&mut self, program: &Program<'arena>) -> Result<(), TypeCheckError> {
@@ -302,6 +829,7 @@ impl<'a, 'arena> TypeChecker<'a, 'arena> {
             let func_decl = extract_function_decl(statement);
             if let Some(func_decl) = func_decl {
                 self.register_function_signature(func_decl)?;
+                self.record_doc_comments(statement, self.source, None);
             }
         }
 
@@ -316,6 +844,10 @@ impl<'a, 'arena> TypeChecker<'a, 'arena> {
                     first_error = Some(e);
                 }
             }
+            // Function declarations had their docs recorded in pass 1
+            if extract_function_decl(statement).is_none() {
+                self.record_doc_comments(statement, self.source, None);
+            }
             statements_checked += 1;
         }
 
@@ -395,6 +927,8 @@ impl<'a, 'arena> TypeChecker<'a, 'arena> {
             Statement::Return(return_stmt) => self.check_return_statement(return_stmt),
             Statement::Break(_) | Statement::Continue(_) => Ok(()),
             Statement::Expression(expr) => {
+                self.register_c_declarations(expr)?;
+                self.record_error_call(expr)?;
                 self.infer_expression_type(expr)?;
                 Ok(())
             }
@@ -408,7 +942,7 @@ impl<'a, 'arena> TypeChecker<'a, 'arena> {
             // Declaration file statements - register them in the symbol table
             Statement::DeclareFunction(func) => self.register_declare_function(func),
             Statement::DeclareNamespace(ns) => self.register_declare_namespace(ns),
-            Statement::DeclareType(alias) => self.check_type_alias(alias), // Reuse existing logic
+            Statement::DeclareType(alias) => self.check_declare_type(alias),
             Statement::DeclareInterface(iface) => self.check_interface_declaration(iface), // Reuse existing logic
             Statement::DeclareConst(const_decl) => self.register_declare_const(const_decl),
             // Exception handling
@@ -418,7 +952,14 @@ impl<'a, 'arena> TypeChecker<'a, 'arena> {
             // File-based namespace declaration
             Statement::Namespace(ns_decl) => self.check_namespace_declaration(ns_decl),
             // Label and Goto (Lua compatibility)
-            Statement::Label(_) | Statement::Goto(_) => Ok(()),
+            Statement::Label(label) => {
+                self.check_goto_supported(label.span);
+                Ok(())
+            }
+            Statement::Goto(goto) => {
+                self.check_goto_supported(goto.span);
+                Ok(())
+            }
             // Multi-assignment: type-check all targets and values
             Statement::MultiAssignment(multi) => {
                 for value in multi.values.iter() {
@@ -426,6 +967,9 @@ impl<'a, 'arena> TypeChecker<'a, 'arena> {
                 }
                 for target in multi.targets.iter() {
                     self.infer_expression_type(target)?;
+                    if let ExpressionKind::Identifier(name) = &target.kind {
+                        self.narrowing.get_context_mut().invalidate(*name);
+                    }
                 }
                 Ok(())
             }
@@ -437,15 +981,54 @@ impl<'a, 'arena> TypeChecker<'a, 'arena> {
         &mut self,
         decl: &VariableDeclaration<'arena>,
     ) -> Result<(), TypeCheckError> {
-        // Infer the type of the initializer
-        let init_type = self.infer_expression_type(&decl.initializer)?;
+        // A coroutine body passed inline is checked against the declared
+        // coroutine type, so its `coroutine.yield` calls know what to accept
+        if let Some(type_ann) = &decl.type_annotation {
+            self.check_coroutine_initializer(&decl.initializer, type_ann)?;
+        }
+
+        // Infer the type of the initializer. `ThisType<T>` in the annotation types
+        // `self` in the methods of an object literal initializer, and a tuple
+        // annotation infers array literals as tuples of their literal types.
+        let this_type = decl
+            .type_annotation
+            .as_ref()
+            .and_then(|type_ann| self.contextual_this_type(type_ann));
+        let const_context = matches!(decl.initializer.kind, ExpressionKind::Array(_))
+            && decl.type_annotation.as_ref().is_some_and(|type_ann| {
+                matches!(
+                    self.evaluate_type(type_ann).map(|t| t.kind),
+                    Ok(TypeKind::Tuple(_))
+                )
+            });
+        let init_type = if this_type.is_some() || const_context {
+            self.infer_expression_type_in_context(&decl.initializer, this_type, const_context)?
+        } else {
+            self.infer_expression_type(&decl.initializer)?
+        };
+
+        // `local [ok, value] = pcall(f)` destructures a union of tuples tagged
+        // by a boolean; testing `ok` then narrows the other variables
+        if decl.type_annotation.is_none() && self.declare_discriminated_tuple(decl, &init_type)? {
+            return Ok(());
+        }
 
         // Get the declared type or use inferred type
         let var_type = if let Some(type_ann) = &decl.type_annotation {
-            // Resolve the type annotation (handles type references)
-            let resolved_type_ann = self
-                .evaluate_type(type_ann)
-                .map_err(|e| TypeCheckError::new(e, decl.span))?;
+            // Resolve the type annotation (handles type references). The variable is
+            // not in scope yet, so `typeof` must not query it.
+            let declared_name = match &decl.pattern {
+                Pattern::Identifier(ident) => Some(self.interner.resolve(ident.node).to_string()),
+                _ => None,
+            };
+            let guarded = declared_name.is_some();
+            self.declaring_names.extend(declared_name);
+            let resolved_type_ann = self.evaluate_annotation(type_ann);
+            if guarded {
+                self.declaring_names.pop();
+            }
+            let resolved_type_ann =
+                resolved_type_ann.map_err(|e| TypeCheckError::new(e, decl.span))?;
 
             // Deep-resolve both types so nested references (e.g., Address | nil in an
             // interface property) are resolved before structural comparison
@@ -461,7 +1044,15 @@ impl<'a, 'arena> TypeChecker<'a, 'arena> {
                 // Fallback: check if source class implements the target interface.
                 // Use original init_type and type_ann (pre-evaluation) since evaluate_type
                 // resolves interface references to ObjectType<'arena>, losing the interface name.
-                if !self.check_implements_assignable(&init_type, type_ann) {
+                // A typed coroutine is also assignable to `thread`/`coroutine`
+                let is_thread = CoroutineSignature::from_type(&deep_init, self.interner).is_some()
+                    && TypeCompatibility::is_assignable_with_env(
+                        &deep_init,
+                        &deep_ann,
+                        &self.type_env,
+                        self.interner,
+                    );
+                if !is_thread && !self.check_implements_assignable(&init_type, type_ann) {
                     self.diagnostic_handler.error(
                         decl.span,
                         &format!(
@@ -472,6 +1063,8 @@ impl<'a, 'arena> TypeChecker<'a, 'arena> {
                 }
             }
             resolved_type_ann
+        } else if let Some(open_table) = self.open_table_declaration(decl, &init_type) {
+            open_table
         } else {
             // For const, use narrow type; for local, widen literals
             if matches!(decl.kind, VariableKind::Const) {
@@ -493,6 +1086,253 @@ impl<'a, 'arena> TypeChecker<'a, 'arena> {
         Ok(())
     }
 
+    /// Check the body of `coroutine.create(function ... end)` declared as a
+    /// `Coroutine<Y, R, T>`, or of `coroutine.wrap(function ... end)` declared
+    /// as a function type whose return type is both the yield and return type.
+    /// Unannotated body parameters take the coroutine's resume types.
+    fn check_coroutine_initializer(
+        &mut self,
+        initializer: &Expression<'arena>,
+        type_ann: &Type<'arena>,
+    ) -> Result<(), TypeCheckError> {
+        let ExpressionKind::Call(callee, args, _) = &initializer.kind else {
+            return Ok(());
+        };
+        let ExpressionKind::Member(object, member) = &callee.kind else {
+            return Ok(());
+        };
+        let is_coroutine_library = matches!(
+            &object.kind,
+            ExpressionKind::Identifier(name) if self.interner.resolve(*name) == "coroutine"
+        );
+        let [body] = args else {
+            return Ok(());
+        };
+        let ExpressionKind::Function(func_expr) = &body.value.kind else {
+            return Ok(());
+        };
+        if !is_coroutine_library {
+            return Ok(());
+        }
+
+        let Ok(declared) = self.evaluate_annotation(type_ann) else {
+            return Ok(());
+        };
+        let signature = match (self.interner.resolve(member.node).as_str(), &declared.kind) {
+            ("create", _) => match CoroutineSignature::from_type(&declared, self.interner) {
+                Some(signature) => signature,
+                None => return Ok(()),
+            },
+            ("wrap", TypeKind::Function(func_type)) => {
+                let output = (*func_type.return_type).clone();
+                CoroutineSignature {
+                    yield_type: output.clone(),
+                    ..CoroutineSignature::for_body(
+                        self.arena,
+                        func_expr.parameters,
+                        output,
+                        body.value.span,
+                    )
+                }
+            }
+            _ => return Ok(()),
+        };
+
+        self.symbol_table.enter_scope();
+        let resume_values = coroutine::unpack_values(&signature.resume_type).unwrap_or(&[]);
+        for (index, param) in func_expr.parameters.iter().enumerate() {
+            let param_type = match &param.type_annotation {
+                Some(type_ann) => self
+                    .evaluate_annotation(type_ann)
+                    .unwrap_or_else(|_| type_ann.clone()),
+                None if !param.is_rest => resume_values.get(index).cloned().unwrap_or_else(|| {
+                    self.type_env
+                        .new_primitive_type(PrimitiveType::Unknown, param.span)
+                }),
+                None => self
+                    .type_env
+                    .new_primitive_type(PrimitiveType::Unknown, param.span),
+            };
+            let param_type = if param.is_rest {
+                Type::new(TypeKind::Array(self.arena.alloc(param_type)), param.span)
+            } else {
+                param_type
+            };
+            if let Err(e) = self.declare_pattern(
+                &param.pattern,
+                param_type,
+                SymbolKind::Parameter,
+                param.span,
+            ) {
+                self.symbol_table.exit_scope();
+                return Err(e);
+            }
+        }
+
+        let old_return_type = self
+            .current_function_return_type
+            .replace(signature.return_type.clone());
+        self.coroutine_signatures.push(signature);
+        let body_result = self.check_block(&func_expr.body);
+        self.coroutine_signatures.pop();
+        self.current_function_return_type = old_return_type;
+        self.symbol_table.exit_scope();
+
+        body_result
+    }
+
+    /// Destructure a union of tuples whose first elements are `true` and
+    /// `false`, such as `[true, R] | [false, E]` from `pcall`. Each variable
+    /// gets the union of its position, and the first one becomes a
+    /// discriminant: testing it narrows the others to the matching tuples.
+    /// Returns false when the declaration does not have this shape.
+    fn declare_discriminated_tuple(
+        &mut self,
+        decl: &VariableDeclaration<'arena>,
+        init_type: &Type<'arena>,
+    ) -> Result<bool, TypeCheckError> {
+        use luanext_parser::ast::pattern::{ArrayPatternElement, PatternWithDefault};
+
+        let Pattern::Array(array_pattern) = &decl.pattern else {
+            return Ok(false);
+        };
+        let TypeKind::Union(members) = &init_type.kind else {
+            return Ok(false);
+        };
+        let mut outcomes = Vec::new();
+        for member in members.iter() {
+            let TypeKind::Tuple(elements) = &member.kind else {
+                return Ok(false);
+            };
+            let Some(TypeKind::Literal(Literal::Boolean(tag))) = elements.first().map(|e| &e.kind)
+            else {
+                return Ok(false);
+            };
+            outcomes.push((*tag, *elements));
+        }
+
+        let mut names = Vec::new();
+        for element in array_pattern.elements.iter() {
+            match element {
+                ArrayPatternElement::Pattern(PatternWithDefault {
+                    pattern: Pattern::Identifier(ident),
+                    ..
+                }) => names.push(Some(ident)),
+                ArrayPatternElement::Hole => names.push(None),
+                _ => return Ok(false),
+            }
+        }
+        let Some(Some(discriminant)) = names.first() else {
+            return Ok(false);
+        };
+
+        let span = decl.span;
+        let is_const = matches!(decl.kind, VariableKind::Const);
+        // The union of position `index` over the tuples tagged `tag` (all when `None`)
+        let position_type = |checker: &Self, index: usize, tag: Option<bool>| {
+            let types: Vec<Type<'arena>> = outcomes
+                .iter()
+                .filter(|(outcome, _)| tag.map_or(true, |tag| tag == *outcome))
+                .map(|(_, elements)| {
+                    elements
+                        .get(index)
+                        .cloned()
+                        .unwrap_or_else(|| Type::new(TypeKind::Primitive(PrimitiveType::Nil), span))
+                })
+                .collect();
+            let union = if types.is_empty() {
+                Type::new(TypeKind::Primitive(PrimitiveType::Nil), span)
+            } else {
+                type_utilities::canonicalize_union(types, span, checker.arena)
+            };
+            if is_const {
+                union
+            } else {
+                checker.widen_type(union)
+            }
+        };
+
+        let symbol_kind = if is_const {
+            SymbolKind::Const
+        } else {
+            SymbolKind::Variable
+        };
+        let mut correlated = Vec::new();
+        for (index, name) in names.iter().enumerate() {
+            let Some(ident) = name else {
+                continue;
+            };
+            let typ = position_type(self, index, None);
+            self.declare_pattern(
+                &Pattern::Identifier((*ident).clone()),
+                typ,
+                symbol_kind,
+                span,
+            )?;
+            if index > 0 {
+                correlated.push((
+                    ident.node,
+                    position_type(self, index, Some(true)),
+                    position_type(self, index, Some(false)),
+                ));
+            }
+        }
+        self.narrowing
+            .get_context_mut()
+            .set_correlated_types(discriminant.node, span, correlated);
+        Ok(true)
+    }
+
+    /// Drop the correlations whose variables have gone out of scope or been
+    /// shadowed, so that `if ok then` only narrows through the `ok` that the
+    /// correlating declaration bound
+    fn prune_correlations(&mut self) {
+        let symbol_table = &self.symbol_table;
+        let interner = self.interner;
+        self.narrowing
+            .get_context_mut()
+            .retain_correlations(|name, declaration| {
+                symbol_table
+                    .lookup(&interner.resolve(name))
+                    .is_some_and(|symbol| {
+                        symbol.span.start == declaration.start && symbol.span.end == declaration.end
+                    })
+            });
+    }
+
+    /// Treat an unannotated `local M = {}` as an open table: the variable gets a
+    /// synthetic `typeof M` reference whose shape grows as fields are assigned
+    /// (`M.__index = M`, `M.new = function() ... end`), which is how Lua-style
+    /// modules and metatable classes are built. The shape is keyed by the
+    /// declaration, so a table shadowing another of the same name starts empty.
+    fn open_table_declaration(
+        &mut self,
+        decl: &VariableDeclaration<'arena>,
+        init_type: &Type<'arena>,
+    ) -> Option<Type<'arena>> {
+        let Pattern::Identifier(ident) = &decl.pattern else {
+            return None;
+        };
+        if !matches!(&decl.initializer.kind, ExpressionKind::Object(props) if props.is_empty()) {
+            return None;
+        }
+
+        let name = luanext_parser::ast::Spanned::new(
+            self.interner
+                .intern(&format!("typeof {}", self.interner.resolve(ident.node))),
+            ident.span,
+        );
+        self.type_env.register_open_table(&name, init_type.clone());
+        Some(Type::new(
+            TypeKind::Reference(TypeReference {
+                name,
+                type_arguments: None,
+                span: ident.span,
+            }),
+            ident.span,
+        ))
+    }
+
     /// Declare symbols from a pattern
     fn declare_pattern(
         &mut self,
@@ -547,7 +1387,7 @@ impl<'a, 'arena> TypeChecker<'a, 'arena> {
                 let elem_type = if let Some(type_ann) = &param.type_annotation {
                     // Evaluate to resolve type references
                     let evaluated = self
-                        .evaluate_type(type_ann)
+                        .evaluate_annotation(type_ann)
                         .map_err(|e| TypeCheckError::new(e, param.span))
                         .unwrap_or_else(|_| type_ann.clone());
                     // Deep resolve to handle nested types
@@ -562,7 +1402,7 @@ impl<'a, 'arena> TypeChecker<'a, 'arena> {
             } else if let Some(type_ann) = &param.type_annotation {
                 // Evaluate to resolve type references
                 let evaluated = self
-                    .evaluate_type(type_ann)
+                    .evaluate_annotation(type_ann)
                     .map_err(|e| TypeCheckError::new(e, param.span))
                     .unwrap_or_else(|_| type_ann.clone());
                 // Deep resolve to handle nested types
@@ -583,13 +1423,19 @@ impl<'a, 'arena> TypeChecker<'a, 'arena> {
         // Set current function return type for return statement checking
         let old_return_type = self.current_function_return_type.clone();
         let resolved_return_type = decl.return_type.as_ref().map(|rt| {
-            let evaluated = self.evaluate_type(rt).unwrap_or_else(|_| rt.clone());
+            // A return type may not query the function's own result
+            self.declaring_names
+                .push(self.interner.resolve(decl.name.node).to_string());
+            let evaluated = self.evaluate_annotation(rt).unwrap_or_else(|_| rt.clone());
+            self.declaring_names.pop();
             self.deep_resolve_type(&evaluated)
         });
         self.current_function_return_type = resolved_return_type;
 
         // Check function body (scope-safe: always exit scope even on error)
+        self.error_channels.push(Vec::new());
         let body_result = self.check_block(&decl.body);
+        let raised = self.error_channels.pop().unwrap_or_default();
 
         // Check that non-void functions have a return statement on all code paths
         if body_result.is_ok() {
@@ -628,14 +1474,77 @@ impl<'a, 'arena> TypeChecker<'a, 'arena> {
         // Exit function scope (this will remove type parameter registrations)
         self.symbol_table.exit_scope();
 
+        // Without a `throws` clause, the values the body raises become the
+        // function's error type, which `pcall` reports on failure
+        if decl.throws.is_none() && !raised.is_empty() {
+            self.record_raised_types(decl, raised);
+        }
+
         body_result
     }
 
-    /// Check if statement
-    fn check_if_statement(&mut self, if_stmt: &IfStatement<'arena>) -> Result<(), TypeCheckError> {
-        // Check condition
-        self.infer_expression_type(&if_stmt.condition)?;
-
+    /// Note the value of an `error(value)` call statement as raised by the
+    /// enclosing function
+    fn record_error_call(&mut self, expr: &Expression<'arena>) -> Result<(), TypeCheckError> {
+        if self.error_channels.is_empty() {
+            return Ok(());
+        }
+        let ExpressionKind::Call(callee, args, _) = &expr.kind else {
+            return Ok(());
+        };
+        let is_error = matches!(
+            &callee.kind,
+            ExpressionKind::Identifier(name) if self.interner.resolve(*name) == "error"
+        ) && self
+            .symbol_table
+            .lookup("error")
+            .is_some_and(|symbol| symbol.kind == SymbolKind::Function);
+        let Some(value) = args.first().filter(|_| is_error) else {
+            return Ok(());
+        };
+        let raised = self.infer_expression_type(&value.value)?;
+        self.raise(raised);
+        Ok(())
+    }
+
+    /// Add a raised type to the innermost function's error channel
+    fn raise(&mut self, typ: Type<'arena>) {
+        let typ = self.widen_type(typ);
+        if let Some(channel) = self.error_channels.last_mut() {
+            channel.push(typ);
+        }
+    }
+
+    /// Give a function declared without `throws` the types its body raises
+    fn record_raised_types(
+        &mut self,
+        decl: &FunctionDeclaration<'arena>,
+        raised: Vec<Type<'arena>>,
+    ) {
+        let name = self.interner.resolve(decl.name.node);
+        let Some(TypeKind::Function(func)) = self
+            .symbol_table
+            .lookup(&name)
+            .map(|symbol| &symbol.typ.kind)
+        else {
+            return;
+        };
+        let func_type = Type::new(
+            TypeKind::Function(FunctionType {
+                throws: Some(self.arena.alloc_slice_fill_iter(raised)),
+                ..func.clone()
+            }),
+            decl.span,
+        );
+        self.symbol_table.set_type(&name, func_type);
+    }
+
+    /// Check if statement
+    fn check_if_statement(&mut self, if_stmt: &IfStatement<'arena>) -> Result<(), TypeCheckError> {
+        // Check condition
+        self.infer_expression_type(&if_stmt.condition)?;
+        self.prune_correlations();
+
         // Collect current variable and function types for narrowing
         // This includes both variables and functions so type predicates can be checked
         let mut variable_types = FxHashMap::default();
@@ -654,9 +1563,10 @@ impl<'a, 'arena> TypeChecker<'a, 'arena> {
         );
 
         // Check then block with narrowed context
-        let saved_context = self.narrowing.get_context().clone();
+        let mut saved_context = self.narrowing.get_context().clone();
         *self.narrowing.get_context_mut() = then_context;
         self.check_block(&if_stmt.then_block)?;
+        saved_context.drop_invalidated(self.narrowing.get_context());
 
         // Restore context for else-if and else
         *self.narrowing.get_context_mut() = else_context.clone();
@@ -676,6 +1586,7 @@ impl<'a, 'arena> TypeChecker<'a, 'arena> {
 
             *self.narrowing.get_context_mut() = elseif_then;
             self.check_block(&else_if.block)?;
+            saved_context.drop_invalidated(self.narrowing.get_context());
             *self.narrowing.get_context_mut() = elseif_else;
         }
 
@@ -684,7 +1595,9 @@ impl<'a, 'arena> TypeChecker<'a, 'arena> {
             self.check_block(else_block)?;
         }
 
-        // Restore original context after if statement
+        // Restore original context after if statement, without what the
+        // branches invalidated by assigning
+        saved_context.drop_invalidated(self.narrowing.get_context());
         *self.narrowing.get_context_mut() = saved_context;
 
         Ok(())
@@ -707,27 +1620,38 @@ impl<'a, 'arena> TypeChecker<'a, 'arena> {
     ) -> Result<(), TypeCheckError> {
         match for_stmt {
             ForStatement::Numeric(numeric) => {
+                // Check start, end, step expressions
+                let start_type = self.infer_expression_type(&numeric.start)?;
+                self.infer_expression_type(&numeric.end)?;
+                let step_type = match &numeric.step {
+                    Some(step) => Some(self.infer_expression_type(step)?),
+                    None => None,
+                };
+
                 self.symbol_table.enter_scope();
 
-                // Declare loop variable as number (using cached primitive)
-                let number_type = self.type_env.get_number_type(numeric.span);
+                // On Lua 5.3+ the loop counts in integers when the start and step
+                // are integers; otherwise the loop variable is a float
+                let integer_loop = self.target().has_integer_subtype()
+                    && type_utilities::is_integer_type(&start_type)
+                    && step_type
+                        .as_ref()
+                        .is_none_or(type_utilities::is_integer_type);
+                let loop_type = if integer_loop {
+                    self.type_env.get_integer_type(numeric.span)
+                } else {
+                    self.type_env.get_number_type(numeric.span)
+                };
                 let symbol = Symbol::new(
                     self.interner.resolve(numeric.variable.node).to_string(),
                     SymbolKind::Variable,
-                    number_type,
+                    loop_type,
                     numeric.span,
                 );
                 self.symbol_table
                     .declare(symbol)
                     .map_err(|e| TypeCheckError::new(e, numeric.span))?;
 
-                // Check start, end, step expressions
-                self.infer_expression_type(&numeric.start)?;
-                self.infer_expression_type(&numeric.end)?;
-                if let Some(step) = &numeric.step {
-                    self.infer_expression_type(step)?;
-                }
-
                 self.check_block(&numeric.body)?;
                 self.symbol_table.exit_scope();
             }
@@ -807,7 +1731,8 @@ impl<'a, 'arena> TypeChecker<'a, 'arena> {
                 .collect();
             let return_types = return_types?;
 
-            // Create the actual return type (single value or tuple)
+            // Create the actual return type (single value or tuple), resolved like the
+            // expected return type so aliases such as `x as UserId` compare structurally
             let actual_return_type = if return_types.len() == 1 {
                 return_types[0].clone()
             } else {
@@ -816,6 +1741,7 @@ impl<'a, 'arena> TypeChecker<'a, 'arena> {
                     return_stmt.span,
                 )
             };
+            let actual_return_type = self.deep_resolve_type(&actual_return_type);
 
             // Check against expected return type
             if let Some(expected_type) = &self.current_function_return_type {
@@ -870,8 +1796,10 @@ impl<'a, 'arena> TypeChecker<'a, 'arena> {
                     first_error = Some(e);
                 }
             }
+            self.record_doc_comments(stmt, self.source, None);
         }
         self.symbol_table.exit_scope();
+        self.prune_correlations();
         if let Some(err) = first_error {
             Err(err)
         } else {
@@ -928,10 +1856,42 @@ impl<'a, 'arena> TypeChecker<'a, 'arena> {
         &mut self,
         alias: &TypeAliasDeclaration<'arena>,
     ) -> Result<(), TypeCheckError> {
+        self.check_alias_declaration(alias, false)
+    }
+
+    /// Check a `declare type` statement. In a source module it declares an
+    /// opaque alias, a brand of its type; in a definition file it is an
+    /// ordinary ambient alias.
+    fn check_declare_type(
+        &mut self,
+        alias: &TypeAliasDeclaration<'arena>,
+    ) -> Result<(), TypeCheckError> {
+        let in_definition_file = self
+            .current_module_id
+            .as_ref()
+            .is_some_and(|id| id.as_str().ends_with(".d.luax"));
+        self.check_alias_declaration(alias, !in_definition_file)
+    }
+
+    fn check_alias_declaration(
+        &mut self,
+        alias: &TypeAliasDeclaration<'arena>,
+        opaque: bool,
+    ) -> Result<(), TypeCheckError> {
+        // A brand declared by an alias is keyed by the declaration, not its tag
+        let branded;
+        let alias = match self.branded_alias(alias, opaque) {
+            Some(expanded) => {
+                branded = expanded;
+                &branded
+            }
+            None => alias,
+        };
+
         // For non-generic aliases, evaluate the type before delegating
         let evaluated_type = if alias.type_parameters.is_none() {
             Some(
-                self.evaluate_type(&alias.type_annotation)
+                self.evaluate_annotation(&alias.type_annotation)
                     .map_err(|e| TypeCheckError::new(e, alias.span))?,
             )
         } else {
@@ -948,6 +1908,67 @@ impl<'a, 'arena> TypeChecker<'a, 'arena> {
         )
     }
 
+    /// Rewrite an alias that declares a brand, `Brand<T>`, `Brand<T, "Tag">` or
+    /// an opaque alias, to `Brand<T, marker>`. The marker is keyed by the
+    /// declaring module and alias name, so brands declared separately never
+    /// mix even when they share a tag, while modules that import the alias
+    /// get the same brand.
+    fn branded_alias(
+        &self,
+        alias: &TypeAliasDeclaration<'arena>,
+        opaque: bool,
+    ) -> Option<TypeAliasDeclaration<'arena>> {
+        let alias_name = self.interner.resolve(alias.name.node).to_string();
+        let brand_args = match &alias.type_annotation.kind {
+            TypeKind::Reference(type_ref)
+                if self.interner.resolve(type_ref.name.node) == "Brand" =>
+            {
+                type_ref.type_arguments
+            }
+            _ => None,
+        };
+        let (base, tag) = match brand_args {
+            Some([base]) => (base.clone(), alias_name.clone()),
+            Some(
+                [base, Type {
+                    kind: TypeKind::Literal(Literal::String(tag)),
+                    ..
+                }],
+            ) => (base.clone(), tag.clone()),
+            Some(_) => return None,
+            None if opaque => (alias.type_annotation.clone(), alias_name.clone()),
+            None => return None,
+        };
+
+        let module = self
+            .current_module_id
+            .as_ref()
+            .map(|id| id.as_str())
+            .unwrap_or_default();
+        let marker = crate::types::utility_types::brand_marker(
+            self.arena,
+            &tag,
+            Some(&format!("{}::{}", module, alias_name)),
+            alias.name.span,
+            self.interner,
+        );
+        let annotation = Type::new(
+            TypeKind::Reference(TypeReference {
+                name: luanext_parser::ast::Spanned::new(
+                    self.interner.intern("Brand"),
+                    alias.type_annotation.span,
+                ),
+                type_arguments: Some(self.arena.alloc_slice_fill_iter([base, marker])),
+                span: alias.type_annotation.span,
+            }),
+            alias.type_annotation.span,
+        );
+        Some(TypeAliasDeclaration {
+            type_annotation: annotation,
+            ..alias.clone()
+        })
+    }
+
     /// Check export statement and register exported symbols
     fn check_export_statement(
         &mut self,
@@ -1195,6 +2216,13 @@ impl<'a, 'arena> TypeChecker<'a, 'arena> {
 
         let span = type_ref.span;
 
+        // A brand marker names no type, it only identifies its brand
+        if crate::types::utility_types::is_brand_marker(&name) {
+            return Ok(Type::new(TypeKind::Reference(type_ref.clone()), span));
+        }
+
+        self.check_type_deprecation(&name, span);
+
         // Cycle detection: if we're already resolving this type, return it as-is
         // to prevent infinite recursion on recursive type aliases like `type List<T> = T | List<T>[]`
         if self.resolving_types.borrow().contains(&name_owned) {
@@ -1212,6 +2240,28 @@ impl<'a, 'arena> TypeChecker<'a, 'arena> {
         result
     }
 
+    /// Warn when a type reference names a deprecated class, interface,
+    /// enum or type alias, once per span
+    fn check_type_deprecation(&self, name: &str, span: Span) {
+        use crate::cli::diagnostics::{error_codes, Diagnostic};
+
+        let Some(deprecation) = self
+            .symbol_table
+            .lookup(name)
+            .and_then(|symbol| symbol.doc.as_ref())
+            .and_then(|doc| doc.deprecated.as_ref())
+        else {
+            return;
+        };
+        let key = (span.start as usize, span.end as usize);
+        if !self.reported_deprecations.borrow_mut().insert(key) {
+            return;
+        }
+        self.diagnostic_handler.report(
+            Diagnostic::warning(span, deprecation.warning(name)).with_code(error_codes::DEPRECATED),
+        );
+    }
+
     fn resolve_type_reference_inner(
         &self,
         type_ref: &TypeReference<'arena>,
@@ -1219,6 +2269,10 @@ impl<'a, 'arena> TypeChecker<'a, 'arena> {
         let name = self.interner.resolve(type_ref.name.node);
         let span = type_ref.span;
 
+        if let Some(shape) = self.type_env.open_table(type_ref) {
+            return Ok(shape.clone());
+        }
+
         // Check if it's a utility type
         if let Some(type_args) = &type_ref.type_arguments {
             if TypeEnvironment::is_utility_type(&name) {
@@ -1248,13 +2302,29 @@ impl<'a, 'arena> TypeChecker<'a, 'arena> {
             // Check for generic type alias
             if let Some(generic_alias) = self.type_env.get_generic_type_alias(&name) {
                 use crate::types::generics::instantiate_type;
-                return instantiate_type(
+                let instantiated = instantiate_type(
                     self.arena,
                     &generic_alias.typ,
                     &generic_alias.type_parameters,
                     type_args,
                 )
-                .map_err(|e| TypeCheckError::new(e, span));
+                .map_err(|e| TypeCheckError::new(e, span))?;
+
+                // Conditional and template literal aliases are evaluated once their
+                // type arguments are known, e.g. `EventName<"onClick">` -> "Click".
+                // A check type that is still an unresolved type parameter is deferred.
+                return match &instantiated.kind {
+                    TypeKind::Conditional(conditional)
+                        if !self.is_unresolved_reference(conditional.check_type) =>
+                    {
+                        self.evaluate_type(&instantiated)
+                            .map_err(|e| TypeCheckError::new(e, span))
+                    }
+                    TypeKind::TemplateLiteral(_) => self
+                        .evaluate_type(&instantiated)
+                        .map_err(|e| TypeCheckError::new(e, span)),
+                    _ => Ok(instantiated),
+                };
             }
         }
 
@@ -1268,6 +2338,20 @@ impl<'a, 'arena> TypeChecker<'a, 'arena> {
         }
     }
 
+    /// Whether a type is a reference that names no known type (typically a
+    /// type parameter of an enclosing generic)
+    fn is_unresolved_reference(&self, typ: &Type<'arena>) -> bool {
+        match &typ.kind {
+            TypeKind::Reference(type_ref) => {
+                let name = self.interner.resolve(type_ref.name.node);
+                self.type_env.lookup_type(&name).is_none()
+                    && self.type_env.get_generic_type_alias(&name).is_none()
+                    && !TypeEnvironment::is_utility_type(&name)
+            }
+            _ => false,
+        }
+    }
+
     /// Check class declaration
     #[instrument(skip(self, class_decl), fields(class_name))]
     fn check_class_declaration(
@@ -1304,8 +2388,11 @@ impl<'a, 'arena> TypeChecker<'a, 'arena> {
             return Ok(());
         }
 
-        // Check decorators
-        self.check_decorators(class_decl.decorators)?;
+        // Check decorators. A class decorator receives the constructor; the
+        // class keeps its nominal type, so a decorated result is only checked,
+        // not substituted.
+        let constructor = self.class_constructor_type(class_decl);
+        self.check_decorators(class_decl.decorators, &constructor)?;
 
         // Check for @readonly decorator and track it
         let has_readonly = class_decl
@@ -1341,10 +2428,10 @@ impl<'a, 'arena> TypeChecker<'a, 'arena> {
 
         // Validate class inheritance (focused function - ~20 lines saved)
         if let Some(extends_type) = &class_decl.extends {
+            self.check_extends_final_class(extends_type);
             phases::validation_phase::validate_class_inheritance(
                 &class_name,
                 extends_type,
-                &self.access_control,
                 &mut self.class_parents,
                 self.interner,
                 class_decl.span,
@@ -1523,9 +2610,51 @@ impl<'a, 'arena> TypeChecker<'a, 'arena> {
                 .register_member(&class_name, member_info);
         }
 
-        // Mark class as final if needed
-        self.access_control
-            .mark_class_final(&class_name, class_decl.is_final);
+        // Record member declaration sites so override diagnostics can point at them
+        for param in &primary_constructor_properties {
+            self.type_env.register_member_span(
+                &class_name,
+                self.interner.resolve(param.name.node).to_string(),
+                param.span,
+            );
+        }
+        for member in class_decl.members.iter() {
+            let (name, span) = match member {
+                ClassMember::Property(prop) => (prop.name.node, prop.span),
+                ClassMember::Method(method) => (method.name.node, method.span),
+                ClassMember::Getter(getter) => (getter.name.node, getter.span),
+                ClassMember::Setter(setter) => (setter.name.node, setter.span),
+                ClassMember::Constructor(_) | ClassMember::Operator(_) => continue,
+            };
+            self.type_env.register_member_span(
+                &class_name,
+                self.interner.resolve(name).to_string(),
+                span,
+            );
+        }
+
+        // Mark class as final if needed (`final class`, `@final` or `@sealed`)
+        let is_final = class_decl.is_final
+            || declaration_checking_phase::has_decorator(
+                class_decl.decorators,
+                "final",
+                self.interner,
+            )
+            || declaration_checking_phase::has_decorator(
+                class_decl.decorators,
+                "sealed",
+                self.interner,
+            );
+        self.access_control.mark_class_final(&class_name, is_final);
+
+        // Register the body constructor so derived classes can check `super(...)` calls
+        if let Some(ctor) = class_decl.members.iter().find_map(|m| match m {
+            ClassMember::Constructor(ctor) => Some(ctor),
+            _ => None,
+        }) {
+            self.type_env
+                .register_constructor_params(class_name.clone(), ctor.parameters);
+        }
 
         // Set current class context
         let old_class = self.access_control.get_current_class().clone();
@@ -1588,6 +2717,10 @@ impl<'a, 'arena> TypeChecker<'a, 'arena> {
             }
         }
 
+        if self.options.strict_property_initialization {
+            self.check_property_initialization(class_decl);
+        }
+
         // Restore previous class context
         self.access_control.set_current_class(old_class);
 
@@ -1681,11 +2814,16 @@ impl<'a, 'arena> TypeChecker<'a, 'arena> {
         }
     }
 
-    /// Check decorators
+    /// Check decorators against the declaration they are applied to.
+    ///
+    /// Decorators apply bottom-up, each receiving the previous result. Returns
+    /// the decorated type when a decorator returns a type compatible with
+    /// `target`.
     fn check_decorators(
         &mut self,
         decorators: &[luanext_parser::ast::statement::Decorator<'arena>],
-    ) -> Result<(), TypeCheckError> {
+        target: &Type<'arena>,
+    ) -> Result<Option<Type<'arena>>, TypeCheckError> {
         // Check if decorators are enabled
         if !decorators.is_empty() && !self.options.enable_decorators {
             return Err(TypeCheckError::new(
@@ -1725,57 +2863,331 @@ impl<'a, 'arena> TypeChecker<'a, 'arena> {
             }
         }
 
-        // For now, we just validate that decorator expressions are valid
-        // Full decorator type checking would require:
-        // 1. Checking that decorator functions exist
-        // 2. Validating decorator function signatures match target type
-        // 3. Checking decorator arguments are type-compatible
-        // This is simplified for now - decorators are allowed but not deeply validated
-
-        for decorator in decorators.iter() {
-            self.check_decorator_expression(&decorator.expression)?;
+        let mut current = target.clone();
+        let mut decorated = None;
+        for decorator in decorators.iter().rev() {
+            let Some(decorator_type) =
+                self.decorator_type(&decorator.expression, decorator.span)?
+            else {
+                continue;
+            };
+            if let Some(result) = self.apply_decorator(&decorator_type, &current, decorator.span) {
+                current = result.clone();
+                decorated = Some(result);
+            }
         }
 
-        Ok(())
+        Ok(decorated)
+    }
+
+    /// The type of a class as a value: a function from its constructor
+    /// parameters to an instance.
+    fn class_constructor_type(&self, class_decl: &ClassDeclaration<'arena>) -> Type<'arena> {
+        let span = class_decl.name.span;
+        let parameters: &'arena [Parameter<'arena>] =
+            if let Some(primary) = class_decl.primary_constructor {
+                self.arena
+                    .alloc_slice_fill_iter(primary.iter().map(|param| Parameter {
+                        pattern: Pattern::Identifier(param.name.clone()),
+                        type_annotation: Some(param.type_annotation.clone()),
+                        default: None,
+                        is_rest: false,
+                        is_optional: false,
+                        span: param.span,
+                    }))
+            } else {
+                class_decl
+                    .members
+                    .iter()
+                    .find_map(|member| match member {
+                        ClassMember::Constructor(ctor) => Some(ctor.parameters),
+                        _ => None,
+                    })
+                    .unwrap_or(&[])
+            };
+        let instance = Type::new(
+            TypeKind::Reference(TypeReference {
+                name: class_decl.name.clone(),
+                type_arguments: None,
+                span,
+            }),
+            span,
+        );
+        Type::new(
+            TypeKind::Function(FunctionType {
+                type_parameters: class_decl.type_parameters,
+                parameters,
+                return_type: self.arena.alloc(instance),
+                throws: None,
+                span,
+            }),
+            span,
+        )
+    }
+
+    /// The type of a built-in decorator, declared in the stdlib's
+    /// `Decorators` namespace.
+    fn builtin_decorator_type(
+        &self,
+        name: luanext_parser::string_interner::StringId,
+    ) -> Option<Type<'arena>> {
+        let namespace = self.symbol_table.lookup(BUILTIN_DECORATORS)?;
+        Self::member_value_type(&namespace.typ, name)
+    }
+
+    /// The type of member `name` of an object type read as a value.
+    fn member_value_type(
+        typ: &Type<'arena>,
+        name: luanext_parser::string_interner::StringId,
+    ) -> Option<Type<'arena>> {
+        let TypeKind::Object(obj) = &typ.kind else {
+            return None;
+        };
+        let members: &'arena [ObjectTypeMember<'arena>] = obj.members;
+        members.iter().find_map(|member| match member {
+            ObjectTypeMember::Method(method) if method.name.node == name => Some(Type::new(
+                TypeKind::Function(FunctionType {
+                    type_parameters: method.type_parameters,
+                    parameters: method.parameters,
+                    return_type: &method.return_type,
+                    throws: None,
+                    span: method.span,
+                }),
+                method.span,
+            )),
+            ObjectTypeMember::Property(prop) if prop.name.node == name => {
+                Some(prop.type_annotation.clone())
+            }
+            _ => None,
+        })
     }
 
-    /// Check a decorator expression
-    fn check_decorator_expression(
+    /// Infer the type of a decorator expression.
+    ///
+    /// Names are looked up among the built-in decorators first, then in scope.
+    /// Returns `None` when the type is not known, e.g. for names that are not
+    /// declared because the stdlib is not loaded.
+    fn decorator_type(
         &mut self,
         expr: &luanext_parser::ast::statement::DecoratorExpression<'arena>,
-    ) -> Result<(), TypeCheckError> {
+        span: Span,
+    ) -> Result<Option<Type<'arena>>, TypeCheckError> {
+        use crate::cli::diagnostics::{error_codes, Diagnostic};
         use luanext_parser::ast::statement::DecoratorExpression;
 
         match expr {
             DecoratorExpression::Identifier(name) => {
-                // Verify the decorator identifier exists (could be a function or imported decorator)
-                // For now, we allow any identifier - full validation would check it's a valid decorator function
-                let name_str = self.interner.resolve(name.node);
-                if self.symbol_table.lookup(&name_str).is_none() {
-                    // It's okay if it doesn't exist - it might be a built-in decorator like @readonly, @sealed
-                    // We'll allow it through for now
+                if let Some(typ) = self.builtin_decorator_type(name.node) {
+                    return Ok(Some(typ));
                 }
-                Ok(())
+                let name_str = self.interner.resolve(name.node);
+                Ok(self
+                    .symbol_table
+                    .lookup(&name_str)
+                    .map(|symbol| symbol.typ.clone()))
             }
             DecoratorExpression::Call {
                 callee, arguments, ..
             } => {
-                // Check the callee
-                self.check_decorator_expression(callee)?;
+                let callee_type = self.decorator_type(callee, span)?;
 
                 // Type check all arguments
+                let mut arg_types = Vec::with_capacity(arguments.len());
                 for arg in arguments.iter() {
-                    self.infer_expression_type(arg)?;
+                    arg_types.push(self.infer_expression_type(arg)?);
                 }
 
-                Ok(())
+                match callee_type.as_ref().map(|t| &t.kind) {
+                    Some(TypeKind::Function(func)) => {
+                        self.check_decorator_arguments(func, arguments, &arg_types, span);
+                        Ok(Some(func.return_type.clone()))
+                    }
+                    None | Some(TypeKind::Primitive(PrimitiveType::Unknown)) => Ok(None),
+                    Some(_) => {
+                        self.diagnostic_handler.report(Diagnostic::error_with_code(
+                            span,
+                            error_codes::NOT_CALLABLE,
+                            "Decorator factory is not callable",
+                        ));
+                        Ok(None)
+                    }
+                }
             }
-            DecoratorExpression::Member { object, .. } => {
-                // Check the object part
-                self.check_decorator_expression(object)?;
-                Ok(())
+            DecoratorExpression::Member {
+                object, property, ..
+            } => {
+                let Some(object_type) = self.decorator_type(object, span)? else {
+                    return Ok(None);
+                };
+                // Only tables and namespaces are checked; class statics and
+                // other values stay untyped
+                let object_type = self.deep_resolve_type(&object_type);
+                if !matches!(object_type.kind, TypeKind::Object(_)) {
+                    return Ok(None);
+                }
+                match Self::member_value_type(&object_type, property.node) {
+                    Some(typ) => Ok(Some(typ)),
+                    None => {
+                        self.diagnostic_handler.report(Diagnostic::error_with_code(
+                            span,
+                            error_codes::PROPERTY_NOT_FOUND,
+                            format!(
+                                "Property '{}' does not exist on type '{}'",
+                                self.interner.resolve(property.node),
+                                self.type_to_string(&object_type)
+                            ),
+                        ));
+                        Ok(None)
+                    }
+                }
+            }
+        }
+    }
+
+    /// Check decorator factory arguments against the factory's parameters.
+    fn check_decorator_arguments(
+        &mut self,
+        func: &FunctionType<'arena>,
+        arguments: &[Expression<'arena>],
+        arg_types: &[Type<'arena>],
+        span: Span,
+    ) {
+        use crate::cli::diagnostics::{error_codes, Diagnostic};
+
+        let params = func.parameters;
+        let required = params
+            .iter()
+            .filter(|p| !p.is_optional && !p.is_rest && p.default.is_none())
+            .count();
+        let rest = params.last().filter(|p| p.is_rest);
+        if arguments.len() < required || (rest.is_none() && arguments.len() > params.len()) {
+            self.diagnostic_handler.report(Diagnostic::error_with_code(
+                span,
+                error_codes::WRONG_ARG_COUNT,
+                format!(
+                    "Decorator factory expects {} argument(s), found {}",
+                    required,
+                    arguments.len()
+                ),
+            ));
+            return;
+        }
+
+        // Generic factories are not instantiated here
+        if func.type_parameters.is_some() {
+            return;
+        }
+
+        for (i, (arg, arg_type)) in arguments.iter().zip(arg_types).enumerate() {
+            let Some(param_type) = params
+                .get(i)
+                .filter(|p| !p.is_rest)
+                .or(rest)
+                .and_then(|p| p.type_annotation.as_ref())
+            else {
+                continue;
+            };
+            if !TypeCompatibility::is_assignable_with_cache(
+                arg_type,
+                param_type,
+                &mut self.type_relation_cache,
+            ) {
+                self.diagnostic_handler.report(Diagnostic::error_with_code(
+                    arg.span,
+                    error_codes::TYPE_MISMATCH,
+                    format!(
+                        "Decorator argument {} has type '{}' which is not assignable to parameter type '{}'",
+                        i + 1,
+                        self.type_to_string(arg_type),
+                        self.type_to_string(param_type)
+                    ),
+                ));
+            }
+        }
+    }
+
+    /// Apply a decorator to a target of type `target`.
+    ///
+    /// The decorator must be callable with the target as its first argument.
+    /// Returns the decorator's return type when it is compatible with the target.
+    fn apply_decorator(
+        &mut self,
+        decorator_type: &Type<'arena>,
+        target: &Type<'arena>,
+        span: Span,
+    ) -> Option<Type<'arena>> {
+        use crate::cli::diagnostics::{error_codes, Diagnostic};
+
+        let func = match &decorator_type.kind {
+            TypeKind::Function(func) => func,
+            TypeKind::Primitive(PrimitiveType::Unknown) => return None,
+            _ => {
+                self.diagnostic_handler.report(Diagnostic::error_with_code(
+                    span,
+                    error_codes::NOT_CALLABLE,
+                    format!(
+                        "Decorator of type '{}' is not callable",
+                        self.type_to_string(decorator_type)
+                    ),
+                ));
+                return None;
+            }
+        };
+
+        // Generic decorators are not instantiated here; accept any target
+        if func.type_parameters.is_some() {
+            return None;
+        }
+
+        let Some(param) = func.parameters.first() else {
+            self.diagnostic_handler.report(Diagnostic::error_with_code(
+                span,
+                error_codes::WRONG_ARG_COUNT,
+                "Decorator must accept the decorated declaration as its first parameter",
+            ));
+            return None;
+        };
+        if let Some(param_type) = &param.type_annotation {
+            if !TypeCompatibility::is_assignable_with_cache(
+                target,
+                param_type,
+                &mut self.type_relation_cache,
+            ) {
+                self.diagnostic_handler.report(Diagnostic::error_with_code(
+                    span,
+                    error_codes::TYPE_MISMATCH,
+                    format!(
+                        "Decorator expects '{}' but is applied to a declaration of type '{}'",
+                        self.type_to_string(param_type),
+                        self.type_to_string(target)
+                    ),
+                ));
+                return None;
             }
         }
+
+        let return_type = func.return_type;
+        if matches!(
+            return_type.kind,
+            TypeKind::Primitive(PrimitiveType::Void | PrimitiveType::Unknown)
+        ) {
+            return None;
+        }
+        TypeCompatibility::is_assignable_with_cache(
+            return_type,
+            target,
+            &mut self.type_relation_cache,
+        )
+        .then(|| return_type.clone())
+    }
+
+    /// Replace the registered kind of a member of the class being checked.
+    fn update_current_class_member(&mut self, member_name: &str, kind: ClassMemberKind<'arena>) {
+        if let Some(class_ctx) = self.access_control.get_current_class() {
+            let class_name = class_ctx.name.clone();
+            self.access_control
+                .update_member_kind(&class_name, member_name, kind);
+        }
     }
 
     /// Check class property
@@ -1783,8 +3195,33 @@ impl<'a, 'arena> TypeChecker<'a, 'arena> {
         &mut self,
         prop: &PropertyDeclaration<'arena>,
     ) -> Result<(), TypeCheckError> {
+        // `typeof` queries in the annotation are resolved in the class scope
+        let declared = if contains_type_query(&prop.type_annotation) {
+            let declared = self
+                .evaluate_annotation(&prop.type_annotation)
+                .map_err(|e| TypeCheckError::new(e, prop.span))?;
+            let name = self.interner.resolve(prop.name.node);
+            self.update_current_class_member(
+                &name,
+                ClassMemberKind::Property {
+                    type_annotation: declared.clone(),
+                },
+            );
+            declared
+        } else {
+            prop.type_annotation.clone()
+        };
+
         // Check decorators
-        self.check_decorators(prop.decorators)?;
+        if let Some(decorated) = self.check_decorators(prop.decorators, &declared)? {
+            let name = self.interner.resolve(prop.name.node);
+            self.update_current_class_member(
+                &name,
+                ClassMemberKind::Property {
+                    type_annotation: decorated,
+                },
+            );
+        }
 
         // Check initializer if present
         if let Some(initializer) = &prop.initializer {
@@ -1793,7 +3230,7 @@ impl<'a, 'arena> TypeChecker<'a, 'arena> {
             // Verify initializer type is assignable to declared type
             if !TypeCompatibility::is_assignable_with_cache(
                 &init_type,
-                &prop.type_annotation,
+                &declared,
                 &mut self.type_relation_cache,
             ) {
                 return Err(TypeCheckError::new(
@@ -1841,6 +3278,19 @@ impl<'a, 'arena> TypeChecker<'a, 'arena> {
                 self.symbol_table
                     .declare(symbol)
                     .map_err(|e| TypeCheckError::new(e, ctor.span))?;
+
+                // Declare 'super' as the parent class in derived constructors
+                if let Some(extends_type) = class_ctx.extends_type.clone() {
+                    let symbol = crate::utils::symbol_table::Symbol::new(
+                        "super".to_string(),
+                        crate::utils::symbol_table::SymbolKind::Parameter,
+                        extends_type,
+                        ctor.span,
+                    );
+                    self.symbol_table
+                        .declare(symbol)
+                        .map_err(|e| TypeCheckError::new(e, ctor.span))?;
+                }
             }
 
             // Declare parameters
@@ -1848,7 +3298,7 @@ impl<'a, 'arena> TypeChecker<'a, 'arena> {
                 let param_type = if let Some(type_ann) = &param.type_annotation {
                     // Evaluate the type annotation to resolve any type references
                     let evaluated = self
-                        .evaluate_type(type_ann)
+                        .evaluate_annotation(type_ann)
                         .map_err(|e| TypeCheckError::new(e, param.span))
                         .unwrap_or_else(|_| type_ann.clone()); // Fall back to unevaluated if evaluation fails
 
@@ -1867,7 +3317,17 @@ impl<'a, 'arena> TypeChecker<'a, 'arena> {
             }
 
             // Check constructor body
-            self.check_block(&ctor.body)?;
+            let parent = self
+                .access_control
+                .get_current_class()
+                .as_ref()
+                .and_then(|ctx| ctx.parent.clone());
+            match parent {
+                Some(parent) if self.options.strict_property_initialization => {
+                    self.check_derived_constructor_body(ctor, &parent)?
+                }
+                _ => self.check_block(&ctor.body)?,
+            }
 
             Ok(())
         })();
@@ -1878,34 +3338,246 @@ impl<'a, 'arena> TypeChecker<'a, 'arena> {
         result
     }
 
-    /// Check class method
-    fn check_class_method(
+    /// Check the body of a derived class constructor under `strictPropertyInitialization`.
+    ///
+    /// The constructor must call `super(...)` before `self` is used, either at its
+    /// top level or in a nested block, and the arguments must match the parent
+    /// constructor.
+    fn check_derived_constructor_body(
         &mut self,
-        method: &MethodDeclaration<'arena>,
+        ctor: &ConstructorDeclaration<'arena>,
+        parent_name: &str,
     ) -> Result<(), TypeCheckError> {
-        // Check decorators
-        self.check_decorators(method.decorators)?;
+        use crate::cli::diagnostics::{error_codes, Diagnostic};
 
-        // Check override keyword if present
-        if method.is_override {
-            self.check_method_override(method)?;
-        } else if let Some(class_context) = self.access_control.get_current_class() {
-            // Check if method shadows a parent method without override keyword
-            if let Some(parent_name) = &class_context.parent {
-                if let Some(parent_members) = self.access_control.get_class_members(parent_name) {
-                    let method_name = self.interner.resolve(method.name.node);
-                    if parent_members.iter().any(|m| m.name == method_name) {
-                        self.diagnostic_handler.warning(
-                            method.span,
-                            &format!(
-                                "Method '{}' overrides a method from parent class '{}' but is missing the 'override' keyword",
-                                method_name,
-                                parent_name
-                            ),
-                        );
-                    }
+        self.symbol_table.enter_scope();
+        let mut first_error: Option<TypeCheckError> = None;
+        let mut super_called = false;
+        let mut reported_self_use = false;
+
+        for stmt in ctor.body.statements.iter() {
+            let super_calls = if super_called {
+                Vec::new()
+            } else {
+                control_flow::nested_super_calls(stmt)
+            };
+
+            if !super_called && super_calls.is_empty() && !reported_self_use {
+                if let Some(self_span) = control_flow::statement_self_use(stmt) {
+                    self.diagnostic_handler.report(
+                        Diagnostic::error_with_code(
+                            self_span,
+                            error_codes::SELF_BEFORE_SUPER,
+                            "'self' cannot be used before 'super(...)' is called in a derived class constructor",
+                        ),
+                    );
+                    reported_self_use = true;
                 }
             }
+
+            let result = self.check_statement(stmt);
+            if let Err(e) = result {
+                if first_error.is_none() {
+                    first_error = Some(e);
+                }
+            }
+
+            if !super_calls.is_empty() {
+                super_called = true;
+                for args in super_calls {
+                    self.check_super_call_arguments(parent_name, args, ctor.span);
+                }
+            }
+        }
+        self.symbol_table.exit_scope();
+
+        if !super_called {
+            self.diagnostic_handler.report(
+                Diagnostic::error_with_code(
+                    ctor.span,
+                    error_codes::MISSING_SUPER_CALL,
+                    format!(
+                        "Constructor of a class derived from '{}' must call 'super(...)'",
+                        parent_name
+                    ),
+                )
+                .with_suggestion(
+                    ctor.span,
+                    "super()".to_string(),
+                    "Call the parent constructor first",
+                ),
+            );
+        }
+
+        match first_error {
+            Some(err) => Err(err),
+            None => Ok(()),
+        }
+    }
+
+    /// Check `super(...)` arguments against the parent class constructor.
+    fn check_super_call_arguments(
+        &mut self,
+        parent_name: &str,
+        args: &[Argument<'arena>],
+        span: Span,
+    ) {
+        use crate::cli::diagnostics::{error_codes, Diagnostic};
+
+        // (parameter type, required, rest) for each parent constructor parameter
+        let params: Vec<(Option<Type<'arena>>, bool, bool)> =
+            if let Some(params) = self.type_env.get_constructor_params(parent_name) {
+                params
+                    .iter()
+                    .map(|p| {
+                        (
+                            p.type_annotation.clone(),
+                            !p.is_optional && !p.is_rest && p.default.is_none(),
+                            p.is_rest,
+                        )
+                    })
+                    .collect()
+            } else if let Some(params) = self.type_env.get_class_constructor(parent_name) {
+                params
+                    .iter()
+                    .map(|p| (Some(p.type_annotation.clone()), true, false))
+                    .collect()
+            } else {
+                return;
+            };
+
+        let required = params.iter().filter(|(_, required, _)| *required).count();
+        let has_rest = params.last().is_some_and(|(_, _, rest)| *rest);
+        if args.len() < required || (!has_rest && args.len() > params.len()) {
+            self.diagnostic_handler.report(Diagnostic::error_with_code(
+                span,
+                error_codes::WRONG_ARG_COUNT,
+                format!(
+                    "Constructor of '{}' expects {} argument(s) but 'super(...)' passes {}",
+                    parent_name,
+                    if has_rest || required == params.len() {
+                        required.to_string()
+                    } else {
+                        format!("{}-{}", required, params.len())
+                    },
+                    args.len()
+                ),
+            ));
+            return;
+        }
+
+        for (i, arg) in args.iter().enumerate() {
+            let Some((Some(param_type), _, _)) = params.get(i.min(params.len() - 1)) else {
+                continue;
+            };
+            // Resolve aliases in the parent's declared parameter type
+            let param_type = match self.evaluate_annotation(param_type) {
+                Ok(evaluated) => self.deep_resolve_type(&evaluated),
+                Err(_) => param_type.clone(),
+            };
+            let Ok(arg_type) = self.infer_expression_type(&arg.value) else {
+                continue;
+            };
+            if !TypeCompatibility::is_assignable_with_cache(
+                &arg_type,
+                &param_type,
+                &mut self.type_relation_cache,
+            ) {
+                self.diagnostic_handler.report(Diagnostic::error_with_code(
+                    arg.value.span,
+                    error_codes::TYPE_MISMATCH,
+                    format!(
+                        "Argument {} of 'super(...)' has type '{}' which is not assignable to parameter type '{}'",
+                        i + 1,
+                        self.type_to_string(&arg_type),
+                        self.type_to_string(&param_type)
+                    ),
+                ));
+            }
+        }
+    }
+
+    /// Report non-nil properties that have no initializer and are not assigned on
+    /// every path through the constructor (`strictPropertyInitialization`).
+    fn check_property_initialization(&self, class_decl: &ClassDeclaration<'arena>) {
+        use crate::cli::diagnostics::{error_codes, Diagnostic};
+
+        let constructor = class_decl.members.iter().find_map(|m| match m {
+            ClassMember::Constructor(ctor) => Some(ctor),
+            _ => None,
+        });
+        let assigned = constructor
+            .map(|ctor| {
+                control_flow::definitely_assigned_self_properties(&ctor.body, self.interner)
+            })
+            .unwrap_or_default();
+
+        for member in class_decl.members.iter() {
+            let ClassMember::Property(prop) = member else {
+                continue;
+            };
+            let name = self.interner.resolve(prop.name.node);
+            if prop.is_static
+                || prop.initializer.is_some()
+                || type_utilities::type_accepts_nil(&prop.type_annotation)
+                || assigned.contains(name.as_str())
+            {
+                continue;
+            }
+
+            let mut diagnostic = Diagnostic::error_with_code(
+                prop.span,
+                error_codes::PROPERTY_NOT_INITIALIZED,
+                format!(
+                    "Property '{}' has no initializer and is not definitely assigned in the constructor",
+                    name
+                ),
+            );
+            if let Some(ctor) = constructor {
+                diagnostic = diagnostic.with_related(ctor.span, "Constructor declared here");
+            }
+            self.diagnostic_handler.report(diagnostic);
+        }
+    }
+
+    /// Check class method
+    fn check_class_method(
+        &mut self,
+        method: &MethodDeclaration<'arena>,
+    ) -> Result<(), TypeCheckError> {
+        // Check decorators
+        let method_type =
+            Type::new(
+                TypeKind::Function(FunctionType {
+                    type_parameters: method.type_parameters,
+                    parameters: method.parameters,
+                    return_type: self.arena.alloc(method.return_type.clone().unwrap_or_else(
+                        || Type::new(TypeKind::Primitive(PrimitiveType::Void), method.span),
+                    )),
+                    throws: None,
+                    span: method.span,
+                }),
+                method.span,
+            );
+        if let Some(decorated) = self.check_decorators(method.decorators, &method_type)? {
+            if let TypeKind::Function(func) = &decorated.kind {
+                let name = self.interner.resolve(method.name.node);
+                self.update_current_class_member(
+                    &name,
+                    ClassMemberKind::Method {
+                        parameters: func.parameters.to_vec(),
+                        return_type: Some(func.return_type.clone()),
+                        is_abstract: method.is_abstract,
+                    },
+                );
+            }
+        }
+
+        // Check override discipline, then signature compatibility with the
+        // inherited member
+        let overrides_member = self.check_override_discipline(method);
+        if method.is_override && overrides_member {
+            self.check_method_override(method)?;
         }
 
         // Abstract methods don't have a body to check
@@ -1972,9 +3644,11 @@ impl<'a, 'arena> TypeChecker<'a, 'arena> {
             // Declare parameters
             for param in method.parameters.iter() {
                 let param_type = if let Some(type_ann) = &param.type_annotation {
+                    let type_ann = self.substitute_self_in_class(type_ann, method.span);
+
                     // Evaluate the type annotation to resolve any type references (e.g., T, U in generic methods)
                     let evaluated = self
-                        .evaluate_type(type_ann)
+                        .evaluate_annotation(&type_ann)
                         .map_err(|e| TypeCheckError::new(e, param.span))
                         .unwrap_or_else(|_| type_ann.clone()); // Fall back to unevaluated if evaluation fails
 
@@ -1993,8 +3667,12 @@ impl<'a, 'arena> TypeChecker<'a, 'arena> {
                 )?;
             }
 
-            // Set current function return type for return statement checking
-            self.current_function_return_type = method.return_type.clone();
+            // Set current function return type for return statement checking.
+            // Inside the body, `Self` is the enclosing class.
+            self.current_function_return_type = method
+                .return_type
+                .as_ref()
+                .map(|rt| self.substitute_self_in_class(rt, method.span));
 
             // Check method body
             if let Some(body) = &method.body {
@@ -2019,13 +3697,38 @@ impl<'a, 'arena> TypeChecker<'a, 'arena> {
         result
     }
 
+    /// Replace the polymorphic `Self` type with a reference to the class currently
+    /// being checked. Outside a class the type is returned unchanged.
+    fn substitute_self_in_class(&self, typ: &Type<'arena>, span: Span) -> Type<'arena> {
+        let Some(class_ctx) = self.access_control.get_current_class() else {
+            return typ.clone();
+        };
+        let class_type = Type::new(
+            TypeKind::Reference(TypeReference {
+                name: luanext_parser::ast::Spanned::new(
+                    self.interner.intern(&class_ctx.name),
+                    span,
+                ),
+                type_arguments: None,
+                span,
+            }),
+            span,
+        );
+        crate::types::generics::substitute_self_type(
+            self.arena,
+            typ,
+            self.interner.intern(crate::types::generics::SELF_TYPE_NAME),
+            &class_type,
+        )
+    }
+
     /// Check class getter
     fn check_class_getter(
         &mut self,
         getter: &GetterDeclaration<'arena>,
     ) -> Result<(), TypeCheckError> {
         // Check decorators
-        self.check_decorators(getter.decorators)?;
+        self.check_decorators(getter.decorators, &getter.return_type)?;
 
         // Enter getter scope
         self.symbol_table.enter_scope();
@@ -2078,7 +3781,11 @@ impl<'a, 'arena> TypeChecker<'a, 'arena> {
         setter: &SetterDeclaration<'arena>,
     ) -> Result<(), TypeCheckError> {
         // Check decorators
-        self.check_decorators(setter.decorators)?;
+        let setter_target =
+            setter.parameter.type_annotation.clone().unwrap_or_else(|| {
+                Type::new(TypeKind::Primitive(PrimitiveType::Unknown), setter.span)
+            });
+        self.check_decorators(setter.decorators, &setter_target)?;
 
         // Enter setter scope
         self.symbol_table.enter_scope();
@@ -2113,7 +3820,7 @@ impl<'a, 'arena> TypeChecker<'a, 'arena> {
         let param_type = if let Some(type_ann) = &setter.parameter.type_annotation {
             // Evaluate to resolve type references
             let evaluated = self
-                .evaluate_type(type_ann)
+                .evaluate_annotation(type_ann)
                 .map_err(|e| TypeCheckError::new(e, setter.parameter.span))
                 .unwrap_or_else(|_| type_ann.clone());
             // Deep resolve to handle nested types
@@ -2266,6 +3973,134 @@ impl<'a, 'arena> TypeChecker<'a, 'arena> {
         type_utilities::operator_kind_name(op)
     }
 
+    /// Check a method against the member it redeclares from an ancestor class.
+    ///
+    /// Reports overriding a `final` member, `override` without an inherited
+    /// member and, under `noImplicitOverride`, a missing `override` modifier.
+    /// Returns whether an inherited member was found.
+    fn check_override_discipline(&self, method: &MethodDeclaration<'arena>) -> bool {
+        use crate::cli::diagnostics::{error_codes, Diagnostic};
+
+        let Some(class_ctx) = self.access_control.get_current_class() else {
+            return false;
+        };
+        let method_name = self.interner.resolve(method.name.node);
+        let inherited = class_ctx
+            .parent
+            .as_ref()
+            .and_then(|parent| self.find_inherited_member(parent, &method_name));
+
+        let Some((owner, is_final)) = inherited else {
+            if method.is_override {
+                let message = match &class_ctx.parent {
+                    Some(_) => format!(
+                        "Method '{}' marked as override but parent class does not have this method",
+                        method_name
+                    ),
+                    None => format!(
+                        "Method '{}' uses override but class '{}' has no parent class",
+                        method_name, class_ctx.name
+                    ),
+                };
+                let mut diagnostic = Diagnostic::error_with_code(
+                    method.span,
+                    error_codes::OVERRIDE_WITHOUT_BASE,
+                    message,
+                );
+                if let Some(extends_type) = &class_ctx.extends_type {
+                    diagnostic =
+                        diagnostic.with_related(extends_type.span, "Parent class named here");
+                }
+                self.diagnostic_handler.report(diagnostic);
+            }
+            return false;
+        };
+
+        let original = self.type_env.get_member_span(&owner, &method_name);
+        let diagnostic = if is_final {
+            Diagnostic::error_with_code(
+                method.span,
+                error_codes::OVERRIDE_OF_FINAL_MEMBER,
+                format!(
+                    "Cannot override final method '{}' from class '{}'",
+                    method_name, owner
+                ),
+            )
+        } else if method.is_override {
+            return true;
+        } else if self.options.no_implicit_override {
+            Diagnostic::error_with_code(
+                method.span,
+                error_codes::MISSING_OVERRIDE_MODIFIER,
+                format!(
+                    "Method '{}' overrides a member of class '{}' and must be marked 'override'",
+                    method_name, owner
+                ),
+            )
+        } else {
+            self.diagnostic_handler.warning(
+                method.span,
+                &format!(
+                    "Method '{}' overrides a method from parent class '{}' but is missing the 'override' keyword",
+                    method_name, owner
+                ),
+            );
+            return true;
+        };
+
+        let diagnostic = match original {
+            Some(span) if is_final => {
+                diagnostic.with_related(span, format!("'{}' is declared final here", method_name))
+            }
+            Some(span) => diagnostic.with_related(span, "Overridden member declared here"),
+            None => diagnostic,
+        };
+        self.diagnostic_handler.report(diagnostic);
+        true
+    }
+
+    /// Find a member in `class_name` or its ancestors, returning the declaring
+    /// class and whether the member is final.
+    fn find_inherited_member(&self, class_name: &str, member_name: &str) -> Option<(String, bool)> {
+        let mut current = class_name.to_string();
+        loop {
+            if let Some(member) = self
+                .access_control
+                .get_class_members(&current)
+                .and_then(|members| members.iter().find(|m| m.name == member_name))
+            {
+                return Some((current, member.is_final));
+            }
+            current = self.access_control.get_parent_class(&current)?;
+        }
+    }
+
+    /// Report extending a `final` class, pointing at the parent's declaration.
+    fn check_extends_final_class(&self, extends_type: &Type<'arena>) {
+        use crate::cli::diagnostics::{error_codes, Diagnostic};
+
+        let TypeKind::Reference(type_ref) = &extends_type.kind else {
+            return;
+        };
+        let parent_name = self.interner.resolve(type_ref.name.node);
+        if !self.access_control.is_class_final(&parent_name) {
+            return;
+        }
+
+        let mut diagnostic = Diagnostic::error_with_code(
+            extends_type.span,
+            error_codes::EXTENDS_FINAL_CLASS,
+            format!("Cannot extend final class '{}'", parent_name),
+        );
+        if let Some(symbol) = self.symbol_table.lookup(&parent_name) {
+            diagnostic = diagnostic.with_related(
+                symbol.span,
+                format!("'{}' is declared final here", parent_name),
+            );
+        }
+        self.diagnostic_handler.report(diagnostic);
+    }
+
     /// Check that an override method properly overrides a parent method
     fn check_method_override(
         &self,
@@ -2329,17 +4164,73 @@ impl<'a, 'arena> TypeChecker<'a, 'arena> {
         }
     }
 
+    /// The configured target Lua version, with `Auto` detected once
+    fn target(&self) -> LuaVersion {
+        *self
+            .effective_target
+            .get_or_init(|| self.options.target.effective())
+    }
+
     /// Infer the type of an expression
     /// Delegates to TypeInferrer visitor
     fn infer_expression_type(
         &mut self,
         expr: &Expression<'arena>,
     ) -> Result<Type<'arena>, TypeCheckError> {
+        let target = self.target();
+        let ctx = crate::visitors::InferenceContext {
+            access_control: &self.access_control,
+            interner: self.interner,
+            diagnostic_handler: &self.diagnostic_handler,
+            class_type_params: &self.class_type_params,
+            target,
+            c_declarations: &self.c_declarations,
+            coroutine: self.coroutine_signatures.last(),
+            reported_deprecations: &self.reported_deprecations,
+            module_registry: self.module_registry.as_ref(),
+            module_resolver: self.module_resolver.as_ref(),
+            current_module_id: self.current_module_id.as_ref(),
+            lazy_callback: self.lazy_callback.as_deref(),
+            required_modules: &self.required_modules,
+            reported_plain_lua_requires: &self.reported_plain_lua_requires,
+        };
+        let mut inferrer = TypeInferrer::new(
+            self.arena,
+            &mut self.symbol_table,
+            &mut self.type_env,
+            self.narrowing.get_context_mut(),
+            &ctx,
+        );
+        let result = inferrer.infer_expression(expr);
+        self.record_required_modules();
+        result
+    }
+
+    /// Infer an expression with contextual typing from its declared type: object
+    /// literal methods get `receiver` as `self`, and `const_context` infers
+    /// literals as if asserted `as const`
+    fn infer_expression_type_in_context(
+        &mut self,
+        expr: &Expression<'arena>,
+        receiver: Option<Type<'arena>>,
+        const_context: bool,
+    ) -> Result<Type<'arena>, TypeCheckError> {
+        let target = self.target();
         let ctx = crate::visitors::InferenceContext {
             access_control: &self.access_control,
             interner: self.interner,
             diagnostic_handler: &self.diagnostic_handler,
             class_type_params: &self.class_type_params,
+            target,
+            c_declarations: &self.c_declarations,
+            coroutine: self.coroutine_signatures.last(),
+            reported_deprecations: &self.reported_deprecations,
+            module_registry: self.module_registry.as_ref(),
+            module_resolver: self.module_resolver.as_ref(),
+            current_module_id: self.current_module_id.as_ref(),
+            lazy_callback: self.lazy_callback.as_deref(),
+            required_modules: &self.required_modules,
+            reported_plain_lua_requires: &self.reported_plain_lua_requires,
         };
         let mut inferrer = TypeInferrer::new(
             self.arena,
@@ -2348,7 +4239,252 @@ impl<'a, 'arena> TypeChecker<'a, 'arena> {
             self.narrowing.get_context_mut(),
             &ctx,
         );
-        inferrer.infer_expression(expr)
+        if let Some(receiver) = receiver {
+            inferrer = inferrer.with_object_receiver(receiver);
+        }
+        if const_context {
+            inferrer = inferrer.with_const_context();
+        }
+        let result = inferrer.infer_expression(expr);
+        self.record_required_modules();
+        result
+    }
+
+    /// Add the modules loaded by the `require` calls just inferred to the
+    /// module dependencies, once each, as an expression may be inferred again
+    fn record_required_modules(&mut self) {
+        for dependency in self.required_modules.get_mut().drain(..) {
+            if !self.module_dependencies.contains(&dependency) {
+                self.module_dependencies.push(dependency);
+            }
+        }
+    }
+
+    /// Find the `T` of a `ThisType<T>` marker in an annotation (directly or in an intersection)
+    fn contextual_this_type(&self, typ: &Type<'arena>) -> Option<Type<'arena>> {
+        match &typ.kind {
+            TypeKind::Reference(type_ref)
+                if self.interner.resolve(type_ref.name.node) == "ThisType" =>
+            {
+                match type_ref.type_arguments {
+                    Some([this_type]) => Some(this_type.clone()),
+                    _ => None,
+                }
+            }
+            TypeKind::Intersection(members) => members
+                .iter()
+                .find_map(|member| self.contextual_this_type(member)),
+            TypeKind::Parenthesized(inner) => self.contextual_this_type(inner),
+            _ => None,
+        }
+    }
+
+    /// Evaluate a type annotation in the current scope.
+    ///
+    /// `typeof` queries need the inferrer (and so mutable access to the symbol
+    /// table), so they are resolved here before delegating to `evaluate_type`.
+    fn evaluate_annotation(&mut self, typ: &Type<'arena>) -> Result<Type<'arena>, String> {
+        let resolved = self.resolve_type_queries(typ)?;
+        let evaluated = self.evaluate_type(&resolved)?;
+        if self.target().has_integer_subtype() {
+            Ok(evaluated)
+        } else {
+            Ok(type_utilities::integer_as_number(self.arena, &evaluated))
+        }
+    }
+
+    /// Replace `typeof expr` nodes in a type with the inferred type of `expr`
+    fn resolve_type_queries(&mut self, typ: &Type<'arena>) -> Result<Type<'arena>, String> {
+        if !contains_type_query(typ) {
+            return Ok(typ.clone());
+        }
+
+        let kind = match &typ.kind {
+            TypeKind::TypeQuery(expr) => return self.infer_type_query(expr, typ.span),
+            TypeKind::Union(members) => {
+                let resolved = self.resolve_type_query_list(members)?;
+                TypeKind::Union(resolved)
+            }
+            TypeKind::Intersection(members) => {
+                let resolved = self.resolve_type_query_list(members)?;
+                TypeKind::Intersection(resolved)
+            }
+            TypeKind::Tuple(elements) => {
+                let resolved = self.resolve_type_query_list(elements)?;
+                TypeKind::Tuple(resolved)
+            }
+            TypeKind::Array(elem) => {
+                let resolved = self.resolve_type_queries(elem)?;
+                TypeKind::Array(self.arena.alloc(resolved))
+            }
+            TypeKind::Nullable(inner) => {
+                let resolved = self.resolve_type_queries(inner)?;
+                TypeKind::Nullable(self.arena.alloc(resolved))
+            }
+            TypeKind::Parenthesized(inner) => {
+                let resolved = self.resolve_type_queries(inner)?;
+                TypeKind::Parenthesized(self.arena.alloc(resolved))
+            }
+            TypeKind::KeyOf(operand) => {
+                let resolved = self.resolve_type_queries(operand)?;
+                TypeKind::KeyOf(self.arena.alloc(resolved))
+            }
+            TypeKind::IndexAccess(object, index) => {
+                let object = self.resolve_type_queries(object)?;
+                let index = self.resolve_type_queries(index)?;
+                TypeKind::IndexAccess(self.arena.alloc(object), self.arena.alloc(index))
+            }
+            // Type arguments, e.g. `ReturnType<typeof(greet)>`
+            TypeKind::Reference(type_ref) => {
+                let Some(args) = type_ref.type_arguments else {
+                    return Ok(typ.clone());
+                };
+                let resolved = self.resolve_type_query_list(args)?;
+                TypeKind::Reference(TypeReference {
+                    type_arguments: Some(resolved),
+                    ..type_ref.clone()
+                })
+            }
+            TypeKind::Object(obj) => {
+                let members = obj
+                    .members
+                    .iter()
+                    .map(|member| {
+                        Ok(match member {
+                            ObjectTypeMember::Property(prop) => {
+                                ObjectTypeMember::Property(PropertySignature {
+                                    type_annotation: self
+                                        .resolve_type_queries(&prop.type_annotation)?,
+                                    ..prop.clone()
+                                })
+                            }
+                            ObjectTypeMember::Method(method) => {
+                                ObjectTypeMember::Method(MethodSignature {
+                                    parameters: self
+                                        .resolve_parameter_type_queries(method.parameters)?,
+                                    return_type: self.resolve_type_queries(&method.return_type)?,
+                                    ..method.clone()
+                                })
+                            }
+                            ObjectTypeMember::Index(index) => {
+                                ObjectTypeMember::Index(IndexSignature {
+                                    value_type: self.resolve_type_queries(&index.value_type)?,
+                                    ..index.clone()
+                                })
+                            }
+                        })
+                    })
+                    .collect::<Result<Vec<_>, String>>()?;
+                TypeKind::Object(ObjectType {
+                    members: self.arena.alloc_slice_fill_iter(members),
+                    span: obj.span,
+                })
+            }
+            TypeKind::Function(func) => {
+                let parameters = self.resolve_parameter_type_queries(func.parameters)?;
+                let return_type = self.resolve_type_queries(func.return_type)?;
+                TypeKind::Function(FunctionType {
+                    parameters,
+                    return_type: self.arena.alloc(return_type),
+                    ..func.clone()
+                })
+            }
+            TypeKind::Conditional(conditional) => {
+                let check_type = self.resolve_type_queries(conditional.check_type)?;
+                let extends_type = self.resolve_type_queries(conditional.extends_type)?;
+                let true_type = self.resolve_type_queries(conditional.true_type)?;
+                let false_type = self.resolve_type_queries(conditional.false_type)?;
+                TypeKind::Conditional(ConditionalType {
+                    check_type: self.arena.alloc(check_type),
+                    extends_type: self.arena.alloc(extends_type),
+                    true_type: self.arena.alloc(true_type),
+                    false_type: self.arena.alloc(false_type),
+                    span: conditional.span,
+                })
+            }
+            TypeKind::Mapped(mapped) => {
+                let in_type = self.resolve_type_queries(mapped.in_type)?;
+                let value_type = self.resolve_type_queries(mapped.value_type)?;
+                TypeKind::Mapped(MappedType {
+                    in_type: self.arena.alloc(in_type),
+                    value_type: self.arena.alloc(value_type),
+                    ..mapped.clone()
+                })
+            }
+            _ => return Ok(typ.clone()),
+        };
+        Ok(Type::new(kind, typ.span))
+    }
+
+    fn resolve_parameter_type_queries(
+        &mut self,
+        parameters: &'arena [Parameter<'arena>],
+    ) -> Result<&'arena [Parameter<'arena>], String> {
+        if !parameters_contain_type_query(parameters) {
+            return Ok(parameters);
+        }
+        let resolved = parameters
+            .iter()
+            .map(|param| {
+                Ok(Parameter {
+                    type_annotation: param
+                        .type_annotation
+                        .as_ref()
+                        .map(|t| self.resolve_type_queries(t))
+                        .transpose()?,
+                    ..param.clone()
+                })
+            })
+            .collect::<Result<Vec<_>, String>>()?;
+        Ok(self.arena.alloc_slice_fill_iter(resolved))
+    }
+
+    fn resolve_type_query_list(
+        &mut self,
+        types: &[Type<'arena>],
+    ) -> Result<&'arena [Type<'arena>], String> {
+        let resolved = types
+            .iter()
+            .map(|t| self.resolve_type_queries(t))
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(self.arena.alloc_slice_fill_iter(resolved))
+    }
+
+    /// Infer the type of the expression in `typeof expr` in the current scope
+    fn infer_type_query(
+        &mut self,
+        expr: &Expression<'arena>,
+        span: Span,
+    ) -> Result<Type<'arena>, String> {
+        use crate::cli::diagnostics::{error_codes, Diagnostic};
+
+        if let Some(root) = query_root_name(expr, self.interner) {
+            if self.declaring_names.contains(&root) {
+                self.diagnostic_handler.report(Diagnostic::error_with_code(
+                    span,
+                    error_codes::CIRCULAR_TYPE,
+                    format!(
+                        "'{}' is referenced directly or indirectly in its own type annotation",
+                        root
+                    ),
+                ));
+                return Ok(Type::new(TypeKind::Primitive(PrimitiveType::Unknown), span));
+            }
+        }
+
+        match self.infer_expression_type(expr) {
+            Ok(typ) => Ok(typ),
+            Err(err) => {
+                // Fall back to type-level names for bare identifiers
+                if let ExpressionKind::Identifier(name_id) = &expr.kind {
+                    let name = self.interner.resolve(*name_id);
+                    if let Some(typ) = self.type_env.lookup_type(&name) {
+                        return Ok(typ.clone());
+                    }
+                }
+                Err(format!("Cannot resolve typeof: {}", err.message))
+            }
+        }
     }
 
     /// Evaluate special type constructs (keyof, mapped types, conditional types, etc.)
@@ -2377,54 +4513,6 @@ impl<'a, 'arena> TypeChecker<'a, 'arena> {
                 use crate::types::utility_types::evaluate_template_literal_type;
                 evaluate_template_literal_type(self.arena, template, &self.type_env, self.interner)
             }
-            TypeKind::TypeQuery(expr) => {
-                // typeof(expression) - Look up the type of the expression
-                // For identifiers, we can look them up directly in the type environment
-                use luanext_parser::ast::expression::ExpressionKind;
-                match &expr.kind {
-                    ExpressionKind::Identifier(name_id) => {
-                        let name = self.interner.resolve(*name_id);
-                        match self.type_env.lookup_type(&name) {
-                            Some(t) => Ok(t.clone()),
-                            None => match self.symbol_table.lookup(&name) {
-                                Some(symbol) => Ok(symbol.typ.clone()),
-                                None => {
-                                    Err(format!("Cannot resolve typeof for identifier '{}'", name))
-                                }
-                            },
-                        }
-                    }
-                    ExpressionKind::Call(callee, _args, _type_args) => {
-                        // For function calls like typeof(getNumber()),
-                        // look up the return type of the function
-                        if let ExpressionKind::Identifier(name_id) = &callee.kind {
-                            let name = self.interner.resolve(*name_id);
-                            match self.symbol_table.lookup(&name) {
-                                Some(symbol) => {
-                                    if let TypeKind::Function(func) = &symbol.typ.kind {
-                                        Ok((*func.return_type).clone())
-                                    } else {
-                                        Ok(Type::new(
-                                            TypeKind::Primitive(PrimitiveType::Unknown),
-                                            typ.span,
-                                        ))
-                                    }
-                                }
-                                None => Err(format!("Cannot resolve typeof for call '{}'", name)),
-                            }
-                        } else {
-                            Ok(Type::new(
-                                TypeKind::Primitive(PrimitiveType::Unknown),
-                                typ.span,
-                            ))
-                        }
-                    }
-                    _ => Ok(Type::new(
-                        TypeKind::Primitive(PrimitiveType::Unknown),
-                        typ.span,
-                    )),
-                }
-            }
             TypeKind::Reference(type_ref) => {
                 // Resolve type reference using the proper resolution logic
                 // This handles utility types, generic types, and regular type aliases
@@ -2434,10 +4522,240 @@ impl<'a, 'arena> TypeChecker<'a, 'arena> {
                     Err(_) => Ok(typ.clone()), // Return the reference unresolved (might be a type parameter)
                 }
             }
+            TypeKind::IndexAccess(object, index) => {
+                self.evaluate_index_access(object, index, typ.span)
+            }
             _ => Ok(typ.clone()),
         }
     }
 
+    /// Evaluate an indexed access type `T[K]`.
+    ///
+    /// Keys are looked up in object members, class members, index signatures,
+    /// tuples (`T[1]` is the first element, as in Lua) and arrays (`T[number]`).
+    /// Unions distribute over both the key (`T["a" | "b"]`) and the object.
+    /// Accesses involving unresolved references such as type parameters are
+    /// deferred. Missing keys are reported with PROPERTY_NOT_FOUND and evaluate
+    /// to `unknown`.
+    fn evaluate_index_access(
+        &self,
+        object: &Type<'arena>,
+        index: &Type<'arena>,
+        span: Span,
+    ) -> Result<Type<'arena>, String> {
+        use crate::cli::diagnostics::{error_codes, Diagnostic};
+
+        let index_type = self.evaluate_type(index)?;
+        if let TypeKind::Union(keys) = &index_type.kind {
+            let results = keys
+                .iter()
+                .map(|key| self.evaluate_index_access(object, key, span))
+                .collect::<Result<Vec<_>, _>>()?;
+            return Ok(self.union_of(results, span));
+        }
+
+        let mut object_type = self.evaluate_type(object)?;
+        if matches!(object.kind, TypeKind::IndexAccess(..))
+            && matches!(object_type.kind, TypeKind::Reference(_))
+        {
+            // `T["a"]["b"]` - the inner access may itself yield a named type
+            object_type = self.evaluate_type(&object_type)?;
+        }
+        if let TypeKind::Union(members) = &object_type.kind {
+            let results = members
+                .iter()
+                .map(|member| self.evaluate_index_access(member, &index_type, span))
+                .collect::<Result<Vec<_>, _>>()?;
+            return Ok(self.union_of(results, span));
+        }
+
+        // Unresolved references (e.g. type parameters) stay deferred until substitution
+        let unresolved_object = match &object_type.kind {
+            TypeKind::Reference(type_ref) => self
+                .access_control
+                .get_class_members(&self.interner.resolve(type_ref.name.node))
+                .is_none(),
+            _ => false,
+        };
+        let unresolved_index = matches!(
+            index_type.kind,
+            TypeKind::Reference(_) | TypeKind::KeyOf(_) | TypeKind::IndexAccess(..)
+        );
+        if unresolved_object || unresolved_index {
+            return Ok(Type::new(
+                TypeKind::IndexAccess(
+                    self.arena.alloc(object_type.clone()),
+                    self.arena.alloc(index_type.clone()),
+                ),
+                span,
+            ));
+        }
+
+        let key = match &index_type.kind {
+            TypeKind::Literal(Literal::String(name)) => IndexKey::Name(name.clone()),
+            TypeKind::Literal(Literal::Number(n)) if n.fract() == 0.0 && *n >= 0.0 => {
+                IndexKey::Position(*n as usize)
+            }
+            TypeKind::Literal(Literal::Integer(n)) if *n >= 0 => IndexKey::Position(*n as usize),
+            TypeKind::Primitive(PrimitiveType::String) => IndexKey::AnyString,
+            TypeKind::Primitive(PrimitiveType::Number | PrimitiveType::Integer) => {
+                IndexKey::AnyNumber
+            }
+            _ => {
+                return Err(format!(
+                    "Type '{}' cannot be used as an index type",
+                    crate::utils::type_formatter::format_type_for_error(&index_type, self.interner)
+                ))
+            }
+        };
+
+        if let Some(found) = self.lookup_indexed_member(&object_type, &key, span) {
+            return Ok(found);
+        }
+
+        let key_display = match &key {
+            IndexKey::Name(name) => format!("'{}'", name),
+            IndexKey::Position(position) => position.to_string(),
+            IndexKey::AnyString => "string".to_string(),
+            IndexKey::AnyNumber => "number".to_string(),
+        };
+        self.diagnostic_handler.report(Diagnostic::error_with_code(
+            span,
+            error_codes::PROPERTY_NOT_FOUND,
+            format!(
+                "Property {} does not exist on type '{}'",
+                key_display,
+                crate::utils::type_formatter::format_type_for_error(object, self.interner)
+            ),
+        ));
+        Ok(Type::new(TypeKind::Primitive(PrimitiveType::Unknown), span))
+    }
+
+    /// Find the type of a single key in an (already evaluated) object type
+    fn lookup_indexed_member(
+        &self,
+        object_type: &Type<'arena>,
+        key: &IndexKey,
+        span: Span,
+    ) -> Option<Type<'arena>> {
+        match &object_type.kind {
+            TypeKind::Object(obj) => {
+                if let IndexKey::Name(name) = key {
+                    for member in obj.members.iter() {
+                        match member {
+                            ObjectTypeMember::Property(prop)
+                                if self.interner.resolve(prop.name.node) == *name =>
+                            {
+                                return Some(prop.type_annotation.clone());
+                            }
+                            ObjectTypeMember::Method(method)
+                                if self.interner.resolve(method.name.node) == *name =>
+                            {
+                                return Some(Type::new(
+                                    TypeKind::Function(FunctionType {
+                                        type_parameters: method.type_parameters,
+                                        parameters: method.parameters,
+                                        return_type: self.arena.alloc(method.return_type.clone()),
+                                        throws: None,
+                                        span: method.span,
+                                    }),
+                                    method.span,
+                                ));
+                            }
+                            _ => {}
+                        }
+                    }
+                }
+                let wants_number = matches!(key, IndexKey::Position(_) | IndexKey::AnyNumber);
+                let members: &'arena [ObjectTypeMember<'arena>] = obj.members;
+                members.iter().find_map(|member| match member {
+                    ObjectTypeMember::Index(index_sig) => {
+                        let matches = match index_sig.key_type {
+                            IndexKeyType::String => !wants_number,
+                            IndexKeyType::Number => wants_number,
+                        };
+                        matches.then(|| index_sig.value_type.clone())
+                    }
+                    _ => None,
+                })
+            }
+            TypeKind::Array(elem) => match key {
+                IndexKey::Position(_) | IndexKey::AnyNumber => Some((*elem).clone()),
+                _ => None,
+            },
+            TypeKind::Tuple(elems) => match key {
+                IndexKey::Position(position) => position
+                    .checked_sub(1)
+                    .and_then(|index| elems.get(index))
+                    .cloned(),
+                IndexKey::AnyNumber => Some(self.union_of(elems.to_vec(), span)),
+                _ => None,
+            },
+            TypeKind::Nullable(inner) | TypeKind::Parenthesized(inner) => {
+                self.lookup_indexed_member(inner, key, span)
+            }
+            TypeKind::Reference(type_ref) => {
+                let IndexKey::Name(name) = key else {
+                    return None;
+                };
+                let mut class_name = self.interner.resolve(type_ref.name.node).to_string();
+                loop {
+                    let member = self
+                        .access_control
+                        .get_class_members(&class_name)
+                        .and_then(|members| members.iter().find(|m| m.name == *name));
+                    if let Some(member) = member {
+                        return Some(self.class_member_value_type(&member.kind, span));
+                    }
+                    class_name = self.access_control.get_parent_class(&class_name)?;
+                }
+            }
+            _ => None,
+        }
+    }
+
+    /// The value type of a class member when read as a property
+    fn class_member_value_type(&self, kind: &ClassMemberKind<'arena>, span: Span) -> Type<'arena> {
+        match kind {
+            ClassMemberKind::Property { type_annotation } => type_annotation.clone(),
+            ClassMemberKind::Getter { return_type } => return_type.clone(),
+            ClassMemberKind::Setter { parameter_type } => parameter_type.clone(),
+            ClassMemberKind::Method {
+                parameters,
+                return_type,
+                ..
+            }
+            | ClassMemberKind::Operator {
+                parameters,
+                return_type,
+                ..
+            } => Type::new(
+                TypeKind::Function(FunctionType {
+                    type_parameters: None,
+                    parameters: self.arena.alloc_slice_fill_iter(parameters.iter().cloned()),
+                    return_type: self.arena.alloc(return_type.clone().unwrap_or_else(|| {
+                        Type::new(TypeKind::Primitive(PrimitiveType::Unknown), span)
+                    })),
+                    throws: None,
+                    span,
+                }),
+                span,
+            ),
+        }
+    }
+
+    /// Collapse a list of types into a single type or a union
+    fn union_of(&self, mut types: Vec<Type<'arena>>, span: Span) -> Type<'arena> {
+        match types.len() {
+            0 => Type::new(TypeKind::Primitive(PrimitiveType::Never), span),
+            1 => types.pop().expect("length checked above"),
+            _ => Type::new(
+                TypeKind::Union(self.arena.alloc_slice_fill_iter(types)),
+                span,
+            ),
+        }
+    }
+
     /// Deeply resolve all type references within a type tree.
     /// Unlike evaluate_type which only resolves top-level references,
     /// this recursively walks Object, Union, Nullable, Array, etc.
@@ -2500,6 +4818,14 @@ impl<'a, 'arena> TypeChecker<'a, 'arena> {
                     typ.span,
                 )
             }
+            TypeKind::Intersection(members) => {
+                let resolved: Vec<Type<'arena>> =
+                    members.iter().map(|m| self.deep_resolve_type(m)).collect();
+                Type::new(
+                    TypeKind::Intersection(self.arena.alloc_slice_fill_iter(resolved)),
+                    typ.span,
+                )
+            }
             TypeKind::Nullable(inner) => {
                 let resolved = self.deep_resolve_type(inner);
                 Type::new(TypeKind::Nullable(self.arena.alloc(resolved)), typ.span)
@@ -2542,6 +4868,14 @@ impl<'a, 'arena> TypeChecker<'a, 'arena> {
                     typ.span,
                 )
             }
+            TypeKind::IndexAccess(object, index) => {
+                match self.evaluate_index_access(object, index, typ.span) {
+                    Ok(evaluated) if !matches!(evaluated.kind, TypeKind::IndexAccess(..)) => {
+                        self.deep_resolve_type(&evaluated)
+                    }
+                    _ => typ.clone(),
+                }
+            }
             _ => typ.clone(),
         }
     }
@@ -2611,9 +4945,10 @@ impl<'a, 'arena> TypeChecker<'a, 'arena> {
         }
     }
 
-    /// Widen literal types to their base primitive types
+    /// Widen literal types to their base primitive types, including literals
+    /// nested in arrays and mutable object properties
     fn widen_type(&self, typ: Type<'arena>) -> Type<'arena> {
-        type_utilities::widen_type(typ)
+        type_utilities::widen_mutable_type(self.arena, typ)
     }
 
     /// Register a declare function statement in the global scope
@@ -2629,6 +4964,48 @@ impl<'a, 'arena> TypeChecker<'a, 'arena> {
         )
     }
 
+    /// Parse the C declarations of an `ffi.cdef("...")` call statement so later
+    /// uses of `ffi.C`, `ffi.new` and friends are typed
+    fn register_c_declarations(&mut self, expr: &Expression<'arena>) -> Result<(), TypeCheckError> {
+        let ExpressionKind::Call(callee, args, _) = &expr.kind else {
+            return Ok(());
+        };
+        let ExpressionKind::Member(object, member) = &callee.kind else {
+            return Ok(());
+        };
+        if self.interner.resolve(member.node) != "cdef" {
+            return Ok(());
+        }
+        let Some(ExpressionKind::Literal(Literal::String(source))) =
+            args.first().map(|arg| &arg.value.kind)
+        else {
+            return Ok(());
+        };
+        let object_type = self.infer_expression_type(object)?;
+        if !ffi::is_ffi_namespace(&object_type, self.interner) {
+            return Ok(());
+        }
+        self.c_declarations.declare(source).map_err(|e| {
+            TypeCheckError::new(
+                format!("Invalid C declaration in ffi.cdef: {}", e),
+                args[0].value.span,
+            )
+        })
+    }
+
+    /// Report `goto` and labels when the target predates Lua 5.2
+    fn check_goto_supported(&self, span: Span) {
+        use crate::cli::diagnostics::{error_codes, Diagnostic};
+
+        if let Some(message) = target_features::unsupported_goto(self.target()) {
+            self.diagnostic_handler.report(Diagnostic::error_with_code(
+                span,
+                error_codes::UNSUPPORTED_FEATURE,
+                message,
+            ));
+        }
+    }
+
     /// Register a declare const statement in the global scope
     fn register_declare_const(
         &mut self,
@@ -2784,13 +5161,18 @@ impl<'a, 'arena> TypeChecker<'a, 'arena> {
         self.type_env.lookup_type(name)
     }
 
+    /// The runtime type information of a checked class or interface, for the
+    /// code generator to embed for `Reflect`
+    pub fn runtime_type_info(&self, name: &str) -> Option<RuntimeTypeInfo> {
+        RuntimeTypeInfo::collect(name, &self.type_env, &self.access_control, self.interner)
+    }
+
     /// Extract exports from a program for module system
     pub fn extract_exports(
         &self,
         program: &Program<'arena>,
     ) -> crate::module_resolver::ModuleExports {
         // Delegate to module_phase for export extraction
-        // Note: lazy_callback is None for now - will be passed from CLI integration in future
         phases::module_phase::extract_exports(
             program,
             &self.symbol_table,
@@ -2798,7 +5180,7 @@ impl<'a, 'arena> TypeChecker<'a, 'arena> {
             self.module_registry.as_ref(),
             self.module_resolver.as_ref(),
             self.current_module_id.as_ref(),
-            None, // lazy_callback - TODO: Implement in CLI integration
+            self.lazy_callback.as_deref(),
             &self.diagnostic_handler,
         )
     }
@@ -2807,7 +5189,8 @@ impl<'a, 'arena> TypeChecker<'a, 'arena> {
         &mut self,
         stmt: &ThrowStatement<'arena>,
     ) -> Result<(), TypeCheckError> {
-        self.infer_expression_type(&stmt.expression)?;
+        let thrown = self.infer_expression_type(&stmt.expression)?;
+        self.raise(thrown);
         Ok(())
     }
 
@@ -2820,7 +5203,6 @@ impl<'a, 'arena> TypeChecker<'a, 'arena> {
         import: &ImportDeclaration<'arena>,
     ) -> Result<(), TypeCheckError> {
         // Delegate to module_phase for import processing
-        // Note: lazy_callback is None for now - will be implemented in CLI integration (Step 7)
         phases::module_phase::check_import_statement(
             import,
             &mut self.symbol_table,
@@ -2831,7 +5213,7 @@ impl<'a, 'arena> TypeChecker<'a, 'arena> {
             self.module_registry.as_ref(),
             self.module_resolver.as_ref(),
             self.current_module_id.as_ref(),
-            None,
+            self.lazy_callback.as_deref(),
             &self.diagnostic_handler,
         )
     }
@@ -2881,6 +5263,7 @@ impl<'a, 'arena> TypeChecker<'a, 'arena> {
         for statement in program.statements.iter() {
             if let Statement::Function(func_decl) = statement {
                 self.register_function_signature(func_decl)?;
+                self.record_doc_comments(statement, self.source, None);
 
                 // Track declaration for incremental checking (if checker is provided)
                 if incremental_checker.is_some() {
@@ -2906,6 +5289,10 @@ impl<'a, 'arena> TypeChecker<'a, 'arena> {
                     first_error = Some(e);
                 }
             }
+            // Function declarations had their docs recorded in pass 1
+            if !matches!(statement, Statement::Function(_)) {
+                self.record_doc_comments(statement, self.source, None);
+            }
             statements_checked += 1;
         }
 
@@ -3009,7 +5396,16 @@ impl<'a, 'arena> TypeChecker<'a, 'arena> {
     }
 
     fn check_try_statement(&mut self, stmt: &TryStatement<'arena>) -> Result<(), TypeCheckError> {
-        self.check_block(&stmt.try_block)?;
+        // Errors raised in a try block with catch clauses are not raised by the function
+        let catches = !stmt.catch_clauses.is_empty() && !self.error_channels.is_empty();
+        if catches {
+            self.error_channels.push(Vec::new());
+        }
+        let try_result = self.check_block(&stmt.try_block);
+        if catches {
+            self.error_channels.pop();
+        }
+        try_result?;
 
         for catch_clause in stmt.catch_clauses.iter() {
             self.check_catch_clause(catch_clause)?;
@@ -3598,4 +5994,2599 @@ mod tests {
             "Math library should be available from stdlib"
         );
     }
+
+    #[test]
+    fn test_self_return_type_resolves_to_receiver() {
+        let source = r#"
+            class Animal {
+                name: string
+                constructor(name: string) self.name = name end
+                setName(name: string): Self
+                    self.name = name
+                    return self
+                end
+            }
+
+            class Dog extends Animal {
+                constructor(name: string) super(name) end
+                bark(): string return "woof" end
+            }
+
+            const rex = new Dog("rex")
+            const d: Dog = rex:setName("max")
+            const sound: string = rex:setName("max"):bark()
+        "#;
+        let result = type_check_source(source);
+        if let Err(ref e) = result {
+            eprintln!("Error: {}", e.message);
+        }
+        assert!(
+            result.is_ok(),
+            "Self should resolve to the derived receiver"
+        );
+    }
+
+    #[test]
+    fn test_self_return_type_is_not_erased() {
+        let source = r#"
+            class Builder {
+                add(): Self return self end
+            }
+
+            const b = new Builder()
+            const s: string = b:add()
+        "#;
+        assert!(
+            type_check_source(source).is_err(),
+            "b:add() is a Builder, not a string"
+        );
+    }
+
+    #[test]
+    fn test_override_of_self_returning_method() {
+        let source = r#"
+            class Shape {
+                scale(factor: number): Self return self end
+            }
+
+            class Circle extends Shape {
+                override scale(factor: number): Self return self end
+            }
+        "#;
+        let result = type_check_source(source);
+        if let Err(ref e) = result {
+            eprintln!("Error: {}", e.message);
+        }
+        assert!(result.is_ok(), "Self overrides should be compatible");
+
+        // A concrete class would not be the receiver's type in a subclass of `Square`
+        let concrete = r#"
+            class Shape {
+                scale(factor: number): Self return self end
+            }
+
+            class Square extends Shape {
+                override scale(factor: number): Square return self end
+            }
+        "#;
+        assert!(type_check_source(concrete).is_err());
+    }
+
+    /// Type check source code with custom compiler options and return all diagnostics
+    fn diagnostics_with_options(
+        source: &str,
+        options: CompilerOptions,
+    ) -> Vec<crate::cli::diagnostics::Diagnostic> {
+        let arena = Bump::new();
+        let handler = Arc::new(CollectingDiagnosticHandler::new());
+        let (interner, common) =
+            luanext_parser::string_interner::StringInterner::new_with_common_identifiers();
+        let mut lexer = Lexer::new(source, handler.clone(), &interner);
+        let tokens = lexer.tokenize().expect("Lexing failed");
+        let mut parser = Parser::new(tokens, handler.clone(), &interner, &common, &arena);
+        let program = parser.parse().expect("Parsing failed");
+
+        let mut type_checker =
+            TypeChecker::new(handler.clone(), &interner, &common, &arena).with_options(options);
+        let _ = type_checker.check_program(&program);
+        handler.get_diagnostics()
+    }
+
+    fn check_with_options(source: &str, options: CompilerOptions) -> Result<(), TypeCheckError> {
+        let arena = Bump::new();
+        let handler = Arc::new(CollectingDiagnosticHandler::new());
+        let (interner, common) =
+            luanext_parser::string_interner::StringInterner::new_with_common_identifiers();
+        let mut lexer = Lexer::new(source, handler.clone(), &interner);
+        let tokens = lexer.tokenize().expect("Lexing failed");
+        let mut parser = Parser::new(tokens, handler.clone(), &interner, &common, &arena);
+        let program = parser.parse().expect("Parsing failed");
+
+        let mut type_checker =
+            TypeChecker::new(handler.clone(), &interner, &common, &arena).with_options(options);
+        type_checker.check_program(&program)
+    }
+
+    /// Like `diagnostics_with_options`, with the standard library loaded for
+    /// the default target before the options are applied
+    fn diagnostics_with_stdlib(
+        source: &str,
+        options: CompilerOptions,
+    ) -> Vec<crate::cli::diagnostics::Diagnostic> {
+        let arena = Bump::new();
+        let handler = Arc::new(CollectingDiagnosticHandler::new());
+        let (interner, common) =
+            luanext_parser::string_interner::StringInterner::new_with_common_identifiers();
+        let mut lexer = Lexer::new(source, handler.clone(), &interner);
+        let tokens = lexer.tokenize().expect("Lexing failed");
+        let mut parser = Parser::new(tokens, handler.clone(), &interner, &common, &arena);
+        let program = parser.parse().expect("Parsing failed");
+
+        let mut type_checker =
+            TypeChecker::new_with_stdlib(handler.clone(), &interner, &common, &arena)
+                .expect("Failed to load stdlib")
+                .with_options(options);
+        let _ = type_checker.check_program(&program);
+        handler.get_diagnostics()
+    }
+
+    /// Like `check_with_options`, with the standard library loaded for the
+    /// default target before the options are applied
+    fn check_with_stdlib(source: &str, options: CompilerOptions) -> Result<(), TypeCheckError> {
+        let arena = Bump::new();
+        let handler = Arc::new(CollectingDiagnosticHandler::new());
+        let (interner, common) =
+            luanext_parser::string_interner::StringInterner::new_with_common_identifiers();
+        let mut lexer = Lexer::new(source, handler.clone(), &interner);
+        let tokens = lexer.tokenize().expect("Lexing failed");
+        let mut parser = Parser::new(tokens, handler.clone(), &interner, &common, &arena);
+        let program = parser.parse().expect("Parsing failed");
+
+        let mut type_checker =
+            TypeChecker::new_with_stdlib(handler.clone(), &interner, &common, &arena)
+                .expect("Failed to load stdlib")
+                .with_options(options);
+        type_checker.check_program(&program)
+    }
+
+    fn has_code(
+        diagnostics: &[crate::cli::diagnostics::Diagnostic],
+        code: crate::cli::diagnostics::DiagnosticCode,
+    ) -> bool {
+        diagnostics.iter().any(|d| d.code == Some(code))
+    }
+
+    fn strict_init_options() -> CompilerOptions {
+        CompilerOptions {
+            strict_property_initialization: true,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_strict_init_requires_super_call() {
+        use crate::cli::diagnostics::error_codes::MISSING_SUPER_CALL;
+        let source = r#"
+            class Animal {
+                name: string
+                constructor(name: string) self.name = name end
+            }
+
+            class Dog extends Animal {
+                constructor(name: string) end
+            }
+        "#;
+        let diagnostics = diagnostics_with_options(source, strict_init_options());
+        assert!(has_code(&diagnostics, MISSING_SUPER_CALL));
+
+        let diagnostics = diagnostics_with_options(source, CompilerOptions::default());
+        assert!(!has_code(&diagnostics, MISSING_SUPER_CALL));
+    }
+
+    #[test]
+    fn test_strict_init_self_before_super() {
+        use crate::cli::diagnostics::error_codes::SELF_BEFORE_SUPER;
+        let source = r#"
+            class Animal {
+                name: string
+                constructor(name: string) self.name = name end
+            }
+
+            class Dog extends Animal {
+                breed: string
+                constructor(name: string)
+                    self.breed = "lab"
+                    super(name)
+                end
+            }
+        "#;
+        let diagnostics = diagnostics_with_options(source, strict_init_options());
+        let self_use = diagnostics
+            .iter()
+            .find(|d| d.code == Some(SELF_BEFORE_SUPER))
+            .expect("self before super");
+        assert_eq!(
+            self_use.span.start as usize,
+            source.find("self.breed").unwrap()
+        );
+    }
+
+    #[test]
+    fn test_strict_init_super_arguments() {
+        use crate::cli::diagnostics::error_codes::{
+            MISSING_SUPER_CALL, SELF_BEFORE_SUPER, TYPE_MISMATCH, WRONG_ARG_COUNT,
+        };
+        let valid = r#"
+            class Animal {
+                name: string
+                constructor(name: string) self.name = name end
+            }
+
+            class Dog extends Animal {
+                breed: string
+                constructor(name: string, breed: string)
+                    super(name)
+                    self.breed = breed
+                end
+            }
+        "#;
+        let diagnostics = diagnostics_with_options(valid, strict_init_options());
+        assert!(!has_code(&diagnostics, MISSING_SUPER_CALL));
+        assert!(!has_code(&diagnostics, SELF_BEFORE_SUPER));
+        assert!(!has_code(&diagnostics, WRONG_ARG_COUNT));
+
+        let wrong_count = r#"
+            class Animal {
+                name: string
+                constructor(name: string) self.name = name end
+            }
+
+            class Dog extends Animal {
+                constructor() super() end
+            }
+        "#;
+        let diagnostics = diagnostics_with_options(wrong_count, strict_init_options());
+        assert!(has_code(&diagnostics, WRONG_ARG_COUNT));
+
+        let wrong_type = r#"
+            class Animal {
+                name: string
+                constructor(name: string) self.name = name end
+            }
+
+            class Dog extends Animal {
+                constructor() super(42) end
+            }
+        "#;
+        let diagnostics = diagnostics_with_options(wrong_type, strict_init_options());
+        assert!(has_code(&diagnostics, TYPE_MISMATCH));
+
+        // `super(...)` in every branch of an `if`, against an aliased parameter type
+        let nested = r#"
+            type Name = string
+
+            class Animal {
+                name: Name
+                constructor(name: Name) self.name = name end
+            }
+
+            class Dog extends Animal {
+                constructor(name: string, loud: boolean)
+                    if loud then
+                        super(name .. "!")
+                    else
+                        super(name)
+                    end
+                end
+            }
+        "#;
+        let diagnostics = diagnostics_with_options(nested, strict_init_options());
+        assert!(!has_code(&diagnostics, MISSING_SUPER_CALL));
+        assert!(!has_code(&diagnostics, TYPE_MISMATCH));
+    }
+
+    #[test]
+    fn test_strict_init_uninitialized_property() {
+        use crate::cli::diagnostics::error_codes::PROPERTY_NOT_INITIALIZED;
+        let source = r#"
+            class Point {
+                x: number
+                y: number
+                label: string | nil
+                scale: number = 1
+                constructor(x: number, flip: boolean)
+                    self.x = x
+                    if flip then
+                        self.y = 0
+                    end
+                end
+            }
+        "#;
+        let diagnostics = diagnostics_with_options(source, strict_init_options());
+        let uninitialized: Vec<_> = diagnostics
+            .iter()
+            .filter(|d| d.code == Some(PROPERTY_NOT_INITIALIZED))
+            .collect();
+        assert_eq!(
+            uninitialized.len(),
+            1,
+            "only 'y' is not definitely assigned"
+        );
+        assert!(uninitialized[0].message.contains("'y'"));
+    }
+
+    #[test]
+    fn test_strict_init_assigned_in_all_branches() {
+        use crate::cli::diagnostics::error_codes::PROPERTY_NOT_INITIALIZED;
+        let source = r#"
+            class Flag {
+                value: boolean
+                constructor(on: boolean)
+                    if on then
+                        self.value = true
+                    else
+                        self.value = false
+                    end
+                end
+            }
+        "#;
+        let diagnostics = diagnostics_with_options(source, strict_init_options());
+        assert!(!has_code(&diagnostics, PROPERTY_NOT_INITIALIZED));
+    }
+
+    #[test]
+    fn test_strict_init_ignores_branches_that_exit() {
+        use crate::cli::diagnostics::error_codes::PROPERTY_NOT_INITIALIZED;
+        let source = r#"
+            class Config {
+                a: number
+                b: number
+                constructor(ok: boolean, fallback: boolean)
+                    if ok then
+                        self.a = 1
+                    else
+                        error("bad")
+                    end
+                    if fallback then
+                        self.b = 1
+                    elseif ok then
+                        return
+                    else
+                        self.b = 2
+                    end
+                end
+            }
+        "#;
+        let diagnostics = diagnostics_with_options(source, strict_init_options());
+        assert!(!has_code(&diagnostics, PROPERTY_NOT_INITIALIZED));
+
+        // A branch that falls through still has to assign
+        let source = r#"
+            class Config {
+                a: number
+                constructor(ok: boolean, strict: boolean)
+                    if ok then
+                        self.a = 1
+                    elseif strict then
+                        error("bad")
+                    else
+                        local missing = 0
+                    end
+                end
+            }
+        "#;
+        let diagnostics = diagnostics_with_options(source, strict_init_options());
+        assert!(has_code(&diagnostics, PROPERTY_NOT_INITIALIZED));
+    }
+
+    #[test]
+    fn test_no_implicit_override_requires_modifier() {
+        use crate::cli::diagnostics::error_codes::MISSING_OVERRIDE_MODIFIER;
+        let source = r#"
+            class Animal {
+                speak(): string return "..." end
+            }
+
+            class Dog extends Animal {
+                speak(): string return "woof" end
+            }
+        "#;
+        let options = CompilerOptions {
+            no_implicit_override: true,
+            ..Default::default()
+        };
+        let diagnostics = diagnostics_with_options(source, options);
+        let missing = diagnostics
+            .iter()
+            .find(|d| d.code == Some(MISSING_OVERRIDE_MODIFIER))
+            .expect("missing 'override' should be reported");
+        assert_eq!(missing.related_information.len(), 1);
+
+        let diagnostics = diagnostics_with_options(source, CompilerOptions::default());
+        assert!(!has_code(&diagnostics, MISSING_OVERRIDE_MODIFIER));
+    }
+
+    #[test]
+    fn test_override_without_base_member() {
+        use crate::cli::diagnostics::error_codes::OVERRIDE_WITHOUT_BASE;
+        let source = r#"
+            class Animal {
+                speak(): string return "..." end
+            }
+
+            class Dog extends Animal {
+                override fetch(): string return "ball" end
+            }
+        "#;
+        let diagnostics = diagnostics_with_options(source, CompilerOptions::default());
+        assert!(has_code(&diagnostics, OVERRIDE_WITHOUT_BASE));
+    }
+
+    #[test]
+    fn test_final_method_cannot_be_overridden() {
+        use crate::cli::diagnostics::error_codes::OVERRIDE_OF_FINAL_MEMBER;
+        let source = r#"
+            class Animal {
+                @final
+                speak(): string return "..." end
+            }
+
+            class Dog extends Animal {}
+
+            class Puppy extends Dog {
+                override speak(): string return "yip" end
+            }
+        "#;
+        let diagnostics = diagnostics_with_options(source, CompilerOptions::default());
+        let error = diagnostics
+            .iter()
+            .find(|d| d.code == Some(OVERRIDE_OF_FINAL_MEMBER))
+            .expect("overriding a final method should be reported");
+        assert!(error.message.contains("Animal"));
+        assert_eq!(error.related_information.len(), 1);
+    }
+
+    #[test]
+    fn test_sealed_class_cannot_be_extended() {
+        use crate::cli::diagnostics::error_codes::EXTENDS_FINAL_CLASS;
+        let source = r#"
+            @sealed
+            class Animal {}
+
+            class Dog extends Animal {}
+        "#;
+        let diagnostics = diagnostics_with_options(source, CompilerOptions::default());
+        let error = diagnostics
+            .iter()
+            .find(|d| d.code == Some(EXTENDS_FINAL_CLASS))
+            .expect("extending a sealed class should be reported");
+        assert_eq!(error.related_information.len(), 1);
+    }
+
+    #[test]
+    fn test_decorator_must_be_callable() {
+        use crate::cli::diagnostics::error_codes::NOT_CALLABLE;
+        let source = r#"
+            const notDecorator: number = 5
+
+            @notDecorator
+            class Service {}
+        "#;
+        let diagnostics = diagnostics_with_options(source, CompilerOptions::default());
+        assert!(has_code(&diagnostics, NOT_CALLABLE));
+    }
+
+    #[test]
+    fn test_decorator_parameter_must_accept_target() {
+        use crate::cli::diagnostics::error_codes::TYPE_MISMATCH;
+        let source = r#"
+            function onlyNumbers(target: number): void end
+
+            class Service {
+                @onlyNumbers
+                name: string = "svc"
+            }
+        "#;
+        let diagnostics = diagnostics_with_options(source, CompilerOptions::default());
+        assert!(has_code(&diagnostics, TYPE_MISMATCH));
+    }
+
+    #[test]
+    fn test_decorator_factory_arguments_are_checked() {
+        use crate::cli::diagnostics::error_codes::{TYPE_MISMATCH, WRONG_ARG_COUNT};
+        let factory = r#"
+            function tag(label: string): (target: unknown) -> void
+                return function(target: unknown): void end
+            end
+        "#;
+
+        let valid = format!(
+            "{}\nclass Service {{\n@tag(\"svc\")\nrun(): void end\n}}",
+            factory
+        );
+        let diagnostics = diagnostics_with_options(&valid, CompilerOptions::default());
+        assert!(!has_code(&diagnostics, TYPE_MISMATCH));
+        assert!(!has_code(&diagnostics, WRONG_ARG_COUNT));
+
+        let wrong_type = format!(
+            "{}\nclass Service {{\n@tag(42)\nrun(): void end\n}}",
+            factory
+        );
+        let diagnostics = diagnostics_with_options(&wrong_type, CompilerOptions::default());
+        assert!(has_code(&diagnostics, TYPE_MISMATCH));
+
+        let missing = format!("{}\nclass Service {{\n@tag()\nrun(): void end\n}}", factory);
+        let diagnostics = diagnostics_with_options(&missing, CompilerOptions::default());
+        assert!(has_code(&diagnostics, WRONG_ARG_COUNT));
+    }
+
+    #[test]
+    fn test_decorator_return_type_replaces_property_type() {
+        let source = r#"
+            function numeric(target: number | string): number return 0 end
+
+            class Box {
+                @numeric
+                value: number | string = 1
+            }
+
+            const box = new Box()
+            const n: number = box.value
+        "#;
+        assert!(
+            type_check_source(source).is_ok(),
+            "decorated property should take the decorator's return type"
+        );
+    }
+
+    #[test]
+    fn test_builtin_decorators_have_signatures() {
+        let source = r#"
+            @sealed
+            class Service {
+                @deprecated("use start instead")
+                run(): void end
+
+                @deprecated
+                stop(): void end
+            }
+        "#;
+        assert!(type_check_source_with_stdlib(source).is_ok());
+    }
+
+    #[test]
+    fn test_builtin_decorator_names_are_not_reserved() {
+        let source = r#"
+            local final = true
+            local sealed: string = "closed"
+
+            function deprecated(): number
+                return 1
+            end
+
+            @sealed
+            class Service {
+                @final
+                run(): void end
+            }
+        "#;
+        assert!(type_check_source_with_stdlib(source).is_ok());
+    }
+
+    #[test]
+    fn test_class_decorator_receives_constructor() {
+        use crate::cli::diagnostics::error_codes::TYPE_MISMATCH;
+        let service = r#"
+            class Service {
+                name: string = ""
+
+                constructor(name: string) {}
+            }
+        "#;
+
+        let constructor = format!(
+            "function register(ctor: (name: string) -> Service): void end\n@register\n{}",
+            service
+        );
+        let diagnostics = diagnostics_with_stdlib(&constructor, CompilerOptions::default());
+        assert!(!has_code(&diagnostics, TYPE_MISMATCH));
+
+        let instance = format!(
+            "function onInstance(target: {{ name: string }}): void end\n@onInstance\n{}",
+            service
+        );
+        let diagnostics = diagnostics_with_stdlib(&instance, CompilerOptions::default());
+        assert!(has_code(&diagnostics, TYPE_MISMATCH));
+    }
+
+    #[test]
+    fn test_member_decorators_are_typed() {
+        use crate::cli::diagnostics::error_codes::{PROPERTY_NOT_FOUND, TYPE_MISMATCH};
+        let prelude = r#"
+            const checks = {
+                numeric = function(target: number | string): number return 0 end,
+                onlyNumbers = function(target: number): void end,
+            }
+        "#;
+
+        let valid = format!(
+            "{}\nclass Box {{\n@checks.numeric\nvalue: number | string = 1\n}}\nconst box = new Box()\nconst n: number = box.value",
+            prelude
+        );
+        assert!(type_check_source_with_stdlib(&valid).is_ok());
+
+        let mismatch = format!(
+            "{}\nclass Box {{\n@checks.onlyNumbers\nname: string = \"box\"\n}}",
+            prelude
+        );
+        let diagnostics = diagnostics_with_stdlib(&mismatch, CompilerOptions::default());
+        assert!(has_code(&diagnostics, TYPE_MISMATCH));
+
+        let missing = format!(
+            "{}\nclass Box {{\n@checks.missing\nname: string = \"box\"\n}}",
+            prelude
+        );
+        let diagnostics = diagnostics_with_stdlib(&missing, CompilerOptions::default());
+        assert!(has_code(&diagnostics, PROPERTY_NOT_FOUND));
+    }
+
+    #[test]
+    fn test_metatable_class_methods_resolve_on_instances() {
+        let prelude = r#"
+            local Account = {}
+            Account.__index = Account
+
+            Account.new = function(balance: number)
+                return setmetatable({ balance = balance }, Account)
+            end
+
+            Account.deposit = function(self, amount: number): number
+                return amount
+            end
+
+            local account = Account.new(100)
+        "#;
+
+        let valid = format!("{}\nlocal n: number = account:deposit(5)", prelude);
+        assert!(
+            type_check_source_with_stdlib(&valid).is_ok(),
+            "methods on the metatable should resolve on instances"
+        );
+
+        let fields = format!("{}\nlocal b: number = account.balance", prelude);
+        assert!(type_check_source_with_stdlib(&fields).is_ok());
+
+        let mismatch = format!("{}\nlocal s: string = account:deposit(5)", prelude);
+        assert!(type_check_source_with_stdlib(&mismatch).is_err());
+    }
+
+    #[test]
+    fn test_open_table_fields_keep_their_type() {
+        let valid = r#"
+            local M = {}
+            M.count = 0
+            M.count = 1
+            local n: number = M.count
+        "#;
+        assert!(type_check_source(valid).is_ok());
+
+        let invalid = r#"
+            local M = {}
+            M.count = 0
+            M.count = "many"
+        "#;
+        assert!(type_check_source(invalid).is_err());
+    }
+
+    #[test]
+    fn test_setmetatable_with_object_index() {
+        let source = r#"
+            local base = { greet = function(): string return "hi" end }
+            local mt = { __index = base }
+            local obj = setmetatable({ name = "x" }, mt)
+            local s: string = obj:greet()
+            local name: string = obj.name
+        "#;
+        assert!(type_check_source_with_stdlib(source).is_ok());
+    }
+
+    #[test]
+    fn test_colon_methods_on_open_tables() {
+        let prelude = r#"
+            local Counter = {}
+            Counter.__index = Counter
+
+            function Counter.new(start: number)
+                return setmetatable({ count = start }, Counter)
+            end
+
+            function Counter:label(prefix: string): string
+                return prefix
+            end
+
+            local counter = Counter.new(1)
+        "#;
+
+        let valid = format!("{}\nlocal s: string = counter:label(\"n\")", prelude);
+        assert!(
+            type_check_source_with_stdlib(&valid).is_ok(),
+            "`function M:foo()` should declare a method on the table"
+        );
+
+        let mismatch = format!("{}\nlocal n: number = counter:label(\"n\")", prelude);
+        assert!(type_check_source_with_stdlib(&mismatch).is_err());
+    }
+
+    #[test]
+    fn test_setmetatable_does_not_add_instance_fields_to_table() {
+        let source = r#"
+            local Account = {}
+            Account.__index = Account
+
+            Account.new = function(balance: number)
+                return setmetatable({ balance = balance }, Account)
+            end
+
+            local account = Account.new(100)
+            local n: number = account.balance
+            Account.balance = "not an instance field"
+        "#;
+        assert!(type_check_source_with_stdlib(source).is_ok());
+    }
+
+    #[test]
+    fn test_shadowed_open_tables_have_separate_shapes() {
+        let source = r#"
+            local M = {}
+            M.count = 0
+
+            do
+                local M = {}
+                M.count = "inner"
+            end
+
+            local n: number = M.count
+        "#;
+        assert!(type_check_source(source).is_ok());
+    }
+
+    #[test]
+    fn test_indexed_access_types() {
+        let source = r#"
+            interface Address {
+                city: string
+            }
+            interface User {
+                name: string
+                age: number
+                address: Address
+            }
+            type Pair = [string, number]
+
+            const city: User["address"]["city"] = "Paris"
+            const key: User["name" | "age"] = 42
+            const first: Pair[1] = "a"
+            const second: Pair[2] = 2
+            const either: Pair[number] = 1
+        "#;
+        assert!(
+            type_check_source(source).is_ok(),
+            "indexed access types should resolve through members, tuples and arrays"
+        );
+
+        let mismatch = r#"
+            interface User {
+                name: string
+            }
+            const n: User["name"] = 42
+        "#;
+        assert!(type_check_source(mismatch).is_err());
+    }
+
+    #[test]
+    fn test_indexed_access_missing_key() {
+        use crate::cli::diagnostics::error_codes::PROPERTY_NOT_FOUND;
+
+        let source = r#"
+            interface User {
+                name: string
+            }
+            const email: User["email"] = "x"
+        "#;
+        let diagnostics = diagnostics_with_options(source, CompilerOptions::default());
+        assert!(has_code(&diagnostics, PROPERTY_NOT_FOUND));
+    }
+
+    #[test]
+    fn test_indexed_access_tuple_positions_start_at_one() {
+        use crate::cli::diagnostics::error_codes::PROPERTY_NOT_FOUND;
+
+        let zero = r#"
+            type Pair = [string, number]
+            const first: Pair[0] = "a"
+        "#;
+        let diagnostics = diagnostics_with_options(zero, CompilerOptions::default());
+        assert!(has_code(&diagnostics, PROPERTY_NOT_FOUND));
+
+        let past_end = r#"
+            type Pair = [string, number]
+            const third: Pair[3] = "a"
+        "#;
+        let diagnostics = diagnostics_with_options(past_end, CompilerOptions::default());
+        assert!(has_code(&diagnostics, PROPERTY_NOT_FOUND));
+    }
+
+    #[test]
+    fn test_indexed_access_with_type_parameter_key_is_deferred() {
+        let source = r#"
+            function get<T, K extends keyof T>(obj: T, key: K): T[K]
+                return obj[key]
+            end
+
+            interface Config {
+                name: string
+            }
+            type Values<T> = T[keyof T]
+            const v: Values<Config> = "x"
+        "#;
+        let diagnostics = diagnostics_with_options(source, CompilerOptions::default());
+        assert!(
+            !diagnostics
+                .iter()
+                .any(|d| d.message.contains("cannot be used as an index type")),
+            "a type parameter key should not be rejected before substitution"
+        );
+    }
+
+    #[test]
+    fn test_indexed_access_class_member() {
+        let source = r#"
+            class Point {
+                x: number = 0
+            }
+            const x: Point["x"] = 1
+        "#;
+        assert!(type_check_source(source).is_ok());
+    }
+
+    #[test]
+    fn test_typeof_member_chains_and_calls() {
+        let source = r#"
+            interface Db {
+                host: string
+                port: number
+            }
+            interface Config {
+                db: Db
+            }
+            local config: Config = { db = { host = "localhost", port = 5432 } }
+            const port: typeof(config.db.port) = 8080
+            const db: typeof(config.db) = { host = "remote", port = 1 }
+
+            function make(): number return 1 end
+            const made: typeof(make()) = 2
+
+            const items: number[] = [1, 2, 3]
+            const first: typeof(items[1]) = 4
+        "#;
+        assert!(
+            type_check_source(source).is_ok(),
+            "typeof should infer member chains, calls and index expressions"
+        );
+
+        let mismatch = r#"
+            interface Config {
+                port: number
+            }
+            local config: Config = { port = 5432 }
+            const port: typeof(config.port) = "8080"
+        "#;
+        assert!(type_check_source(mismatch).is_err());
+    }
+
+    #[test]
+    fn test_typeof_nested_in_annotations() {
+        let source = r#"
+            local port = 8080
+
+            type Server = { port: typeof(port) }
+            type Listen = (p: typeof(port)) -> typeof(port)
+            type Ports = { [K in "http" | "https"]: typeof(port) }
+
+            const server: Server = { port = 1 }
+            const listen: Listen = function(p: number): number return p end
+            const ports: Ports = { http = 80, https = 443 }
+
+            class Proxy {
+                target: typeof(port) = 3000
+            }
+            local proxy = new Proxy()
+            const upstream: number = proxy.target
+        "#;
+        assert!(
+            type_check_source(source).is_ok(),
+            "typeof should resolve inside object, function, mapped and class property types"
+        );
+
+        let mismatch = r#"
+            local port = 8080
+            const server: { port: typeof(port) } = { port = "80" }
+        "#;
+        assert!(type_check_source(mismatch).is_err());
+
+        let property_mismatch = r#"
+            local port = 8080
+            class Proxy {
+                target: typeof(port) = "3000"
+            }
+        "#;
+        assert!(type_check_source(property_mismatch).is_err());
+    }
+
+    #[test]
+    fn test_typeof_imported_module_value() {
+        use crate::cli::fs::MockFileSystem;
+        use crate::module_resolver::{
+            ExportedSymbol, LuaFilePolicy, ModuleConfig, ModuleExports, ModuleId, ModuleRegistry,
+            ModuleResolver, PathAliasResolver,
+        };
+        use std::path::{Path, PathBuf};
+
+        let main = Path::new("/project/src/main.luax");
+        let mut fs = MockFileSystem::new();
+        fs.add_file(main, "");
+        fs.add_file("/project/src/settings.luax", "");
+        let config = ModuleConfig {
+            module_paths: vec![],
+            lua_file_policy: LuaFilePolicy::RequireDeclaration,
+            path_aliases: PathAliasResolver::empty(),
+        };
+        let resolver = Arc::new(ModuleResolver::new(
+            Arc::new(fs),
+            config,
+            PathBuf::from("/project"),
+        ));
+
+        let registry = Arc::new(ModuleRegistry::new());
+        let settings_id = resolver.resolve("./settings", main).unwrap();
+        let span = Span::default();
+        let timeout = Symbol::new(
+            "timeout".to_string(),
+            SymbolKind::Const,
+            Type::new(TypeKind::Primitive(PrimitiveType::Number), span),
+            span,
+        );
+        let mut exports = ModuleExports::new();
+        exports.add_named("timeout".to_string(), ExportedSymbol::new(timeout, false));
+        registry.register_parsed(settings_id.clone(), Arc::new(SymbolTable::new()));
+        registry.register_exports(&settings_id, exports).unwrap();
+
+        let check = |source: &str| -> bool {
+            let arena = Bump::new();
+            let handler = Arc::new(CollectingDiagnosticHandler::new());
+            let (interner, common) =
+                luanext_parser::string_interner::StringInterner::new_with_common_identifiers();
+            let mut lexer = Lexer::new(source, handler.clone(), &interner);
+            let tokens = lexer.tokenize().expect("Lexing failed");
+            let mut parser = Parser::new(tokens, handler.clone(), &interner, &common, &arena);
+            let program = parser.parse().expect("Parsing failed");
+            let mut checker = TypeChecker::new_with_module_support(
+                handler.clone(),
+                &interner,
+                &common,
+                &arena,
+                registry.clone(),
+                ModuleId::new(main.to_path_buf()),
+                resolver.clone(),
+            );
+            let result = checker.check_program(&program);
+            let has_errors = handler
+                .get_diagnostics()
+                .iter()
+                .any(|d| d.level == crate::cli::diagnostics::DiagnosticLevel::Error);
+            result.is_ok() && !has_errors
+        };
+
+        assert!(check(
+            r#"
+            import { timeout } from "./settings"
+            local retry: typeof(timeout) = 30
+        "#
+        ));
+        assert!(!check(
+            r#"
+            import { timeout } from "./settings"
+            local retry: typeof(timeout) = "30"
+        "#
+        ));
+    }
+
+    #[test]
+    fn test_typeof_self_reference_is_circular() {
+        use crate::cli::diagnostics::error_codes::CIRCULAR_TYPE;
+
+        let diagnostics =
+            diagnostics_with_options("local x: typeof x = 1", CompilerOptions::default());
+        assert!(has_code(&diagnostics, CIRCULAR_TYPE));
+
+        let diagnostics = diagnostics_with_options(
+            "function f(): typeof(f()) return 1 end",
+            CompilerOptions::default(),
+        );
+        assert!(has_code(&diagnostics, CIRCULAR_TYPE));
+    }
+
+    #[test]
+    fn test_instance_type_and_constructor_parameters() {
+        let source = r#"
+            class Point {
+                constructor(x: number, y: string) end
+            }
+
+            type P = InstanceType<typeof(Point)>
+            type Args = ConstructorParameters<typeof(Point)>
+
+            const p: P = new Point(1, "a")
+            const x: Args[1] = 1
+            const y: Args[2] = "a"
+        "#;
+        assert!(type_check_source(source).is_ok());
+
+        let mismatch = r#"
+            class Point {
+                constructor(x: number, y: string) end
+            }
+            type Args = ConstructorParameters<typeof(Point)>
+            const x: Args[1] = "a"
+        "#;
+        assert!(type_check_source(mismatch).is_err());
+    }
+
+    #[test]
+    fn test_constructor_parameters_of_unannotated_function() {
+        let source = r#"
+            type Make = (name: string, options) -> nil
+            type Args = ConstructorParameters<Make>
+            const name: Args[1] = "a"
+            const options: Args[2] = 42
+        "#;
+        assert!(type_check_source(source).is_ok());
+    }
+
+    #[test]
+    fn test_awaited_unwraps_stdlib_tasks() {
+        let source = r#"
+            const a: Awaited<Task<Task<string>>> = "done"
+            const b: Awaited<Coroutine<nil, nil, number>> = 1
+        "#;
+        assert!(type_check_source_with_stdlib(source).is_ok());
+
+        let user_task = r#"
+            interface Task<T> {
+                value: T
+            }
+            const a: Awaited<Task<string>> = "done"
+        "#;
+        assert!(type_check_source_with_stdlib(user_task).is_err());
+    }
+
+    #[test]
+    fn test_string_case_utility_types() {
+        let source = r#"
+            type Event = "click" | "hover"
+            const upper: Uppercase<Event> = "CLICK"
+            const handler: Capitalize<Event> = "Hover"
+        "#;
+        assert!(type_check_source(source).is_ok());
+
+        let mismatch = r#"
+            const upper: Uppercase<"click"> = "click"
+        "#;
+        assert!(type_check_source(mismatch).is_err());
+    }
+
+    #[test]
+    fn test_this_type_types_object_literal_self() {
+        let source = r#"
+            interface Counter {
+                count: number
+            }
+
+            local methods: ThisType<Counter> = {
+                next = function(self): number return self.count + 1 end
+            }
+        "#;
+        assert!(type_check_source(source).is_ok());
+
+        let mismatch = r#"
+            interface Named {
+                name: string
+            }
+
+            local methods: ThisType<Named> = {
+                size = function(self): number return self.name end
+            }
+        "#;
+        assert!(type_check_source(mismatch).is_err());
+    }
+
+    #[test]
+    fn test_conditional_infer_in_template_literal() {
+        let source = r#"
+            type EventName<T> = T extends `on${infer E}` ? E : never
+            type Split<T> = T extends `${infer Head}.${infer Rest}` ? [Head, Rest] : [T]
+
+            const event: EventName<"onClick"> = "Click"
+            const head: Split<"server.port">[1] = "server"
+            const rest: Split<"server.http.port">[2] = "http.port"
+        "#;
+        assert!(
+            type_check_source(source).is_ok(),
+            "conditional types should infer placeholders inside template literals"
+        );
+
+        let mismatch = r#"
+            type EventName<T> = T extends `on${infer E}` ? E : never
+            const event: EventName<"onClick"> = "onClick"
+        "#;
+        assert!(type_check_source(mismatch).is_err());
+    }
+
+    #[test]
+    fn test_template_literal_expansion_limit() {
+        let source = r#"
+            type Digit = "0" | "1" | "2" | "3" | "4" | "5" | "6" | "7" | "8" | "9"
+            type Code = `${Digit}${Digit}${Digit}${Digit}${Digit}`
+            const code: Code = "12345"
+        "#;
+        assert!(
+            type_check_source(source).is_err(),
+            "template literal expansion past the combination limit should be an error"
+        );
+    }
+
+    #[test]
+    fn test_branded_types_are_nominal() {
+        let source = r#"
+            type UserId = Brand<string>
+            type OrderId = Brand<string, "OrderId">
+
+            function makeUserId(raw: string): UserId
+                return raw as UserId
+            end
+
+            function identity<T>(value: T): T
+                return value
+            end
+
+            const id: UserId = makeUserId("u-1")
+            const same: UserId = identity(id)
+            const raw: string = id
+            const order: OrderId = "o-1" as OrderId
+            const frozen: Readonly<UserId> = id
+            const unwrapped: string = order as string
+        "#;
+        assert!(
+            type_check_source(source).is_ok(),
+            "branded values should be created by casts and usable as their base type"
+        );
+
+        let from_base = r#"
+            type UserId = Brand<string>
+            const id: UserId = "u-1"
+        "#;
+        assert!(
+            type_check_source(from_base).is_err(),
+            "the base type must not be assignable to a brand"
+        );
+
+        let across_brands = r#"
+            type UserId = Brand<string>
+            type OrderId = Brand<string>
+            const order: OrderId = "o-1" as OrderId
+            const id: UserId = order
+        "#;
+        assert!(
+            type_check_source(across_brands).is_err(),
+            "brands over the same base type must not be interchangeable"
+        );
+    }
+
+    #[test]
+    fn test_brands_are_keyed_by_their_declaration() {
+        let same_tag = r#"
+            type UserId = Brand<string, "Id">
+            type OrderId = Brand<string, "Id">
+            const order: OrderId = "o-1" as OrderId
+            const id: UserId = order
+        "#;
+        assert!(
+            type_check_source(same_tag).is_err(),
+            "separately declared brands must not mix when they share a tag"
+        );
+
+        let inline = r#"
+            type UserId = Brand<string>
+            const id: Brand<string, "UserId"> = "u-1" as UserId
+        "#;
+        assert!(
+            type_check_source(inline).is_err(),
+            "an inline brand must not forge a declared one"
+        );
+
+        // The same alias declared by different modules is a different brand,
+        // while the declaring module always produces the same one
+        use crate::cli::fs::MockFileSystem;
+        use crate::module_resolver::{
+            LuaFilePolicy, ModuleConfig, ModuleId, ModuleRegistry, ModuleResolver,
+            PathAliasResolver,
+        };
+        use std::path::PathBuf;
+
+        let resolver = Arc::new(ModuleResolver::new(
+            Arc::new(MockFileSystem::new()),
+            ModuleConfig {
+                module_paths: vec![],
+                lua_file_policy: LuaFilePolicy::RequireDeclaration,
+                path_aliases: PathAliasResolver::empty(),
+            },
+            PathBuf::from("/project"),
+        ));
+        let registry = Arc::new(ModuleRegistry::new());
+        let arena = Bump::new();
+        let handler = Arc::new(CollectingDiagnosticHandler::new());
+        let (interner, common) =
+            luanext_parser::string_interner::StringInterner::new_with_common_identifiers();
+        let source = "type Id = Brand<string>";
+        let mut lexer = Lexer::new(source, handler.clone(), &interner);
+        let tokens = lexer.tokenize().expect("Lexing failed");
+        let mut parser = Parser::new(tokens, handler.clone(), &interner, &common, &arena);
+        let program = parser.parse().expect("Parsing failed");
+
+        let declare_in = |module: &str| {
+            let mut checker = TypeChecker::new_with_module_support(
+                handler.clone(),
+                &interner,
+                &common,
+                &arena,
+                registry.clone(),
+                ModuleId::new(PathBuf::from(module)),
+                resolver.clone(),
+            );
+            checker
+                .check_program(&program)
+                .expect("Type checking failed");
+            checker.type_env.lookup_type("Id").cloned().unwrap()
+        };
+        let users = declare_in("/project/src/users.luax");
+        let orders = declare_in("/project/src/orders.luax");
+        assert!(!TypeCompatibility::is_assignable(&orders, &users));
+        assert!(TypeCompatibility::is_assignable(
+            &declare_in("/project/src/users.luax"),
+            &users
+        ));
+    }
+
+    #[test]
+    fn test_declare_type_declares_an_opaque_alias() {
+        let source = r#"
+            declare type UserId = string
+
+            function makeUserId(raw: string): UserId
+                return raw as UserId
+            end
+
+            const id: UserId = makeUserId("u-1")
+            const raw: string = id
+            const frozen: Readonly<UserId> = id
+        "#;
+        assert!(
+            type_check_source(source).is_ok(),
+            "opaque values should be created by casts and usable as their base type"
+        );
+
+        let from_base = r#"
+            declare type UserId = string
+            const id: UserId = "u-1"
+        "#;
+        assert!(
+            type_check_source(from_base).is_err(),
+            "the base type must not be assignable to an opaque alias"
+        );
+    }
+
+    #[test]
+    fn test_literal_widening_depends_on_mutability() {
+        let source = r#"
+            local cfg = { mode = "dark", sizes = {1, 2} }
+            cfg.mode = "light"
+
+            const DIRS = {"n", "s"}
+            const dirs: ("n" | "s")[] = DIRS
+            const theme = { mode = "dark" }
+            const mode: "dark" = theme.mode
+        "#;
+        assert!(
+            type_check_source(source).is_ok(),
+            "mutable bindings should widen nested literals while const bindings keep them"
+        );
+
+        let widened = r#"
+            local cfg = { mode = "dark" }
+            const mode: "dark" = cfg.mode
+        "#;
+        assert!(
+            type_check_source(widened).is_err(),
+            "literals inside a mutable binding should be widened"
+        );
+    }
+
+    #[test]
+    fn test_const_assertions_and_contextual_tuples() {
+        let source = r#"
+            const point = { x = 1, tag = "pt" } as const
+            const tag: "pt" = point.tag
+            local pair = {"a", 1} as const
+            const copy: ["a", 1] = pair
+            const entry: [string, number] = {"a", 1}
+        "#;
+        assert!(
+            type_check_source(source).is_ok(),
+            "as const and tuple annotations should infer literal tuples and records"
+        );
+    }
+
+    #[test]
+    fn test_integer_semantics_follow_target() {
+        use crate::cli::config::LuaVersion;
+        use crate::cli::diagnostics::DiagnosticLevel;
+        let has_error = |source: &str, target: LuaVersion| {
+            diagnostics_with_options(
+                source,
+                CompilerOptions {
+                    target,
+                    ..Default::default()
+                },
+            )
+            .iter()
+            .any(|d| d.level == DiagnosticLevel::Error)
+        };
+
+        let integer_ops = r#"
+            const q: integer = 7 // 2
+            const b: integer = 5 & 3
+            const n: integer = 3 + 4 * 2
+            for i = 1, 10 do
+                const k: integer = i
+            end
+        "#;
+        assert!(!has_error(integer_ops, LuaVersion::Lua54));
+
+        assert!(has_error("const r: integer = 7 / 2", LuaVersion::Lua54));
+        assert!(has_error("const f: integer = 1.5", LuaVersion::Lua54));
+        assert!(
+            has_error("local i: integer = 3.0", LuaVersion::Lua54),
+            "3.0 is a float literal even though its value is integral"
+        );
+        assert!(has_error("local i: integer = 1e3", LuaVersion::Lua54));
+        assert!(
+            !has_error("const f: integer = 1.5", LuaVersion::Lua51),
+            "integer is an alias of number before Lua 5.3"
+        );
+    }
+
+    #[test]
+    fn test_math_type_narrows_to_integer() {
+        use crate::cli::config::LuaVersion;
+        use crate::cli::diagnostics::DiagnosticLevel;
+        let has_error = |source: &str| {
+            diagnostics_with_stdlib(
+                source,
+                CompilerOptions {
+                    target: LuaVersion::Lua54,
+                    ..Default::default()
+                },
+            )
+            .iter()
+            .any(|d| d.level == DiagnosticLevel::Error)
+        };
+
+        let narrowed = r#"
+            function f(x: number)
+                if math.type(x) == "integer" then
+                    local i: integer = x
+                end
+                if math.type(x) ~= "integer" then
+                    local n: number = x
+                else
+                    local i: integer = x
+                end
+            end
+        "#;
+        assert!(!has_error(narrowed));
+
+        let unnarrowed = r#"
+            function f(x: number)
+                if math.type(x) == "float" then
+                    local i: integer = x
+                end
+            end
+        "#;
+        assert!(has_error(unnarrowed), "a float is not an integer");
+    }
+
+    #[test]
+    fn test_features_gated_by_target_version() {
+        use crate::cli::config::LuaVersion;
+        use crate::cli::diagnostics::error_codes::UNSUPPORTED_FEATURE;
+        let diagnostics_for = |source: &str, target: LuaVersion| {
+            diagnostics_with_options(
+                source,
+                CompilerOptions {
+                    target,
+                    ..Default::default()
+                },
+            )
+        };
+
+        let integer_ops = "const a = 7 // 2\nconst b = 5 & 3\nconst c = ~b";
+        let diagnostics = diagnostics_for(integer_ops, LuaVersion::Lua52);
+        assert!(diagnostics
+            .iter()
+            .any(|d| d.code == Some(UNSUPPORTED_FEATURE) && d.message.contains("Lua 5.3")));
+        assert!(!has_code(
+            &diagnostics_for(integer_ops, LuaVersion::Lua53),
+            UNSUPPORTED_FEATURE
+        ));
+
+        let jumps = "goto done\n::done::";
+        assert!(has_code(
+            &diagnostics_for(jumps, LuaVersion::Lua51),
+            UNSUPPORTED_FEATURE
+        ));
+        assert!(!has_code(
+            &diagnostics_for(jumps, LuaVersion::LuaJIT),
+            UNSUPPORTED_FEATURE
+        ));
+
+        let check_for = |source: &str, target: LuaVersion| {
+            check_with_options(
+                source,
+                CompilerOptions {
+                    target,
+                    ..Default::default()
+                },
+            )
+        };
+        let removed = check_for("local xs = unpack({1, 2})", LuaVersion::Lua54).unwrap_err();
+        assert!(removed.message.contains("removed in Lua 5.2"));
+        assert!(check_for("local xs = unpack({1, 2})", LuaVersion::Lua51).is_ok());
+
+        let jit_only = check_for("local v = jit.version", LuaVersion::Lua54).unwrap_err();
+        assert!(jit_only.message.contains("only available on LuaJIT"));
+    }
+
+    #[test]
+    fn test_ffi_cdef_types_cdata() {
+        use crate::cli::config::LuaVersion;
+        use crate::cli::diagnostics::DiagnosticLevel;
+        let luajit = || CompilerOptions {
+            target: LuaVersion::LuaJIT,
+            ..Default::default()
+        };
+        let prelude = r#"
+            local ffi = require("ffi")
+            ffi.cdef("typedef struct { double x, y; } point_t; double hypot(double a, double b);")
+        "#;
+
+        let valid = format!(
+            "{}{}",
+            prelude,
+            r#"
+            const d: number = ffi.C.hypot(3, 4)
+            local p = ffi.new("point_t", 1, 2)
+            p.x = 5
+            const sum: number = p.x + p.y
+            local Point = ffi.typeof("point_t")
+            const qx: number = Point().x
+        "#
+        );
+        assert!(check_with_options(&valid, luajit()).is_ok());
+        assert!(!diagnostics_with_options(&valid, luajit())
+            .iter()
+            .any(|d| d.level == DiagnosticLevel::Error));
+
+        let bad_argument = format!("{}{}", prelude, r#"local d = ffi.C.hypot("3", 4)"#);
+        assert!(diagnostics_with_options(&bad_argument, luajit())
+            .iter()
+            .any(|d| d.level == DiagnosticLevel::Error));
+
+        for invalid in [
+            r#"local p = ffi.new("point_t", 1, 2)
+            local z = p.z"#,
+            r#"local p = ffi.new("point_t", 1, 2, 3)"#,
+            r#"local m = ffi.new("struct missing")"#,
+            r#"local f = ffi.C.undeclared(1)"#,
+        ] {
+            let source = format!("{}{}", prelude, invalid);
+            assert!(
+                check_with_options(&source, luajit()).is_err(),
+                "expected an error for: {}",
+                invalid
+            );
+        }
+    }
+
+    #[test]
+    fn test_typed_coroutines() {
+        use crate::cli::diagnostics::DiagnosticLevel;
+        let prelude = r#"
+            local co: Coroutine<number, string, boolean> = coroutine.create(function(s: string): boolean
+                const received: string = coroutine.yield(1)
+                return true
+            end)
+        "#;
+
+        let valid = format!(
+            "{}{}",
+            prelude,
+            r#"
+            const result: [boolean, number | boolean | string] = coroutine.resume(co, "a")
+            local t: thread = co
+            local gen: (n: number) => number = coroutine.wrap(function(n: number): number
+                coroutine.yield(n + 1)
+                return n
+            end)
+            const next: number = gen(1)
+        "#
+        );
+        assert!(type_check_source_with_stdlib(&valid).is_ok());
+        assert!(!diagnostics_with_stdlib(&valid, CompilerOptions::default())
+            .iter()
+            .any(|d| d.level == DiagnosticLevel::Error));
+
+        for invalid in [
+            r#"local bad: Coroutine<number, nil, nil> = coroutine.create(function()
+                coroutine.yield("x")
+            end)"#,
+            r#"const r = coroutine.resume(co, 1)"#,
+            r#"local gen: () => number = coroutine.wrap(function()
+                coroutine.yield("x")
+                return 1
+            end)"#,
+        ] {
+            let source = format!("{}{}", prelude, invalid);
+            assert!(
+                diagnostics_with_stdlib(&source, CompilerOptions::default())
+                    .iter()
+                    .any(|d| d.level == DiagnosticLevel::Error),
+                "expected an error for: {}",
+                invalid
+            );
+        }
+
+        let too_many = format!(
+            "{}{}",
+            prelude, r#"const r = coroutine.resume(co, "a", "b")"#
+        );
+        assert!(check_with_stdlib(&too_many, CompilerOptions::default()).is_err());
+    }
+
+    #[test]
+    fn test_format_strings_checked_against_arguments() {
+        use crate::cli::diagnostics::error_codes;
+        use crate::cli::diagnostics::DiagnosticLevel;
+        let errors = |source: &str| {
+            diagnostics_with_stdlib(source, CompilerOptions::default())
+                .into_iter()
+                .filter(|d| d.level == DiagnosticLevel::Error)
+                .collect::<Vec<_>>()
+        };
+
+        let valid = r#"
+            const count: integer = 3
+            const a: string = string.format("%d items for %s (%5.1f%%)", count, "bob", 99.5)
+            const b: string = ("%-8s|%x"):format("id", 255)
+            function log(fmt: FormatString, ...args: unknown[]): void
+                print(fmt)
+            end
+            log("%s took %dms", "load", 12)
+        "#;
+        assert!(type_check_source_with_stdlib(valid).is_ok());
+        assert!(errors(valid).is_empty());
+
+        let malformed = errors(r#"local s = string.format("%y", 1)"#);
+        assert!(malformed
+            .iter()
+            .any(|d| d.code == Some(error_codes::INVALID_FORMAT_STRING)));
+
+        let count = errors(r#"local s = string.format("%d and %d", 1)"#);
+        assert!(count
+            .iter()
+            .any(|d| d.code == Some(error_codes::WRONG_ARG_COUNT)));
+
+        let lua54 = |source: &str| {
+            let options = CompilerOptions {
+                target: LuaVersion::Lua54,
+                ..Default::default()
+            };
+            diagnostics_with_stdlib(source, options)
+        };
+        assert!(lua54(r#"local s = string.format("%#d", 1)"#)
+            .iter()
+            .any(|d| d.code == Some(error_codes::INVALID_FORMAT_STRING)));
+        assert!(lua54(r#"local s = string.format("%d", 1.5)"#)
+            .iter()
+            .any(|d| d.code == Some(error_codes::TYPE_MISMATCH)));
+        assert!(lua54(r#"local s = string.format("%d", 2.0)"#)
+            .iter()
+            .all(|d| d.level != DiagnosticLevel::Error));
+
+        // Arguments of a FormatString function are inferred once
+        let nested = errors(
+            r#"function log(fmt: FormatString, ...args: unknown[]): void
+            end
+            log("%s", string.format("%y", 1))"#,
+        );
+        assert_eq!(
+            nested
+                .iter()
+                .filter(|d| d.code == Some(error_codes::INVALID_FORMAT_STRING))
+                .count(),
+            1
+        );
+
+        for invalid in [
+            r#"local s = string.format("%d", "ten")"#,
+            r#"local s = ("%f"):format(true)"#,
+            r#"function log(fmt: FormatString, ...args: unknown[]): void
+            end
+            log("%d items", "many")"#,
+        ] {
+            assert!(
+                errors(invalid)
+                    .iter()
+                    .any(|d| d.code == Some(error_codes::TYPE_MISMATCH)),
+                "expected a type mismatch for: {}",
+                invalid
+            );
+        }
+    }
+
+    #[test]
+    fn test_lua_patterns_validated_and_captures_typed() {
+        use crate::cli::diagnostics::error_codes;
+        use crate::cli::diagnostics::DiagnosticLevel;
+        let errors = |source: &str| {
+            diagnostics_with_stdlib(source, CompilerOptions::default())
+                .into_iter()
+                .filter(|d| d.level == DiagnosticLevel::Error)
+                .collect::<Vec<_>>()
+        };
+
+        let valid = r#"
+            const line: string = "key=value"
+            const pair: [string | nil, string | nil] = string.match(line, "(%w+)=(%w+)")
+            const word: string | nil = line:match("%a+")
+            const at: [number | nil, number | nil] = string.find(line, "=", 1, true)
+            local words: () => string = string.gmatch(line, "%a+")
+            const wrapped: [string, number] = string.gsub(line, "(%w+)", "<%1>")
+            const swapped: [string, number] = line:gsub("(%w+)=(%w+)", function(k: string, v: string): string
+                return v
+            end)
+        "#;
+        assert!(type_check_source_with_stdlib(valid).is_ok());
+        assert!(errors(valid).is_empty());
+
+        for invalid in [
+            r#"local m = string.match("x", "[abc")"#,
+            r#"local m = ("x"):find("%")"#,
+            r#"local m = string.match("x", "(%d+")"#,
+            r#"local m = string.gmatch("x", "%f")"#,
+            r#"local g = string.gsub("x", "(%w+)", "%2")"#,
+        ] {
+            assert!(
+                errors(invalid)
+                    .iter()
+                    .any(|d| d.code == Some(error_codes::INVALID_PATTERN)),
+                "expected an invalid pattern for: {}",
+                invalid
+            );
+        }
+
+        let arity = errors(
+            r#"local g = string.gsub("a=b", "(%w+)", function(k: string, v: string): string
+                return k
+            end)"#,
+        );
+        assert!(arity
+            .iter()
+            .any(|d| d.code == Some(error_codes::WRONG_ARG_COUNT)));
+
+        let position = errors(
+            r#"local g = string.gsub("ab", "()b", function(p: string): string
+                return p
+            end)"#,
+        );
+        assert!(position
+            .iter()
+            .any(|d| d.code == Some(error_codes::TYPE_MISMATCH)));
+
+        assert!(!errors(r#"const m: string = string.match("x", "%d+")"#).is_empty());
+    }
+
+    #[test]
+    fn test_protected_calls_typed() {
+        use crate::cli::diagnostics::error_codes;
+        use crate::cli::diagnostics::DiagnosticLevel;
+        let errors = |source: &str| {
+            diagnostics_with_stdlib(source, CompilerOptions::default())
+                .into_iter()
+                .filter(|d| d.level == DiagnosticLevel::Error)
+                .collect::<Vec<_>>()
+        };
+
+        let parse = r#"
+            function parse(s: string): number
+                if s == "" then
+                    error({ code = 1 })
+                end
+                return 1
+            end
+        "#;
+
+        let valid = format!(
+            r#"{}
+            local [ok, res] = pcall(parse, "42")
+            if ok then
+                const n: number = res
+            else
+                const code: number = res.code
+            end
+            local [handled, value] = xpcall(parse, function(err: {{ code: number }}): string
+                return "failed"
+            end, "42")
+            if not handled then
+                const message: string = value
+            end
+        "#,
+            parse
+        );
+        assert!(type_check_source_with_stdlib(&valid).is_ok());
+        assert!(errors(&valid).is_empty(), "{:?}", errors(&valid));
+
+        // The result is only a number once `ok` has been tested
+        let untested = format!(
+            r#"{}
+            local [ok, res] = pcall(parse, "42")
+            const n: number = res
+        "#,
+            parse
+        );
+        assert!(!errors(&untested).is_empty());
+
+        let wrong_argument = format!("{}\nlocal [ok, res] = pcall(parse, 42)", parse);
+        assert!(errors(&wrong_argument)
+            .iter()
+            .any(|d| d.code == Some(error_codes::TYPE_MISMATCH)));
+
+        let missing_argument = format!("{}\nlocal [ok, res] = pcall(parse)", parse);
+        assert!(errors(&missing_argument)
+            .iter()
+            .any(|d| d.code == Some(error_codes::WRONG_ARG_COUNT)));
+
+        let wrong_handler = format!(
+            r#"{}
+            local [ok, res] = xpcall(parse, function(err: number): string
+                return "failed"
+            end, "42")
+        "#,
+            parse
+        );
+        assert!(errors(&wrong_handler)
+            .iter()
+            .any(|d| d.code == Some(error_codes::TYPE_MISMATCH)));
+    }
+
+    #[test]
+    fn test_protected_call_correlation_follows_declaration() {
+        use crate::cli::diagnostics::DiagnosticLevel;
+        let errors = |body: &str| {
+            let source = format!(
+                r#"
+                function parse(s: string): number
+                    return 1
+                end
+                local [ok, res] = pcall(parse, "42")
+                {}
+            "#,
+                body
+            );
+            diagnostics_with_stdlib(&source, CompilerOptions::default())
+                .into_iter()
+                .filter(|d| d.level == DiagnosticLevel::Error)
+                .collect::<Vec<_>>()
+        };
+
+        let tested = r#"
+            if ok then
+                const n: number = res
+            end
+        "#;
+        assert!(errors(tested).is_empty(), "{:?}", errors(tested));
+
+        // Reassigning either variable drops the correlation
+        for reassigned in [
+            r#"
+            ok = true
+            if ok then
+                const n: number = res
+            end
+        "#,
+            r#"
+            res = "failed"
+            if ok then
+                const n: number = res
+            end
+        "#,
+            r#"
+            if res then
+                ok = true
+            end
+            if ok then
+                const n: number = res
+            end
+        "#,
+        ] {
+            assert!(
+                !errors(reassigned).is_empty(),
+                "expected an error for: {}",
+                reassigned
+            );
+        }
+
+        // A shadowing `ok` is not the discriminant
+        let shadowed = r#"
+            do
+                local ok = true
+                if ok then
+                    const n: number = res
+                end
+            end
+        "#;
+        assert!(!errors(shadowed).is_empty());
+
+        // A correlation ends with the block that declared it
+        let out_of_scope = r#"
+            local flag = true
+            local value: number | string = "none"
+            do
+                local [flag, value] = pcall(parse, "42")
+            end
+            if flag then
+                const n: number = value
+            end
+        "#;
+        assert!(!errors(out_of_scope).is_empty());
+    }
+
+    #[test]
+    fn test_definition_packs() {
+        use crate::cli::diagnostics::error_codes;
+        use crate::cli::diagnostics::DiagnosticLevel;
+        let with_libraries = |libraries: &[&str]| CompilerOptions {
+            libraries: libraries.iter().map(|l| l.to_string()).collect(),
+            ..Default::default()
+        };
+
+        let game = r#"
+            love.graphics.setColor(1, 0, 0)
+            love.graphics.print("score", 10, 10)
+            const dt: number = love.timer.getDelta()
+        "#;
+        assert!(check_with_stdlib(game, with_libraries(&["love2d"])).is_ok());
+        assert!(check_with_stdlib(game, CompilerOptions::default()).is_err());
+
+        let wrong = r#"love.graphics.setColor("red")"#;
+        assert!(check_with_stdlib(wrong, with_libraries(&["love2d"])).is_err());
+
+        // Several packs are merged into the same global scope
+        let both = r#"
+            vim.notify("loaded")
+            ngx.say("hello")
+        "#;
+        assert!(check_with_stdlib(both, with_libraries(&["nvim", "openresty"])).is_ok());
+
+        // A type root augmenting a namespace that a bundled pack declares
+        let root = std::env::temp_dir().join(format!("luanext-packs-{}", std::process::id()));
+        std::fs::create_dir_all(&root).unwrap();
+        std::fs::write(
+            root.join("vim.d.luax"),
+            "declare namespace vim {\n  export function spawn(name: string): number\n}\ndeclare function print(value: unknown): void",
+        )
+        .unwrap();
+        let options = CompilerOptions {
+            type_roots: vec![root.display().to_string()],
+            ..with_libraries(&["nvim"])
+        };
+        let diagnostics = diagnostics_with_stdlib(
+            "vim.notify(\"loaded\")\nconst id: number = vim.spawn(\"x\")",
+            options,
+        );
+        std::fs::remove_dir_all(&root).unwrap();
+
+        // The namespaces are merged; redeclaring the stdlib's `print` conflicts
+        let conflicts: Vec<_> = diagnostics
+            .iter()
+            .filter(|d| d.level == DiagnosticLevel::Error)
+            .collect();
+        assert_eq!(conflicts.len(), 1, "{:?}", conflicts);
+        assert_eq!(conflicts[0].code, Some(error_codes::DUPLICATE_DECLARATION));
+        assert!(conflicts[0]
+            .message
+            .contains("'print' is declared by both the standard library"));
+
+        // A member both declare is a conflict
+        std::fs::create_dir_all(&root).unwrap();
+        std::fs::write(
+            root.join("vim.d.luax"),
+            "declare namespace vim {\n  export function notify(message: number): void\n}",
+        )
+        .unwrap();
+        let options = CompilerOptions {
+            type_roots: vec![root.display().to_string()],
+            ..with_libraries(&["nvim"])
+        };
+        let diagnostics = diagnostics_with_stdlib("vim.notify(\"loaded\")", options);
+        std::fs::remove_dir_all(&root).unwrap();
+        assert!(diagnostics.iter().any(|d| d
+            .message
+            .contains("'vim.notify' is declared by both 'nvim'")));
+    }
+
+    #[test]
+    fn test_definition_pack_errors_keep_stdlib() {
+        use crate::cli::diagnostics::error_codes;
+        use crate::cli::diagnostics::DiagnosticLevel;
+
+        let options = CompilerOptions {
+            libraries: vec!["love".to_string(), "love2d".to_string()],
+            type_roots: vec!["/nonexistent/luanext-types".to_string()],
+            ..Default::default()
+        };
+        let diagnostics = diagnostics_with_stdlib(
+            "print(\"start\")\nconst dt: number = love.timer.getDelta()",
+            options,
+        );
+        let errors: Vec<_> = diagnostics
+            .iter()
+            .filter(|d| d.level == DiagnosticLevel::Error)
+            .collect();
+        assert_eq!(errors.len(), 2, "{:?}", errors);
+        assert!(errors
+            .iter()
+            .all(|d| d.code == Some(error_codes::INVALID_CONFIG)));
+        assert!(errors[0].message.contains("Unknown library 'love'"));
+        assert!(errors[1].message.contains("luanext-types"));
+    }
+
+    #[test]
+    fn test_doc_comments_recorded() {
+        let source = r#"
+            ---
+            -- Adds two numbers.
+            -- @param a The first number
+            -- @returns The sum
+            function add(a: number, b: number): number
+                return a + b
+            end
+
+            --- A bank account
+            class Account {
+                --- Current balance
+                balance: number = 0
+
+                ---@deprecated use deposit instead
+                put(amount: number): void {
+                }
+            }
+
+            -- not documentation
+            local plain = 1
+        "#;
+
+        let arena = Bump::new();
+        let handler = Arc::new(CollectingDiagnosticHandler::new());
+        let (interner, common) =
+            luanext_parser::string_interner::StringInterner::new_with_common_identifiers();
+        let mut lexer = Lexer::new(source, handler.clone(), &interner);
+        let tokens = lexer.tokenize().expect("Lexing failed");
+        let mut parser = Parser::new(tokens, handler.clone(), &interner, &common, &arena);
+        let program = parser.parse().expect("Parsing failed");
+
+        let mut type_checker = TypeChecker::new_with_stdlib(handler, &interner, &common, &arena)
+            .expect("Failed to load stdlib")
+            .with_source(source);
+        type_checker.check_program(&program).unwrap();
+
+        let add = type_checker.doc_comment("add").unwrap();
+        assert_eq!(add.summary, "Adds two numbers.");
+        assert_eq!(add.param("a"), Some("The first number"));
+        assert_eq!(add.returns.as_deref(), Some("The sum"));
+
+        assert_eq!(
+            type_checker.doc_comment("Account").unwrap().summary,
+            "A bank account"
+        );
+        assert_eq!(
+            type_checker.doc_comment("Account.balance").unwrap().summary,
+            "Current balance"
+        );
+        let put = type_checker.doc_comment("Account.put").unwrap();
+        assert_eq!(
+            put.deprecated.as_ref().unwrap().message.as_deref(),
+            Some("use deposit instead")
+        );
+        assert!(type_checker.doc_comment("plain").is_none());
+
+        // Standard library declarations and namespace members
+        assert!(type_checker
+            .doc_comment("print")
+            .unwrap()
+            .summary
+            .starts_with("Prints the values"));
+        assert_eq!(
+            type_checker.doc_comment("string.sub").unwrap().summary,
+            "Extracts a substring."
+        );
+    }
+
+    #[test]
+    fn test_deprecated_references_warn() {
+        use crate::cli::config::LuaVersion;
+        use crate::cli::diagnostics::{error_codes::DEPRECATED, DiagnosticLevel};
+
+        let source = r#"
+            ---@deprecated use add
+            function sum(a: number, b: number): number
+                return a + b
+            end
+
+            class Account {
+                @deprecated("use deposit")
+                put(amount: number): void {
+                }
+            }
+
+            enum Color {
+                Red = 1,
+                ---@deprecated use Color.Red
+                Crimson = 2,
+            }
+
+            function pay(account: Account): void
+                account:put(5)
+            end
+
+            local total = sum(1, 2)
+            local color = Color.Crimson
+            local red = Color.Red
+        "#;
+
+        let arena = Bump::new();
+        let handler = Arc::new(CollectingDiagnosticHandler::new());
+        let (interner, common) =
+            luanext_parser::string_interner::StringInterner::new_with_common_identifiers();
+        let mut lexer = Lexer::new(source, handler.clone(), &interner);
+        let tokens = lexer.tokenize().expect("Lexing failed");
+        let mut parser = Parser::new(tokens, handler.clone(), &interner, &common, &arena);
+        let program = parser.parse().expect("Parsing failed");
+
+        let mut type_checker =
+            TypeChecker::new_with_stdlib(handler.clone(), &interner, &common, &arena)
+                .expect("Failed to load stdlib")
+                .with_source(source);
+        type_checker.check_program(&program).unwrap();
+
+        let warnings: Vec<String> = handler
+            .get_diagnostics()
+            .into_iter()
+            .filter(|d| d.level == DiagnosticLevel::Warning && d.code == Some(DEPRECATED))
+            .map(|d| d.message)
+            .collect();
+        assert_eq!(
+            warnings,
+            vec![
+                "'Account.put' is deprecated: use deposit",
+                "'sum' is deprecated: use add",
+                "'Color.Crimson' is deprecated: use Color.Red",
+            ]
+        );
+        assert!(!type_checker
+            .symbol_table()
+            .lookup("sum")
+            .unwrap()
+            .references
+            .is_empty());
+
+        // Standard library entries deprecated on the target
+        let options = |target| CompilerOptions {
+            target,
+            ..Default::default()
+        };
+        let diagnostics = diagnostics_with_stdlib(
+            "local f = loadstring(\"return 1\")",
+            options(LuaVersion::Lua52),
+        );
+        assert!(diagnostics
+            .iter()
+            .any(|d| d.code == Some(DEPRECATED) && d.message.contains("use load")));
+        let diagnostics =
+            diagnostics_with_stdlib("local p = math.pow(2, 3)", options(LuaVersion::Lua52));
+        assert!(has_code(&diagnostics, DEPRECATED));
+        assert!(check_with_stdlib("local p = math.pow(2, 3)", options(LuaVersion::Lua54)).is_err());
+        let diagnostics =
+            diagnostics_with_stdlib("local n = #{} + math.abs(-1)", options(LuaVersion::Lua54));
+        assert!(!has_code(&diagnostics, DEPRECATED));
+    }
+
+    #[test]
+    fn test_with_options_keeps_snapshot_for_the_same_effective_target() {
+        use crate::cli::config::LuaVersion;
+
+        let snapshot = StdlibSnapshot::for_target(LuaVersion::Auto).unwrap();
+        let (interner, common) = snapshot.interner();
+        let arena = Bump::new();
+        let handler = Arc::new(CollectingDiagnosticHandler::new());
+        let marker =
+            |checker: &TypeChecker| checker.symbol_table.lookup("snapshot_marker").is_some();
+
+        let mut checker =
+            TypeChecker::new_with_snapshot(handler.clone(), &interner, &common, &arena, &snapshot)
+                .unwrap();
+        let span = Span::default();
+        checker
+            .symbol_table
+            .declare(Symbol::new(
+                "snapshot_marker".to_string(),
+                SymbolKind::Variable,
+                Type::new(TypeKind::Primitive(PrimitiveType::Number), span),
+                span,
+            ))
+            .unwrap();
+
+        // The default options target `Auto`, which resolves to the snapshot's version
+        let checker = checker.with_options(CompilerOptions::default());
+        assert!(marker(&checker));
+        let checker = checker.with_options(CompilerOptions {
+            target: snapshot.target(),
+            ..Default::default()
+        });
+        assert!(marker(&checker));
+
+        let other = if snapshot.target() == LuaVersion::Lua51 {
+            LuaVersion::Lua52
+        } else {
+            LuaVersion::Lua51
+        };
+        let checker = checker.with_options(CompilerOptions {
+            target: other,
+            ..Default::default()
+        });
+        assert!(!marker(&checker));
+    }
+
+    #[test]
+    fn test_deprecated_type_annotations_warn() {
+        use crate::cli::diagnostics::{error_codes::DEPRECATED, DiagnosticLevel};
+
+        let source = r#"
+            ---@deprecated use Money
+            type Amount = number
+
+            @deprecated("use Wallet")
+            class Purse {
+            }
+
+            local price: Amount = 5
+            local purse: Purse = new Purse()
+            local total: number = price
+        "#;
+
+        let arena = Bump::new();
+        let handler = Arc::new(CollectingDiagnosticHandler::new());
+        let (interner, common) =
+            luanext_parser::string_interner::StringInterner::new_with_common_identifiers();
+        let mut lexer = Lexer::new(source, handler.clone(), &interner);
+        let tokens = lexer.tokenize().expect("Lexing failed");
+        let mut parser = Parser::new(tokens, handler.clone(), &interner, &common, &arena);
+        let program = parser.parse().expect("Parsing failed");
+
+        let mut type_checker =
+            TypeChecker::new_with_stdlib(handler.clone(), &interner, &common, &arena)
+                .expect("Failed to load stdlib")
+                .with_source(source);
+        type_checker.check_program(&program).unwrap();
+
+        let warnings: Vec<String> = handler
+            .get_diagnostics()
+            .into_iter()
+            .filter(|d| d.level == DiagnosticLevel::Warning && d.code == Some(DEPRECATED))
+            .map(|d| d.message)
+            .collect();
+        assert_eq!(
+            warnings,
+            vec![
+                "'Amount' is deprecated: use Money",
+                "'Purse' is deprecated: use Wallet",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_nested_declaration_docs() {
+        use crate::cli::diagnostics::{error_codes::DEPRECATED, DiagnosticLevel};
+
+        let source = r#"
+            function run(): number
+                ---@deprecated use limit
+                local max = 10
+
+                class Counter {
+                    @deprecated("use step")
+                    bump(): void {
+                    }
+                }
+
+                local counter = new Counter()
+                counter:bump()
+                return max
+            end
+        "#;
+
+        let arena = Bump::new();
+        let handler = Arc::new(CollectingDiagnosticHandler::new());
+        let (interner, common) =
+            luanext_parser::string_interner::StringInterner::new_with_common_identifiers();
+        let mut lexer = Lexer::new(source, handler.clone(), &interner);
+        let tokens = lexer.tokenize().expect("Lexing failed");
+        let mut parser = Parser::new(tokens, handler.clone(), &interner, &common, &arena);
+        let program = parser.parse().expect("Parsing failed");
+
+        let mut type_checker =
+            TypeChecker::new_with_stdlib(handler.clone(), &interner, &common, &arena)
+                .expect("Failed to load stdlib")
+                .with_source(source);
+        type_checker.check_program(&program).unwrap();
+
+        let warnings: Vec<String> = handler
+            .get_diagnostics()
+            .into_iter()
+            .filter(|d| d.level == DiagnosticLevel::Warning && d.code == Some(DEPRECATED))
+            .map(|d| d.message)
+            .collect();
+        assert_eq!(
+            warnings,
+            vec![
+                "'Counter.bump' is deprecated: use step",
+                "'max' is deprecated: use limit",
+            ]
+        );
+        // The docs belong to the function's scope
+        assert!(type_checker.doc_comment("Counter.bump").is_none());
+    }
+
+    #[test]
+    fn test_overloads_are_selected_by_literal_arguments() {
+        use crate::cli::config::LuaVersion;
+        use crate::cli::diagnostics::DiagnosticLevel;
+
+        let accepts = |source: &str, target| {
+            let options = || CompilerOptions {
+                target,
+                ..Default::default()
+            };
+            check_with_stdlib(source, options()).is_ok()
+                && !diagnostics_with_stdlib(source, options())
+                    .iter()
+                    .any(|d| d.level == DiagnosticLevel::Error)
+        };
+
+        // Declared overloads: the first signature accepting the arguments wins
+        let parse = r#"
+            declare function parse(kind: "int", text: string): number
+            declare function parse(kind: "bool", text: string): boolean
+        "#;
+        assert!(accepts(
+            &format!(
+                "{}\nlocal n: number = parse(\"int\", \"1\")\nlocal b: boolean = parse(\"bool\", \"true\")",
+                parse
+            ),
+            LuaVersion::Lua54
+        ));
+        assert!(!accepts(
+            &format!("{}\nlocal b: boolean = parse(\"int\", \"1\")", parse),
+            LuaVersion::Lua54
+        ));
+        assert!(!accepts(
+            &format!("{}\nlocal b = parse(\"float\", \"1.5\")", parse),
+            LuaVersion::Lua54
+        ));
+
+        // Read formats, date formats and open modes of the standard library
+        for source in [
+            "local n: number | nil = io.read(\"n\")",
+            "local n: number | nil = io.stdin:read(\"*n\")",
+            "local line: string | nil = io.stdin:read(\"l\")",
+            "local line: string | nil = io.read()",
+            "local chunk: string | nil = io.read(1024)",
+            "local numbers: () -> Nullable<number> = io.lines(\"data.txt\", \"n\")",
+            "local t: DateTable = os.date(\"*t\")",
+            "local utc: DateTable = os.date(\"!*t\", 0)",
+            "local s: string = os.date(\"%Y-%m-%d\")",
+            "local f = io.open(\"data.txt\", \"rb\")",
+            "local mode: string = \"r\"\nlocal f = io.open(\"data.txt\", mode)",
+        ] {
+            assert!(accepts(source, LuaVersion::Lua54), "{}", source);
+        }
+        for source in [
+            "local n: number | nil = io.read(\"l\")",
+            "local n: number | nil = io.stdin:read(\"a\")",
+            "local s: string = os.date(\"*t\")",
+            "local f = io.open(\"data.txt\", 1)",
+        ] {
+            assert!(!accepts(source, LuaVersion::Lua54), "{}", source);
+        }
+
+        // Lua 5.1 only knows the starred formats
+        assert!(accepts(
+            "local n: number | nil = io.read(\"*n\")",
+            LuaVersion::Lua51
+        ));
+        assert!(!accepts(
+            "local s: string | nil = io.read(\"*n\")",
+            LuaVersion::Lua51
+        ));
+    }
+
+    #[test]
+    fn test_reflect_calls_are_typed_from_the_argument() {
+        let classes = r#"
+            class Entity {
+                id: number
+            }
+
+            class User extends Entity {
+                name: string
+                static count: number
+                greet(): string return self.name end
+            }
+        "#;
+        let check = |body: &str| type_check_source_with_stdlib(&format!("{}\n{}", classes, body));
+
+        assert!(check(
+            r#"
+            function describe(user: User): void
+                local field: "id" | "name" | "count" = Reflect.getFields(user)[1].name
+                local own: "name" | "count" = Reflect.getOwnFields(user)[1].name
+                local method: "greet" = Reflect.getMethods(user)[1].name
+                local info: ReflectMember = Reflect.getFields(user)[1]
+            end
+        "#
+        )
+        .is_ok());
+        assert!(check(
+            r#"
+            function describe(user: User): void
+                local field: "id" | "name" = Reflect.getFields(user)[1].name
+            end
+        "#
+        )
+        .is_err());
+
+        // `Reflect.isInstance` narrows like `instanceof`
+        assert!(check(
+            r#"
+            function nameOf(value: Entity | string): string
+                if Reflect.isInstance(value, User) then
+                    local user: User = value
+                    return user.name
+                end
+                return ""
+            end
+        "#
+        )
+        .is_ok());
+    }
+
+    #[test]
+    fn test_require_calls_are_typed_from_module_exports() {
+        use crate::cli::fs::MockFileSystem;
+        use crate::module_resolver::{
+            EdgeKind, ExportedSymbol, LuaFilePolicy, ModuleConfig, ModuleExports, ModuleId,
+            ModuleRegistry, ModuleResolver, PathAliasResolver, TypedDependency,
+        };
+        use std::path::{Path, PathBuf};
+
+        let main = Path::new("/project/src/main.luax");
+        let mut fs = MockFileSystem::new();
+        fs.add_file(main, "");
+        fs.add_file("/project/lua_modules/dkjson.lua", "");
+        fs.add_file("/project/lua_modules/dkjson.d.luax", "");
+        fs.add_file("/project/lua_modules/util/init.luax", "");
+        fs.add_file("/project/lua_modules/legacy.lua", "");
+        let config = ModuleConfig {
+            module_paths: vec![PathBuf::from("/project/lua_modules")],
+            lua_file_policy: LuaFilePolicy::RequireDeclaration,
+            path_aliases: PathAliasResolver::empty(),
+        };
+        let resolver = Arc::new(ModuleResolver::new(
+            Arc::new(fs),
+            config,
+            PathBuf::from("/project"),
+        ));
+
+        let export = |name: &str, kind: SymbolKind, typ: PrimitiveType| {
+            let span = Span::default();
+            let symbol = Symbol::new(
+                name.to_string(),
+                kind,
+                Type::new(TypeKind::Primitive(typ), span),
+                span,
+            );
+            ExportedSymbol::new(symbol, kind == SymbolKind::TypeAlias)
+        };
+        let registry = Arc::new(ModuleRegistry::new());
+        let json_id = resolver.resolve("dkjson", main).unwrap();
+        let mut json_exports = ModuleExports::new();
+        json_exports.add_named(
+            "version".to_string(),
+            export("version", SymbolKind::Variable, PrimitiveType::String),
+        );
+        json_exports.add_named(
+            "JsonValue".to_string(),
+            export("JsonValue", SymbolKind::TypeAlias, PrimitiveType::Unknown),
+        );
+        registry.register_parsed(json_id.clone(), Arc::new(SymbolTable::new()));
+        registry.register_exports(&json_id, json_exports).unwrap();
+        let util_id = resolver.resolve("util", main).unwrap();
+        let mut util_exports = ModuleExports::new();
+        util_exports.set_default(export("count", SymbolKind::Variable, PrimitiveType::Number));
+        registry.register_parsed(util_id.clone(), Arc::new(SymbolTable::new()));
+        registry.register_exports(&util_id, util_exports).unwrap();
+
+        let check = |source: &str| -> (bool, Vec<TypedDependency>) {
+            let arena = Bump::new();
+            let handler = Arc::new(CollectingDiagnosticHandler::new());
+            let (interner, common) =
+                luanext_parser::string_interner::StringInterner::new_with_common_identifiers();
+            let mut lexer = Lexer::new(source, handler.clone(), &interner);
+            let tokens = lexer.tokenize().expect("Lexing failed");
+            let mut parser = Parser::new(tokens, handler.clone(), &interner, &common, &arena);
+            let program = parser.parse().expect("Parsing failed");
+            let mut checker = TypeChecker::new_with_module_support(
+                handler.clone(),
+                &interner,
+                &common,
+                &arena,
+                registry.clone(),
+                ModuleId::new(main.to_path_buf()),
+                resolver.clone(),
+            );
+            checker.load_stdlib().expect("Failed to load stdlib");
+            let result = checker.check_program(&program);
+            let has_errors = handler
+                .get_diagnostics()
+                .iter()
+                .any(|d| d.level == crate::cli::diagnostics::DiagnosticLevel::Error);
+            (
+                result.is_ok() && !has_errors,
+                checker.get_module_dependencies().to_vec(),
+            )
+        };
+
+        // A declared plain Lua module, a package `init.luax` with a default
+        // export and a module outside the compilation
+        let (ok, dependencies) = check(
+            r#"
+            local json = require("dkjson")
+            local version: string = json.version
+            local again = require("dkjson")
+            local count: number = require("util")
+            local lfs = require("lfs")
+        "#,
+        );
+        assert!(ok);
+        assert_eq!(
+            dependencies,
+            vec![
+                TypedDependency::new(json_id.path().to_path_buf(), EdgeKind::Value),
+                TypedDependency::new(util_id.path().to_path_buf(), EdgeKind::Value),
+            ]
+        );
+
+        assert!(!check(r#"local version: number = require("dkjson").version"#).0);
+        // Type-only exports are not part of the module's value
+        assert!(!check(r#"local value = require("dkjson").JsonValue"#).0);
+        // A plain Lua module needs a declaration
+        assert!(!check(r#"local legacy = require("legacy")"#).0);
+    }
+
+    #[test]
+    fn test_require_checks_unchecked_modules_through_the_lazy_callback() {
+        use crate::cli::fs::MockFileSystem;
+        use crate::module_resolver::{
+            ExportedSymbol, LuaFilePolicy, ModuleConfig, ModuleError, ModuleExports, ModuleId,
+            ModuleRegistry, ModuleResolver, PathAliasResolver,
+        };
+        use crate::phases::module_phase::LazyTypeCheckCallback;
+        use std::path::{Path, PathBuf};
+
+        struct DefaultNumberExport {
+            registry: Arc<ModuleRegistry>,
+        }
+
+        impl LazyTypeCheckCallback for DefaultNumberExport {
+            fn type_check_dependency(&self, module_id: &ModuleId) -> Result<(), ModuleError> {
+                let span = Span::default();
+                let count = Symbol::new(
+                    "count".to_string(),
+                    SymbolKind::Variable,
+                    Type::new(TypeKind::Primitive(PrimitiveType::Number), span),
+                    span,
+                );
+                let mut exports = ModuleExports::new();
+                exports.set_default(ExportedSymbol::new(count, false));
+                self.registry
+                    .register_parsed(module_id.clone(), Arc::new(SymbolTable::new()));
+                self.registry.register_exports(module_id, exports)
+            }
+        }
+
+        let main = Path::new("/project/src/main.luax");
+        let mut fs = MockFileSystem::new();
+        fs.add_file(main, "");
+        fs.add_file("/project/lua_modules/counter.luax", "");
+        let resolver = Arc::new(ModuleResolver::new(
+            Arc::new(fs),
+            ModuleConfig {
+                module_paths: vec![PathBuf::from("/project/lua_modules")],
+                lua_file_policy: LuaFilePolicy::RequireDeclaration,
+                path_aliases: PathAliasResolver::empty(),
+            },
+            PathBuf::from("/project"),
+        ));
+        let registry = Arc::new(ModuleRegistry::new());
+
+        let source = r#"local count: number = require("counter")"#;
+        let arena = Bump::new();
+        let handler = Arc::new(CollectingDiagnosticHandler::new());
+        let (interner, common) =
+            luanext_parser::string_interner::StringInterner::new_with_common_identifiers();
+        let mut lexer = Lexer::new(source, handler.clone(), &interner);
+        let tokens = lexer.tokenize().expect("Lexing failed");
+        let mut parser = Parser::new(tokens, handler.clone(), &interner, &common, &arena);
+        let program = parser.parse().expect("Parsing failed");
+        let mut checker = TypeChecker::new_with_module_support(
+            handler.clone(),
+            &interner,
+            &common,
+            &arena,
+            registry.clone(),
+            ModuleId::new(main.to_path_buf()),
+            resolver,
+        )
+        .with_lazy_callback(Arc::new(DefaultNumberExport {
+            registry: registry.clone(),
+        }));
+        checker.load_stdlib().expect("Failed to load stdlib");
+
+        assert!(checker.check_program(&program).is_ok());
+        assert!(!handler
+            .get_diagnostics()
+            .iter()
+            .any(|d| d.level == crate::cli::diagnostics::DiagnosticLevel::Error));
+        let counter = ModuleId::new(PathBuf::from("/project/lua_modules/counter.luax"));
+        assert!(registry.get_exports(&counter).is_ok());
+    }
 }
@@ -150,6 +150,16 @@ impl TypeCompatibility {
             (TypeKind::Reference(s_ref), _) => {
                 let s_name = interner.resolve(s_ref.name.node);
 
+                // A typed coroutine is still a Lua thread
+                if s_name == crate::types::coroutine::COROUTINE_TYPE
+                    && matches!(
+                        target.kind,
+                        TypeKind::Primitive(PrimitiveType::Coroutine | PrimitiveType::Thread)
+                    )
+                {
+                    return true;
+                }
+
                 if let Some(resolved) = type_env.lookup_type_alias(&s_name) {
                     Self::is_assignable_with_env_recursive(
                         resolved, target, type_env, interner, visited,
@@ -262,19 +272,20 @@ impl TypeCompatibility {
                     .all(|s| Self::is_assignable_recursive(s, target, visited))
             }
 
-            // Intersection types
-            (TypeKind::Intersection(sources), _) => {
-                // Intersection is assignable to target if any member is assignable
-                sources
-                    .iter()
-                    .any(|s| Self::is_assignable_recursive(s, target, visited))
-            }
+            // Intersection types. The target is split first so that `A & B` to
+            // `A & B` checks each target member against the whole source.
             (_, TypeKind::Intersection(targets)) => {
                 // Source is assignable to intersection if assignable to all members
                 targets
                     .iter()
                     .all(|t| Self::is_assignable_recursive(source, t, visited))
             }
+            (TypeKind::Intersection(sources), _) => {
+                // Intersection is assignable to target if any member is assignable
+                sources
+                    .iter()
+                    .any(|s| Self::is_assignable_recursive(s, target, visited))
+            }
 
             // Array types
             (TypeKind::Array(s_elem), TypeKind::Array(t_elem)) => {
@@ -380,9 +391,15 @@ impl TypeCompatibility {
 
     /// Check if a literal is assignable to a primitive type
     fn is_literal_assignable_to_primitive(lit: &Literal, prim: PrimitiveType) -> bool {
+        // Float literals such as `3.0` or `1e3` are never integers, even
+        // when their value is integral
         matches!(
             (lit, prim),
             (Literal::Number(_), PrimitiveType::Number)
+                | (
+                    Literal::Integer(_),
+                    PrimitiveType::Number | PrimitiveType::Integer
+                )
                 | (Literal::String(_), PrimitiveType::String)
                 | (Literal::Boolean(_), PrimitiveType::Boolean)
                 | (Literal::Nil, PrimitiveType::Nil)
@@ -498,6 +515,14 @@ mod tests {
         assert!(!TypeCompatibility::is_assignable(&num_lit, &string));
         assert!(TypeCompatibility::is_assignable(&str_lit, &string));
         assert!(!TypeCompatibility::is_assignable(&str_lit, &number));
+
+        let integer = make_type(TypeKind::Primitive(PrimitiveType::Integer));
+        let int_lit = make_type(TypeKind::Literal(Literal::Integer(42)));
+        let float_lit = make_type(TypeKind::Literal(Literal::Number(0.5)));
+        assert!(TypeCompatibility::is_assignable(&int_lit, &integer));
+        assert!(TypeCompatibility::is_assignable(&int_lit, &number));
+        assert!(!TypeCompatibility::is_assignable(&num_lit, &integer));
+        assert!(!TypeCompatibility::is_assignable(&float_lit, &integer));
     }
 
     #[test]
@@ -625,4 +650,47 @@ mod tests {
         // Functions should be compatible
         assert!(TypeCompatibility::is_assignable(&func1, &func2));
     }
+
+    #[test]
+    fn test_intersection_to_intersection() {
+        let arena = Bump::new();
+        let interner = luanext_parser::string_interner::StringInterner::new();
+        let object = |name: &str, kind: PrimitiveType| {
+            make_type(TypeKind::Object(ObjectType {
+                members: arena.alloc_slice_fill_iter([ObjectTypeMember::Property(
+                    luanext_parser::ast::types::PropertySignature {
+                        is_readonly: false,
+                        name: luanext_parser::ast::Ident::new(
+                            interner.intern(name),
+                            Span::new(0, 0, 0, 0),
+                        ),
+                        is_optional: false,
+                        type_annotation: make_type(TypeKind::Primitive(kind)),
+                        span: Span::new(0, 0, 0, 0),
+                    },
+                )]),
+                span: Span::new(0, 0, 0, 0),
+            }))
+        };
+        let a = object("a", PrimitiveType::Number);
+        let b = object("b", PrimitiveType::String);
+        let c = object("c", PrimitiveType::Boolean);
+        let a_and_b = make_type(TypeKind::Intersection(
+            arena.alloc_slice_fill_iter([a.clone(), b.clone()]),
+        ));
+        let b_and_a = make_type(TypeKind::Intersection(
+            arena.alloc_slice_fill_iter([b.clone(), a.clone()]),
+        ));
+        let a_and_c = make_type(TypeKind::Intersection(
+            arena.alloc_slice_fill_iter([a.clone(), c]),
+        ));
+
+        // No single member of `{a} & {b}` satisfies both members of the same
+        // intersection, so the target has to be split before the source
+        assert!(TypeCompatibility::is_assignable(&a_and_b, &a_and_b.clone()));
+        assert!(TypeCompatibility::is_assignable(&a_and_b, &b_and_a));
+        assert!(TypeCompatibility::is_assignable(&a_and_b, &a));
+        assert!(!TypeCompatibility::is_assignable(&a, &a_and_b));
+        assert!(!TypeCompatibility::is_assignable(&a_and_c, &a_and_b));
+    }
 }
@@ -1,6 +1,9 @@
-use luanext_parser::ast::statement::{ConstructorParameter, TypeParameter};
-use luanext_parser::ast::types::{PrimitiveType, Type, TypeKind};
+use luanext_parser::ast::expression::Literal;
+use luanext_parser::ast::statement::{ConstructorParameter, Parameter, TypeParameter};
+use luanext_parser::ast::types::{PrimitiveType, Type, TypeKind, TypeReference};
+use luanext_parser::ast::Ident;
 use luanext_parser::span::Span;
+use luanext_parser::string_interner::StringId;
 use rustc_hash::FxHashMap;
 use std::sync::Arc;
 
@@ -46,6 +49,14 @@ pub struct TypeEnvironment<'arena> {
     abstract_classes: FxHashMap<String, bool>,
     /// Class primary constructors (class name -> constructor parameters)
     class_constructors: FxHashMap<String, &'arena [ConstructorParameter<'arena>]>,
+    /// Class body constructors (class name -> `constructor(...)` parameters)
+    class_constructor_params: FxHashMap<String, &'arena [Parameter<'arena>]>,
+    /// Class member declaration spans (class name -> member name -> span)
+    class_member_spans: FxHashMap<String, FxHashMap<String, Span>>,
+    /// Shapes of open Lua tables (`local M = {}`) that grow by assignment, keyed by
+    /// the name and span of the reference naming them, so that same-named tables
+    /// declared in different scopes stay apart
+    open_tables: FxHashMap<(StringId, usize, usize), Type<'arena>>,
     /// Interface type parameter names (interface name -> ordered parameter names)
     interface_type_params: FxHashMap<String, Vec<String>>,
     /// Cached primitive types (singletons)
@@ -99,6 +110,9 @@ impl<'arena> TypeEnvironment<'arena> {
             class_implements: FxHashMap::with_capacity_and_hasher(32, Default::default()),
             abstract_classes: FxHashMap::with_capacity_and_hasher(16, Default::default()),
             class_constructors: FxHashMap::with_capacity_and_hasher(32, Default::default()),
+            class_constructor_params: FxHashMap::with_capacity_and_hasher(32, Default::default()),
+            class_member_spans: FxHashMap::with_capacity_and_hasher(32, Default::default()),
+            open_tables: FxHashMap::default(),
             interface_type_params: FxHashMap::with_capacity_and_hasher(16, Default::default()),
             primitive_nil,
             primitive_boolean,
@@ -123,6 +137,42 @@ impl<'arena> TypeEnvironment<'arena> {
         env
     }
 
+    /// Copy the registered types into a new environment with empty caches,
+    /// e.g. to start a checker from a shared
+    /// [`StdlibSnapshot`](crate::state::StdlibSnapshot)
+    pub fn fork<'b>(&self) -> TypeEnvironment<'b>
+    where
+        'arena: 'b,
+    {
+        TypeEnvironment {
+            type_aliases: self.type_aliases.clone(),
+            generic_type_aliases: self.generic_type_aliases.clone(),
+            interfaces: self.interfaces.clone(),
+            builtins: self.builtins.clone(),
+            resolving: std::cell::RefCell::new(std::collections::HashSet::new()),
+            type_param_constraints: self.type_param_constraints.clone(),
+            class_implements: self.class_implements.clone(),
+            abstract_classes: self.abstract_classes.clone(),
+            class_constructors: self.class_constructors.clone(),
+            class_constructor_params: self.class_constructor_params.clone(),
+            class_member_spans: self.class_member_spans.clone(),
+            open_tables: self.open_tables.clone(),
+            interface_type_params: self.interface_type_params.clone(),
+            primitive_nil: self.primitive_nil.clone(),
+            primitive_boolean: self.primitive_boolean.clone(),
+            primitive_number: self.primitive_number.clone(),
+            primitive_integer: self.primitive_integer.clone(),
+            primitive_string: self.primitive_string.clone(),
+            primitive_unknown: self.primitive_unknown.clone(),
+            primitive_never: self.primitive_never.clone(),
+            primitive_void: self.primitive_void.clone(),
+            primitive_table: self.primitive_table.clone(),
+            primitive_coroutine: self.primitive_coroutine.clone(),
+            utility_type_cache: std::cell::RefCell::new(FxHashMap::default()),
+            generic_instantiation_cache: std::cell::RefCell::new(FxHashMap::default()),
+        }
+    }
+
     /// Register built-in types using cached primitives
     fn register_builtins(&mut self) {
         self.builtins
@@ -374,6 +424,54 @@ impl<'arena> TypeEnvironment<'arena> {
         self.class_constructors.get(class_name)
     }
 
+    /// Register the parameters of a class's `constructor(...)` member
+    pub fn register_constructor_params(
+        &mut self,
+        class_name: String,
+        params: &'arena [Parameter<'arena>],
+    ) {
+        self.class_constructor_params.insert(class_name, params);
+    }
+
+    /// Get the parameters of a class's `constructor(...)` member
+    pub fn get_constructor_params(&self, class_name: &str) -> Option<&'arena [Parameter<'arena>]> {
+        self.class_constructor_params.get(class_name).copied()
+    }
+
+    /// Record where a class member was declared
+    pub fn register_member_span(&mut self, class_name: &str, member_name: String, span: Span) {
+        self.class_member_spans
+            .entry(class_name.to_string())
+            .or_default()
+            .insert(member_name, span);
+    }
+
+    /// Get the declaration span of a class member
+    pub fn get_member_span(&self, class_name: &str, member_name: &str) -> Option<Span> {
+        self.class_member_spans
+            .get(class_name)
+            .and_then(|members| members.get(member_name))
+            .copied()
+    }
+
+    /// Register (or replace) the current shape of an open table.
+    ///
+    /// `name` is the name of the reference standing for the table, spanned at
+    /// its declaration. References to the table look the shape up here, so they
+    /// always see the latest shape as fields are added.
+    pub fn register_open_table(&mut self, name: &Ident, typ: Type<'arena>) {
+        self.open_tables.insert(Self::open_table_key(name), typ);
+    }
+
+    /// The current shape of the open table a reference stands for
+    pub fn open_table(&self, type_ref: &TypeReference<'arena>) -> Option<&Type<'arena>> {
+        self.open_tables.get(&Self::open_table_key(&type_ref.name))
+    }
+
+    fn open_table_key(name: &Ident) -> (StringId, usize, usize) {
+        (name.node, name.span.start as usize, name.span.end as usize)
+    }
+
     /// Resolve a type reference, detecting cycles
     pub fn resolve_type_reference(&self, name: &str) -> Result<Option<Type<'arena>>, String> {
         // Check if we're already resolving this type (cycle detection)
@@ -448,6 +546,15 @@ impl<'arena> TypeEnvironment<'arena> {
                 | "Nilable"
                 | "ReturnType"
                 | "Parameters"
+                | "InstanceType"
+                | "ConstructorParameters"
+                | "ThisType"
+                | "Uppercase"
+                | "Lowercase"
+                | "Capitalize"
+                | "Uncapitalize"
+                | "Awaited"
+                | "Brand"
         )
     }
 
@@ -494,7 +601,28 @@ impl<'arena> TypeEnvironment<'arena> {
                 }
                 h
             }
-            TypeKind::Reference(_type_ref) => 600,
+            TypeKind::Reference(type_ref) => {
+                use std::hash::{Hash, Hasher};
+                let mut hasher = rustc_hash::FxHasher::default();
+                type_ref.name.node.hash(&mut hasher);
+                let mut h = 600u64.wrapping_mul(31).wrapping_add(hasher.finish());
+                for arg in type_ref.type_arguments.unwrap_or(&[]) {
+                    h = h.wrapping_mul(31).wrapping_add(Self::type_fingerprint(arg));
+                }
+                h
+            }
+            TypeKind::Literal(literal) => {
+                use std::hash::{Hash, Hasher};
+                let mut hasher = rustc_hash::FxHasher::default();
+                match literal {
+                    Literal::String(s) => (0u8, s).hash(&mut hasher),
+                    Literal::Number(n) => (1u8, n.to_bits()).hash(&mut hasher),
+                    Literal::Integer(i) => (2u8, i).hash(&mut hasher),
+                    Literal::Boolean(b) => (3u8, b).hash(&mut hasher),
+                    Literal::Nil => 4u8.hash(&mut hasher),
+                }
+                1600u64.wrapping_mul(31).wrapping_add(hasher.finish())
+            }
             TypeKind::KeyOf(_) => 700,
             TypeKind::Mapped(_) => 800,
             TypeKind::Conditional(_) => 900,
@@ -547,8 +675,16 @@ impl<'arena> TypeEnvironment<'arena> {
             return Ok(cached.clone());
         }
 
-        use crate::types::utility_types::apply_utility_type;
-        let result = apply_utility_type(arena, name, type_args, span, interner, common_ids)?;
+        use crate::types::utility_types::{apply_utility_type, awaited, constructor_parameters};
+        let result = match name {
+            // Needs the registered class constructors
+            "ConstructorParameters" => {
+                constructor_parameters(arena, type_args, span, self, interner)?
+            }
+            // Needs the generic aliases that expand to coroutines
+            "Awaited" => awaited(arena, type_args, self, interner)?,
+            _ => apply_utility_type(arena, name, type_args, span, interner, common_ids)?,
+        };
 
         cache.insert(cache_key, result.clone());
         Ok(result)
@@ -578,6 +714,29 @@ mod tests {
         assert!(env.lookup_type("void").is_some());
     }
 
+    #[test]
+    fn test_literal_fingerprints_follow_values() {
+        let literal = |literal| Type::new(TypeKind::Literal(literal), Span::new(0, 0, 0, 0));
+        let fingerprint = |literal| TypeEnvironment::type_fingerprint(&literal);
+
+        assert_eq!(
+            fingerprint(literal(Literal::String("a".to_string()))),
+            fingerprint(literal(Literal::String("a".to_string())))
+        );
+        assert_ne!(
+            fingerprint(literal(Literal::String("1".to_string()))),
+            fingerprint(literal(Literal::Integer(1)))
+        );
+        assert_ne!(
+            fingerprint(literal(Literal::Integer(1))),
+            fingerprint(literal(Literal::Number(1.0)))
+        );
+        assert_ne!(
+            fingerprint(literal(Literal::Boolean(true))),
+            fingerprint(literal(Literal::Boolean(false)))
+        );
+    }
+
     #[test]
     fn test_register_type_alias() {
         let mut env = TypeEnvironment::new();
@@ -822,6 +981,15 @@ mod tests {
             "Nilable",
             "ReturnType",
             "Parameters",
+            "InstanceType",
+            "ConstructorParameters",
+            "ThisType",
+            "Uppercase",
+            "Lowercase",
+            "Capitalize",
+            "Uncapitalize",
+            "Awaited",
+            "Brand",
         ];
 
         for utility in &utility_types {
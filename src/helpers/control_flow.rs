@@ -3,9 +3,13 @@
 //! This module provides utilities for analyzing control flow in TypedLua programs,
 //! including checking whether code paths always return or terminate.
 
-use luanext_parser::ast::expression::ExpressionKind;
-use luanext_parser::ast::statement::{Block, Statement};
+use luanext_parser::ast::expression::{
+    Argument, ArrayElement, Expression, ExpressionKind, ObjectProperty, TemplatePart,
+};
+use luanext_parser::ast::statement::{Block, ForStatement, Statement};
+use luanext_parser::span::Span;
 use luanext_parser::string_interner::StringInterner;
+use std::collections::HashSet;
 
 /// Check if a block always returns (has a return statement on all code paths).
 ///
@@ -101,3 +105,224 @@ pub fn statement_always_returns(stmt: &Statement, interner: &StringInterner) ->
         _ => false,
     }
 }
+
+/// If a statement is a `super(...)` constructor call, return its arguments.
+pub fn super_call_arguments<'a, 'arena>(
+    stmt: &'a Statement<'arena>,
+) -> Option<&'a [Argument<'arena>]> {
+    if let Statement::Expression(expr) = stmt {
+        if let ExpressionKind::Call(callee, args, _) = &expr.kind {
+            if matches!(callee.kind, ExpressionKind::SuperKeyword) {
+                return Some(*args);
+            }
+        }
+    }
+    None
+}
+
+/// The `super(...)` constructor calls of a statement, including those nested in
+/// its `if` branches, loops and `do` blocks but not in nested function bodies.
+pub fn nested_super_calls<'a, 'arena>(stmt: &'a Statement<'arena>) -> Vec<&'a [Argument<'arena>]> {
+    let mut calls = Vec::new();
+    collect_super_calls(stmt, &mut calls);
+    calls
+}
+
+fn collect_super_calls<'a, 'arena>(
+    stmt: &'a Statement<'arena>,
+    calls: &mut Vec<&'a [Argument<'arena>]>,
+) {
+    if let Some(args) = super_call_arguments(stmt) {
+        calls.push(args);
+        return;
+    }
+    let mut collect_block = |block: &'a Block<'arena>| {
+        for stmt in block.statements.iter() {
+            collect_super_calls(stmt, calls);
+        }
+    };
+    match stmt {
+        Statement::If(if_stmt) => {
+            collect_block(&if_stmt.then_block);
+            for elif in if_stmt.else_ifs.iter() {
+                collect_block(&elif.block);
+            }
+            if let Some(else_block) = &if_stmt.else_block {
+                collect_block(else_block);
+            }
+        }
+        Statement::While(while_stmt) => collect_block(&while_stmt.body),
+        Statement::Repeat(repeat) => collect_block(&repeat.body),
+        Statement::Block(block) => collect_block(block),
+        Statement::For(for_stmt) => match for_stmt {
+            ForStatement::Numeric(numeric) => collect_block(&numeric.body),
+            ForStatement::Generic(generic) => collect_block(&generic.body),
+        },
+        _ => {}
+    }
+}
+
+/// The span of the first `self` reference in a statement, excluding nested
+/// function bodies.
+///
+/// Used to detect `self` access before the `super(...)` call in derived constructors.
+pub fn statement_self_use(stmt: &Statement) -> Option<Span> {
+    match stmt {
+        Statement::Expression(expr) => expression_self_use(expr),
+        Statement::Variable(decl) => expression_self_use(&decl.initializer),
+        Statement::Return(ret) => ret.values.iter().find_map(expression_self_use),
+        Statement::Throw(throw) => expression_self_use(&throw.expression),
+        Statement::If(if_stmt) => expression_self_use(&if_stmt.condition)
+            .or_else(|| block_self_use(&if_stmt.then_block))
+            .or_else(|| {
+                if_stmt.else_ifs.iter().find_map(|elif| {
+                    expression_self_use(&elif.condition).or_else(|| block_self_use(&elif.block))
+                })
+            })
+            .or_else(|| if_stmt.else_block.as_ref().and_then(block_self_use)),
+        Statement::While(while_stmt) => {
+            expression_self_use(&while_stmt.condition).or_else(|| block_self_use(&while_stmt.body))
+        }
+        Statement::Repeat(repeat) => {
+            block_self_use(&repeat.body).or_else(|| expression_self_use(&repeat.until))
+        }
+        Statement::Block(block) => block_self_use(block),
+        Statement::MultiAssignment(multi) => multi
+            .targets
+            .iter()
+            .chain(multi.values.iter())
+            .find_map(expression_self_use),
+        _ => None,
+    }
+}
+
+fn block_self_use(block: &Block) -> Option<Span> {
+    block.statements.iter().find_map(statement_self_use)
+}
+
+/// The span of the first `self` reference in an expression, excluding nested
+/// function bodies.
+pub fn expression_self_use(expr: &Expression) -> Option<Span> {
+    match &expr.kind {
+        ExpressionKind::SelfKeyword => Some(expr.span),
+        ExpressionKind::Binary(_, left, right)
+        | ExpressionKind::Index(left, right)
+        | ExpressionKind::OptionalIndex(left, right)
+        | ExpressionKind::Pipe(left, right)
+        | ExpressionKind::ErrorChain(left, right) => {
+            expression_self_use(left).or_else(|| expression_self_use(right))
+        }
+        ExpressionKind::Assignment(target, _, value) => {
+            expression_self_use(target).or_else(|| expression_self_use(value))
+        }
+        ExpressionKind::Unary(_, inner)
+        | ExpressionKind::Parenthesized(inner)
+        | ExpressionKind::TypeAssertion(inner, _)
+        | ExpressionKind::Member(inner, _)
+        | ExpressionKind::OptionalMember(inner, _) => expression_self_use(inner),
+        ExpressionKind::Call(callee, args, _)
+        | ExpressionKind::OptionalCall(callee, args, _)
+        | ExpressionKind::New(callee, args, _) => {
+            expression_self_use(callee).or_else(|| arguments_self_use(args))
+        }
+        ExpressionKind::MethodCall(obj, _, args, _)
+        | ExpressionKind::OptionalMethodCall(obj, _, args, _) => {
+            expression_self_use(obj).or_else(|| arguments_self_use(args))
+        }
+        ExpressionKind::Conditional(cond, then_expr, else_expr) => expression_self_use(cond)
+            .or_else(|| expression_self_use(then_expr))
+            .or_else(|| expression_self_use(else_expr)),
+        ExpressionKind::Array(elements) => elements.iter().find_map(|elem| match elem {
+            ArrayElement::Expression(e) | ArrayElement::Spread(e) => expression_self_use(e),
+        }),
+        ExpressionKind::Object(props) => props.iter().find_map(|prop| match prop {
+            ObjectProperty::Property { value, .. } => expression_self_use(value),
+            ObjectProperty::Computed { key, value, .. } => {
+                expression_self_use(key).or_else(|| expression_self_use(value))
+            }
+            ObjectProperty::Spread { value, .. } => expression_self_use(value),
+        }),
+        ExpressionKind::Template(template) => template.parts.iter().find_map(|part| match part {
+            TemplatePart::Expression(e) => expression_self_use(e),
+            _ => None,
+        }),
+        _ => None,
+    }
+}
+
+fn arguments_self_use(args: &[Argument]) -> Option<Span> {
+    args.iter().find_map(|arg| expression_self_use(&arg.value))
+}
+
+/// Collect the names of properties assigned through `self.name = ...` on every
+/// code path of a block.
+///
+/// Assignments inside an `if` count only when every branch (including `else`)
+/// assigns the property, leaving out the branches that always return or raise
+/// an error. Loops and nested functions are not considered, since their
+/// bodies may not run.
+pub fn definitely_assigned_self_properties(
+    block: &Block,
+    interner: &StringInterner,
+) -> HashSet<String> {
+    let mut assigned = HashSet::new();
+    for stmt in block.statements.iter() {
+        collect_assigned_self_properties(stmt, interner, &mut assigned);
+        if statement_always_returns(stmt, interner) {
+            break;
+        }
+    }
+    assigned
+}
+
+fn collect_assigned_self_properties(
+    stmt: &Statement,
+    interner: &StringInterner,
+    assigned: &mut HashSet<String>,
+) {
+    match stmt {
+        Statement::Expression(expr) => {
+            if let ExpressionKind::Assignment(target, _, _) = &expr.kind {
+                if let Some(name) = self_member_name(target, interner) {
+                    assigned.insert(name);
+                }
+            }
+        }
+        Statement::MultiAssignment(multi) => {
+            for target in multi.targets.iter() {
+                if let Some(name) = self_member_name(target, interner) {
+                    assigned.insert(name);
+                }
+            }
+        }
+        Statement::Block(block) => {
+            assigned.extend(definitely_assigned_self_properties(block, interner));
+        }
+        Statement::If(if_stmt) => {
+            let Some(else_block) = &if_stmt.else_block else {
+                return;
+            };
+            let mut branches = std::iter::once(&if_stmt.then_block)
+                .chain(if_stmt.else_ifs.iter().map(|elif| &elif.block))
+                .chain(std::iter::once(else_block))
+                .filter(|b| !block_always_returns(b, interner))
+                .map(|b| definitely_assigned_self_properties(b, interner));
+            if let Some(first) = branches.next() {
+                let common = branches.fold(first, |acc, branch| {
+                    acc.intersection(&branch).cloned().collect()
+                });
+                assigned.extend(common);
+            }
+        }
+        _ => {}
+    }
+}
+
+fn self_member_name(target: &Expression, interner: &StringInterner) -> Option<String> {
+    if let ExpressionKind::Member(obj, member) = &target.kind {
+        if matches!(obj.kind, ExpressionKind::SelfKeyword) {
+            return Some(interner.resolve(member.node).to_string());
+        }
+    }
+    None
+}
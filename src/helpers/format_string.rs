@@ -0,0 +1,317 @@
+//! Parsing `string.format` format strings
+//!
+//! A literal format string is split into its conversions so the arguments of
+//! the call can be counted and typed. The accepted conversions follow the
+//! target: `%a` needs Lua 5.2 (LuaJIT also has it), `%p` needs Lua 5.4, and
+//! Lua 5.4 only accepts the flags and precision each conversion supports
+//! (`%#d` and `%.3c` are errors there) and rejects any modifier on `%q`.
+//! Flags may repeat (`%--5d`), but Lua 5.1-5.3 reject a run of more than
+//! five and Lua 5.4 limits flags and width together.
+//! Integer conversions need an integer-valued argument from Lua 5.3 on.
+
+use crate::cli::config::LuaVersion;
+use luanext_parser::ast::types::{PrimitiveType, Type, TypeKind};
+use luanext_parser::span::Span;
+
+/// Flags accepted before the width of a conversion
+const FLAGS: &str = "-+ #0";
+
+/// Longest run of flags, width and specifier Lua 5.4 accepts
+/// (`MAX_FORMAT - 10` in lstrlib.c)
+const LUA54_MAX_SPEC: usize = 22;
+
+/// The flags a conversion accepts on Lua 5.4 and later, and whether it
+/// accepts a precision
+fn lua54_modifiers(specifier: char) -> (&'static str, bool) {
+    match specifier {
+        'c' | 'p' => ("-", false),
+        's' => ("-", true),
+        'd' | 'i' => ("-+0 ", true),
+        'u' => ("-0", true),
+        'o' | 'x' | 'X' => ("-#0", true),
+        _ => (FLAGS, true),
+    }
+}
+
+/// The argument a conversion consumes
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FormatArgument {
+    /// `%d %i %c %o %u %x %X`: a number with an integer value, typed as
+    /// `integer` from Lua 5.3 on
+    Integer,
+    /// `%e %E %f %g %G %a %A`: any number
+    Float,
+    /// `%s`: any value, converted with `tostring`
+    Any,
+    /// `%q`: a value that can be written back as a Lua literal
+    Quoted,
+    /// `%p`: any value; its address is printed
+    Pointer,
+}
+
+/// One `%` conversion of a format string
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FormatConversion {
+    /// The conversion character, e.g. `d` for `%5.2d`
+    pub specifier: char,
+    /// What the conversion expects from its argument
+    pub argument: FormatArgument,
+}
+
+impl FormatArgument {
+    /// The type an argument for this conversion must have on `target`, or
+    /// `None` when any value is accepted
+    pub fn expected_type<'arena>(
+        self,
+        arena: &'arena bumpalo::Bump,
+        target: LuaVersion,
+        span: Span,
+    ) -> Option<Type<'arena>> {
+        let primitive = |p| Type::new(TypeKind::Primitive(p), span);
+        match self {
+            // Lua 5.3 raises "number has no integer representation"
+            FormatArgument::Integer
+                if matches!(
+                    target,
+                    LuaVersion::Lua53 | LuaVersion::Lua54 | LuaVersion::Lua55
+                ) =>
+            {
+                Some(primitive(PrimitiveType::Integer))
+            }
+            FormatArgument::Integer | FormatArgument::Float => {
+                Some(primitive(PrimitiveType::Number))
+            }
+            FormatArgument::Quoted => {
+                let mut members = vec![
+                    primitive(PrimitiveType::String),
+                    primitive(PrimitiveType::Number),
+                ];
+                // Lua 5.4 also quotes booleans and nil
+                if matches!(target, LuaVersion::Lua54 | LuaVersion::Lua55) {
+                    members.push(primitive(PrimitiveType::Boolean));
+                    members.push(primitive(PrimitiveType::Nil));
+                }
+                Some(Type::new(
+                    TypeKind::Union(arena.alloc_slice_fill_iter(members)),
+                    span,
+                ))
+            }
+            FormatArgument::Any | FormatArgument::Pointer => None,
+        }
+    }
+}
+
+/// Parse the conversions of a format string, or describe why `string.format`
+/// would reject it on `target`
+pub fn parse_format(format: &str, target: LuaVersion) -> Result<Vec<FormatConversion>, String> {
+    let mut conversions = Vec::new();
+    let mut chars = format.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        if c != '%' {
+            continue;
+        }
+        if chars.peek() == Some(&'%') {
+            chars.next();
+            continue;
+        }
+
+        let mut spec = String::from("%");
+        while let Some(&flag) = chars.peek().filter(|c| FLAGS.contains(**c)) {
+            spec.push(flag);
+            chars.next();
+        }
+        let flag_count = spec.len() - 1;
+        let has_flags = flag_count > 0;
+        // Lua 5.1-5.3 stop at `sizeof(FLAGS)` flags, counting the terminator
+        if flag_count > FLAGS.len()
+            && matches!(
+                target,
+                LuaVersion::Lua51 | LuaVersion::Lua52 | LuaVersion::Lua53
+            )
+        {
+            return Err(format!("invalid format '{}' (repeated flags)", spec));
+        }
+
+        let width = take_digits(&mut chars, &mut spec);
+        // Lua 5.4 spans flags and width together into a fixed-size buffer
+        if flag_count + width + 1 >= LUA54_MAX_SPEC
+            && matches!(target, LuaVersion::Lua54 | LuaVersion::Lua55)
+        {
+            return Err(format!("invalid format string '{}' to 'format'", spec));
+        }
+        let mut precision = 0;
+        let mut has_precision = false;
+        if chars.peek() == Some(&'.') {
+            spec.push('.');
+            chars.next();
+            has_precision = true;
+            precision = take_digits(&mut chars, &mut spec);
+        }
+        if width > 2 || precision > 2 {
+            return Err(format!(
+                "invalid format '{}' (width or precision too long)",
+                spec
+            ));
+        }
+
+        let Some(specifier) = chars.next() else {
+            return Err("invalid conversion '%' at the end of the format string".to_string());
+        };
+        spec.push(specifier);
+
+        let argument = match specifier {
+            'd' | 'i' | 'c' | 'o' | 'u' | 'x' | 'X' => FormatArgument::Integer,
+            'e' | 'E' | 'f' | 'g' | 'G' => FormatArgument::Float,
+            'a' | 'A' if target != LuaVersion::Lua51 => FormatArgument::Float,
+            's' => FormatArgument::Any,
+            'q' => FormatArgument::Quoted,
+            'p' if matches!(target, LuaVersion::Lua54 | LuaVersion::Lua55) => {
+                FormatArgument::Pointer
+            }
+            'a' | 'A' | 'p' => {
+                return Err(format!(
+                    "conversion '{}' is not supported by string.format on {}",
+                    spec,
+                    target.display_name()
+                ))
+            }
+            _ => return Err(format!("invalid conversion '{}' to 'format'", spec)),
+        };
+
+        if matches!(target, LuaVersion::Lua54 | LuaVersion::Lua55) {
+            let has_modifiers = has_flags || width > 0 || has_precision;
+            if specifier == 'q' && has_modifiers {
+                return Err(format!("specifier '{}' cannot have modifiers", spec));
+            }
+            let (flags, precision_allowed) = lua54_modifiers(specifier);
+            let flags_allowed = spec[1..]
+                .chars()
+                .take_while(|c| FLAGS.contains(*c))
+                .all(|flag| flags.contains(flag));
+            if !flags_allowed || (has_precision && !precision_allowed) {
+                return Err(format!("invalid conversion '{}' to 'format'", spec));
+            }
+        }
+
+        conversions.push(FormatConversion {
+            specifier,
+            argument,
+        });
+    }
+
+    Ok(conversions)
+}
+
+/// Consume a run of digits into `spec`, returning how many there were
+fn take_digits(chars: &mut std::iter::Peekable<std::str::Chars>, spec: &mut String) -> usize {
+    let mut count = 0;
+    while let Some(&digit) = chars.peek().filter(|c| c.is_ascii_digit()) {
+        spec.push(digit);
+        chars.next();
+        count += 1;
+    }
+    count
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn specifiers(format: &str) -> Vec<char> {
+        parse_format(format, LuaVersion::Lua54)
+            .unwrap()
+            .iter()
+            .map(|c| c.specifier)
+            .collect()
+    }
+
+    #[test]
+    fn test_parse_conversions() {
+        assert_eq!(specifiers("%d items, %-10s|%5.2f%%"), vec!['d', 's', 'f']);
+        assert_eq!(specifiers("100%% done"), Vec::<char>::new());
+        assert_eq!(
+            parse_format("%x %q", LuaVersion::Lua51).unwrap()[1].argument,
+            FormatArgument::Quoted
+        );
+    }
+
+    #[test]
+    fn test_malformed_formats() {
+        for format in ["%y", "trailing %", "%100d", "%.123f"] {
+            assert!(
+                parse_format(format, LuaVersion::Lua54).is_err(),
+                "{} should be rejected",
+                format
+            );
+        }
+        assert!(parse_format("%5q", LuaVersion::Lua54).is_err());
+        assert!(parse_format("%5q", LuaVersion::Lua53).is_ok());
+    }
+
+    #[test]
+    fn test_repeated_flags_follow_target() {
+        for target in [LuaVersion::Lua51, LuaVersion::Lua53, LuaVersion::Lua54] {
+            assert!(
+                parse_format("%--5d", target).is_ok(),
+                "repeated flags are accepted on {:?}",
+                target
+            );
+        }
+        assert!(parse_format("%-----d", LuaVersion::Lua53).is_ok());
+        assert!(parse_format("%------d", LuaVersion::Lua53).is_err());
+        assert!(parse_format("%------d", LuaVersion::Lua54).is_ok());
+        assert!(parse_format("%--------------------d", LuaVersion::Lua54).is_ok());
+        assert!(parse_format("%---------------------d", LuaVersion::Lua54).is_err());
+    }
+
+    #[test]
+    fn test_conversions_follow_target() {
+        assert!(parse_format("%a", LuaVersion::Lua51).is_err());
+        assert!(parse_format("%a", LuaVersion::LuaJIT).is_ok());
+        assert!(parse_format("%p", LuaVersion::Lua53).is_err());
+        assert!(parse_format("%p", LuaVersion::Lua54).is_ok());
+    }
+
+    #[test]
+    fn test_modifiers_follow_target() {
+        for format in ["%#d", "%+u", "%+x", "%05c", "%.2c", "%+s", "%.1p"] {
+            assert!(
+                parse_format(format, LuaVersion::Lua54).is_err(),
+                "{} should be rejected on 5.4",
+                format
+            );
+            assert!(
+                parse_format(format, LuaVersion::Lua53).is_ok(),
+                "{} should be accepted on 5.3",
+                format
+            );
+        }
+        for format in ["%-+5d", "%#x", "%08.3f", "%-10s", "%.3s", "%-3c"] {
+            assert!(
+                parse_format(format, LuaVersion::Lua54).is_ok(),
+                "{} should be accepted on 5.4",
+                format
+            );
+        }
+    }
+
+    #[test]
+    fn test_integer_conversions_expect_integers_from_lua53() {
+        let arena = bumpalo::Bump::new();
+        let span = Span::new(0, 0, 0, 0);
+        let expected = |target| {
+            FormatArgument::Integer
+                .expected_type(&arena, target, span)
+                .map(|t| t.kind)
+        };
+        assert!(matches!(
+            expected(LuaVersion::Lua53),
+            Some(TypeKind::Primitive(PrimitiveType::Integer))
+        ));
+        assert!(matches!(
+            expected(LuaVersion::Lua51),
+            Some(TypeKind::Primitive(PrimitiveType::Number))
+        ));
+    }
+}
@@ -0,0 +1,188 @@
+//! Validating Lua patterns
+//!
+//! A literal pattern passed to `string.match`, `gmatch`, `find` or `gsub` is
+//! scanned the way `lstrlib.c` reads it, so a pattern Lua would reject at run
+//! time is reported up front, and the captures it produces are known when the
+//! call's result is typed.
+
+use crate::cli::config::LuaVersion;
+
+/// Lua's `LUA_MAXCAPTURES`
+const MAX_CAPTURES: usize = 32;
+
+/// What a capture in a pattern produces
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CaptureKind {
+    /// `(...)`: the matched substring
+    Substring,
+    /// `()`: the position in the subject, an integer
+    Position,
+}
+
+/// Scan a pattern, returning its captures in order or describing why Lua
+/// would reject it
+pub fn parse_pattern(pattern: &str) -> Result<Vec<CaptureKind>, String> {
+    let bytes = pattern.as_bytes();
+    let mut captures = Vec::new();
+    // Indices into `captures` of the captures that are still open
+    let mut open = Vec::new();
+    let mut i = 0;
+
+    while i < bytes.len() {
+        match bytes[i] {
+            b'(' => {
+                if captures.len() == MAX_CAPTURES {
+                    return Err("too many captures".to_string());
+                }
+                if bytes.get(i + 1) == Some(&b')') {
+                    captures.push(CaptureKind::Position);
+                    i += 2;
+                } else {
+                    open.push(captures.len());
+                    captures.push(CaptureKind::Substring);
+                    i += 1;
+                }
+            }
+            b')' => {
+                if open.pop().is_none() {
+                    return Err("invalid pattern capture (unmatched ')')".to_string());
+                }
+                i += 1;
+            }
+            b'[' => i = skip_set(bytes, i)?,
+            b'%' => match bytes.get(i + 1) {
+                None => return Err("malformed pattern (ends with '%')".to_string()),
+                Some(b'b') => {
+                    if bytes.len() < i + 4 {
+                        return Err("malformed pattern (missing arguments to '%b')".to_string());
+                    }
+                    i += 4;
+                }
+                Some(b'f') => {
+                    if bytes.get(i + 2) != Some(&b'[') {
+                        return Err("missing '[' after '%f' in pattern".to_string());
+                    }
+                    i = skip_set(bytes, i + 2)?;
+                }
+                Some(digit @ b'0'..=b'9') => {
+                    let index = usize::from(digit - b'0');
+                    let closed =
+                        index >= 1 && index <= captures.len() && !open.contains(&(index - 1));
+                    if !closed {
+                        return Err(format!("invalid capture index %{} in pattern", index));
+                    }
+                    i += 2;
+                }
+                Some(_) => i += 2,
+            },
+            _ => i += 1,
+        }
+    }
+
+    if !open.is_empty() {
+        return Err("unfinished capture".to_string());
+    }
+    Ok(captures)
+}
+
+/// Check the `%n` references of a `gsub` replacement string against the
+/// number of captures in the pattern. Without captures, `%1` is the whole match.
+pub fn check_replacement(
+    replacement: &str,
+    captures: usize,
+    target: LuaVersion,
+) -> Result<(), String> {
+    let bytes = replacement.as_bytes();
+    let available = captures.max(1);
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] != b'%' {
+            i += 1;
+            continue;
+        }
+        match bytes.get(i + 1) {
+            Some(b'%') => {}
+            Some(digit @ b'0'..=b'9') => {
+                let index = usize::from(digit - b'0');
+                if index > available {
+                    return Err(format!(
+                        "invalid capture index %{} in replacement string",
+                        index
+                    ));
+                }
+            }
+            // Lua 5.1 copies any other escaped character as is
+            _ if matches!(target, LuaVersion::Lua51 | LuaVersion::LuaJIT) => {}
+            _ => return Err("invalid use of '%' in replacement string".to_string()),
+        }
+        i += 2;
+    }
+    Ok(())
+}
+
+/// Skip a `[set]` starting at `start`, returning the index after its `]`.
+/// A `]` right after `[` or `[^` is part of the set.
+fn skip_set(bytes: &[u8], start: usize) -> Result<usize, String> {
+    let mut i = start + 1;
+    if bytes.get(i) == Some(&b'^') {
+        i += 1;
+    }
+    let first = i;
+    loop {
+        match bytes.get(i) {
+            None => return Err("malformed pattern (missing ']')".to_string()),
+            Some(b']') if i > first => return Ok(i + 1),
+            Some(b'%') => {
+                if i + 1 >= bytes.len() {
+                    return Err("malformed pattern (ends with '%')".to_string());
+                }
+                i += 2;
+            }
+            Some(_) => i += 1,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_captures() {
+        use CaptureKind::*;
+        assert_eq!(parse_pattern("%d+").unwrap(), vec![]);
+        assert_eq!(
+            parse_pattern("(%d+)-(%a+)").unwrap(),
+            vec![Substring, Substring]
+        );
+        assert_eq!(
+            parse_pattern("()(%w+)()").unwrap(),
+            vec![Position, Substring, Position]
+        );
+        assert_eq!(parse_pattern("((a)(b))").unwrap().len(), 3);
+        assert_eq!(parse_pattern("[]%]]+(%b())").unwrap(), vec![Substring]);
+        assert_eq!(parse_pattern("(['\"])(.-)%1").unwrap().len(), 2);
+    }
+
+    #[test]
+    fn test_malformed_patterns() {
+        for pattern in [
+            "abc%", "[abc", "[^", "%b(", "%fa", "(abc", "abc)", "(a%1)", "%2(a)", "[a%",
+        ] {
+            assert!(
+                parse_pattern(pattern).is_err(),
+                "{} should be rejected",
+                pattern
+            );
+        }
+    }
+
+    #[test]
+    fn test_replacement_indices() {
+        assert!(check_replacement("%1-%2", 2, LuaVersion::Lua54).is_ok());
+        assert!(check_replacement("<%1>", 0, LuaVersion::Lua54).is_ok());
+        assert!(check_replacement("%3", 2, LuaVersion::Lua54).is_err());
+        assert!(check_replacement("100%", 0, LuaVersion::Lua54).is_err());
+        assert!(check_replacement("%x", 0, LuaVersion::Lua51).is_ok());
+    }
+}
@@ -4,4 +4,7 @@
 //! and have clear separation of concerns from the main type checker logic.
 
 pub mod control_flow;
+pub mod format_string;
+pub mod lua_pattern;
+pub mod target_features;
 pub mod type_utilities;
@@ -0,0 +1,117 @@
+//! Target-version gating for Lua language features
+//!
+//! The parser accepts every construct LuaNext knows about; these helpers decide
+//! which of them exist on the configured Lua target and describe, for the
+//! diagnostic, the version where the feature is available.
+
+use crate::cli::config::LuaVersion;
+use luanext_parser::ast::expression::{BinaryOp, UnaryOp};
+
+/// Globals that only exist on LuaJIT
+const LUAJIT_GLOBALS: &[&str] = &["bit", "ffi", "jit"];
+
+/// Globals removed in Lua 5.2
+const LUA51_GLOBALS: &[&str] = &["setfenv", "getfenv", "unpack", "loadstring"];
+
+/// Explain why a binary operator cannot be used on `target`
+pub fn unsupported_binary_operator(op: BinaryOp, target: LuaVersion) -> Option<String> {
+    let symbol = match op {
+        BinaryOp::BitwiseAnd => "&",
+        BinaryOp::BitwiseOr => "|",
+        BinaryOp::BitwiseXor => "~",
+        BinaryOp::ShiftLeft => "<<",
+        BinaryOp::ShiftRight => ">>",
+        BinaryOp::IntegerDivide => "//",
+        _ => return None,
+    };
+    integer_operator_message(symbol, target)
+}
+
+/// Explain why a unary operator cannot be used on `target`
+pub fn unsupported_unary_operator(op: UnaryOp, target: LuaVersion) -> Option<String> {
+    match op {
+        UnaryOp::BitwiseNot => integer_operator_message("~", target),
+        _ => None,
+    }
+}
+
+fn integer_operator_message(symbol: &str, target: LuaVersion) -> Option<String> {
+    if target.supports_integer_operators() {
+        return None;
+    }
+    let hint = if target == LuaVersion::LuaJIT {
+        "; use the 'bit' library instead"
+    } else {
+        ""
+    };
+    Some(format!(
+        "Operator '{}' requires Lua 5.3 or later, but the target is {}{}",
+        symbol,
+        target.display_name(),
+        hint
+    ))
+}
+
+/// Explain why `goto` and labels cannot be used on `target`
+pub fn unsupported_goto(target: LuaVersion) -> Option<String> {
+    if target.supports_goto() {
+        return None;
+    }
+    Some(format!(
+        "'goto' and labels require Lua 5.2 or later, but the target is {}",
+        target.display_name()
+    ))
+}
+
+/// Explain why the global `name` does not exist on `target`
+pub fn unavailable_global(name: &str, target: LuaVersion) -> Option<String> {
+    if LUAJIT_GLOBALS.contains(&name) && target != LuaVersion::LuaJIT {
+        return Some(format!(
+            "'{}' is only available on LuaJIT, but the target is {}",
+            name,
+            target.display_name()
+        ));
+    }
+    if LUA51_GLOBALS.contains(&name) && !target.has_lua51_globals() {
+        let replacement = match name {
+            "unpack" => "; use 'table.unpack' instead",
+            "loadstring" => "; use 'load' instead",
+            _ => "; use '_ENV' instead",
+        };
+        return Some(format!(
+            "'{}' is only available on Lua 5.1 and LuaJIT; it was removed in Lua 5.2 (target is {}){}",
+            name,
+            target.display_name(),
+            replacement
+        ));
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_integer_operators_gated_before_5_3() {
+        assert!(unsupported_binary_operator(BinaryOp::IntegerDivide, LuaVersion::Lua54).is_none());
+        assert!(unsupported_binary_operator(BinaryOp::Add, LuaVersion::Lua51).is_none());
+        let message = unsupported_binary_operator(BinaryOp::BitwiseAnd, LuaVersion::Lua52).unwrap();
+        assert!(message.contains("Lua 5.3"));
+        let message = unsupported_unary_operator(UnaryOp::BitwiseNot, LuaVersion::LuaJIT).unwrap();
+        assert!(message.contains("'bit' library"));
+    }
+
+    #[test]
+    fn test_unavailable_globals() {
+        assert!(unavailable_global("ffi", LuaVersion::LuaJIT).is_none());
+        assert!(unavailable_global("jit", LuaVersion::Lua54)
+            .unwrap()
+            .contains("LuaJIT"));
+        assert!(unavailable_global("setfenv", LuaVersion::Lua51).is_none());
+        assert!(unavailable_global("unpack", LuaVersion::Lua53)
+            .unwrap()
+            .contains("table.unpack"));
+        assert!(unavailable_global("print", LuaVersion::Lua51).is_none());
+    }
+}
@@ -47,6 +47,165 @@ pub fn widen_type<'arena>(typ: Type<'arena>) -> Type<'arena> {
     }
 }
 
+/// Widens the inferred type of a mutable binding.
+///
+/// Like [`widen_type`], but also widens literals nested in arrays, unions,
+/// nullable types and mutable object properties, so `local dirs = {"n", "s"}`
+/// is a `string[]` and `local cfg = {mode = "dark"}` is `{ mode: string }`.
+/// Readonly properties and tuples (produced by `as const` and contextual
+/// typing) keep their literal types.
+///
+/// # Examples
+///
+/// ```rust,ignore
+/// // ("n" | "s")[] → string[]
+/// // { mode: "dark" } → { mode: string }
+/// // { readonly mode: "dark" } → unchanged
+/// // 1 | 2 | nil → number | nil
+/// ```
+pub fn widen_mutable_type<'arena>(arena: &'arena Bump, typ: Type<'arena>) -> Type<'arena> {
+    match &typ.kind {
+        TypeKind::Literal(_) => widen_type(typ),
+        TypeKind::Array(elem) => Type::new(
+            TypeKind::Array(arena.alloc(widen_mutable_type(arena, (*elem).clone()))),
+            typ.span,
+        ),
+        TypeKind::Nullable(inner) => Type::new(
+            TypeKind::Nullable(arena.alloc(widen_mutable_type(arena, (*inner).clone()))),
+            typ.span,
+        ),
+        TypeKind::Union(members) => {
+            let mut widened: Vec<Type<'arena>> = Vec::with_capacity(members.len());
+            for member in members.iter() {
+                let member = widen_mutable_type(arena, member.clone());
+                let duplicate = matches!(member.kind, TypeKind::Primitive(_))
+                    && widened.iter().any(|w| w.kind == member.kind);
+                if !duplicate {
+                    widened.push(member);
+                }
+            }
+            if widened.len() == 1 {
+                widened.pop().expect("length checked above")
+            } else {
+                Type::new(
+                    TypeKind::Union(arena.alloc_slice_fill_iter(widened)),
+                    typ.span,
+                )
+            }
+        }
+        TypeKind::Object(obj) => {
+            let members: Vec<ObjectTypeMember<'arena>> = obj
+                .members
+                .iter()
+                .map(|member| match member {
+                    ObjectTypeMember::Property(prop) if !prop.is_readonly => {
+                        ObjectTypeMember::Property(PropertySignature {
+                            type_annotation: widen_mutable_type(
+                                arena,
+                                prop.type_annotation.clone(),
+                            ),
+                            ..prop.clone()
+                        })
+                    }
+                    other => other.clone(),
+                })
+                .collect();
+            Type::new(
+                TypeKind::Object(ObjectType {
+                    members: arena.alloc_slice_fill_iter(members),
+                    span: obj.span,
+                }),
+                typ.span,
+            )
+        }
+        _ => typ,
+    }
+}
+
+/// Replaces `integer` with `number` throughout a type.
+///
+/// Used for targets without an integer subtype (Lua 5.1, 5.2 and LuaJIT),
+/// where `integer` is only an alias for `number`.
+pub fn integer_as_number<'arena>(arena: &'arena Bump, typ: &Type<'arena>) -> Type<'arena> {
+    let map_all = |types: &[Type<'arena>]| -> &'arena [Type<'arena>] {
+        arena.alloc_slice_fill_iter(types.iter().map(|t| integer_as_number(arena, t)))
+    };
+    let kind = match &typ.kind {
+        TypeKind::Primitive(PrimitiveType::Integer) => TypeKind::Primitive(PrimitiveType::Number),
+        TypeKind::Union(types) => TypeKind::Union(map_all(types)),
+        TypeKind::Intersection(types) => TypeKind::Intersection(map_all(types)),
+        TypeKind::Tuple(types) => TypeKind::Tuple(map_all(types)),
+        TypeKind::Array(elem) => TypeKind::Array(arena.alloc(integer_as_number(arena, elem))),
+        TypeKind::Nullable(inner) => {
+            TypeKind::Nullable(arena.alloc(integer_as_number(arena, inner)))
+        }
+        TypeKind::Parenthesized(inner) => {
+            TypeKind::Parenthesized(arena.alloc(integer_as_number(arena, inner)))
+        }
+        TypeKind::Function(func) => {
+            let parameters: Vec<Parameter<'arena>> = func
+                .parameters
+                .iter()
+                .map(|param| Parameter {
+                    type_annotation: param
+                        .type_annotation
+                        .as_ref()
+                        .map(|t| integer_as_number(arena, t)),
+                    ..param.clone()
+                })
+                .collect();
+            TypeKind::Function(FunctionType {
+                parameters: arena.alloc_slice_fill_iter(parameters),
+                return_type: arena.alloc(integer_as_number(arena, func.return_type)),
+                ..func.clone()
+            })
+        }
+        TypeKind::Object(obj) => {
+            let members: Vec<ObjectTypeMember<'arena>> = obj
+                .members
+                .iter()
+                .map(|member| match member {
+                    ObjectTypeMember::Property(prop) => {
+                        ObjectTypeMember::Property(PropertySignature {
+                            type_annotation: integer_as_number(arena, &prop.type_annotation),
+                            ..prop.clone()
+                        })
+                    }
+                    other => other.clone(),
+                })
+                .collect();
+            TypeKind::Object(ObjectType {
+                members: arena.alloc_slice_fill_iter(members),
+                span: obj.span,
+            })
+        }
+        _ => return typ.clone(),
+    };
+    Type::new(kind, typ.span)
+}
+
+/// Checks if a type is `number`, `integer` or a numeric literal.
+pub fn is_numeric_type(typ: &Type) -> bool {
+    matches!(
+        typ.kind,
+        TypeKind::Primitive(PrimitiveType::Number | PrimitiveType::Integer)
+            | TypeKind::Literal(Literal::Number(_) | Literal::Integer(_))
+    )
+}
+
+/// Checks if a numeric type is statically known to hold an integer.
+///
+/// # Returns
+///
+/// `true` for `integer` and integer literals, `false` otherwise. Float
+/// literals such as `3.0` are not integers even when their value is integral.
+pub fn is_integer_type(typ: &Type) -> bool {
+    matches!(
+        typ.kind,
+        TypeKind::Primitive(PrimitiveType::Integer) | TypeKind::Literal(Literal::Integer(_))
+    )
+}
+
 /// Checks if a type is the boolean primitive type.
 ///
 /// # Returns
@@ -56,6 +215,23 @@ pub fn is_boolean_type(typ: &Type) -> bool {
     matches!(typ.kind, TypeKind::Primitive(PrimitiveType::Boolean))
 }
 
+/// Checks if `nil` is a valid value of a type.
+///
+/// # Returns
+///
+/// `true` for `nil`, `void`, `unknown`, nullable types (`T?`) and unions
+/// containing any of these, `false` otherwise.
+pub fn type_accepts_nil(typ: &Type) -> bool {
+    match &typ.kind {
+        TypeKind::Primitive(PrimitiveType::Nil | PrimitiveType::Void | PrimitiveType::Unknown)
+        | TypeKind::Literal(Literal::Nil)
+        | TypeKind::Nullable(_) => true,
+        TypeKind::Union(types) => types.iter().any(type_accepts_nil),
+        TypeKind::Parenthesized(inner) => type_accepts_nil(inner),
+        _ => false,
+    }
+}
+
 /// Maps operator kinds to their Lua metamethod names.
 ///
 /// # Examples
@@ -366,4 +542,18 @@ mod tests {
             panic!("Expected union type");
         }
     }
+
+    #[test]
+    fn test_type_accepts_nil() {
+        let arena = Bump::new();
+        let string_type = make_type(TypeKind::Primitive(PrimitiveType::String));
+        let nil_type = make_type(TypeKind::Primitive(PrimitiveType::Nil));
+        let optional = make_type(TypeKind::Union(
+            arena.alloc_slice_clone(&[string_type.clone(), nil_type.clone()]),
+        ));
+
+        assert!(type_accepts_nil(&nil_type));
+        assert!(type_accepts_nil(&optional));
+        assert!(!type_accepts_nil(&string_type));
+    }
 }
@@ -1,5 +1,5 @@
 use crate::helpers::type_utilities::{
-    is_boolean_type, operator_kind_name, type_to_string, widen_type,
+    is_boolean_type, operator_kind_name, type_to_string, widen_mutable_type, widen_type,
 };
 use luanext_parser::ast::expression::OperatorKind;
 use luanext_parser::ast::types::{Literal, PrimitiveType, Type, TypeKind};
@@ -161,3 +161,35 @@ fn test_type_to_string_nil() {
     let result = type_to_string(&nil_type);
     assert!(!result.is_empty());
 }
+
+#[test]
+fn test_widen_mutable_type_nested_literals() {
+    let arena = bumpalo::Bump::new();
+    let members = arena.alloc_slice_fill_iter([
+        create_type(TypeKind::Literal(Literal::String("n".into()))),
+        create_type(TypeKind::Literal(Literal::String("s".into()))),
+    ]);
+    let array = create_type(TypeKind::Array(
+        arena.alloc(create_type(TypeKind::Union(members))),
+    ));
+
+    let widened = widen_mutable_type(&arena, array);
+    match &widened.kind {
+        TypeKind::Array(elem) => assert!(matches!(
+            elem.kind,
+            TypeKind::Primitive(PrimitiveType::String)
+        )),
+        _ => panic!("Expected array type"),
+    }
+}
+
+#[test]
+fn test_widen_mutable_type_keeps_tuples() {
+    let arena = bumpalo::Bump::new();
+    let elements =
+        arena.alloc_slice_fill_iter([create_type(TypeKind::Literal(Literal::Integer(1)))]);
+    let tuple = create_type(TypeKind::Tuple(elements));
+
+    let widened = widen_mutable_type(&arena, tuple.clone());
+    assert_eq!(widened.kind, tuple.kind);
+}
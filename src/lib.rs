@@ -21,7 +21,7 @@ pub use incremental::{
     CompilationCache, DeclarationHash, DeclarationId, DependencyGraph, IncrementalChecker,
     InvalidationResult,
 };
-pub use state::TypeCheckerState;
+pub use state::{StdlibSnapshot, TypeCheckerState};
 pub use types::generics::{
     build_substitutions, check_type_constraints, infer_type_arguments,
     instantiate_function_declaration, instantiate_type,
@@ -30,6 +30,7 @@ pub use types::utility_types::{
     apply_utility_type, evaluate_conditional_type, evaluate_keyof, evaluate_mapped_type,
     evaluate_template_literal_type,
 };
+pub use utils::doc_comment::{Deprecation, DocComment, DocParam};
 pub use utils::symbol_table::{
     Scope, SerializableSymbol, SerializableSymbolTable, Symbol, SymbolKind, SymbolTable,
 };
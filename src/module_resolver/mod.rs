@@ -121,6 +121,11 @@ impl ModuleResolver {
         }
     }
 
+    /// The project directory that relative paths are resolved against
+    pub fn base_dir(&self) -> &Path {
+        &self.base_dir
+    }
+
     /// Resolve an import source to a module ID
     ///
     /// Supports three resolution strategies (tried in order):
@@ -146,6 +151,25 @@ impl ModuleResolver {
         }
     }
 
+    /// The plain `.lua` file `source` would resolve to if `LuaFilePolicy::Block`
+    /// did not skip `.lua` files, so a blocked source can be reported as such
+    /// rather than as missing. Returns `None` under any other policy.
+    pub fn blocked_lua_file(&self, source: &str, from_file: &Path) -> Option<ModuleId> {
+        if self.config.lua_file_policy != LuaFilePolicy::Block {
+            return None;
+        }
+        let permissive = Self {
+            fs: Arc::clone(&self.fs),
+            config: ModuleConfig {
+                lua_file_policy: LuaFilePolicy::RequireDeclaration,
+                ..self.config.clone()
+            },
+            base_dir: self.base_dir.clone(),
+        };
+        let id = permissive.resolve(source, from_file).ok()?;
+        (permissive.get_module_kind(id.path()) == Some(ModuleKind::PlainLua)).then_some(id)
+    }
+
     /// Resolve alias candidates by trying extension/index search on each candidate path
     fn resolve_alias_candidates(
         &self,
@@ -247,6 +271,12 @@ impl ModuleResolver {
             if self.fs.exists(&index_path) {
                 return self.canonicalize(&index_path);
             }
+
+            // Try as a Lua package directory ("foo/init.luax", like `?/init.lua`)
+            if let Ok(resolved) = self.try_extensions(&candidate.join("init"), &mut searched_paths)
+            {
+                return Ok(resolved);
+            }
         }
 
         Err(ModuleError::NotFound {
@@ -404,6 +434,27 @@ mod tests {
         assert!(id.as_str().contains("bar.luax"));
     }
 
+    #[test]
+    fn test_resolve_package_init_file() {
+        let mut fs = MockFileSystem::new();
+        fs.add_file("/project/src/main.luax", "-- main");
+        fs.add_file("/project/lua_modules/json/init.luax", "-- json");
+        fs.add_file("/project/lua_modules/socket/init.lua", "-- socket");
+        fs.add_file("/project/lua_modules/socket/init.d.luax", "-- socket types");
+        let resolver = make_resolver(Arc::new(fs));
+
+        let id = resolver
+            .resolve("json", Path::new("/project/src/main.luax"))
+            .unwrap();
+        assert!(id.as_str().ends_with("json/init.luax"));
+
+        // A plain Lua package resolves to its declaration
+        let id = resolver
+            .resolve("socket", Path::new("/project/src/main.luax"))
+            .unwrap();
+        assert!(id.as_str().ends_with("socket/init.d.luax"));
+    }
+
     #[test]
     fn test_resolve_not_found() {
         let fs = make_test_fs();
@@ -456,6 +507,16 @@ mod tests {
         // Should not find .lua file when policy is Block
         let result = resolver.resolve("./legacy", Path::new("/project/src/main.luax"));
         assert!(result.is_err());
+
+        // ...but can tell which .lua file was skipped
+        let blocked = resolver.blocked_lua_file("./legacy", Path::new("/project/src/main.luax"));
+        assert_eq!(
+            blocked.map(|id| id.path().to_path_buf()),
+            Some(PathBuf::from("/project/src/legacy.lua"))
+        );
+        assert!(resolver
+            .blocked_lua_file("./missing", Path::new("/project/src/main.luax"))
+            .is_none());
     }
 
     fn make_alias_resolver(fs: Arc<dyn FileSystem>, aliases: &[(&str, &[&str])]) -> ModuleResolver {
@@ -18,8 +18,8 @@ use crate::visitors::{AccessControl, AccessControlVisitor, ClassMemberInfo, Clas
 use crate::TypeCheckError;
 use luanext_parser::ast::expression::Literal;
 use luanext_parser::ast::statement::{
-    AccessModifier, ClassDeclaration, ClassMember, EnumDeclaration, InterfaceDeclaration,
-    InterfaceMember, TypeAliasDeclaration,
+    AccessModifier, ClassDeclaration, ClassMember, Decorator, DecoratorExpression, EnumDeclaration,
+    InterfaceDeclaration, InterfaceMember, TypeAliasDeclaration,
 };
 use luanext_parser::ast::types::{
     ObjectType, ObjectTypeMember, PrimitiveType, Type, TypeKind, TypeReference,
@@ -90,6 +90,8 @@ pub fn check_type_alias<'arena>(
         span: alias.span,
         is_exported: true,
         references: Vec::new(),
+        doc: None,
+        member_docs: Default::default(),
     };
     let _ = symbol_table.declare(symbol);
 
@@ -260,6 +262,19 @@ pub fn check_interface_declaration<'arena>(
     // Register interface with access control
     access_control.register_class(&iface_name, None);
 
+    // Register the interfaces it extends, for member lookup and reflection
+    let extended: Vec<String> = iface
+        .extends
+        .iter()
+        .filter_map(|parent| match &parent.kind {
+            TypeKind::Reference(type_ref) => Some(interner.resolve(type_ref.name.node).to_string()),
+            _ => None,
+        })
+        .collect();
+    if !extended.is_empty() {
+        access_control.register_class_implements(&iface_name, extended);
+    }
+
     // Register interface members for access control
     for member in iface.members.iter() {
         let member_info = match member {
@@ -327,6 +342,8 @@ pub fn check_interface_declaration<'arena>(
             span: iface.span,
             is_exported: true,
             references: Vec::new(),
+            doc: None,
+            member_docs: Default::default(),
         };
         let _ = symbol_table.declare(symbol);
 
@@ -419,6 +436,8 @@ pub fn check_interface_declaration<'arena>(
         span: iface.span,
         is_exported: true,
         references: Vec::new(),
+        doc: None,
+        member_docs: Default::default(),
     };
     let _ = symbol_table.declare(symbol);
 
@@ -639,7 +658,8 @@ pub fn extract_class_member_infos<'arena>(
                         return_type: method.return_type.clone(),
                         is_abstract: method.is_abstract,
                     },
-                    is_final: method.is_final,
+                    is_final: method.is_final
+                        || has_decorator(method.decorators, "final", interner),
                 });
             }
             ClassMember::Getter(getter) => {
@@ -689,6 +709,19 @@ pub fn extract_class_member_infos<'arena>(
     member_infos
 }
 
+/// Check whether a declaration carries a decorator with the given name.
+///
+/// Matches both the bare form (`@final`) and the call form (`@final()`).
+pub fn has_decorator(decorators: &[Decorator], target: &str, interner: &StringInterner) -> bool {
+    decorators.iter().any(|decorator| match &decorator.expression {
+        DecoratorExpression::Identifier(name) => interner.resolve(name.node) == target,
+        DecoratorExpression::Call { callee, .. } => {
+            matches!(&**callee, DecoratorExpression::Identifier(name) if interner.resolve(name.node) == target)
+        }
+        _ => false,
+    })
+}
+
 /// Classify a class member error as critical or non-critical.
 ///
 /// Critical errors should fail compilation immediately, while non-critical errors
@@ -705,7 +738,6 @@ pub fn is_critical_member_error(error_message: &str) -> bool {
     (error_message.contains("Abstract method") && error_message.contains("abstract class"))
         || error_message.contains("one constructor")
         || error_message.contains("Decorators require decorator features")
-        || error_message.contains("Cannot override final method")
         || error_message.contains("is incompatible with parent")
         || error_message.contains("must implement abstract method")
         || error_message.contains("uses override but class")
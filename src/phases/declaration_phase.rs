@@ -460,6 +460,8 @@ pub fn declare_pattern<'arena>(
 ///
 /// Ambient function declarations (using `declare function`) don't have bodies.
 /// They just register the function signature for type checking purposes.
+/// Declaring a function again in the same scope adds an overload: the symbol's
+/// type becomes the intersection of the signatures, in declaration order.
 pub fn register_declare_function<'arena>(
     func: &DeclareFunctionStatement<'arena>,
     symbol_table: &mut SymbolTable<'arena>,
@@ -477,12 +479,34 @@ pub fn register_declare_function<'arena>(
         func.span,
     );
 
-    let symbol = Symbol::new(
-        interner.resolve(func.name.node).to_string(),
-        SymbolKind::Function,
-        func_type,
-        func.span,
-    );
+    let name = interner.resolve(func.name.node);
+    if let Some(existing) = symbol_table
+        .lookup_local(&name)
+        .filter(|symbol| symbol.kind == SymbolKind::Function)
+    {
+        let mut signatures = match &existing.typ.kind {
+            TypeKind::Function(_) => vec![existing.typ.clone()],
+            TypeKind::Intersection(signatures)
+                if signatures
+                    .iter()
+                    .all(|signature| matches!(signature.kind, TypeKind::Function(_))) =>
+            {
+                signatures.to_vec()
+            }
+            _ => Vec::new(),
+        };
+        if !signatures.is_empty() {
+            signatures.push(func_type);
+            let overloads = Type::new(
+                TypeKind::Intersection(arena.alloc_slice_fill_iter(signatures)),
+                existing.span,
+            );
+            symbol_table.set_type(&name, overloads);
+            return Ok(());
+        }
+    }
+
+    let symbol = Symbol::new(name, SymbolKind::Function, func_type, func.span);
 
     symbol_table
         .declare(symbol)
@@ -521,8 +545,35 @@ pub fn register_declare_namespace<'arena>(
     arena: &'arena Bump,
 ) -> Result<(), TypeCheckError> {
     // Create object type from namespace members
-    let members: Vec<_> = ns
-        .members
+    let members = namespace_members(ns);
+
+    let members_slice = arena.alloc_slice_fill_iter(members);
+    let namespace_type = Type::new(
+        TypeKind::Object(ObjectType {
+            members: members_slice,
+            span: ns.span,
+        }),
+        ns.span,
+    );
+
+    let symbol = Symbol::new(
+        interner.resolve(ns.name.node).to_string(),
+        SymbolKind::Const,
+        namespace_type,
+        ns.span,
+    );
+
+    symbol_table
+        .declare(symbol)
+        .map_err(|e| TypeCheckError::new(e, ns.span))
+}
+
+/// The members of a declared namespace's object type: its exported functions
+/// as methods and its exported constants as readonly properties
+pub fn namespace_members<'arena>(
+    ns: &DeclareNamespaceStatement<'arena>,
+) -> Vec<ObjectTypeMember<'arena>> {
+    ns.members
         .iter()
         .filter_map(|member| match member {
             Statement::DeclareFunction(func) if func.is_export => {
@@ -546,25 +597,5 @@ pub fn register_declare_namespace<'arena>(
             }
             _ => None,
         })
-        .collect();
-
-    let members_slice = arena.alloc_slice_fill_iter(members);
-    let namespace_type = Type::new(
-        TypeKind::Object(ObjectType {
-            members: members_slice,
-            span: ns.span,
-        }),
-        ns.span,
-    );
-
-    let symbol = Symbol::new(
-        interner.resolve(ns.name.node).to_string(),
-        SymbolKind::Const,
-        namespace_type,
-        ns.span,
-    );
-
-    symbol_table
-        .declare(symbol)
-        .map_err(|e| TypeCheckError::new(e, ns.span))
+        .collect()
 }
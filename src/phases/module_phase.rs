@@ -12,16 +12,18 @@
 use crate::cli::diagnostics::DiagnosticHandler;
 use crate::core::type_environment::TypeEnvironment;
 use crate::module_resolver::{
-    EdgeKind, ExportedSymbol, ModuleError, ModuleExports, ModuleId, ModuleRegistry, ModuleResolver,
-    TypedDependency,
+    EdgeKind, ExportedSymbol, ModuleError, ModuleExports, ModuleId, ModuleKind, ModuleRegistry,
+    ModuleResolver, TypedDependency,
 };
 use crate::utils::symbol_table::{Symbol, SymbolKind, SymbolTable};
 use crate::visitors::{AccessControl, AccessControlVisitor, ClassMemberInfo, ClassMemberKind};
 use crate::TypeCheckError;
 use luanext_parser::ast::pattern::Pattern;
 use luanext_parser::ast::statement::{ExportKind, ImportClause, ImportDeclaration, Statement};
-use luanext_parser::ast::types::{ObjectTypeMember, PrimitiveType, Type, TypeKind};
-use luanext_parser::ast::Program;
+use luanext_parser::ast::types::{
+    ObjectType, ObjectTypeMember, PrimitiveType, PropertySignature, Type, TypeKind,
+};
+use luanext_parser::ast::{Program, Spanned};
 use luanext_parser::prelude::AccessModifier;
 use luanext_parser::span::Span;
 use luanext_parser::string_interner::StringInterner;
@@ -195,6 +197,8 @@ pub fn extract_exports<'arena>(
                         span: export_decl.span,
                         is_exported: true,
                         references: Vec::new(),
+                        doc: None,
+                        member_docs: Default::default(),
                     };
                     exports
                         .set_default(ExportedSymbol::new(symbol_to_static(default_symbol), false));
@@ -443,12 +447,20 @@ pub fn check_import_statement<'arena>(
                     diagnostic_handler,
                 )?;
 
-                let symbol = Symbol::new(
+                let mut symbol = Symbol::new(
                     name_str.to_string(),
                     SymbolKind::Variable,
                     import_type,
                     spec.span,
                 );
+                attach_imported_docs(
+                    &mut symbol,
+                    &import.source,
+                    &name_str,
+                    module_registry,
+                    module_resolver,
+                    current_module_id,
+                );
                 symbol_table
                     .declare(symbol)
                     .map_err(|e| TypeCheckError::new(e, spec.span))?;
@@ -561,12 +573,20 @@ pub fn check_import_statement<'arena>(
                     diagnostic_handler,
                 )?;
 
-                let symbol = Symbol::new(
+                let mut symbol = Symbol::new(
                     name_str.to_string(),
                     SymbolKind::Variable,
                     import_type,
                     spec.span,
                 );
+                attach_imported_docs(
+                    &mut symbol,
+                    &import.source,
+                    &name_str,
+                    module_registry,
+                    module_resolver,
+                    current_module_id,
+                );
                 symbol_table
                     .declare(symbol)
                     .map_err(|e| TypeCheckError::new(e, spec.span))?;
@@ -576,6 +596,31 @@ pub fn check_import_statement<'arena>(
     Ok(())
 }
 
+/// Copy the doc comments of the export `symbol_name` of `source` onto the
+/// importing `symbol`, so an import carries the documentation and
+/// deprecation of the declaration it names and of its members
+fn attach_imported_docs(
+    symbol: &mut Symbol<'_>,
+    source: &str,
+    symbol_name: &str,
+    module_registry: Option<&Arc<ModuleRegistry>>,
+    module_resolver: Option<&Arc<ModuleResolver>>,
+    current_module_id: Option<&ModuleId>,
+) {
+    let (Some(registry), Some(resolver), Some(current_id)) =
+        (module_registry, module_resolver, current_module_id)
+    else {
+        return;
+    };
+    let Ok(source_module_id) = resolver.resolve(source, current_id.path()) else {
+        return;
+    };
+    if let Ok(exported) = registry.get_named_export(&source_module_id, symbol_name) {
+        symbol.doc = exported.symbol.doc;
+        symbol.member_docs = exported.symbol.member_docs;
+    }
+}
+
 /// Maximum recursion depth for lazy type-checking (prevents infinite loops)
 const MAX_LAZY_DEPTH: usize = 10;
 
@@ -738,6 +783,147 @@ fn resolve_import_type<'arena>(
     }
 }
 
+/// Resolve the type of a `require("source")` call in plain Lua style code.
+///
+/// The source is resolved like an import source, so a dotted package path is
+/// searched in the module paths and a package directory may hold an
+/// `init.luax`. The resolved module is tracked as a value dependency and the
+/// call yields its export type (see `module_export_type`). A `.lua` target
+/// is only resolved under `LuaFilePolicy::RequireDeclaration`, and only when
+/// no `.d.luax` declaration sits next to it, so it is reported as untyped.
+/// Under `LuaFilePolicy::Block` a `.lua` target is reported as blocked.
+///
+/// A module that has not been type-checked yet is checked through
+/// `lazy_callback` first, as for imports; without a callback, or in a cycle of
+/// requires deeper than `MAX_LAZY_DEPTH`, the call yields `unknown`. A plain
+/// Lua target is reported once per call span, recorded in `reported`, and
+/// the call yields `unknown`.
+///
+/// Returns `None` when module resolution is not configured or the source is
+/// not found: Lua also loads modules from `package.path` and C libraries from
+/// `package.cpath`, so such a call keeps the type `require` is declared with.
+#[allow(clippy::too_many_arguments)]
+pub fn resolve_require_type<'arena>(
+    source: &str,
+    span: Span,
+    module_dependencies: &mut Vec<TypedDependency>,
+    module_registry: Option<&Arc<ModuleRegistry>>,
+    module_resolver: Option<&Arc<ModuleResolver>>,
+    current_module_id: Option<&ModuleId>,
+    lazy_callback: Option<&dyn LazyTypeCheckCallback>,
+    arena: &'arena bumpalo::Bump,
+    interner: &StringInterner,
+    reported: &std::cell::RefCell<rustc_hash::FxHashSet<(usize, usize)>>,
+    diagnostic_handler: &Arc<dyn DiagnosticHandler>,
+) -> Option<Type<'arena>> {
+    use crate::cli::diagnostics::{error_codes, Diagnostic};
+
+    let (registry, resolver, current_id) = (module_registry?, module_resolver?, current_module_id?);
+    let unknown = Type::new(TypeKind::Primitive(PrimitiveType::Unknown), span);
+    let report_once = |message: String| {
+        if reported
+            .borrow_mut()
+            .insert((span.start as usize, span.end as usize))
+        {
+            diagnostic_handler.report(Diagnostic::error_with_code(
+                span,
+                error_codes::UNTYPED_LUA_MODULE,
+                message,
+            ));
+        }
+    };
+
+    let source_module_id = match resolver.resolve(source, current_id.path()) {
+        Ok(id) => id,
+        Err(_) => {
+            // A `.lua` file skipped by `LuaFilePolicy::Block` is reported, as
+            // an import of it is; anything else may load from `package.path`
+            let blocked = resolver.blocked_lua_file(source, current_id.path())?;
+            report_once(format!(
+                "Module '{}' resolves to plain Lua file '{}', which cannot be required unless 'allowNonTypedLua' is set",
+                source, blocked
+            ));
+            return Some(unknown);
+        }
+    };
+    module_dependencies.push(TypedDependency::new(
+        source_module_id.path().to_path_buf(),
+        EdgeKind::Value,
+    ));
+
+    if resolver.get_module_kind(source_module_id.path()) == Some(ModuleKind::PlainLua) {
+        report_once(format!(
+            "Module '{}' resolves to plain Lua file '{}', which needs a '.d.luax' declaration to be typed",
+            source, source_module_id
+        ));
+        return Some(unknown);
+    }
+
+    match registry.get_exports(&source_module_id) {
+        Ok(exports) => Some(module_export_type(&exports, span, arena, interner)),
+        Err(ModuleError::NotCompiled { id }) => {
+            // Not checked yet: check it first when a callback can, otherwise
+            // (or in a cycle of requires) the module stays opaque
+            let Some(callback) = lazy_callback else {
+                return Some(unknown);
+            };
+            if registry.get_type_check_depth(&id).unwrap_or(0) > MAX_LAZY_DEPTH {
+                return Some(unknown);
+            }
+
+            let _ = registry.increment_type_check_depth(&id);
+            let check_result = callback.type_check_dependency(&id);
+            let _ = registry.decrement_type_check_depth(&id);
+            if let Err(e) = check_result {
+                diagnostic_handler.error(span, &e.to_string());
+                return Some(unknown);
+            }
+
+            // Retry export lookup after type-checking
+            Some(match registry.get_exports(&source_module_id) {
+                Ok(exports) => module_export_type(&exports, span, arena, interner),
+                Err(_) => unknown,
+            })
+        }
+        Err(_) => Some(unknown),
+    }
+}
+
+/// The type of the value a module returns to `require`: its default export
+/// when it has no runtime named exports, otherwise a table of the named ones
+fn module_export_type<'arena>(
+    exports: &ModuleExports,
+    span: Span,
+    arena: &'arena bumpalo::Bump,
+    interner: &StringInterner,
+) -> Type<'arena> {
+    let members: Vec<ObjectTypeMember<'arena>> = exports
+        .named
+        .iter()
+        .filter(|(_, exported)| exported.is_runtime())
+        .map(|(name, exported)| {
+            ObjectTypeMember::Property(PropertySignature {
+                is_readonly: false,
+                name: Spanned::new(interner.intern(name), span),
+                is_optional: false,
+                type_annotation: exported.symbol.typ.clone(),
+                span,
+            })
+        })
+        .collect();
+
+    if let (true, Some(default)) = (members.is_empty(), &exports.default) {
+        return default.symbol.typ.clone();
+    }
+    Type::new(
+        TypeKind::Object(ObjectType {
+            members: arena.alloc_slice_fill_iter(members),
+            span,
+        }),
+        span,
+    )
+}
+
 /// Resolve a re-exported symbol through potentially multiple re-export chains.
 ///
 /// Returns the original `ExportedSymbol` after following the chain to its source.
@@ -837,6 +1023,8 @@ fn resolve_re_export(
                     span: Span::new(0, 0, 0, 0),
                     is_exported: true,
                     references: Vec::new(),
+                    doc: None,
+                    member_docs: Default::default(),
                 };
                 return Ok(ExportedSymbol::new(
                     symbol_to_static(synthetic_symbol),
@@ -1233,4 +1421,234 @@ mod tests {
         );
         assert!(result.is_ok());
     }
+
+    #[test]
+    fn test_attach_imported_docs() {
+        use crate::cli::fs::MockFileSystem;
+        use crate::module_resolver::{
+            LuaFilePolicy, ModuleConfig, ModuleExports, ModuleRegistry, ModuleResolver,
+            PathAliasResolver,
+        };
+        use crate::utils::doc_comment::{Deprecation, DocComment};
+        use std::path::{Path, PathBuf};
+
+        let main = Path::new("/project/src/main.luax");
+        let mut fs = MockFileSystem::new();
+        fs.add_file(main, "");
+        fs.add_file("/project/src/bank.luax", "");
+        let config = ModuleConfig {
+            module_paths: vec![],
+            lua_file_policy: LuaFilePolicy::RequireDeclaration,
+            path_aliases: PathAliasResolver::empty(),
+        };
+        let resolver = Arc::new(ModuleResolver::new(
+            Arc::new(fs),
+            config,
+            PathBuf::from("/project"),
+        ));
+
+        let span = Span::default();
+        let number = Type::new(TypeKind::Primitive(PrimitiveType::Number), span);
+        let mut account = Symbol::new(
+            "Account".to_string(),
+            SymbolKind::Class,
+            number.clone(),
+            span,
+        );
+        account.doc = Some(Arc::new(DocComment {
+            summary: "A bank account".to_string(),
+            ..Default::default()
+        }));
+        account.member_docs.insert(
+            "put".to_string(),
+            Arc::new(DocComment {
+                deprecated: Some(Deprecation {
+                    message: Some("use deposit".to_string()),
+                }),
+                ..Default::default()
+            }),
+        );
+        let mut exports = ModuleExports::new();
+        exports.add_named("Account".to_string(), ExportedSymbol::new(account, false));
+        let registry = Arc::new(ModuleRegistry::new());
+        let bank_id = resolver.resolve("./bank", main).unwrap();
+        registry.register_parsed(bank_id.clone(), Arc::new(SymbolTable::new()));
+        registry.register_exports(&bank_id, exports).unwrap();
+        let main_id = ModuleId::new(main.to_path_buf());
+
+        let mut import = Symbol::new("Account".to_string(), SymbolKind::Variable, number, span);
+        attach_imported_docs(
+            &mut import,
+            "./bank",
+            "Account",
+            Some(&registry),
+            Some(&resolver),
+            Some(&main_id),
+        );
+        assert_eq!(import.doc.as_ref().unwrap().summary, "A bank account");
+        assert!(import.member_docs["put"].deprecated.is_some());
+
+        // Unknown exports and missing module support leave the import undocumented
+        let mut missing = import.clone();
+        missing.doc = None;
+        missing.member_docs.clear();
+        attach_imported_docs(
+            &mut missing,
+            "./bank",
+            "Ledger",
+            Some(&registry),
+            Some(&resolver),
+            Some(&main_id),
+        );
+        attach_imported_docs(&mut missing, "./bank", "Account", None, None, None);
+        assert!(missing.doc.is_none());
+        assert!(missing.member_docs.is_empty());
+    }
+
+    #[test]
+    fn test_resolve_require_type_checks_lazily_and_reports_plain_lua_once() {
+        use crate::cli::diagnostics::error_codes;
+        use crate::cli::fs::MockFileSystem;
+        use crate::module_resolver::{LuaFilePolicy, ModuleConfig, PathAliasResolver};
+        use std::cell::RefCell;
+        use std::path::{Path, PathBuf};
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        struct RegisteringCallback {
+            registry: Arc<ModuleRegistry>,
+            calls: AtomicUsize,
+        }
+
+        impl LazyTypeCheckCallback for RegisteringCallback {
+            fn type_check_dependency(&self, module_id: &ModuleId) -> Result<(), ModuleError> {
+                self.calls.fetch_add(1, Ordering::SeqCst);
+                let span = Span::default();
+                let count = Symbol::new(
+                    "count".to_string(),
+                    SymbolKind::Variable,
+                    Type::new(TypeKind::Primitive(PrimitiveType::Number), span),
+                    span,
+                );
+                let mut exports = ModuleExports::new();
+                exports.set_default(ExportedSymbol::new(count, false));
+                self.registry
+                    .register_parsed(module_id.clone(), Arc::new(SymbolTable::new()));
+                self.registry.register_exports(module_id, exports)
+            }
+        }
+
+        let main = Path::new("/project/src/main.luax");
+        let mut fs = MockFileSystem::new();
+        fs.add_file(main, "");
+        fs.add_file("/project/src/counter.luax", "");
+        fs.add_file("/project/lua_modules/legacy.lua", "");
+        let config = ModuleConfig {
+            module_paths: vec![PathBuf::from("/project/lua_modules")],
+            lua_file_policy: LuaFilePolicy::RequireDeclaration,
+            path_aliases: PathAliasResolver::empty(),
+        };
+        let resolver = Arc::new(ModuleResolver::new(
+            Arc::new(fs),
+            config,
+            PathBuf::from("/project"),
+        ));
+        let main_id = ModuleId::new(main.to_path_buf());
+        let arena = bumpalo::Bump::new();
+        let interner = StringInterner::new();
+        let collecting = Arc::new(CollectingDiagnosticHandler::new());
+        let handler: Arc<dyn DiagnosticHandler> = collecting.clone();
+        let reported = RefCell::new(rustc_hash::FxHashSet::default());
+        let span = Span::new(10, 30, 1, 10);
+
+        let require = |source: &str,
+                       registry: &Arc<ModuleRegistry>,
+                       callback: Option<&dyn LazyTypeCheckCallback>| {
+            resolve_require_type(
+                source,
+                span,
+                &mut Vec::new(),
+                Some(registry),
+                Some(&resolver),
+                Some(&main_id),
+                callback,
+                &arena,
+                &interner,
+                &reported,
+                &handler,
+            )
+            .unwrap()
+        };
+
+        // Without a callback an unchecked module stays opaque
+        let unchecked = Arc::new(ModuleRegistry::new());
+        assert!(matches!(
+            require("./counter", &unchecked, None).kind,
+            TypeKind::Primitive(PrimitiveType::Unknown)
+        ));
+
+        let registry = Arc::new(ModuleRegistry::new());
+        let callback = RegisteringCallback {
+            registry: registry.clone(),
+            calls: AtomicUsize::new(0),
+        };
+        assert!(matches!(
+            require("./counter", &registry, Some(&callback)).kind,
+            TypeKind::Primitive(PrimitiveType::Number)
+        ));
+        // Once checked, the exports are used directly
+        require("./counter", &registry, Some(&callback));
+        assert_eq!(callback.calls.load(Ordering::SeqCst), 1);
+
+        // A plain Lua module is reported once per call span
+        require("legacy", &registry, None);
+        require("legacy", &registry, None);
+        let errors: Vec<_> = collecting
+            .get_diagnostics()
+            .into_iter()
+            .filter(|d| d.message.contains("plain Lua file"))
+            .collect();
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].code, Some(error_codes::UNTYPED_LUA_MODULE));
+
+        // Under the Block policy the skipped `.lua` file is reported rather
+        // than left to `package.path`, and an unknown module still is
+        let mut blocked_fs = MockFileSystem::new();
+        blocked_fs.add_file(main, "");
+        blocked_fs.add_file("/project/lua_modules/legacy.lua", "");
+        let blocking = Arc::new(ModuleResolver::new(
+            Arc::new(blocked_fs),
+            ModuleConfig {
+                module_paths: vec![PathBuf::from("/project/lua_modules")],
+                lua_file_policy: LuaFilePolicy::Block,
+                path_aliases: PathAliasResolver::empty(),
+            },
+            PathBuf::from("/project"),
+        ));
+        let blocked_reported = RefCell::new(rustc_hash::FxHashSet::default());
+        let require_blocked = |source: &str| {
+            resolve_require_type(
+                source,
+                span,
+                &mut Vec::new(),
+                Some(&registry),
+                Some(&blocking),
+                Some(&main_id),
+                None,
+                &arena,
+                &interner,
+                &blocked_reported,
+                &handler,
+            )
+        };
+        assert!(matches!(
+            require_blocked("legacy").map(|t| t.kind),
+            Some(TypeKind::Primitive(PrimitiveType::Unknown))
+        ));
+        assert!(require_blocked("lfs").is_none());
+        assert!(collecting
+            .get_diagnostics()
+            .iter()
+            .any(|d| d.message.contains("allowNonTypedLua")
+                && d.code == Some(error_codes::UNTYPED_LUA_MODULE)));
+    }
 }
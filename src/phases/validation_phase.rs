@@ -21,17 +21,19 @@ use luanext_parser::ast::expression::Expression;
 use luanext_parser::ast::statement::{
     ClassMember, Decorator, DecoratorExpression, IndexSignature, MethodDeclaration, TypeParameter,
 };
-use luanext_parser::ast::types::{ObjectTypeMember, Type};
+use luanext_parser::ast::types::{ObjectTypeMember, Type, TypeKind, TypeReference};
+use luanext_parser::ast::Spanned;
 use luanext_parser::prelude::ClassDeclaration;
 use luanext_parser::span::Span;
-use luanext_parser::string_interner::StringInterner;
+use luanext_parser::string_interner::{StringId, StringInterner};
 use rustc_hash::FxHashMap;
 use std::collections::HashSet;
 use std::sync::Arc;
 
 /// Validate interface members for duplicate property names.
 ///
-/// Interfaces cannot have duplicate property or method names.
+/// Interfaces cannot have duplicate property or method names, except that a
+/// method may be declared several times to overload it.
 /// This function checks all members and returns an error if duplicates are found.
 ///
 /// # Parameters
@@ -46,18 +48,18 @@ pub fn validate_interface_members<'arena>(
     members: &[ObjectTypeMember<'arena>],
     span: Span,
 ) -> Result<(), TypeCheckError> {
-    // Check for duplicate property names
-    let mut seen_names = HashSet::new();
+    // Check for duplicate property names, remembering whether each is a method
+    let mut seen_names = FxHashMap::default();
 
     for member in members {
-        let name = match member {
-            ObjectTypeMember::Property(prop) => Some(&prop.name.node),
-            ObjectTypeMember::Method(method) => Some(&method.name.node),
-            ObjectTypeMember::Index(_) => None,
+        let (name, is_method) = match member {
+            ObjectTypeMember::Property(prop) => (&prop.name.node, false),
+            ObjectTypeMember::Method(method) => (&method.name.node, true),
+            ObjectTypeMember::Index(_) => continue,
         };
 
-        if let Some(name) = name {
-            if !seen_names.insert(*name) {
+        if let Some(seen_method) = seen_names.insert(*name, is_method) {
+            if !(seen_method && is_method) {
                 return Err(TypeCheckError::new(
                     format!("Duplicate property '{}' in interface", name),
                     span,
@@ -299,7 +301,6 @@ where
 /// When a method is marked with the `override` keyword, this function validates:
 /// - The class has a parent class
 /// - The parent class has a method with the same name
-/// - The parent method is not marked `final`
 /// - Parameter types are compatible (contravariant)
 /// - Return types are compatible (covariant)
 /// - Generic parent types are properly instantiated
@@ -344,17 +345,15 @@ where
         )
     })?;
 
-    // Walk the inheritance chain to find the method and check if it's final
+    // Walk the inheritance chain to find the overridden method
     let method_name = interner.resolve(method.name.node);
     let mut current_class = parent_name.clone();
     let mut found_method = None;
-    let mut found_in_class = None;
 
     loop {
         if let Some(parent_members) = access_control.get_class_members(&current_class) {
             if let Some(parent_method) = parent_members.iter().find(|m| m.name == method_name) {
                 found_method = Some(parent_method);
-                found_in_class = Some(current_class.clone());
                 break;
             }
         }
@@ -377,17 +376,24 @@ where
         )
     })?;
 
-    // Check if parent method is final anywhere in the inheritance chain
-    if parent_method.is_final {
-        return Err(TypeCheckError::new(
-            format!(
-                "Cannot override final method {} from ancestor class {}",
-                method.name.node,
-                found_in_class.unwrap()
-            ),
-            method.span,
-        ));
-    }
+    // `Self` in either signature stands for the overriding class
+    let self_name = interner.intern(generics::SELF_TYPE_NAME);
+    let class_type = Type::new(
+        TypeKind::Reference(TypeReference {
+            name: Spanned::new(interner.intern(class_name), method.span),
+            type_arguments: None,
+            span: method.span,
+        }),
+        method.span,
+    );
+    let mut resolve_with_self = |typ: &Type<'arena>| {
+        deep_resolve_type(&generics::substitute_self_type(
+            arena,
+            typ,
+            self_name,
+            &class_type,
+        ))
+    };
 
     match &parent_method.kind {
         ClassMemberKind::Method {
@@ -448,8 +454,8 @@ where
                 };
 
                 // Deep-resolve both types for comparison
-                let resolved_child = deep_resolve_type(child_type);
-                let resolved_parent = deep_resolve_type(&parent_type);
+                let resolved_child = resolve_with_self(child_type);
+                let resolved_parent = resolve_with_self(&parent_type);
 
                 // Parameters are contravariant: parent type must be assignable to child type
                 // (child can accept a more specific type than parent)
@@ -478,12 +484,43 @@ where
                         raw_parent_ret.clone()
                     };
 
-                    let resolved_child_ret = deep_resolve_type(child_return);
-                    let resolved_parent_ret = deep_resolve_type(&parent_ret);
-
-                    // Child return type must be assignable to parent return type
-                    if !TypeCompatibility::is_assignable(&resolved_parent_ret, &resolved_child_ret)
-                    {
+                    // Child return type must be assignable to parent return type.
+                    // A parent returning `Self` returns the receiver's own class,
+                    // so only `Self` can override it: a concrete class would no
+                    // longer be the receiver's type in a further subclass.
+                    let compatible = if mentions_self(&parent_ret, self_name) {
+                        mentions_self(child_return, self_name)
+                            && TypeCompatibility::is_assignable(
+                                &resolve_with_self(&parent_ret),
+                                &resolve_with_self(child_return),
+                            )
+                    } else {
+                        let resolved_child_ret = resolve_with_self(child_return);
+                        let resolved_parent_ret = resolve_with_self(&parent_ret);
+
+                        // Returning the overriding class is valid where an
+                        // ancestor class is expected
+                        let returns_subclass =
+                            match (&resolved_child_ret.kind, &resolved_parent_ret.kind) {
+                                (
+                                    TypeKind::Reference(child_ref),
+                                    TypeKind::Reference(parent_ref),
+                                ) => {
+                                    child_ref.type_arguments.is_none()
+                                        && access_control.is_subclass(
+                                            &interner.resolve(child_ref.name.node),
+                                            &interner.resolve(parent_ref.name.node),
+                                        )
+                                }
+                                _ => false,
+                            };
+                        returns_subclass
+                            || TypeCompatibility::is_assignable(
+                                &resolved_parent_ret,
+                                &resolved_child_ret,
+                            )
+                    };
+                    if !compatible {
                         return Err(TypeCheckError::new(
                             format!(
                                 "Method '{}' return type is incompatible with parent return type",
@@ -515,6 +552,20 @@ where
     }
 }
 
+/// Whether a return type is `Self`, possibly nullable or in a union
+fn mentions_self(typ: &Type<'_>, self_name: StringId) -> bool {
+    match &typ.kind {
+        TypeKind::Reference(type_ref) => {
+            type_ref.name.node == self_name && type_ref.type_arguments.is_none()
+        }
+        TypeKind::Union(members) => members.iter().any(|m| mentions_self(m, self_name)),
+        TypeKind::Nullable(inner) | TypeKind::Parenthesized(inner) => {
+            mentions_self(inner, self_name)
+        }
+        _ => false,
+    }
+}
+
 /// Check if a class has circular inheritance.
 ///
 /// Detects inheritance cycles like: A extends B, B extends C, C extends A.
@@ -571,7 +622,6 @@ pub fn check_class_implements_interface<'arena>(
     interner: &StringInterner,
 ) -> Result<(), TypeCheckError> {
     use luanext_parser::ast::statement::ClassMember;
-    use luanext_parser::ast::types::{ObjectTypeMember, TypeKind};
 
     if let TypeKind::Object(obj_type) = &interface.kind {
         for required_member in obj_type.members.iter() {
@@ -725,8 +775,6 @@ pub fn check_implements_assignable<'arena>(
     type_env: &crate::core::type_environment::TypeEnvironment<'arena>,
     interner: &StringInterner,
 ) -> bool {
-    use luanext_parser::ast::types::TypeKind;
-
     if let (TypeKind::Reference(s_ref), TypeKind::Reference(t_ref)) = (&source.kind, &target.kind) {
         let source_name = interner.resolve(s_ref.name.node);
         let target_name = interner.resolve(t_ref.name.node);
@@ -764,11 +812,10 @@ pub fn check_implements_assignable<'arena>(
     false
 }
 
-/// Validate class inheritance - checks for final parent and circular inheritance.
+/// Validate class inheritance - checks for circular inheritance.
 ///
-/// This focused function validates that a class's inheritance is valid:
-/// - Parent class is not marked as final
-/// - No circular inheritance exists
+/// Extending a `final` class is reported by the type checker, which can
+/// point at the parent's declaration.
 ///
 /// # Returns
 ///
@@ -776,24 +823,13 @@ pub fn check_implements_assignable<'arena>(
 pub fn validate_class_inheritance(
     class_name: &str,
     extends_type: &luanext_parser::ast::types::Type,
-    access_control: &crate::visitors::AccessControl,
     class_parents: &mut rustc_hash::FxHashMap<String, String>,
     interner: &luanext_parser::string_interner::StringInterner,
     span: luanext_parser::span::Span,
 ) -> Result<String, crate::TypeCheckError> {
-    use luanext_parser::ast::types::TypeKind;
-
     if let TypeKind::Reference(type_ref) = &extends_type.kind {
         let parent_name = interner.resolve(type_ref.name.node).to_string();
 
-        // Check if parent class is final
-        if access_control.is_class_final(&parent_name) {
-            return Err(crate::TypeCheckError::new(
-                format!("Cannot extend final class {}", parent_name),
-                span,
-            ));
-        }
-
         // Check for circular inheritance
         class_parents.insert(class_name.to_string(), parent_name.clone());
         if has_circular_inheritance(class_name, class_parents) {
@@ -5,10 +5,12 @@
 
 pub mod metrics;
 pub mod stdlib_loader;
+pub mod stdlib_snapshot;
 pub mod type_checker_state;
 
 #[cfg(test)]
 mod metrics_tests;
 
 pub use metrics::{MetricSummary, Metrics};
+pub use stdlib_snapshot::StdlibSnapshot;
 pub use type_checker_state::TypeCheckerState;
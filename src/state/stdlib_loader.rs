@@ -4,19 +4,42 @@
 //! definition files into AST programs. The caller is responsible for
 //! processing the statements (e.g., type checking, populating symbol tables).
 
-use crate::cli::config::LuaVersion;
-use crate::cli::diagnostics::CollectingDiagnosticHandler;
+use crate::cli::config::CompilerOptions;
+use crate::cli::diagnostics::{CollectingDiagnosticHandler, DiagnosticHandler, DiagnosticLevel};
 use bumpalo::Bump;
 use luanext_parser::ast::Program;
 use luanext_parser::lexer::Lexer;
 use luanext_parser::parser::Parser;
 use luanext_parser::string_interner::{CommonIdentifiers, StringInterner};
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
 
-/// Parses the standard library definition files for the specified Lua version.
+/// A parsed definition file and the pack it was loaded from
+pub struct DefinitionFile<'arena> {
+    /// `None` for the standard library, the library name for a bundled pack
+    /// (e.g. `love2d`), or the file's path for a file under a type root
+    pub pack: Option<String>,
+    /// The file's text, for reading doc comments
+    pub source: &'arena str,
+    pub program: Program<'arena>,
+}
+
+/// The parsed definition files, and what went wrong with the selected packs
+pub struct StdlibFiles<'arena> {
+    /// The standard library and the packs that could be loaded, in load order
+    pub files: Vec<DefinitionFile<'arena>>,
+    /// Unknown library names, unreadable type roots and definition files that
+    /// failed to parse; the remaining packs are loaded regardless
+    pub errors: Vec<String>,
+}
+
+/// Parses the standard library and the selected definition packs.
 ///
-/// This function reads and parses all stdlib files for the given Lua version,
-/// returning the parsed AST programs. The caller is responsible for processing
+/// This function reads and parses all stdlib files for the target Lua version,
+/// then the bundled packs named in `options.libraries`, then every `.d.luax`
+/// file under the directories in `options.type_roots`, returning the parsed
+/// AST programs in that order. Relative type roots are resolved against
+/// `base_dir`, the project directory. The caller is responsible for processing
 /// these programs (e.g., type checking statements, populating symbol tables).
 ///
 /// # Separation of Concerns
@@ -33,62 +56,151 @@ use std::sync::Arc;
 ///
 /// # Arguments
 ///
-/// * `target_version` - The Lua version to parse stdlib for (5.1, 5.2, 5.3, 5.4, 5.5, or jit)
+/// * `options` - The target Lua version (5.1, 5.2, 5.3, 5.4, 5.5, or jit),
+///   libraries and type roots to load
+/// * `base_dir` - The directory relative type roots are resolved against
 /// * `interner` - String interner for parsing identifiers
 /// * `common` - Common identifiers for parsing
 ///
 /// # Returns
 ///
-/// Returns `Ok(Vec<Program>)` containing the parsed stdlib programs, or an error
-/// message if parsing failed.
+/// Returns the parsed programs along with the problems found in the selected
+/// packs, or an error message if a standard library file failed to parse.
 ///
 /// # Example
 ///
 /// ```rust,ignore
 /// use luanext_typechecker::state::stdlib_loader;
-/// use luanext_typechecker::config::LuaVersion;
+/// use luanext_typechecker::config::CompilerOptions;
 ///
 /// let (interner, common) = StringInterner::new_with_common_identifiers();
-/// let programs = stdlib_loader::parse_stdlib_files(
-///     LuaVersion::Lua54,
-///     &interner,
-///     &common
-/// )?;
+/// let options = CompilerOptions {
+///     libraries: vec!["love2d".to_string()],
+///     ..Default::default()
+/// };
+/// let parsed =
+///     stdlib_loader::parse_stdlib_files(&options, Path::new("."), &interner, &common, &arena)?;
 ///
 /// // Process the programs as needed
-/// for mut program in programs {
-///     for statement in &mut program.statements {
+/// for file in parsed.files {
+///     for statement in file.program.statements.iter() {
 ///         // Check statement, register types, etc.
 ///     }
 /// }
 /// ```
 pub fn parse_stdlib_files<'arena>(
-    target_version: LuaVersion,
+    options: &CompilerOptions,
+    base_dir: &Path,
     interner: &StringInterner,
     common: &CommonIdentifiers,
     arena: &'arena Bump,
-) -> Result<Vec<Program<'arena>>, String> {
+) -> Result<StdlibFiles<'arena>, String> {
     use crate::stdlib;
 
-    let stdlib_files = stdlib::get_all_stdlib(target_version);
-    let mut programs = Vec::with_capacity(stdlib_files.len());
+    let mut errors = Vec::new();
+    let mut sources: Vec<(Option<String>, String, &'arena str)> =
+        stdlib::get_all_stdlib(options.target)
+            .into_iter()
+            .map(|(filename, source)| (None, filename.to_string(), source))
+            .collect();
 
-    for (filename, source) in stdlib_files {
-        let handler = Arc::new(CollectingDiagnosticHandler::new());
-        let mut lexer = Lexer::new(source, handler.clone(), interner);
-        let tokens = lexer
-            .tokenize()
-            .map_err(|e| format!("Failed to lex {}: {:?}", filename, e))?;
+    for library in &options.libraries {
+        match stdlib::get_library(library) {
+            Some(source) => {
+                sources.push((Some(library.clone()), format!("{}.d.luax", library), source))
+            }
+            None => {
+                let available: Vec<&str> =
+                    stdlib::LIBRARIES.iter().map(|(name, _)| *name).collect();
+                errors.push(format!(
+                    "Unknown library '{}' (available: {})",
+                    library,
+                    available.join(", ")
+                ));
+            }
+        }
+    }
+
+    for root in &options.type_roots {
+        let dir = base_dir.join(root);
+        let mut paths = Vec::new();
+        if let Err(e) = collect_definition_files(&dir, &mut paths) {
+            errors.push(format!("Failed to read type root {}: {}", dir.display(), e));
+            continue;
+        }
+        paths.sort();
+        for path in paths {
+            match std::fs::read_to_string(&path) {
+                Ok(source) => {
+                    let name = path.display().to_string();
+                    sources.push((Some(name.clone()), name, arena.alloc_str(&source)));
+                }
+                Err(e) => errors.push(format!("Failed to read {}: {}", path.display(), e)),
+            }
+        }
+    }
 
-        let mut parser = Parser::new(tokens, handler.clone(), interner, common, arena);
-        let program = parser
-            .parse()
-            .map_err(|e| format!("Failed to parse {}: {:?}", filename, e))?;
+    let mut files = Vec::with_capacity(sources.len());
 
-        programs.push(program);
+    for (pack, filename, source) in sources {
+        let handler = Arc::new(CollectingDiagnosticHandler::new());
+        let parsed = parse_definition_file(&filename, source, &handler, interner, common, arena);
+        // The standard library must load; a pack that fails to parse is skipped
+        match parsed {
+            Ok(program) if pack.is_none() || !handler.has_errors() => {
+                files.push(DefinitionFile {
+                    pack,
+                    source,
+                    program,
+                });
+            }
+            Ok(_) => {
+                let diagnostics = handler.get_diagnostics();
+                let message = diagnostics
+                    .iter()
+                    .find(|d| d.level == DiagnosticLevel::Error)
+                    .map_or("", |d| d.message.as_str());
+                errors.push(format!("Failed to parse {}: {}", filename, message));
+            }
+            Err(e) if pack.is_none() => return Err(e),
+            Err(e) => errors.push(e),
+        }
     }
 
-    Ok(programs)
+    Ok(StdlibFiles { files, errors })
+}
+
+/// Lex and parse one definition file, reporting recovered errors to `handler`
+fn parse_definition_file<'arena>(
+    filename: &str,
+    source: &'arena str,
+    handler: &Arc<CollectingDiagnosticHandler>,
+    interner: &StringInterner,
+    common: &CommonIdentifiers,
+    arena: &'arena Bump,
+) -> Result<Program<'arena>, String> {
+    let mut lexer = Lexer::new(source, handler.clone(), interner);
+    let tokens = lexer
+        .tokenize()
+        .map_err(|e| format!("Failed to lex {}: {:?}", filename, e))?;
+
+    let mut parser = Parser::new(tokens, handler.clone(), interner, common, arena);
+    parser
+        .parse()
+        .map_err(|e| format!("Failed to parse {}: {:?}", filename, e))
+}
+
+/// Collect the `.d.luax` files under `dir`, including its subdirectories
+fn collect_definition_files(dir: &Path, paths: &mut Vec<PathBuf>) -> std::io::Result<()> {
+    for entry in std::fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.is_dir() {
+            collect_definition_files(&path, paths)?;
+        } else if path.to_string_lossy().ends_with(".d.luax") {
+            paths.push(path);
+        }
+    }
+    Ok(())
 }
 
 #[cfg(test)]
@@ -97,22 +209,39 @@ mod tests {
     use crate::cli::config::LuaVersion;
     use luanext_parser::string_interner::StringInterner;
 
+    fn here() -> &'static Path {
+        Path::new(".")
+    }
+
+    fn target(version: LuaVersion) -> CompilerOptions {
+        CompilerOptions {
+            target: version,
+            ..Default::default()
+        }
+    }
+
     #[test]
     fn test_parse_stdlib_lua51() {
         let arena = Bump::new();
         let (interner, common) = StringInterner::new_with_common_identifiers();
 
-        let result = parse_stdlib_files(LuaVersion::Lua51, &interner, &common, &arena);
+        let result = parse_stdlib_files(
+            &target(LuaVersion::Lua51),
+            here(),
+            &interner,
+            &common,
+            &arena,
+        );
 
         assert!(result.is_ok());
-        let programs = result.unwrap();
+        let programs = result.unwrap().files;
         assert!(
             !programs.is_empty(),
             "Should parse at least one stdlib file"
         );
 
         // Verify programs have statements
-        let total_statements: usize = programs.iter().map(|p| p.statements.len()).sum();
+        let total_statements: usize = programs.iter().map(|f| f.program.statements.len()).sum();
         assert!(
             total_statements > 0,
             "Stdlib should contain type definitions"
@@ -134,7 +263,7 @@ mod tests {
             let arena = Bump::new();
             let (interner, common) = StringInterner::new_with_common_identifiers();
 
-            let result = parse_stdlib_files(version, &interner, &common, &arena);
+            let result = parse_stdlib_files(&target(version), here(), &interner, &common, &arena);
 
             assert!(
                 result.is_ok(),
@@ -142,7 +271,7 @@ mod tests {
                 version
             );
 
-            let programs = result.unwrap();
+            let programs = result.unwrap().files;
             assert!(
                 !programs.is_empty(),
                 "Should parse at least one stdlib file for {:?}",
@@ -155,15 +284,113 @@ mod tests {
     fn test_parse_stdlib_returns_valid_programs() {
         let arena = Bump::new();
         let (interner, common) = StringInterner::new_with_common_identifiers();
-        let programs = parse_stdlib_files(LuaVersion::Lua54, &interner, &common, &arena).unwrap();
+        let programs = parse_stdlib_files(
+            &target(LuaVersion::Lua54),
+            here(),
+            &interner,
+            &common,
+            &arena,
+        )
+        .unwrap()
+        .files;
 
         // Verify each program is valid
-        for program in programs {
+        for file in programs {
             // Programs should have statements (stdlib definitions)
+            assert!(file.pack.is_none());
             assert!(
-                !program.statements.is_empty(),
+                !file.program.statements.is_empty(),
                 "Each stdlib file should have declarations"
             );
         }
     }
+
+    #[test]
+    fn test_parse_libraries() {
+        let arena = Bump::new();
+        let (interner, common) = StringInterner::new_with_common_identifiers();
+        let options = CompilerOptions {
+            libraries: vec!["love2d".to_string(), "nvim".to_string()],
+            ..Default::default()
+        };
+
+        let parsed = parse_stdlib_files(&options, here(), &interner, &common, &arena).unwrap();
+        let packs: Vec<_> = parsed
+            .files
+            .iter()
+            .filter_map(|f| f.pack.as_deref())
+            .collect();
+        assert_eq!(packs, vec!["love2d", "nvim"]);
+        assert!(parsed.errors.is_empty());
+
+        for library in ["openresty", "defold"] {
+            let options = CompilerOptions {
+                libraries: vec![library.to_string()],
+                ..Default::default()
+            };
+            let parsed = parse_stdlib_files(&options, here(), &interner, &common, &arena).unwrap();
+            assert!(parsed.errors.is_empty());
+        }
+
+        // An unknown library is reported; the stdlib and the other packs still load
+        let unknown = CompilerOptions {
+            libraries: vec!["love".to_string(), "nvim".to_string()],
+            ..Default::default()
+        };
+        let parsed = parse_stdlib_files(&unknown, here(), &interner, &common, &arena).unwrap();
+        assert_eq!(parsed.errors.len(), 1);
+        assert!(parsed.errors[0].contains("Unknown library 'love'"));
+        assert!(parsed.files.iter().any(|f| f.pack.is_none()));
+        assert!(parsed
+            .files
+            .iter()
+            .any(|f| f.pack.as_deref() == Some("nvim")));
+    }
+
+    #[test]
+    fn test_parse_type_roots() {
+        let root = std::env::temp_dir().join(format!("luanext-type-roots-{}", std::process::id()));
+        std::fs::create_dir_all(root.join("nested")).unwrap();
+        std::fs::write(
+            root.join("game.d.luax"),
+            "declare function spawn(name: string): number",
+        )
+        .unwrap();
+        std::fs::write(
+            root.join("nested").join("ui.d.luax"),
+            "declare namespace ui {\n  export function show(): void\n}",
+        )
+        .unwrap();
+        std::fs::write(root.join("notes.txt"), "not a definition file").unwrap();
+
+        std::fs::write(root.join("broken.d.luax"), "declare function (").unwrap();
+
+        let arena = Bump::new();
+        let (interner, common) = StringInterner::new_with_common_identifiers();
+        // Relative roots are resolved against the project directory
+        let options = CompilerOptions {
+            type_roots: vec![root.file_name().unwrap().to_string_lossy().to_string()],
+            ..Default::default()
+        };
+        let parent = root.parent().unwrap();
+        let parsed = parse_stdlib_files(&options, parent, &interner, &common, &arena);
+        std::fs::remove_dir_all(&root).unwrap();
+
+        let parsed = parsed.unwrap();
+        let packs: Vec<_> = parsed.files.into_iter().filter_map(|f| f.pack).collect();
+        assert_eq!(packs.len(), 2);
+        assert!(packs[0].ends_with("game.d.luax"));
+        assert!(packs[1].ends_with("ui.d.luax"));
+        assert_eq!(parsed.errors.len(), 1);
+        assert!(parsed.errors[0].contains("broken.d.luax"));
+
+        // A missing root is reported and the stdlib still loads
+        let missing = CompilerOptions {
+            type_roots: vec![root.display().to_string()],
+            ..Default::default()
+        };
+        let parsed = parse_stdlib_files(&missing, here(), &interner, &common, &arena).unwrap();
+        assert_eq!(parsed.errors.len(), 1);
+        assert!(!parsed.files.is_empty());
+    }
 }
@@ -0,0 +1,277 @@
+//! Shared standard library snapshots
+//!
+//! Loading the standard library lexes, parses and checks every `.d.luax`
+//! file of the target, which dominates the cost of checking a small file.
+//! A [`StdlibSnapshot`] holds the state that loading produces for one Lua
+//! version. It is built once per process, on first use, and shared
+//! immutably: a checker created with [`TypeChecker::new_with_snapshot`]
+//! copies the globals, types and docs instead of loading them again.
+//!
+//! Types refer to names by [`StringId`], so the snapshot also records the
+//! strings of the interner it was built with, in id order. A checker's
+//! interner must start with the same strings; [`StdlibSnapshot::interner`]
+//! creates one that does.
+//!
+//! The snapshot owns the arena its types live in. Checkers borrow the
+//! snapshot for as long as they use its types, so they cannot outlive it.
+//!
+//! Snapshots are only built at run time. Serializing them at build time is
+//! deferred: the parser's types can be serialized but not deserialized into
+//! an arena, and a build script cannot run this crate's own checker.
+
+use crate::cli::config::{CompilerOptions, LuaVersion};
+use crate::cli::diagnostics::CollectingDiagnosticHandler;
+use crate::core::type_environment::TypeEnvironment;
+use crate::utils::symbol_table::SymbolTable;
+use crate::visitors::AccessControl;
+use crate::TypeChecker;
+use luanext_parser::ast::statement::TypeParameter;
+use luanext_parser::string_interner::{CommonIdentifiers, StringId, StringInterner};
+use rustc_hash::FxHashMap;
+use std::ptr::NonNull;
+use std::sync::{Arc, Mutex};
+
+/// Interned after the snapshot is built to find where its strings end. It
+/// cannot be an identifier, so the stdlib never interns it.
+const END_MARKER: &str = "<end of stdlib snapshot>";
+
+/// Snapshots built so far, one per Lua version
+static SNAPSHOTS: Mutex<Vec<(LuaVersion, Arc<StdlibSnapshot>)>> = Mutex::new(Vec::new());
+
+/// The state loading the standard library populates in a checker
+pub(crate) struct StdlibState<'arena> {
+    pub symbol_table: SymbolTable<'arena>,
+    pub type_env: TypeEnvironment<'arena>,
+    pub access_control: AccessControl<'arena>,
+    pub class_type_params: FxHashMap<String, Vec<TypeParameter<'arena>>>,
+}
+
+/// The standard library of one Lua version, loaded once and shared between
+/// checkers, including checkers on other threads
+pub struct StdlibSnapshot {
+    target: LuaVersion,
+    /// The strings of the interner the snapshot was built with, in id order
+    strings: Vec<String>,
+    symbol_table: SymbolTable<'static>,
+    /// Behind a mutex only because the environment's memo caches are
+    /// `RefCell`s; the snapshot never changes it
+    type_env: Mutex<TypeEnvironment<'static>>,
+    access_control: AccessControl<'static>,
+    class_type_params: FxHashMap<String, Vec<TypeParameter<'static>>>,
+    /// The arena the fields above point into. Their `'static` stands for the
+    /// lifetime of this field, which is declared last so it is dropped after
+    /// them.
+    _arena: SnapshotArena,
+}
+
+/// The arena of a snapshot, owned through a pointer so the references into
+/// it stay valid while the snapshot moves
+struct SnapshotArena(NonNull<bumpalo::Bump>);
+
+impl SnapshotArena {
+    fn new() -> Self {
+        Self(NonNull::from(Box::leak(Box::default())))
+    }
+
+    /// # Safety
+    ///
+    /// The reference must not be used after `self` is dropped.
+    unsafe fn get(&self) -> &'static bumpalo::Bump {
+        // SAFETY: the pointer comes from a box that lives until `drop`
+        unsafe { self.0.as_ref() }
+    }
+}
+
+impl Drop for SnapshotArena {
+    fn drop(&mut self) {
+        // SAFETY: the pointer comes from `Box::leak` in `new` and is freed once
+        drop(unsafe { Box::from_raw(self.0.as_ptr()) });
+    }
+}
+
+// SAFETY: the arena is only allocated in while the snapshot is built, on one
+// thread. Afterwards it is only read through the snapshot's types, whose own
+// `Send` and `Sync` bounds the snapshot checks, and freed when dropped.
+unsafe impl Send for SnapshotArena {}
+unsafe impl Sync for SnapshotArena {}
+
+impl StdlibSnapshot {
+    /// The snapshot for `target`, building it on first use. `Auto` is
+    /// resolved to the detected version first.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the stdlib fails to parse.
+    pub fn for_target(target: LuaVersion) -> Result<Arc<Self>, String> {
+        let target = target.effective();
+        // Holding the lock while building makes concurrent first uses wait
+        // for one build instead of each building their own
+        let mut snapshots = SNAPSHOTS.lock().unwrap_or_else(|e| e.into_inner());
+        if let Some((_, snapshot)) = snapshots.iter().find(|(version, _)| *version == target) {
+            return Ok(snapshot.clone());
+        }
+        let snapshot = Arc::new(Self::build(target)?);
+        snapshots.push((target, snapshot.clone()));
+        Ok(snapshot)
+    }
+
+    fn build(target: LuaVersion) -> Result<Self, String> {
+        let owned_arena = SnapshotArena::new();
+        // SAFETY: the arena is moved into the snapshot below, and `state`
+        // only hands its types out for as long as the snapshot is borrowed
+        let arena = unsafe { owned_arena.get() };
+        let (interner, common) = StringInterner::new_with_common_identifiers();
+        let handler = Arc::new(CollectingDiagnosticHandler::new());
+        let options = CompilerOptions {
+            target,
+            ..Default::default()
+        };
+        let state =
+            TypeChecker::new(handler, &interner, &common, arena).into_stdlib_state(options)?;
+
+        // Ids are handed out in order, so the strings interned so far are
+        // the ones before the marker
+        let end = interner.intern(END_MARKER);
+        let strings = (0..)
+            .map(StringId::from_u32)
+            .take_while(|id| *id != end)
+            .map(|id| interner.resolve(id).to_string())
+            .collect();
+
+        Ok(Self {
+            target,
+            strings,
+            symbol_table: state.symbol_table,
+            type_env: Mutex::new(state.type_env),
+            access_control: state.access_control,
+            class_type_params: state.class_type_params,
+            _arena: owned_arena,
+        })
+    }
+
+    /// The Lua version the snapshot was loaded for
+    pub fn target(&self) -> LuaVersion {
+        self.target
+    }
+
+    /// Create an interner that starts with the snapshot's strings, to parse
+    /// the programs checked against the snapshot
+    pub fn interner(&self) -> (StringInterner, CommonIdentifiers) {
+        let (interner, common) = StringInterner::new_with_common_identifiers();
+        for string in &self.strings {
+            interner.intern(string);
+        }
+        (interner, common)
+    }
+
+    /// Whether the names in the snapshot's types mean the same strings in
+    /// `interner`
+    pub fn is_compatible(&self, interner: &StringInterner) -> bool {
+        // Looked up rather than interned, so checking leaves `interner` as it was
+        self.strings
+            .iter()
+            .enumerate()
+            .all(|(index, string)| interner.get(string) == Some(StringId::from_u32(index as u32)))
+    }
+
+    /// A copy of the snapshot's state for a checker to own, pointing into
+    /// the snapshot's arena
+    pub(crate) fn state(&self) -> StdlibState<'_> {
+        StdlibState {
+            symbol_table: self.symbol_table.clone(),
+            type_env: self
+                .type_env
+                .lock()
+                .unwrap_or_else(|e| e.into_inner())
+                .fork(),
+            access_control: self.access_control.clone(),
+            class_type_params: self.class_type_params.clone(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cli::diagnostics::DiagnosticHandler;
+    use luanext_parser::{Lexer, Parser};
+
+    /// Whether checking `source` against `snapshot` fails
+    fn fails(snapshot: &StdlibSnapshot, source: &str) -> bool {
+        let arena = bumpalo::Bump::new();
+        let handler = Arc::new(CollectingDiagnosticHandler::new());
+        let (interner, common) = snapshot.interner();
+        let mut lexer = Lexer::new(source, handler.clone(), &interner);
+        let tokens = lexer.tokenize().expect("Lexing failed");
+        let mut parser = Parser::new(tokens, handler.clone(), &interner, &common, &arena);
+        let program = parser.parse().expect("Parsing failed");
+
+        let mut checker =
+            TypeChecker::new_with_snapshot(handler.clone(), &interner, &common, &arena, snapshot)
+                .expect("interner should be compatible");
+        checker.check_program(&program).is_err() || handler.has_errors()
+    }
+
+    #[test]
+    fn test_snapshot_is_built_once_per_version() {
+        let first = StdlibSnapshot::for_target(LuaVersion::Lua54).unwrap();
+        let second = StdlibSnapshot::for_target(LuaVersion::Lua54).unwrap();
+        assert!(Arc::ptr_eq(&first, &second));
+
+        let other = StdlibSnapshot::for_target(LuaVersion::Lua51).unwrap();
+        assert!(!Arc::ptr_eq(&first, &other));
+        assert_eq!(other.target(), LuaVersion::Lua51);
+    }
+
+    #[test]
+    fn test_checkers_share_snapshot_across_threads() {
+        let snapshot = StdlibSnapshot::for_target(LuaVersion::Lua54).unwrap();
+        let handles: Vec<_> = (0..4)
+            .map(|i| {
+                let snapshot = snapshot.clone();
+                std::thread::spawn(move || {
+                    let valid = format!(
+                        "local s: string = string.format(\"%d\", {})\nlocal n: number = math.max(1, {})",
+                        i, i
+                    );
+                    let invalid = format!("local s: string = math.floor({})", i);
+                    (fails(&snapshot, &valid), fails(&snapshot, &invalid))
+                })
+            })
+            .collect();
+        for handle in handles {
+            assert_eq!(handle.join().unwrap(), (false, true));
+        }
+    }
+
+    #[test]
+    fn test_snapshot_is_send_and_sync() {
+        fn assert_send_sync<T: Send + Sync>() {}
+        assert_send_sync::<StdlibSnapshot>();
+
+        let snapshot = StdlibSnapshot::for_target(LuaVersion::Lua53).unwrap();
+        let shared = Arc::clone(&snapshot);
+        let target = std::thread::spawn(move || shared.target()).join().unwrap();
+        assert_eq!(target, LuaVersion::Lua53);
+    }
+
+    #[test]
+    fn test_unrelated_interner_is_rejected() {
+        let snapshot = StdlibSnapshot::for_target(LuaVersion::Lua54).unwrap();
+        let (interner, common) = StringInterner::new_with_common_identifiers();
+        interner.intern("declared_before_the_stdlib");
+        assert!(!snapshot.is_compatible(&interner));
+
+        let arena = bumpalo::Bump::new();
+        let handler = Arc::new(CollectingDiagnosticHandler::new());
+        assert!(
+            TypeChecker::new_with_snapshot(handler, &interner, &common, &arena, &snapshot).is_err()
+        );
+        assert!(snapshot.is_compatible(&snapshot.interner().0));
+
+        // Checking does not intern the snapshot's strings
+        let (fresh, _) = StringInterner::new_with_common_identifiers();
+        assert!(!snapshot.is_compatible(&fresh));
+        assert!(fresh.get(snapshot.strings.last().unwrap()).is_none());
+    }
+}
@@ -26,6 +26,37 @@ pub const LUAJIT: &str = include_str!("luajit.d.luax");
 /// Reflection runtime module (available in all Lua versions)
 pub const REFLECTION: &str = include_str!("reflection.d.luax");
 
+/// Built-in decorators (available in all Lua versions)
+pub const DECORATORS: &str = include_str!("decorators.d.luax");
+
+/// LÖVE (love2d) game framework definition pack
+pub const LOVE2D: &str = include_str!("love2d.d.luax");
+
+/// Neovim plugin API definition pack
+pub const NVIM: &str = include_str!("nvim.d.luax");
+
+/// OpenResty (lua-nginx-module) definition pack
+pub const OPENRESTY: &str = include_str!("openresty.d.luax");
+
+/// Defold engine definition pack
+pub const DEFOLD: &str = include_str!("defold.d.luax");
+
+/// Bundled definition packs, selected by name with the `libraries` option
+pub const LIBRARIES: &[(&str, &str)] = &[
+    ("love2d", LOVE2D),
+    ("nvim", NVIM),
+    ("openresty", OPENRESTY),
+    ("defold", DEFOLD),
+];
+
+/// Get a bundled definition pack by its `libraries` name
+pub fn get_library(name: &str) -> Option<&'static str> {
+    LIBRARIES
+        .iter()
+        .find(|(library, _)| *library == name)
+        .map(|(_, source)| *source)
+}
+
 /// Get the appropriate stdlib content based on Lua version
 pub fn get_stdlib(version: LuaVersion) -> &'static str {
     match version.effective() {
@@ -39,7 +70,7 @@ pub fn get_stdlib(version: LuaVersion) -> &'static str {
     }
 }
 
-/// Get all stdlib sources (builtins + version-specific + reflection)
+/// Get all stdlib sources (builtins + version-specific + reflection + decorators)
 pub fn get_all_stdlib(version: LuaVersion) -> Vec<(&'static str, &'static str)> {
     let effective_version = version.effective();
     vec![
@@ -57,6 +88,7 @@ pub fn get_all_stdlib(version: LuaVersion) -> Vec<(&'static str, &'static str)>
             get_stdlib(version),
         ),
         ("reflection.d.luax", REFLECTION),
+        ("decorators.d.luax", DECORATORS),
     ]
 }
 
@@ -126,12 +158,30 @@ mod tests {
         assert_eq!(get_stdlib(LuaVersion::LuaJIT), LUAJIT);
     }
 
+    #[test]
+    fn test_get_library() {
+        assert!(get_library("love2d")
+            .unwrap()
+            .contains("declare namespace love"));
+        assert!(get_library("nvim")
+            .unwrap()
+            .contains("declare namespace vim"));
+        assert!(get_library("openresty")
+            .unwrap()
+            .contains("declare namespace ngx"));
+        assert!(get_library("defold")
+            .unwrap()
+            .contains("declare namespace go"));
+        assert!(get_library("love").is_none());
+    }
+
     #[test]
     fn test_get_all_stdlib() {
         let stdlib = get_all_stdlib(LuaVersion::Lua54);
-        assert_eq!(stdlib.len(), 3);
+        assert_eq!(stdlib.len(), 4);
         assert_eq!(stdlib[0].0, "builtins.d.luax");
         assert_eq!(stdlib[1].0, "lua54.d.luax");
         assert_eq!(stdlib[2].0, "reflection.d.luax");
+        assert_eq!(stdlib[3].0, "decorators.d.luax");
     }
 }
@@ -0,0 +1,225 @@
+//! Typed coroutines: `Coroutine<Yield, Resume, Return>`
+//!
+//! `Coroutine` is a generic reference type that is never resolved to a
+//! structure; its three type arguments describe the values a coroutine passes
+//! to `coroutine.yield`, receives from `coroutine.resume`, and finally returns.
+//! Several values passed at once (a body with two parameters, `yield(a, b)`)
+//! are packed into a tuple, and passing no values is `nil`.
+
+use luanext_parser::ast::statement::Parameter;
+use luanext_parser::ast::types::{PrimitiveType, Type, TypeKind, TypeReference};
+use luanext_parser::ast::Spanned;
+use luanext_parser::span::Span;
+use luanext_parser::string_interner::StringInterner;
+
+/// Name of the coroutine reference type
+pub const COROUTINE_TYPE: &str = "Coroutine";
+
+/// The three type arguments of a `Coroutine`
+#[derive(Debug, Clone)]
+pub struct CoroutineSignature<'arena> {
+    /// Values passed to `coroutine.yield`
+    pub yield_type: Type<'arena>,
+    /// Values passed to `coroutine.resume`: the body's parameters on the first
+    /// resume, the result of `coroutine.yield` afterwards
+    pub resume_type: Type<'arena>,
+    /// Value the body returns when it finishes
+    pub return_type: Type<'arena>,
+}
+
+impl<'arena> CoroutineSignature<'arena> {
+    /// Read the signature of a `Coroutine<Y, R, T>` reference
+    pub fn from_type(typ: &Type<'arena>, interner: &StringInterner) -> Option<Self> {
+        match &typ.kind {
+            TypeKind::Reference(type_ref)
+                if interner.resolve(type_ref.name.node) == COROUTINE_TYPE =>
+            {
+                match type_ref.type_arguments {
+                    Some([yield_type, resume_type, return_type]) => Some(Self {
+                        yield_type: yield_type.clone(),
+                        resume_type: resume_type.clone(),
+                        return_type: return_type.clone(),
+                    }),
+                    _ => None,
+                }
+            }
+            TypeKind::Parenthesized(inner) => Self::from_type(inner, interner),
+            _ => None,
+        }
+    }
+
+    /// Signature of a coroutine running a function with `parameters` that
+    /// returns `return_type`. Its yields are not known from the function alone.
+    pub fn for_body(
+        arena: &'arena bumpalo::Bump,
+        parameters: &[Parameter<'arena>],
+        return_type: Type<'arena>,
+        span: Span,
+    ) -> Self {
+        Self {
+            yield_type: Type::new(TypeKind::Primitive(PrimitiveType::Unknown), span),
+            resume_type: parameters_as_values(arena, parameters, span),
+            return_type,
+        }
+    }
+
+    /// The `Coroutine<Y, R, T>` reference for this signature
+    pub fn to_type(
+        &self,
+        arena: &'arena bumpalo::Bump,
+        interner: &StringInterner,
+        span: Span,
+    ) -> Type<'arena> {
+        let args = arena.alloc_slice_fill_iter([
+            self.yield_type.clone(),
+            self.resume_type.clone(),
+            self.return_type.clone(),
+        ]);
+        Type::new(
+            TypeKind::Reference(TypeReference {
+                name: Spanned::new(interner.intern(COROUTINE_TYPE), span),
+                type_arguments: Some(args),
+                span,
+            }),
+            span,
+        )
+    }
+
+    /// `Yield | Return`: what the coroutine hands back to its caller
+    pub fn output_type(&self, arena: &'arena bumpalo::Bump, span: Span) -> Type<'arena> {
+        union_of(
+            arena,
+            [self.yield_type.clone(), self.return_type.clone()],
+            span,
+        )
+    }
+
+    /// Result of `coroutine.resume`: `(boolean, Yield | Return | string)`.
+    /// The string is the error message when the body raised an error.
+    pub fn resume_result(&self, arena: &'arena bumpalo::Bump, span: Span) -> Type<'arena> {
+        let value = union_of(
+            arena,
+            [
+                self.yield_type.clone(),
+                self.return_type.clone(),
+                Type::new(TypeKind::Primitive(PrimitiveType::String), span),
+            ],
+            span,
+        );
+        let elements = arena.alloc_slice_fill_iter([
+            Type::new(TypeKind::Primitive(PrimitiveType::Boolean), span),
+            value,
+        ]);
+        Type::new(TypeKind::Tuple(elements), span)
+    }
+}
+
+/// Pack the types of several values passed together: none is `nil`, one is
+/// itself and more become a tuple
+pub fn pack_values<'arena>(
+    arena: &'arena bumpalo::Bump,
+    mut values: Vec<Type<'arena>>,
+    span: Span,
+) -> Type<'arena> {
+    match values.len() {
+        0 => Type::new(TypeKind::Primitive(PrimitiveType::Nil), span),
+        1 => values.remove(0),
+        _ => Type::new(TypeKind::Tuple(arena.alloc_slice_fill_iter(values)), span),
+    }
+}
+
+/// The values a packed type expects, or `None` when it accepts any values
+/// (`unknown`, or a rest parameter)
+pub fn unpack_values<'t, 'arena>(packed: &'t Type<'arena>) -> Option<&'t [Type<'arena>]> {
+    match &packed.kind {
+        TypeKind::Primitive(PrimitiveType::Unknown) => None,
+        TypeKind::Primitive(PrimitiveType::Nil | PrimitiveType::Void) => Some(&[]),
+        TypeKind::Tuple(values) => Some(values),
+        _ => Some(std::slice::from_ref(packed)),
+    }
+}
+
+/// Pack the parameter types of a coroutine body. A rest parameter accepts
+/// any number of values, so the whole pack becomes `unknown`.
+fn parameters_as_values<'arena>(
+    arena: &'arena bumpalo::Bump,
+    parameters: &[Parameter<'arena>],
+    span: Span,
+) -> Type<'arena> {
+    if parameters.iter().any(|param| param.is_rest) {
+        return Type::new(TypeKind::Primitive(PrimitiveType::Unknown), span);
+    }
+    let values = parameters
+        .iter()
+        .map(|param| {
+            param
+                .type_annotation
+                .clone()
+                .unwrap_or_else(|| Type::new(TypeKind::Primitive(PrimitiveType::Unknown), span))
+        })
+        .collect();
+    pack_values(arena, values, span)
+}
+
+/// Union of `types`, collapsing to `unknown` if any member is unknown
+fn union_of<'arena, const N: usize>(
+    arena: &'arena bumpalo::Bump,
+    types: [Type<'arena>; N],
+    span: Span,
+) -> Type<'arena> {
+    if types
+        .iter()
+        .any(|typ| matches!(typ.kind, TypeKind::Primitive(PrimitiveType::Unknown)))
+    {
+        return Type::new(TypeKind::Primitive(PrimitiveType::Unknown), span);
+    }
+    Type::new(TypeKind::Union(arena.alloc_slice_fill_iter(types)), span)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_signature_round_trips_through_reference() {
+        let arena = bumpalo::Bump::new();
+        let interner = StringInterner::new();
+        let span = Span::default();
+        let number = Type::new(TypeKind::Primitive(PrimitiveType::Number), span);
+        let string = Type::new(TypeKind::Primitive(PrimitiveType::String), span);
+        let boolean = Type::new(TypeKind::Primitive(PrimitiveType::Boolean), span);
+
+        let signature = CoroutineSignature {
+            yield_type: number,
+            resume_type: string,
+            return_type: boolean,
+        };
+        let typ = signature.to_type(&arena, &interner, span);
+        let read = CoroutineSignature::from_type(&typ, &interner).unwrap();
+        assert!(matches!(
+            read.resume_type.kind,
+            TypeKind::Primitive(PrimitiveType::String)
+        ));
+
+        let TypeKind::Tuple(result) = signature.resume_result(&arena, span).kind else {
+            panic!("resume should return a tuple");
+        };
+        assert!(matches!(result[1].kind, TypeKind::Union(members) if members.len() == 3));
+    }
+
+    #[test]
+    fn test_pack_and_unpack_values() {
+        let arena = bumpalo::Bump::new();
+        let span = Span::default();
+        let number = Type::new(TypeKind::Primitive(PrimitiveType::Number), span);
+
+        let none = pack_values(&arena, Vec::new(), span);
+        assert_eq!(unpack_values(&none).map(<[_]>::len), Some(0));
+        let one = pack_values(&arena, vec![number.clone()], span);
+        assert_eq!(unpack_values(&one).map(<[_]>::len), Some(1));
+        let two = pack_values(&arena, vec![number.clone(), number], span);
+        assert_eq!(unpack_values(&two).map(<[_]>::len), Some(2));
+        let unknown = Type::new(TypeKind::Primitive(PrimitiveType::Unknown), span);
+        assert!(unpack_values(&unknown).is_none());
+    }
+}
@@ -0,0 +1,966 @@
+//! LuaJIT FFI declarations
+//!
+//! `ffi.cdef` takes C source in a string. This module parses the subset of C
+//! used by FFI bindings (structs, unions, enums, typedefs, function prototypes
+//! and extern variables) and maps the declared C types onto LuaNext types, so
+//! cdata created by `ffi.new`, `ffi.cast` or `ffi.typeof` and functions called
+//! through `ffi.C` are checked like any other value.
+
+use luanext_parser::ast::pattern::Pattern;
+use luanext_parser::ast::statement::{Parameter, PropertySignature};
+use luanext_parser::ast::types::{
+    FunctionType, ObjectType, ObjectTypeMember, PrimitiveType, Type, TypeKind,
+};
+use luanext_parser::ast::Spanned;
+use luanext_parser::span::Span;
+use luanext_parser::string_interner::StringInterner;
+use rustc_hash::FxHashMap;
+
+/// Name of the interface `ffi.C` and `ffi.load` are declared with in the
+/// LuaJIT stdlib; the checker replaces it with the declared C members
+pub const C_LIBRARY_TYPE: &str = "CLibrary";
+
+/// Members of the `ffi` module whose names are keywords in LuaNext, so the
+/// stdlib cannot declare them
+pub const FFI_KEYWORD_MEMBERS: &[&str] = &["new", "typeof", "string"];
+
+/// Type names that denote a C integer or floating point type
+const NUMERIC_TYPE_NAMES: &[&str] = &[
+    "short",
+    "int",
+    "long",
+    "float",
+    "double",
+    "signed",
+    "unsigned",
+    "int8_t",
+    "int16_t",
+    "int32_t",
+    "int64_t",
+    "uint8_t",
+    "uint16_t",
+    "uint32_t",
+    "uint64_t",
+    "intptr_t",
+    "uintptr_t",
+    "ptrdiff_t",
+    "size_t",
+    "ssize_t",
+    "off_t",
+    "time_t",
+    "wchar_t",
+];
+
+/// Qualifiers and storage classes that do not affect the type
+const IGNORED_SPECIFIERS: &[&str] = &[
+    "const",
+    "volatile",
+    "restrict",
+    "__restrict",
+    "extern",
+    "static",
+    "inline",
+    "__inline",
+    "__extension__",
+];
+
+/// A C type as written in an FFI declaration
+#[derive(Debug, Clone, PartialEq)]
+pub enum CType {
+    Void,
+    Bool,
+    /// `char` in any signedness; pointers to it accept Lua strings
+    Char,
+    /// Any other integer, floating point or enum type
+    Number,
+    /// A struct or union, keyed by its tag (`struct point`)
+    Record(String),
+    Pointer(Box<CType>),
+    /// A fixed (`[4]`) or variable-length (`[?]`) array
+    Array(Box<CType>),
+    Function(Box<CFunction>),
+    /// A type name that was never declared
+    Opaque(String),
+}
+
+/// A C function prototype
+#[derive(Debug, Clone, PartialEq)]
+pub struct CFunction {
+    pub params: Vec<CType>,
+    pub return_type: CType,
+    pub is_variadic: bool,
+}
+
+/// A field of a struct or union
+#[derive(Debug, Clone, PartialEq)]
+pub struct CField {
+    pub name: String,
+    pub ctype: CType,
+}
+
+/// All C declarations seen in `ffi.cdef` calls
+#[derive(Debug, Clone, Default)]
+pub struct CDeclarations {
+    /// Struct and union fields by tag; `None` for forward declarations
+    records: FxHashMap<String, Option<Vec<CField>>>,
+    typedefs: FxHashMap<String, CType>,
+    functions: FxHashMap<String, CFunction>,
+    variables: FxHashMap<String, CType>,
+    /// Enumerators, which LuaJIT exposes as constants on `ffi.C`
+    constants: Vec<String>,
+    anonymous_records: usize,
+}
+
+impl CDeclarations {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Parse the C source passed to `ffi.cdef` and register its declarations.
+    /// Nothing is registered if the source does not parse.
+    pub fn declare(&mut self, source: &str) -> Result<(), String> {
+        let tokens = tokenize(source)?;
+        let mut parser = CParser::new(self, tokens);
+        parser.parse_declarations()?;
+        let added = parser.added;
+
+        self.anonymous_records = added.anonymous_records;
+        for (tag, fields) in added.records {
+            // A forward declaration never hides an earlier definition
+            if fields.is_some() || !self.records.contains_key(&tag) {
+                self.records.insert(tag, fields);
+            }
+        }
+        self.typedefs.extend(added.typedefs);
+        self.functions.extend(added.functions);
+        self.variables.extend(added.variables);
+        for constant in added.constants {
+            if !self.constants.contains(&constant) {
+                self.constants.push(constant);
+            }
+        }
+        Ok(())
+    }
+
+    /// Parse a C type name as accepted by `ffi.new` and friends (`"struct point[?]"`)
+    pub fn parse_type_name(&self, source: &str) -> Result<CType, String> {
+        let tokens = tokenize(source)?;
+        let mut parser = CParser::new(self, tokens);
+        let base = parser.parse_specifiers()?;
+        let (name, ctype) = parser.parse_declarator(base)?;
+        if let Some(name) = name {
+            return Err(format!("unexpected name '{}' in a type", name));
+        }
+        if let Some(token) = parser.peek() {
+            return Err(format!("unexpected '{}'", token));
+        }
+        Ok(ctype)
+    }
+
+    /// The struct or union tag `ctype` refers to, if it has not been defined
+    pub fn undefined_record<'c>(&self, ctype: &'c CType) -> Option<&'c str> {
+        match ctype {
+            CType::Record(tag) => match self.records.get(tag) {
+                Some(Some(_)) => None,
+                _ => Some(tag),
+            },
+            CType::Pointer(_) => None,
+            CType::Array(inner) => self.undefined_record(inner),
+            _ => None,
+        }
+    }
+
+    /// Fields of a defined struct or union
+    pub fn record_fields(&self, tag: &str) -> Option<&[CField]> {
+        self.records.get(tag).and_then(|fields| fields.as_deref())
+    }
+
+    /// Type of `ffi.C`: declared functions, variables and enum constants
+    pub fn library_type<'arena>(
+        &self,
+        arena: &'arena bumpalo::Bump,
+        interner: &StringInterner,
+        span: Span,
+    ) -> Type<'arena> {
+        let converter = Converter {
+            declarations: self,
+            arena,
+            interner,
+            span,
+        };
+        let mut members: Vec<(&str, Type<'arena>, bool)> = Vec::new();
+        for (name, function) in &self.functions {
+            members.push((name.as_str(), converter.function_type(function), true));
+        }
+        for (name, ctype) in &self.variables {
+            members.push((
+                name.as_str(),
+                converter.value_type(ctype, &mut Vec::new()),
+                false,
+            ));
+        }
+        for name in &self.constants {
+            members.push((name.as_str(), converter.number(), true));
+        }
+        members.sort_by(|a, b| a.0.cmp(b.0));
+
+        converter.object(members.into_iter())
+    }
+
+    /// Type of a cdata object holding a `ctype`
+    pub fn cdata_type<'arena>(
+        &self,
+        ctype: &CType,
+        arena: &'arena bumpalo::Bump,
+        interner: &StringInterner,
+        span: Span,
+    ) -> Type<'arena> {
+        Converter {
+            declarations: self,
+            arena,
+            interner,
+            span,
+        }
+        .value_type(ctype, &mut Vec::new())
+    }
+
+    /// Type of the constructor `ffi.typeof` returns for `ctype`
+    pub fn ctype_constructor<'arena>(
+        &self,
+        ctype: &CType,
+        arena: &'arena bumpalo::Bump,
+        interner: &StringInterner,
+        span: Span,
+    ) -> Type<'arena> {
+        let converter = Converter {
+            declarations: self,
+            arena,
+            interner,
+            span,
+        };
+        let instance = converter.value_type(ctype, &mut Vec::new());
+        converter.function(Vec::new(), true, instance)
+    }
+}
+
+/// Whether `typ` is the `CLibrary` placeholder from the LuaJIT stdlib
+pub fn is_c_library(typ: &Type<'_>, interner: &StringInterner) -> bool {
+    matches!(&typ.kind, TypeKind::Reference(type_ref)
+        if type_ref.type_arguments.is_none()
+            && interner.resolve(type_ref.name.node) == C_LIBRARY_TYPE)
+}
+
+/// Whether `typ` is the `ffi` module namespace, recognised by its `C` member
+pub fn is_ffi_namespace(typ: &Type<'_>, interner: &StringInterner) -> bool {
+    let TypeKind::Object(object) = &typ.kind else {
+        return false;
+    };
+    object.members.iter().any(|member| {
+        matches!(member, ObjectTypeMember::Property(prop)
+            if interner.resolve(prop.name.node) == "C"
+                && is_c_library(&prop.type_annotation, interner))
+    })
+}
+
+/// Builds LuaNext types for C types
+struct Converter<'d, 'i, 'arena> {
+    declarations: &'d CDeclarations,
+    arena: &'arena bumpalo::Bump,
+    interner: &'i StringInterner,
+    span: Span,
+}
+
+impl<'arena> Converter<'_, '_, 'arena> {
+    fn primitive(&self, primitive: PrimitiveType) -> Type<'arena> {
+        Type::new(TypeKind::Primitive(primitive), self.span)
+    }
+
+    fn number(&self) -> Type<'arena> {
+        self.primitive(PrimitiveType::Number)
+    }
+
+    fn unknown(&self) -> Type<'arena> {
+        self.primitive(PrimitiveType::Unknown)
+    }
+
+    /// The type of a value read from a field, variable or return value.
+    /// `visiting` holds the records being expanded, so self-referential
+    /// structs end in `unknown` instead of recursing forever.
+    fn value_type(&self, ctype: &CType, visiting: &mut Vec<String>) -> Type<'arena> {
+        match ctype {
+            CType::Void => self.primitive(PrimitiveType::Void),
+            CType::Bool => self.primitive(PrimitiveType::Boolean),
+            CType::Char | CType::Number => self.number(),
+            CType::Record(tag) => self.record_type(tag, visiting),
+            CType::Pointer(inner) => match &**inner {
+                // Pointers to structs index like the struct itself
+                CType::Record(tag) => self.record_type(tag, visiting),
+                _ => self.unknown(),
+            },
+            CType::Array(element) => {
+                let element = self.value_type(element, visiting);
+                Type::new(TypeKind::Array(self.arena.alloc(element)), self.span)
+            }
+            CType::Function(function) => self.function_type(function),
+            CType::Opaque(_) => self.unknown(),
+        }
+    }
+
+    /// The type a parameter accepts. Pointers to structs may be NULL (`nil`);
+    /// other pointers take strings, buffers and callbacks, so they are not checked.
+    fn parameter_type(&self, ctype: &CType) -> Type<'arena> {
+        match ctype {
+            CType::Pointer(inner) if matches!(**inner, CType::Record(_)) => {
+                let record = self.value_type(ctype, &mut Vec::new());
+                Type::new(TypeKind::Nullable(self.arena.alloc(record)), self.span)
+            }
+            CType::Pointer(_) | CType::Array(_) => self.unknown(),
+            other => self.value_type(other, &mut Vec::new()),
+        }
+    }
+
+    fn record_type(&self, tag: &str, visiting: &mut Vec<String>) -> Type<'arena> {
+        let Some(fields) = self.declarations.record_fields(tag) else {
+            return self.unknown();
+        };
+        if visiting.iter().any(|seen| seen == tag) {
+            return self.unknown();
+        }
+        visiting.push(tag.to_string());
+        let members: Vec<(&str, Type<'arena>, bool)> = fields
+            .iter()
+            .map(|field| {
+                (
+                    field.name.as_str(),
+                    self.value_type(&field.ctype, visiting),
+                    false,
+                )
+            })
+            .collect();
+        visiting.pop();
+        self.object(members.into_iter())
+    }
+
+    fn function_type(&self, function: &CFunction) -> Type<'arena> {
+        let params = function
+            .params
+            .iter()
+            .map(|param| self.parameter_type(param))
+            .collect();
+        let return_type = self.value_type(&function.return_type, &mut Vec::new());
+        self.function(params, function.is_variadic, return_type)
+    }
+
+    fn function(
+        &self,
+        params: Vec<Type<'arena>>,
+        is_variadic: bool,
+        return_type: Type<'arena>,
+    ) -> Type<'arena> {
+        let parameter = |name: String, typ: Type<'arena>, is_rest: bool| Parameter {
+            pattern: Pattern::Identifier(Spanned::new(self.interner.intern(&name), self.span)),
+            type_annotation: Some(typ),
+            default: None,
+            is_rest,
+            is_optional: false,
+            span: self.span,
+        };
+        let mut parameters: Vec<Parameter<'arena>> = params
+            .into_iter()
+            .enumerate()
+            .map(|(i, typ)| parameter(format!("arg{}", i + 1), typ, false))
+            .collect();
+        if is_variadic {
+            let rest = Type::new(TypeKind::Array(self.arena.alloc(self.unknown())), self.span);
+            parameters.push(parameter("args".to_string(), rest, true));
+        }
+        Type::new(
+            TypeKind::Function(FunctionType {
+                type_parameters: None,
+                parameters: self.arena.alloc_slice_fill_iter(parameters),
+                return_type: self.arena.alloc(return_type),
+                throws: None,
+                span: self.span,
+            }),
+            self.span,
+        )
+    }
+
+    fn object<'n>(
+        &self,
+        members: impl Iterator<Item = (&'n str, Type<'arena>, bool)>,
+    ) -> Type<'arena> {
+        let members: Vec<ObjectTypeMember<'arena>> = members
+            .map(|(name, typ, is_readonly)| {
+                ObjectTypeMember::Property(PropertySignature {
+                    is_readonly,
+                    name: Spanned::new(self.interner.intern(name), self.span),
+                    is_optional: false,
+                    type_annotation: typ,
+                    span: self.span,
+                })
+            })
+            .collect();
+        Type::new(
+            TypeKind::Object(ObjectType {
+                members: self.arena.alloc_slice_fill_iter(members),
+                span: self.span,
+            }),
+            self.span,
+        )
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Ident(String),
+    Number(String),
+    Punct(char),
+    Ellipsis,
+    /// String literals only appear in attributes and `asm` labels
+    Str,
+}
+
+impl std::fmt::Display for Token {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Token::Ident(name) | Token::Number(name) => write!(f, "{}", name),
+            Token::Punct(c) => write!(f, "{}", c),
+            Token::Ellipsis => write!(f, "..."),
+            Token::Str => write!(f, "string literal"),
+        }
+    }
+}
+
+fn tokenize(source: &str) -> Result<Vec<Token>, String> {
+    let chars: Vec<char> = source.chars().collect();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+    let mut line_start = true;
+    while i < chars.len() {
+        let c = chars[i];
+        if c == '\n' {
+            line_start = true;
+            i += 1;
+            continue;
+        }
+        if c.is_whitespace() {
+            i += 1;
+            continue;
+        }
+        // Preprocessor lines are not part of the declarations LuaJIT accepts,
+        // but headers pasted into cdef often contain them
+        if c == '#' && line_start {
+            while i < chars.len() && chars[i] != '\n' {
+                i += 1;
+            }
+            continue;
+        }
+        line_start = false;
+        if c == '/' && chars.get(i + 1) == Some(&'/') {
+            while i < chars.len() && chars[i] != '\n' {
+                i += 1;
+            }
+        } else if c == '/' && chars.get(i + 1) == Some(&'*') {
+            i += 2;
+            while i < chars.len() && !(chars[i] == '*' && chars.get(i + 1) == Some(&'/')) {
+                i += 1;
+            }
+            if i >= chars.len() {
+                return Err("unterminated comment".to_string());
+            }
+            i += 2;
+        } else if c.is_ascii_alphabetic() || c == '_' {
+            let start = i;
+            while i < chars.len() && (chars[i].is_ascii_alphanumeric() || chars[i] == '_') {
+                i += 1;
+            }
+            tokens.push(Token::Ident(chars[start..i].iter().collect()));
+        } else if c.is_ascii_digit() {
+            let start = i;
+            while i < chars.len() && (chars[i].is_ascii_alphanumeric() || chars[i] == '.') {
+                i += 1;
+            }
+            tokens.push(Token::Number(chars[start..i].iter().collect()));
+        } else if c == '"' {
+            i += 1;
+            while i < chars.len() && chars[i] != '"' {
+                i += if chars[i] == '\\' { 2 } else { 1 };
+            }
+            if i >= chars.len() {
+                return Err("unterminated string literal".to_string());
+            }
+            i += 1;
+            tokens.push(Token::Str);
+        } else if c == '.' && chars.get(i + 1) == Some(&'.') && chars.get(i + 2) == Some(&'.') {
+            tokens.push(Token::Ellipsis);
+            i += 3;
+        } else if "{}()[];,*=?:&|<>+-~!%^/'.".contains(c) {
+            tokens.push(Token::Punct(c));
+            i += 1;
+        } else {
+            return Err(format!("unexpected character '{}'", c));
+        }
+    }
+    Ok(tokens)
+}
+
+/// Recursive descent parser over one `ffi.cdef` string. New declarations are
+/// collected in `added` so a failed parse leaves the registry untouched.
+struct CParser<'d> {
+    existing: &'d CDeclarations,
+    added: CDeclarations,
+    tokens: Vec<Token>,
+    pos: usize,
+}
+
+impl<'d> CParser<'d> {
+    fn new(existing: &'d CDeclarations, tokens: Vec<Token>) -> Self {
+        Self {
+            existing,
+            added: CDeclarations {
+                anonymous_records: existing.anonymous_records,
+                ..CDeclarations::default()
+            },
+            tokens,
+            pos: 0,
+        }
+    }
+
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos)
+    }
+
+    fn peek_ident(&self) -> Option<&str> {
+        match self.peek() {
+            Some(Token::Ident(name)) => Some(name),
+            _ => None,
+        }
+    }
+
+    fn eat_punct(&mut self, c: char) -> bool {
+        if self.peek() == Some(&Token::Punct(c)) {
+            self.pos += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn expect_punct(&mut self, c: char) -> Result<(), String> {
+        if self.eat_punct(c) {
+            return Ok(());
+        }
+        match self.peek() {
+            Some(token) => Err(format!("expected '{}' but found '{}'", c, token)),
+            None => Err(format!("expected '{}' at end of declaration", c)),
+        }
+    }
+
+    fn eat_ident(&mut self, name: &str) -> bool {
+        if self.peek_ident() == Some(name) {
+            self.pos += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn typedef(&self, name: &str) -> Option<&CType> {
+        self.added
+            .typedefs
+            .get(name)
+            .or_else(|| self.existing.typedefs.get(name))
+    }
+
+    fn record_fields(&self, tag: &str) -> Option<&Vec<CField>> {
+        match self.added.records.get(tag) {
+            Some(Some(fields)) => Some(fields),
+            _ => self.existing.records.get(tag).and_then(|f| f.as_ref()),
+        }
+    }
+
+    fn parse_declarations(&mut self) -> Result<(), String> {
+        while self.peek().is_some() {
+            if self.eat_punct(';') {
+                continue;
+            }
+            let is_typedef = self.eat_ident("typedef");
+            let base = self.parse_specifiers()?;
+            if self.eat_punct(';') {
+                continue;
+            }
+            loop {
+                let (name, ctype) = self.parse_declarator(base.clone())?;
+                let name = name.ok_or_else(|| "expected a declaration name".to_string())?;
+                if self.eat_punct('=') {
+                    self.skip_until(&[',', ';']);
+                }
+                if is_typedef {
+                    self.added.typedefs.insert(name, ctype);
+                } else if let CType::Function(function) = ctype {
+                    self.added.functions.insert(name, *function);
+                } else {
+                    self.added.variables.insert(name, ctype);
+                }
+                if !self.eat_punct(',') {
+                    break;
+                }
+            }
+            self.expect_punct(';')?;
+        }
+        Ok(())
+    }
+
+    /// Parse the type specifiers before a declarator (`const unsigned int`,
+    /// `struct point { ... }`, a typedef name)
+    fn parse_specifiers(&mut self) -> Result<CType, String> {
+        let mut result: Option<CType> = None;
+        let mut is_numeric = false;
+        while let Some(word) = self.peek_ident().map(str::to_string) {
+            if IGNORED_SPECIFIERS.contains(&word.as_str()) {
+                self.pos += 1;
+                continue;
+            }
+            if word == "__attribute__" || word == "__declspec" {
+                self.pos += 1;
+                self.skip_parenthesized()?;
+                continue;
+            }
+            if result.is_some() {
+                break;
+            }
+            match word.as_str() {
+                "struct" | "union" => {
+                    self.pos += 1;
+                    result = Some(self.parse_record(&word)?);
+                }
+                "enum" => {
+                    self.pos += 1;
+                    self.parse_enum()?;
+                    result = Some(CType::Number);
+                }
+                "void" => {
+                    self.pos += 1;
+                    result = Some(CType::Void);
+                }
+                "bool" | "_Bool" => {
+                    self.pos += 1;
+                    result = Some(CType::Bool);
+                }
+                "char" => {
+                    self.pos += 1;
+                    result = Some(CType::Char);
+                }
+                _ if NUMERIC_TYPE_NAMES.contains(&word.as_str()) => {
+                    self.pos += 1;
+                    is_numeric = true;
+                }
+                // A name after `unsigned`/`long` is the declarator
+                _ if is_numeric => break,
+                _ => {
+                    self.pos += 1;
+                    result = Some(
+                        self.typedef(&word)
+                            .cloned()
+                            .unwrap_or_else(|| CType::Opaque(word.clone())),
+                    );
+                }
+            }
+        }
+        match result {
+            Some(ctype) => Ok(ctype),
+            None if is_numeric => Ok(CType::Number),
+            None => match self.peek() {
+                Some(token) => Err(format!("expected a type but found '{}'", token)),
+                None => Err("expected a type".to_string()),
+            },
+        }
+    }
+
+    fn parse_record(&mut self, keyword: &str) -> Result<CType, String> {
+        let tag = match self.peek_ident() {
+            Some(name) => {
+                let tag = format!("{} {}", keyword, name);
+                self.pos += 1;
+                tag
+            }
+            None => {
+                self.added.anonymous_records += 1;
+                format!("{} <anonymous {}>", keyword, self.added.anonymous_records)
+            }
+        };
+        if !self.eat_punct('{') {
+            if !self.added.records.contains_key(&tag) {
+                self.added.records.insert(tag.clone(), None);
+            }
+            return Ok(CType::Record(tag));
+        }
+
+        let mut fields = Vec::new();
+        while !self.eat_punct('}') {
+            if self.peek().is_none() {
+                return Err(format!("unterminated body of '{}'", tag));
+            }
+            let base = self.parse_specifiers()?;
+            if self.eat_punct(';') {
+                // Members of an anonymous struct or union are accessed directly
+                if let CType::Record(inner) = &base {
+                    if let Some(inner_fields) = self.record_fields(inner) {
+                        fields.extend(inner_fields.iter().cloned());
+                    }
+                }
+                continue;
+            }
+            loop {
+                let (name, ctype) = self.parse_declarator(base.clone())?;
+                let name = name.ok_or_else(|| format!("expected a field name in '{}'", tag))?;
+                if self.eat_punct(':') {
+                    // Bit-field width
+                    self.skip_until(&[',', ';']);
+                }
+                fields.push(CField { name, ctype });
+                if !self.eat_punct(',') {
+                    break;
+                }
+            }
+            self.expect_punct(';')?;
+        }
+        self.added.records.insert(tag.clone(), Some(fields));
+        Ok(CType::Record(tag))
+    }
+
+    fn parse_enum(&mut self) -> Result<(), String> {
+        if self.peek_ident().is_some() {
+            self.pos += 1;
+        }
+        if !self.eat_punct('{') {
+            return Ok(());
+        }
+        while !self.eat_punct('}') {
+            let Some(name) = self.peek_ident().map(str::to_string) else {
+                return Err("expected an enumerator name".to_string());
+            };
+            self.pos += 1;
+            if self.eat_punct('=') {
+                self.skip_until(&[',', '}']);
+            }
+            self.added.constants.push(name);
+            if !self.eat_punct(',') {
+                self.expect_punct('}')?;
+                break;
+            }
+        }
+        Ok(())
+    }
+
+    /// Parse a declarator around `base`: pointers, the name (optional in
+    /// parameters and type names), a parameter list and array suffixes
+    fn parse_declarator(&mut self, base: CType) -> Result<(Option<String>, CType), String> {
+        let mut ctype = base;
+        while self.eat_punct('*') {
+            ctype = CType::Pointer(Box::new(ctype));
+            while self
+                .peek_ident()
+                .is_some_and(|word| IGNORED_SPECIFIERS.contains(&word))
+            {
+                self.pos += 1;
+            }
+        }
+
+        // Function pointer: `(*name)(params)`
+        if self.peek() == Some(&Token::Punct('('))
+            && self.tokens.get(self.pos + 1) == Some(&Token::Punct('*'))
+        {
+            self.pos += 1;
+            while self.eat_punct('*') {}
+            let name = self.peek_ident().map(str::to_string);
+            if name.is_some() {
+                self.pos += 1;
+            }
+            self.expect_punct(')')?;
+            self.expect_punct('(')?;
+            let (params, is_variadic) = self.parse_parameters()?;
+            let function = CType::Function(Box::new(CFunction {
+                params,
+                return_type: ctype,
+                is_variadic,
+            }));
+            return Ok((name, CType::Pointer(Box::new(function))));
+        }
+
+        let name = match self.peek_ident() {
+            Some(word) if !IGNORED_SPECIFIERS.contains(&word) => {
+                let name = word.to_string();
+                self.pos += 1;
+                Some(name)
+            }
+            _ => None,
+        };
+
+        if self.eat_punct('(') {
+            let (params, is_variadic) = self.parse_parameters()?;
+            ctype = CType::Function(Box::new(CFunction {
+                params,
+                return_type: ctype,
+                is_variadic,
+            }));
+        }
+        while self.eat_punct('[') {
+            self.skip_until(&[']']);
+            self.expect_punct(']')?;
+            ctype = CType::Array(Box::new(ctype));
+        }
+        // Trailing attributes and symbol renames: `__attribute__((...))`, `asm("sym")`
+        while let Some(word) = self.peek_ident() {
+            if !matches!(word, "__attribute__" | "asm" | "__asm__") {
+                break;
+            }
+            self.pos += 1;
+            self.skip_parenthesized()?;
+        }
+        Ok((name, ctype))
+    }
+
+    /// Parse a parameter list after its opening parenthesis
+    fn parse_parameters(&mut self) -> Result<(Vec<CType>, bool), String> {
+        let mut params = Vec::new();
+        if self.eat_punct(')') {
+            return Ok((params, false));
+        }
+        if self.peek_ident() == Some("void")
+            && self.tokens.get(self.pos + 1) == Some(&Token::Punct(')'))
+        {
+            self.pos += 2;
+            return Ok((params, false));
+        }
+        loop {
+            if self.peek() == Some(&Token::Ellipsis) {
+                self.pos += 1;
+                self.expect_punct(')')?;
+                return Ok((params, true));
+            }
+            let base = self.parse_specifiers()?;
+            let (_, ctype) = self.parse_declarator(base)?;
+            // Array parameters decay to pointers
+            params.push(match ctype {
+                CType::Array(element) => CType::Pointer(element),
+                other => other,
+            });
+            if !self.eat_punct(',') {
+                self.expect_punct(')')?;
+                return Ok((params, false));
+            }
+        }
+    }
+
+    /// Skip tokens up to (not including) one of `stops` at nesting depth zero
+    fn skip_until(&mut self, stops: &[char]) {
+        let mut depth = 0usize;
+        while let Some(token) = self.peek() {
+            match token {
+                Token::Punct(c) if depth == 0 && stops.contains(c) => return,
+                Token::Punct('(' | '[' | '{') => depth += 1,
+                Token::Punct(')' | ']' | '}') => {
+                    if depth == 0 {
+                        return;
+                    }
+                    depth -= 1;
+                }
+                _ => {}
+            }
+            self.pos += 1;
+        }
+    }
+
+    fn skip_parenthesized(&mut self) -> Result<(), String> {
+        self.expect_punct('(')?;
+        self.skip_until(&[')']);
+        self.expect_punct(')')
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_declare_structs_typedefs_and_functions() {
+        let mut declarations = CDeclarations::new();
+        declarations
+            .declare(
+                r#"
+                typedef struct point { double x, y; } point_t;
+                typedef struct lua_State lua_State;
+                struct node { int value; struct node *next; };
+                enum color { RED, GREEN = 2, BLUE };
+                int printf(const char *fmt, ...);
+                size_t strlen(const char *s);
+                double distance(const point_t *a, const point_t *b);
+                void qsort(void *base, size_t n, size_t size, int (*cmp)(const void *, const void *));
+                "#,
+            )
+            .unwrap();
+
+        let fields = declarations.record_fields("struct point").unwrap();
+        assert_eq!(fields.len(), 2);
+        assert_eq!(fields[1].name, "y");
+        assert!(declarations.record_fields("struct lua_State").is_none());
+        assert_eq!(
+            declarations.parse_type_name("point_t").unwrap(),
+            CType::Record("struct point".to_string())
+        );
+
+        let printf = &declarations.functions["printf"];
+        assert!(printf.is_variadic);
+        assert_eq!(printf.params, vec![CType::Pointer(Box::new(CType::Char))]);
+        assert_eq!(declarations.functions["qsort"].params.len(), 4);
+        assert_eq!(declarations.constants, vec!["RED", "GREEN", "BLUE"]);
+    }
+
+    #[test]
+    fn test_parse_type_names() {
+        let mut declarations = CDeclarations::new();
+        declarations.declare("struct vec { float x; };").unwrap();
+        assert_eq!(
+            declarations.parse_type_name("struct vec *").unwrap(),
+            CType::Pointer(Box::new(CType::Record("struct vec".to_string())))
+        );
+        assert_eq!(
+            declarations.parse_type_name("uint8_t[?]").unwrap(),
+            CType::Array(Box::new(CType::Number))
+        );
+        assert!(declarations
+            .undefined_record(&declarations.parse_type_name("struct missing").unwrap())
+            .is_some());
+    }
+
+    #[test]
+    fn test_invalid_declaration_registers_nothing() {
+        let mut declarations = CDeclarations::new();
+        assert!(declarations
+            .declare("int ok(void); struct broken {")
+            .is_err());
+        assert!(declarations.functions.is_empty());
+    }
+
+    #[test]
+    fn test_self_referential_struct_converts() {
+        let arena = bumpalo::Bump::new();
+        let interner = StringInterner::new();
+        let mut declarations = CDeclarations::new();
+        declarations
+            .declare("struct node { int value; struct node *next; };")
+            .unwrap();
+        let typ = declarations.cdata_type(
+            &CType::Record("struct node".to_string()),
+            &arena,
+            &interner,
+            Span::default(),
+        );
+        let TypeKind::Object(object) = &typ.kind else {
+            panic!("expected an object type");
+        };
+        assert_eq!(object.members.len(), 2);
+    }
+}
@@ -1,5 +1,7 @@
 use luanext_parser::ast::statement::TypeParameter;
-use luanext_parser::ast::types::{Type, TypeKind, TypeReference};
+use luanext_parser::ast::types::{
+    ConditionalType, TemplateLiteralType, TemplateLiteralTypePart, Type, TypeKind, TypeReference,
+};
 use luanext_parser::string_interner::StringId;
 use rustc_hash::FxHashMap;
 
@@ -30,6 +32,25 @@ pub fn instantiate_type<'arena>(
     substitute_type(arena, typ, &substitutions)
 }
 
+/// Name of the polymorphic self type usable in class and interface members
+pub const SELF_TYPE_NAME: &str = "Self";
+
+/// Replaces the polymorphic `Self` type with the concrete receiver type.
+///
+/// Used at call sites so that fluent methods declared as returning `Self`
+/// keep the receiver's (possibly derived) type through a chain of calls.
+pub fn substitute_self_type<'arena>(
+    arena: &'arena bumpalo::Bump,
+    typ: &Type<'arena>,
+    self_name: StringId,
+    receiver: &Type<'arena>,
+) -> Type<'arena> {
+    let mut substitutions: FxHashMap<StringId, Type<'arena>> = FxHashMap::default();
+    substitutions.insert(self_name, receiver.clone());
+
+    substitute_type(arena, typ, &substitutions).unwrap_or_else(|_| typ.clone())
+}
+
 /// Recursively substitute type parameters in a type
 fn substitute_type<'arena>(
     arena: &'arena bumpalo::Bump,
@@ -233,7 +254,60 @@ fn substitute_type<'arena>(
             ))
         }
 
-        // Conditional types, mapped types, etc. would need similar handling
+        // Indexed access type: substitute both the object and the key
+        TypeKind::IndexAccess(object, index) => {
+            let substituted_object = substitute_type(arena, object, substitutions)?;
+            let substituted_index = substitute_type(arena, index, substitutions)?;
+            Ok(Type::new(
+                TypeKind::IndexAccess(
+                    arena.alloc(substituted_object),
+                    arena.alloc(substituted_index),
+                ),
+                typ.span,
+            ))
+        }
+
+        // Conditional type: substitute every branch so that instantiated
+        // aliases can be evaluated once the check type is known
+        TypeKind::Conditional(conditional) => {
+            let check_type = substitute_type(arena, conditional.check_type, substitutions)?;
+            let extends_type = substitute_type(arena, conditional.extends_type, substitutions)?;
+            let true_type = substitute_type(arena, conditional.true_type, substitutions)?;
+            let false_type = substitute_type(arena, conditional.false_type, substitutions)?;
+            Ok(Type::new(
+                TypeKind::Conditional(ConditionalType {
+                    check_type: arena.alloc(check_type),
+                    extends_type: arena.alloc(extends_type),
+                    true_type: arena.alloc(true_type),
+                    false_type: arena.alloc(false_type),
+                    span: conditional.span,
+                }),
+                typ.span,
+            ))
+        }
+
+        // Template literal type: substitute the interpolated types
+        TypeKind::TemplateLiteral(template) => {
+            let parts = template
+                .parts
+                .iter()
+                .map(|part| match part {
+                    TemplateLiteralTypePart::Type(t) => Ok(TemplateLiteralTypePart::Type(
+                        arena.alloc(substitute_type(arena, t, substitutions)?),
+                    )),
+                    other => Ok(other.clone()),
+                })
+                .collect::<Result<Vec<_>, String>>()?;
+            Ok(Type::new(
+                TypeKind::TemplateLiteral(TemplateLiteralType {
+                    parts: arena.alloc_slice_fill_iter(parts),
+                    span: template.span,
+                }),
+                typ.span,
+            ))
+        }
+
+        // Mapped types, etc. would need similar handling
         // For now, just clone types that don't contain type parameters
         _ => Ok(typ.clone()),
     }
@@ -1102,6 +1176,47 @@ mod tests {
         ));
     }
 
+    #[test]
+    fn test_substitute_self_type_in_array() {
+        let arena = Bump::new();
+        let span = Span::new(0, 0, 0, 0);
+        let interner = luanext_parser::string_interner::StringInterner::new();
+        let self_id = interner.intern(SELF_TYPE_NAME);
+        let dog_id = interner.intern("Dog");
+
+        // Self[]
+        let self_array = Type::new(
+            TypeKind::Array(&*arena.alloc(Type::new(
+                TypeKind::Reference(TypeReference {
+                    name: Spanned::new(self_id, span),
+                    type_arguments: None,
+                    span,
+                }),
+                span,
+            ))),
+            span,
+        );
+
+        let dog_type = Type::new(
+            TypeKind::Reference(TypeReference {
+                name: Spanned::new(dog_id, span),
+                type_arguments: None,
+                span,
+            }),
+            span,
+        );
+
+        let result = substitute_self_type(&arena, &self_array, self_id, &dog_type);
+
+        match &result.kind {
+            TypeKind::Array(elem) => match &elem.kind {
+                TypeKind::Reference(type_ref) => assert_eq!(type_ref.name.node, dog_id),
+                _ => panic!("Expected Dog reference"),
+            },
+            _ => panic!("Expected array type"),
+        }
+    }
+
     #[test]
     fn test_instantiate_array_type() {
         let arena = Bump::new();
@@ -1648,6 +1763,62 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_instantiate_index_access_type() {
+        let arena = Bump::new();
+        let span = Span::new(0, 0, 0, 0);
+        let interner = luanext_parser::string_interner::StringInterner::new();
+        let t_id = interner.intern("T");
+        let user_id = interner.intern("User");
+
+        let type_param = TypeParameter {
+            name: Spanned::new(t_id, span),
+            constraint: None,
+            default: None,
+            span,
+        };
+
+        // T["name"]
+        let index_access = Type::new(
+            TypeKind::IndexAccess(
+                arena.alloc(Type::new(
+                    TypeKind::Reference(TypeReference {
+                        name: Spanned::new(t_id, span),
+                        type_arguments: None,
+                        span,
+                    }),
+                    span,
+                )),
+                arena.alloc(Type::new(
+                    TypeKind::Literal(luanext_parser::ast::expression::Literal::String(
+                        "name".to_string(),
+                    )),
+                    span,
+                )),
+            ),
+            span,
+        );
+
+        let user_type = Type::new(
+            TypeKind::Reference(TypeReference {
+                name: Spanned::new(user_id, span),
+                type_arguments: None,
+                span,
+            }),
+            span,
+        );
+
+        let result = instantiate_type(&arena, &index_access, &[type_param], &[user_type]).unwrap();
+
+        match &result.kind {
+            TypeKind::IndexAccess(object, index) => {
+                assert!(matches!(&object.kind, TypeKind::Reference(r) if r.name.node == user_id));
+                assert!(matches!(index.kind, TypeKind::Literal(_)));
+            }
+            _ => panic!("Expected indexed access type"),
+        }
+    }
+
     #[test]
     fn test_instantiate_nested_generic() {
         let arena = Bump::new();
@@ -1,2 +1,5 @@
+pub mod coroutine;
+pub mod ffi;
 pub mod generics;
+pub mod reflection;
 pub mod utility_types;
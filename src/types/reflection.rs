@@ -0,0 +1,298 @@
+//! Typed reflection
+//!
+//! The `Reflect` namespace of `reflection.d.luax` reads the type information
+//! the code generator embeds for each class and interface. The checker types
+//! `Reflect.getFields(obj)` and friends from the static type of `obj`, so a
+//! field descriptor's `name` is one of the field names of that type, and
+//! narrows the first argument of `Reflect.isInstance(x, Cls)` like
+//! `x instanceof Cls`.
+//!
+//! [`RuntimeTypeInfo`] is the embedded information for one type, collected
+//! from the checker's access control and type environment. It is written as a
+//! Lua table constructor by [`RuntimeTypeInfo::to_lua`], whose shape is the
+//! `ReflectType` interface of the declaration file.
+
+use crate::core::type_environment::TypeEnvironment;
+use crate::utils::type_formatter::format_type_for_error;
+use crate::visitors::{AccessControl, AccessControlVisitor, ClassMemberInfo, ClassMemberKind};
+use luanext_parser::ast::types::{ObjectTypeMember, TypeKind};
+use luanext_parser::string_interner::StringInterner;
+
+/// Name of the reflection namespace
+pub const REFLECT_NAMESPACE: &str = "Reflect";
+
+/// Whether a class or an interface
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RuntimeTypeKind {
+    Class,
+    Interface,
+}
+
+impl RuntimeTypeKind {
+    /// The `kind` string the runtime reports
+    pub fn as_str(self) -> &'static str {
+        match self {
+            RuntimeTypeKind::Class => "class",
+            RuntimeTypeKind::Interface => "interface",
+        }
+    }
+}
+
+/// A field or method of a type, as the runtime reports it
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RuntimeMemberInfo {
+    pub name: String,
+    /// The declared type, as written in diagnostics
+    pub type_name: String,
+    pub is_static: bool,
+}
+
+/// The runtime type information of a class or interface. Only the type's
+/// own members, static ones included, are listed; inherited ones are found
+/// through `parent` and `interfaces`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RuntimeTypeInfo {
+    pub name: String,
+    pub kind: RuntimeTypeKind,
+    /// The parent class
+    pub parent: Option<String>,
+    /// The interfaces a class implements, or the interfaces an interface
+    /// extends
+    pub interfaces: Vec<String>,
+    pub fields: Vec<RuntimeMemberInfo>,
+    pub methods: Vec<RuntimeMemberInfo>,
+}
+
+impl RuntimeTypeInfo {
+    /// Collect the information for the class or interface `name`, or `None`
+    /// if no such type was declared
+    pub fn collect(
+        name: &str,
+        type_env: &TypeEnvironment<'_>,
+        access_control: &AccessControl<'_>,
+        interner: &StringInterner,
+    ) -> Option<Self> {
+        let members = access_control.get_class_members(name)?;
+        let kind = if type_env.get_interface(name).is_some() {
+            RuntimeTypeKind::Interface
+        } else {
+            RuntimeTypeKind::Class
+        };
+
+        let mut fields = Vec::new();
+        let mut methods = Vec::new();
+        for member in members {
+            let (list, typ) = match &member.kind {
+                ClassMemberKind::Property { type_annotation } => (
+                    &mut fields,
+                    format_type_for_error(type_annotation, interner),
+                ),
+                ClassMemberKind::Method { .. } => (&mut methods, "function".to_string()),
+                _ => continue,
+            };
+            // Overloads are one method at runtime
+            if list
+                .iter()
+                .any(|m: &RuntimeMemberInfo| m.name == member.name)
+            {
+                continue;
+            }
+            list.push(RuntimeMemberInfo {
+                name: member.name.clone(),
+                type_name: typ,
+                is_static: member._is_static,
+            });
+        }
+
+        Some(Self {
+            name: name.to_string(),
+            kind,
+            parent: access_control.get_parent_class(name),
+            interfaces: access_control
+                .get_class_implements(name)
+                .cloned()
+                .unwrap_or_default(),
+            fields,
+            methods,
+        })
+    }
+
+    /// The information as a Lua table constructor
+    pub fn to_lua(&self) -> String {
+        let members = |members: &[RuntimeMemberInfo]| {
+            let entries: Vec<String> = members
+                .iter()
+                .map(|member| {
+                    format!(
+                        "{{ name = {}, typeName = {}, isStatic = {} }}",
+                        lua_string(&member.name),
+                        lua_string(&member.type_name),
+                        member.is_static
+                    )
+                })
+                .collect();
+            format!("{{ {} }}", entries.join(", "))
+        };
+        let interfaces: Vec<String> = self.interfaces.iter().map(|i| lua_string(i)).collect();
+        format!(
+            "{{ name = {}, kind = {}, parent = {}, interfaces = {{ {} }}, fields = {}, methods = {} }}",
+            lua_string(&self.name),
+            lua_string(self.kind.as_str()),
+            self.parent
+                .as_deref()
+                .map_or_else(|| "nil".to_string(), lua_string),
+            interfaces.join(", "),
+            members(&self.fields),
+            members(&self.methods)
+        )
+    }
+}
+
+/// The names of the fields (or methods) of the class or interface `name`,
+/// static ones included as in [`RuntimeTypeInfo`], and inherited ones unless
+/// `own_only`, in declaration order with ancestors first
+pub(crate) fn member_names(
+    name: &str,
+    methods: bool,
+    own_only: bool,
+    type_env: &TypeEnvironment<'_>,
+    access_control: &AccessControl<'_>,
+    interner: &StringInterner,
+) -> Option<Vec<String>> {
+    let wanted = |member: &ClassMemberInfo<'_>| match member.kind {
+        ClassMemberKind::Property { .. } => !methods,
+        ClassMemberKind::Method { .. } => methods,
+        _ => false,
+    };
+
+    // An interface's type already contains the members it extends
+    if let (false, Some(iface)) = (own_only, type_env.get_interface(name)) {
+        let TypeKind::Object(obj) = &iface.kind else {
+            return None;
+        };
+        let mut names: Vec<String> = Vec::new();
+        for member in obj.members.iter() {
+            let member_name = match member {
+                ObjectTypeMember::Property(prop) if !methods => interner.resolve(prop.name.node),
+                ObjectTypeMember::Method(method) if methods => interner.resolve(method.name.node),
+                _ => continue,
+            };
+            if !names.contains(&member_name) {
+                names.push(member_name);
+            }
+        }
+        return Some(names);
+    }
+
+    let mut chain = vec![name.to_string()];
+    while !own_only {
+        match access_control.get_parent_class(chain.last()?) {
+            Some(parent) if !chain.contains(&parent) => chain.push(parent),
+            _ => break,
+        }
+    }
+
+    let mut names: Vec<String> = Vec::new();
+    for class in chain.iter().rev() {
+        for member in access_control.get_class_members(class)? {
+            if wanted(member) && !names.contains(&member.name) {
+                names.push(member.name.clone());
+            }
+        }
+    }
+    Some(names)
+}
+
+/// A Lua string literal for `value`
+fn lua_string(value: &str) -> String {
+    let mut literal = String::with_capacity(value.len() + 2);
+    literal.push('"');
+    for c in value.chars() {
+        match c {
+            '"' => literal.push_str("\\\""),
+            '\\' => literal.push_str("\\\\"),
+            '\n' => literal.push_str("\\n"),
+            c if c.is_ascii_control() => literal.push_str(&format!("\\{:03}", c as u32)),
+            c => literal.push(c),
+        }
+    }
+    literal.push('"');
+    literal
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cli::diagnostics::CollectingDiagnosticHandler;
+    use crate::TypeChecker;
+    use bumpalo::Bump;
+    use luanext_parser::{Lexer, Parser};
+    use std::sync::Arc;
+
+    #[test]
+    fn test_runtime_type_info_lists_own_members() {
+        let source = r#"
+            interface Named {
+                name: string
+            }
+
+            interface Titled extends Named {
+                title: string
+            }
+
+            class Entity {
+                id: number
+            }
+
+            class User extends Entity implements Named {
+                name: string
+                static count: number
+
+                greet(): string
+                    return "hi " .. self.name
+                end
+            }
+        "#;
+        let arena = Bump::new();
+        let handler = Arc::new(CollectingDiagnosticHandler::new());
+        let (interner, common) = StringInterner::new_with_common_identifiers();
+        let mut lexer = Lexer::new(source, handler.clone(), &interner);
+        let tokens = lexer.tokenize().expect("Lexing failed");
+        let mut parser = Parser::new(tokens, handler.clone(), &interner, &common, &arena);
+        let program = parser.parse().expect("Parsing failed");
+        let mut checker = TypeChecker::new(handler, &interner, &common, &arena);
+        checker.check_program(&program).unwrap();
+
+        let user = checker.runtime_type_info("User").unwrap();
+        assert_eq!(user.kind, RuntimeTypeKind::Class);
+        assert_eq!(user.parent.as_deref(), Some("Entity"));
+        assert_eq!(user.interfaces, vec!["Named".to_string()]);
+        let fields: Vec<_> = user.fields.iter().map(|f| f.name.as_str()).collect();
+        assert_eq!(fields, vec!["name", "count"]);
+        assert!(user.fields[1].is_static);
+        assert_eq!(user.methods[0].name, "greet");
+        assert_eq!(
+            user.to_lua(),
+            "{ name = \"User\", kind = \"class\", parent = \"Entity\", interfaces = { \"Named\" }, \
+             fields = { { name = \"name\", typeName = \"string\", isStatic = false }, \
+             { name = \"count\", typeName = \"number\", isStatic = true } }, \
+             methods = { { name = \"greet\", typeName = \"function\", isStatic = false } } }"
+        );
+
+        let named = checker.runtime_type_info("Named").unwrap();
+        assert_eq!(named.kind, RuntimeTypeKind::Interface);
+        assert_eq!(named.parent, None);
+        let titled = checker.runtime_type_info("Titled").unwrap();
+        assert_eq!(titled.interfaces, vec!["Named".to_string()]);
+        assert_eq!(titled.fields[0].name, "title");
+        assert!(checker.runtime_type_info("Missing").is_none());
+    }
+
+    #[test]
+    fn test_lua_string_escapes() {
+        assert_eq!(lua_string("a\"b\\c\n"), "\"a\\\"b\\\\c\\n\"");
+        assert_eq!(lua_string("\t"), "\"\\009\"");
+        // A digit after the escape is not read as part of it
+        assert_eq!(lua_string("\u{1}2"), "\"\\0012\"");
+    }
+}
@@ -32,6 +32,12 @@ pub fn apply_utility_type<'arena>(
         "Nilable" => nilable(arena, type_args, span),
         "ReturnType" => return_type(type_args, span),
         "Parameters" => parameters(arena, type_args, span),
+        "InstanceType" => instance_type(type_args, span),
+        "ThisType" => this_type(type_args, span),
+        "Uppercase" | "Lowercase" | "Capitalize" | "Uncapitalize" => {
+            string_case(arena, name, type_args, span)
+        }
+        "Brand" => brand(arena, type_args, span, interner),
         _ => Err(format!("Unknown utility type: {}", name)),
     }
 }
@@ -179,6 +185,21 @@ fn readonly<'arena>(
             // but for now, just return the same array (Lua doesn't enforce readonly)
             Ok(typ.clone())
         }
+        TypeKind::Intersection(members) => {
+            // Make the object members readonly; other members (including brand
+            // markers) are kept unchanged
+            let new_members = members
+                .iter()
+                .map(|member| match member.kind {
+                    TypeKind::Object(_) => readonly(arena, std::slice::from_ref(member), span),
+                    _ => Ok(member.clone()),
+                })
+                .collect::<Result<Vec<_>, String>>()?;
+            Ok(Type::new(
+                TypeKind::Intersection(arena.alloc_slice_fill_iter(new_members)),
+                span,
+            ))
+        }
         _ => Err("Readonly<T> requires T to be an object or array type".to_string()),
     }
 }
@@ -594,6 +615,393 @@ fn parameters<'arena>(
     }
 }
 
+/// InstanceType<C> - The instance type produced by a class or constructor function
+fn instance_type<'arena>(type_args: &[Type<'arena>], _span: Span) -> Result<Type<'arena>, String> {
+    if type_args.len() != 1 {
+        return Err(format!(
+            "InstanceType<C> expects 1 type argument, got {}",
+            type_args.len()
+        ));
+    }
+
+    let typ = &type_args[0];
+
+    match &typ.kind {
+        // `typeof(Point)` is the class reference, which is also its instance type
+        TypeKind::Reference(_) => Ok(typ.clone()),
+        TypeKind::Function(ref func) => Ok((*func.return_type).clone()),
+        _ => Err("InstanceType<C> requires C to be a class or constructor type".to_string()),
+    }
+}
+
+/// ConstructorParameters<C> - Extracts constructor parameter types as a tuple
+pub fn constructor_parameters<'arena>(
+    arena: &'arena bumpalo::Bump,
+    type_args: &[Type<'arena>],
+    span: Span,
+    type_env: &TypeEnvironment<'arena>,
+    interner: &StringInterner,
+) -> Result<Type<'arena>, String> {
+    if type_args.len() != 1 {
+        return Err(format!(
+            "ConstructorParameters<C> expects 1 type argument, got {}",
+            type_args.len()
+        ));
+    }
+
+    let typ = &type_args[0];
+
+    let param_types: Vec<Type<'arena>> = match &typ.kind {
+        TypeKind::Reference(type_ref) => {
+            let class_name = interner.resolve(type_ref.name.node);
+            if let Some(params) = type_env.get_constructor_params(&class_name) {
+                params
+                    .iter()
+                    .map(|p| {
+                        p.type_annotation.clone().unwrap_or_else(|| {
+                            Type::new(TypeKind::Primitive(PrimitiveType::Unknown), p.span)
+                        })
+                    })
+                    .collect()
+            } else if let Some(params) = type_env.get_class_constructor(&class_name) {
+                params.iter().map(|p| p.type_annotation.clone()).collect()
+            } else {
+                // A class without a constructor takes no arguments
+                Vec::new()
+            }
+        }
+        TypeKind::Function(ref func) => func
+            .parameters
+            .iter()
+            .map(|p| {
+                p.type_annotation.clone().unwrap_or_else(|| {
+                    Type::new(TypeKind::Primitive(PrimitiveType::Unknown), p.span)
+                })
+            })
+            .collect(),
+        _ => {
+            return Err(
+                "ConstructorParameters<C> requires C to be a class or constructor type".to_string(),
+            )
+        }
+    };
+
+    Ok(Type::new(
+        TypeKind::Tuple(arena.alloc_slice_fill_iter(param_types)),
+        span,
+    ))
+}
+
+/// ThisType<T> - A marker for the type of `self` in object literal methods.
+///
+/// Evaluates to an empty object type; the checker reads the type argument
+/// from the annotation when inferring the object literal.
+fn this_type<'arena>(type_args: &[Type<'arena>], span: Span) -> Result<Type<'arena>, String> {
+    if type_args.len() != 1 {
+        return Err(format!(
+            "ThisType<T> expects 1 type argument, got {}",
+            type_args.len()
+        ));
+    }
+
+    Ok(Type::new(
+        TypeKind::Object(ObjectType { members: &[], span }),
+        span,
+    ))
+}
+
+/// Prefix of the synthetic reference that marks a branded type. The space keeps
+/// it from colliding with any name a user can write.
+const BRAND_MARKER_PREFIX: &str = "brand ";
+
+/// Whether `name` is the name of a brand marker
+pub fn is_brand_marker(name: &str) -> bool {
+    name.starts_with(BRAND_MARKER_PREFIX)
+}
+
+/// The marker of a brand named `tag`.
+///
+/// A brand declared by a type alias passes the alias's `identity` (its
+/// declaring module and name), so two declarations with the same tag stay
+/// distinct. The tag then rides along as the marker's type argument for
+/// display. An inline `Brand<T, "Tag">` has no identity and is keyed by its tag.
+pub fn brand_marker<'arena>(
+    arena: &'arena bumpalo::Bump,
+    tag: &str,
+    identity: Option<&str>,
+    span: Span,
+    interner: &StringInterner,
+) -> Type<'arena> {
+    let (name, type_arguments) = match identity {
+        Some(identity) => (
+            identity,
+            Some(&*arena.alloc_slice_fill_iter([Type::new(
+                TypeKind::Literal(Literal::String(tag.to_string())),
+                span,
+            )])),
+        ),
+        None => (tag, None),
+    };
+    Type::new(
+        TypeKind::Reference(luanext_parser::ast::types::TypeReference {
+            name: luanext_parser::ast::Spanned::new(
+                interner.intern(&format!("{}{}", BRAND_MARKER_PREFIX, name)),
+                span,
+            ),
+            type_arguments,
+            span,
+        }),
+        span,
+    )
+}
+
+/// Brand<T, Name> - A nominal variant of T.
+///
+/// Evaluates to `T & <brand Name>`, where the marker is a reference no other
+/// type resolves to. Values of T are therefore not assignable to the brand
+/// (they lack the marker), while branded values remain usable as T. Because
+/// the marker is part of the type's structure it survives generics, utility
+/// types and module imports. Name may also be a marker built by
+/// [`brand_marker`], which is how type aliases brand with their identity.
+fn brand<'arena>(
+    arena: &'arena bumpalo::Bump,
+    type_args: &[Type<'arena>],
+    span: Span,
+    interner: &StringInterner,
+) -> Result<Type<'arena>, String> {
+    if type_args.len() != 2 {
+        return Err(format!(
+            "Brand<T, Name> expects 2 type arguments, got {}",
+            type_args.len()
+        ));
+    }
+
+    let marker = match &type_args[1].kind {
+        TypeKind::Literal(Literal::String(tag)) => brand_marker(arena, tag, None, span, interner),
+        TypeKind::Reference(type_ref) if is_brand_marker(&interner.resolve(type_ref.name.node)) => {
+            type_args[1].clone()
+        }
+        _ => {
+            return Err("Brand<T, Name> requires Name to be a string literal type".to_string());
+        }
+    };
+
+    // Re-branding a branded type keeps its existing markers
+    let mut members = match &type_args[0].kind {
+        TypeKind::Intersection(existing) => existing.to_vec(),
+        _ => vec![type_args[0].clone()],
+    };
+    members.push(marker);
+
+    Ok(Type::new(
+        TypeKind::Intersection(arena.alloc_slice_fill_iter(members)),
+        span,
+    ))
+}
+
+/// The name of a branded type, if `typ` is one
+pub fn brand_tag(typ: &Type<'_>, interner: &StringInterner) -> Option<String> {
+    let TypeKind::Intersection(members) = &typ.kind else {
+        return None;
+    };
+    members.iter().rev().find_map(|member| match &member.kind {
+        TypeKind::Reference(type_ref) => {
+            let name = interner.resolve(type_ref.name.node);
+            let inline_tag = name.strip_prefix(BRAND_MARKER_PREFIX)?;
+            match type_ref.type_arguments.and_then(|args| args.first()) {
+                Some(Type {
+                    kind: TypeKind::Literal(Literal::String(tag)),
+                    ..
+                }) => Some(tag.clone()),
+                _ => Some(inline_tag.to_string()),
+            }
+        }
+        _ => None,
+    })
+}
+
+/// The underlying type of a branded type, with its brand markers removed
+pub fn brand_base<'arena>(
+    arena: &'arena bumpalo::Bump,
+    typ: &Type<'arena>,
+    interner: &StringInterner,
+) -> Option<Type<'arena>> {
+    brand_tag(typ, interner)?;
+    let TypeKind::Intersection(members) = &typ.kind else {
+        return None;
+    };
+
+    let mut base: Vec<Type<'arena>> = members
+        .iter()
+        .filter(|member| {
+            !matches!(&member.kind, TypeKind::Reference(type_ref)
+                if is_brand_marker(&interner.resolve(type_ref.name.node)))
+        })
+        .cloned()
+        .collect();
+
+    if base.len() == 1 {
+        base.pop()
+    } else {
+        Some(Type::new(
+            TypeKind::Intersection(arena.alloc_slice_fill_iter(base)),
+            typ.span,
+        ))
+    }
+}
+
+/// Uppercase/Lowercase/Capitalize/Uncapitalize<S> - String-case intrinsics over
+/// string literal and template literal types
+fn string_case<'arena>(
+    arena: &'arena bumpalo::Bump,
+    name: &str,
+    type_args: &[Type<'arena>],
+    span: Span,
+) -> Result<Type<'arena>, String> {
+    if type_args.len() != 1 {
+        return Err(format!(
+            "{}<S> expects 1 type argument, got {}",
+            name,
+            type_args.len()
+        ));
+    }
+
+    apply_string_case(arena, name, &type_args[0])
+}
+
+fn apply_string_case<'arena>(
+    arena: &'arena bumpalo::Bump,
+    name: &str,
+    typ: &Type<'arena>,
+) -> Result<Type<'arena>, String> {
+    use luanext_parser::ast::types::{TemplateLiteralType, TemplateLiteralTypePart};
+
+    match &typ.kind {
+        TypeKind::Literal(Literal::String(value)) => Ok(Type::new(
+            TypeKind::Literal(Literal::String(convert_case(name, value))),
+            typ.span,
+        )),
+        TypeKind::Union(members) => {
+            let converted = members
+                .iter()
+                .map(|member| apply_string_case(arena, name, member))
+                .collect::<Result<Vec<_>, _>>()?;
+            Ok(Type::new(
+                TypeKind::Union(arena.alloc_slice_fill_iter(converted)),
+                typ.span,
+            ))
+        }
+        // Only the literal text of a template can be converted; `Capitalize` and
+        // `Uncapitalize` only touch a leading literal part
+        TypeKind::TemplateLiteral(template) => {
+            let parts = template
+                .parts
+                .iter()
+                .enumerate()
+                .map(|(i, part)| match part {
+                    TemplateLiteralTypePart::String(text)
+                        if i == 0 || matches!(name, "Uppercase" | "Lowercase") =>
+                    {
+                        TemplateLiteralTypePart::String(convert_case(name, text))
+                    }
+                    other => other.clone(),
+                });
+            Ok(Type::new(
+                TypeKind::TemplateLiteral(TemplateLiteralType {
+                    parts: arena.alloc_slice_fill_iter(parts),
+                    span: template.span,
+                }),
+                typ.span,
+            ))
+        }
+        TypeKind::Primitive(PrimitiveType::String) | TypeKind::Primitive(PrimitiveType::Never) => {
+            Ok(typ.clone())
+        }
+        _ => Err(format!(
+            "{}<S> requires S to be a string literal or template literal type",
+            name
+        )),
+    }
+}
+
+fn convert_case(name: &str, value: &str) -> String {
+    let mut chars = value.chars();
+    match name {
+        "Uppercase" => value.to_uppercase(),
+        "Lowercase" => value.to_lowercase(),
+        "Capitalize" => match chars.next() {
+            Some(first) => first.to_uppercase().chain(chars).collect(),
+            None => String::new(),
+        },
+        _ => match chars.next() {
+            Some(first) => first.to_lowercase().chain(chars).collect(),
+            None => String::new(),
+        },
+    }
+}
+
+/// Awaited<T> - The value a coroutine finishes with. Unwraps
+/// `Coroutine<Y, R, T>` to `T`, recursively and distributed over unions.
+/// Generic aliases of a coroutine, such as the stdlib's `Task<T>`, are
+/// expanded first; other types are left as they are.
+pub fn awaited<'arena>(
+    arena: &'arena bumpalo::Bump,
+    type_args: &[Type<'arena>],
+    type_env: &TypeEnvironment<'arena>,
+    interner: &StringInterner,
+) -> Result<Type<'arena>, String> {
+    if type_args.len() != 1 {
+        return Err(format!(
+            "Awaited<T> expects 1 type argument, got {}",
+            type_args.len()
+        ));
+    }
+
+    Ok(unwrap_coroutine(arena, &type_args[0], type_env, interner))
+}
+
+fn unwrap_coroutine<'arena>(
+    arena: &'arena bumpalo::Bump,
+    typ: &Type<'arena>,
+    type_env: &TypeEnvironment<'arena>,
+    interner: &StringInterner,
+) -> Type<'arena> {
+    use crate::types::coroutine::CoroutineSignature;
+
+    match &typ.kind {
+        TypeKind::Reference(type_ref) => {
+            if let Some(signature) = CoroutineSignature::from_type(typ, interner) {
+                return unwrap_coroutine(arena, &signature.return_type, type_env, interner);
+            }
+            let name = interner.resolve(type_ref.name.node);
+            let expanded = type_ref.type_arguments.and_then(|args| {
+                type_env.get_generic_type_alias(&name).and_then(|_| {
+                    type_env
+                        .instantiate_generic_type(arena, &name, args, typ.span)
+                        .ok()
+                })
+            });
+            match expanded {
+                Some(expanded) if CoroutineSignature::from_type(&expanded, interner).is_some() => {
+                    unwrap_coroutine(arena, &expanded, type_env, interner)
+                }
+                _ => typ.clone(),
+            }
+        }
+        TypeKind::Union(members) => {
+            let unwrapped: Vec<_> = members
+                .iter()
+                .map(|member| unwrap_coroutine(arena, member, type_env, interner))
+                .collect();
+            Type::new(
+                TypeKind::Union(arena.alloc_slice_fill_iter(unwrapped)),
+                typ.span,
+            )
+        }
+        _ => typ.clone(),
+    }
+}
+
 /// Evaluate a mapped type: { [K in T]: V }
 /// Transforms the mapped type into a concrete object type
 pub fn evaluate_mapped_type<'arena>(
@@ -879,7 +1287,12 @@ pub fn evaluate_conditional_type<'arena>(
     let mut inferred_types: FxHashMap<String, Type<'arena>> = FxHashMap::default();
     let has_infer = contains_infer(extends_type);
 
-    if has_infer {
+    // Template literal patterns are matched against string literals, so a union
+    // check type is distributed first (below) and each member matched alone
+    let template_pattern = matches!(extends_type.kind, TypeKind::TemplateLiteral(_));
+    let distribute_first = template_pattern && matches!(check_type.kind, TypeKind::Union(_));
+
+    if (has_infer || template_pattern) && !distribute_first {
         // Try to match check_type against extends_type pattern and extract inferred types
         if try_match_and_infer(check_type, extends_type, &mut inferred_types, type_env) {
             // Match succeeded - evaluate true branch with inferred types
@@ -994,6 +1407,10 @@ fn contains_infer<'arena>(typ: &Type<'arena>) -> bool {
             .type_arguments
             .as_ref()
             .is_some_and(|args| args.iter().any(contains_infer)),
+        TypeKind::TemplateLiteral(template) => template.parts.iter().any(|part| match part {
+            luanext_parser::ast::types::TemplateLiteralTypePart::Type(t) => contains_infer(t),
+            luanext_parser::ast::types::TemplateLiteralTypePart::String(_) => false,
+        }),
         _ => false,
     }
 }
@@ -1099,6 +1516,27 @@ fn try_match_and_infer<'arena>(
             }
         }
 
+        // Template literal pattern: `on${infer E}` matches string literals
+        TypeKind::TemplateLiteral(template) => {
+            let resolved_check = resolve_type_reference(check_type, type_env);
+            match &resolved_check.kind {
+                TypeKind::Literal(Literal::String(value)) => {
+                    let mut captures = Vec::new();
+                    if !match_template_parts(value, template.parts, &mut captures) {
+                        return false;
+                    }
+                    for (name, segment) in captures {
+                        inferred.insert(
+                            name,
+                            Type::new(TypeKind::Literal(Literal::String(segment)), check_type.span),
+                        );
+                    }
+                    true
+                }
+                _ => false,
+            }
+        }
+
         // For other patterns, just check type equality
         _ => {
             use crate::core::type_compat::TypeCompatibility;
@@ -1107,6 +1545,85 @@ fn try_match_and_infer<'arena>(
     }
 }
 
+/// Match a string against the parts of a template literal type, collecting the
+/// text captured by each `infer` placeholder.
+///
+/// Like TypeScript, an `infer` followed by literal text captures up to the
+/// first occurrence of that text (backtracking to later occurrences when the
+/// rest fails to match), an `infer` followed by another placeholder captures a
+/// single character, and a trailing `infer` captures the remainder.
+fn match_template_parts(
+    value: &str,
+    parts: &[luanext_parser::ast::types::TemplateLiteralTypePart<'_>],
+    captures: &mut Vec<(String, String)>,
+) -> bool {
+    use luanext_parser::ast::types::TemplateLiteralTypePart;
+
+    let Some((first, rest)) = parts.split_first() else {
+        return value.is_empty();
+    };
+
+    let placeholder = match first {
+        TemplateLiteralTypePart::String(text) => {
+            return value
+                .strip_prefix(text.as_str())
+                .is_some_and(|remaining| match_template_parts(remaining, rest, captures));
+        }
+        TemplateLiteralTypePart::Type(t) => t,
+    };
+
+    // Candidate lengths for the text matched by this placeholder
+    let boundaries: Vec<usize> = value
+        .char_indices()
+        .map(|(i, _)| i)
+        .chain(std::iter::once(value.len()))
+        .collect();
+    let candidates: Vec<usize> = match (&placeholder.kind, rest.first()) {
+        (TypeKind::Infer(_), None) => vec![value.len()],
+        (TypeKind::Infer(_), Some(TemplateLiteralTypePart::String(next))) => {
+            value.match_indices(next.as_str()).map(|(i, _)| i).collect()
+        }
+        (TypeKind::Infer(_), Some(TemplateLiteralTypePart::Type(_))) => {
+            boundaries.get(1).copied().into_iter().collect()
+        }
+        _ => boundaries,
+    };
+
+    for end in candidates {
+        let (segment, remaining) = value.split_at(end);
+        let captured = match &placeholder.kind {
+            TypeKind::Infer(name) => Some((name.node.to_string(), segment.to_string())),
+            _ if segment_matches_type(segment, placeholder) => None,
+            _ => continue,
+        };
+
+        let mark = captures.len();
+        captures.extend(captured);
+        if match_template_parts(remaining, rest, captures) {
+            return true;
+        }
+        captures.truncate(mark);
+    }
+    false
+}
+
+/// Check whether a piece of text is a value of a non-`infer` template placeholder
+fn segment_matches_type(segment: &str, typ: &Type<'_>) -> bool {
+    match &typ.kind {
+        TypeKind::Primitive(PrimitiveType::String) => true,
+        TypeKind::Primitive(PrimitiveType::Number) => segment.parse::<f64>().is_ok(),
+        TypeKind::Primitive(PrimitiveType::Integer) => segment.parse::<i64>().is_ok(),
+        TypeKind::Primitive(PrimitiveType::Boolean) => matches!(segment, "true" | "false"),
+        TypeKind::Literal(Literal::String(s)) => segment == s,
+        TypeKind::Literal(Literal::Number(n)) => segment.parse::<f64>().is_ok_and(|v| v == *n),
+        TypeKind::Literal(Literal::Integer(n)) => segment.parse::<i64>().is_ok_and(|v| v == *n),
+        TypeKind::Literal(Literal::Boolean(b)) => segment == b.to_string(),
+        TypeKind::Union(types) => types.iter().any(|t| segment_matches_type(segment, t)),
+        TypeKind::Parenthesized(inner) => segment_matches_type(segment, inner),
+        _ => false,
+    }
+}
+
 /// Substitute inferred type variables in a type
 fn substitute_inferred_types<'arena>(
     arena: &'arena bumpalo::Bump,
@@ -1267,6 +1784,10 @@ fn is_assignable_to<'arena>(source: &Type<'arena>, target: &Type<'arena>) -> boo
     TypeCompatibility::is_assignable(source, target)
 }
 
+/// Maximum number of string literals a template literal type may expand to
+/// (TypeScript uses 100,000)
+pub const MAX_TEMPLATE_LITERAL_COMBINATIONS: usize = 10000;
+
 /// Evaluate a template literal type to a union of string literals
 /// For example: `Hello ${T}` where T = "World" | "Rust" becomes "Hello World" | "Hello Rust"
 pub fn evaluate_template_literal_type<'arena>(
@@ -1299,21 +1820,22 @@ pub fn evaluate_template_literal_type<'arena>(
         }
     }
 
-    // Generate all combinations
-    let combinations = cartesian_product(&part_expansions);
-
-    // Limit the number of combinations to prevent exponential explosion
-    // TypeScript uses 100,000 as the limit
-    const MAX_TEMPLATE_LITERAL_COMBINATIONS: usize = 10000;
-
-    if combinations.len() > MAX_TEMPLATE_LITERAL_COMBINATIONS {
+    // Limit the number of combinations to prevent exponential explosion. The
+    // size is computed up front so an oversized product is never materialized.
+    let combination_count = part_expansions
+        .iter()
+        .try_fold(1usize, |count, values| count.checked_mul(values.len()))
+        .filter(|count| *count <= MAX_TEMPLATE_LITERAL_COMBINATIONS);
+    let Some(_) = combination_count else {
         return Err(format!(
-            "Template literal type expansion resulted in {} combinations, which exceeds the limit of {}. \
+            "Template literal type expansion exceeds the limit of {} combinations. \
              Consider using a broader type like 'string' instead.",
-            combinations.len(),
             MAX_TEMPLATE_LITERAL_COMBINATIONS
         ));
-    }
+    };
+
+    // Generate all combinations
+    let combinations = cartesian_product(&part_expansions);
 
     // If there's only one combination, return a single string literal
     // Otherwise, return a union of string literals
@@ -2761,4 +3283,339 @@ mod tests {
         // Should deduplicate and return just "x"
         assert!(matches!(result.kind, TypeKind::Literal(Literal::String(_))));
     }
+
+    #[test]
+    fn test_string_case_intrinsics() {
+        let arena = Bump::new();
+        let literal = |s: &str| {
+            Type::new(
+                TypeKind::Literal(Literal::String(s.to_string())),
+                make_span(),
+            )
+        };
+        let expect = |name: &str, input: &str, expected: &str| {
+            let result = string_case(&arena, name, &[literal(input)], make_span()).unwrap();
+            assert!(
+                matches!(&result.kind, TypeKind::Literal(Literal::String(s)) if s == expected),
+                "{}<\"{}\"> should be \"{}\"",
+                name,
+                input,
+                expected
+            );
+        };
+
+        expect("Uppercase", "click", "CLICK");
+        expect("Lowercase", "CLICK", "click");
+        expect("Capitalize", "click", "Click");
+        expect("Uncapitalize", "Click", "click");
+
+        let number_type = Type::new(TypeKind::Primitive(PrimitiveType::Number), make_span());
+        assert!(string_case(&arena, "Uppercase", &[number_type], make_span()).is_err());
+    }
+
+    #[test]
+    fn test_string_case_distributes_over_unions() {
+        let arena = Bump::new();
+        let members = arena.alloc_slice_fill_iter([
+            Type::new(
+                TypeKind::Literal(Literal::String("a".to_string())),
+                make_span(),
+            ),
+            Type::new(
+                TypeKind::Literal(Literal::String("b".to_string())),
+                make_span(),
+            ),
+        ]);
+        let union = Type::new(TypeKind::Union(members), make_span());
+
+        let result = string_case(&arena, "Uppercase", &[union], make_span()).unwrap();
+        match &result.kind {
+            TypeKind::Union(types) => {
+                assert!(
+                    matches!(&types[0].kind, TypeKind::Literal(Literal::String(s)) if s == "A")
+                );
+                assert!(
+                    matches!(&types[1].kind, TypeKind::Literal(Literal::String(s)) if s == "B")
+                );
+            }
+            _ => panic!("Expected union type"),
+        }
+    }
+
+    #[test]
+    fn test_awaited_unwraps_nested_coroutines() {
+        let arena = Bump::new();
+        let interner = luanext_parser::string_interner::StringInterner::new();
+        let coroutine_id = interner.intern(crate::types::coroutine::COROUTINE_TYPE);
+        let task_id = interner.intern("Task");
+        let nil = || Type::new(TypeKind::Primitive(PrimitiveType::Nil), make_span());
+        let reference = |name, args: Vec<Type<'_>>| {
+            Type::new(
+                TypeKind::Reference(luanext_parser::ast::types::TypeReference {
+                    name: luanext_parser::ast::Spanned::new(name, make_span()),
+                    type_arguments: Some(arena.alloc_slice_fill_iter(args)),
+                    span: make_span(),
+                }),
+                make_span(),
+            )
+        };
+
+        let number_type = Type::new(TypeKind::Primitive(PrimitiveType::Number), make_span());
+        let inner = reference(coroutine_id, vec![nil(), nil(), number_type]);
+        let nested = reference(coroutine_id, vec![nil(), nil(), inner]);
+        let type_env = TypeEnvironment::new();
+
+        let result = awaited(&arena, &[nested], &type_env, &interner).unwrap();
+        assert!(matches!(
+            result.kind,
+            TypeKind::Primitive(PrimitiveType::Number)
+        ));
+
+        // A `Task` that is not an alias of a coroutine is left alone
+        let string_type = Type::new(TypeKind::Primitive(PrimitiveType::String), make_span());
+        let task = reference(task_id, vec![string_type]);
+        let result = awaited(&arena, &[task], &type_env, &interner).unwrap();
+        assert!(matches!(result.kind, TypeKind::Reference(_)));
+    }
+
+    #[test]
+    fn test_instance_type_of_constructor_function() {
+        let arena = Bump::new();
+        let string_type = Type::new(TypeKind::Primitive(PrimitiveType::String), make_span());
+        let constructor = Type::new(
+            TypeKind::Function(luanext_parser::ast::types::FunctionType {
+                type_parameters: None,
+                parameters: &[],
+                return_type: arena.alloc(string_type),
+                throws: None,
+                span: make_span(),
+            }),
+            make_span(),
+        );
+
+        let result = instance_type(&[constructor], make_span()).unwrap();
+        assert!(matches!(
+            result.kind,
+            TypeKind::Primitive(PrimitiveType::String)
+        ));
+
+        let number_type = Type::new(TypeKind::Primitive(PrimitiveType::Number), make_span());
+        assert!(instance_type(&[number_type], make_span()).is_err());
+    }
+
+    fn template_parts<'a>(
+        arena: &'a Bump,
+        parts: Vec<luanext_parser::ast::types::TemplateLiteralTypePart<'a>>,
+    ) -> &'a [luanext_parser::ast::types::TemplateLiteralTypePart<'a>] {
+        arena.alloc_slice_fill_iter(parts)
+    }
+
+    #[test]
+    fn test_template_literal_infer_matching() {
+        use luanext_parser::ast::types::TemplateLiteralTypePart as Part;
+        let arena = Bump::new();
+        let interner = luanext_parser::string_interner::StringInterner::new();
+        let infer = |name: &str| {
+            Part::Type(arena.alloc(Type::new(
+                TypeKind::Infer(Ident::new(interner.intern(name), make_span())),
+                make_span(),
+            )))
+        };
+        let head = interner.intern("Head").to_string();
+        let rest = interner.intern("Rest").to_string();
+
+        // `on${infer E}`
+        let on_event = template_parts(&arena, vec![Part::String("on".to_string()), infer("E")]);
+        let mut captures = Vec::new();
+        assert!(match_template_parts("onClick", on_event, &mut captures));
+        assert_eq!(captures[0].1, "Click");
+        assert!(!match_template_parts("click", on_event, &mut Vec::new()));
+
+        // `${infer Head}.${infer Rest}` splits at the first dot
+        let path = template_parts(
+            &arena,
+            vec![infer("Head"), Part::String(".".to_string()), infer("Rest")],
+        );
+        let mut captures = Vec::new();
+        assert!(match_template_parts("a.b.c", path, &mut captures));
+        assert_eq!(
+            captures,
+            vec![(head, "a".to_string()), (rest, "b.c".to_string())]
+        );
+        assert!(!match_template_parts("abc", path, &mut Vec::new()));
+    }
+
+    #[test]
+    fn test_template_literal_matching_backtracks() {
+        use luanext_parser::ast::types::TemplateLiteralTypePart as Part;
+        let arena = Bump::new();
+        let interner = luanext_parser::string_interner::StringInterner::new();
+        let number_type = Type::new(TypeKind::Primitive(PrimitiveType::Number), make_span());
+
+        // `${infer K}:${number}` must skip the first ':' to match "a:b:1"
+        let parts = template_parts(
+            &arena,
+            vec![
+                Part::Type(arena.alloc(Type::new(
+                    TypeKind::Infer(Ident::new(interner.intern("K"), make_span())),
+                    make_span(),
+                ))),
+                Part::String(":".to_string()),
+                Part::Type(arena.alloc(number_type)),
+            ],
+        );
+        let mut captures = Vec::new();
+        assert!(match_template_parts("a:b:1", parts, &mut captures));
+        assert_eq!(captures.len(), 1);
+        assert_eq!(captures[0].1, "a:b");
+        assert!(!match_template_parts("a:b", parts, &mut Vec::new()));
+    }
+
+    #[test]
+    fn test_conditional_infers_from_template_literal() {
+        use luanext_parser::ast::types::{
+            ConditionalType, TemplateLiteralType, TemplateLiteralTypePart as Part,
+        };
+        let arena = Bump::new();
+        let interner = luanext_parser::string_interner::StringInterner::new();
+        let e_id = interner.intern("E");
+        let literal = |s: &str| {
+            Type::new(
+                TypeKind::Literal(Literal::String(s.to_string())),
+                make_span(),
+            )
+        };
+
+        let pattern = Type::new(
+            TypeKind::TemplateLiteral(TemplateLiteralType {
+                parts: template_parts(
+                    &arena,
+                    vec![
+                        Part::String("on".to_string()),
+                        Part::Type(arena.alloc(Type::new(
+                            TypeKind::Infer(Ident::new(e_id, make_span())),
+                            make_span(),
+                        ))),
+                    ],
+                ),
+                span: make_span(),
+            }),
+            make_span(),
+        );
+        let e_ref = Type::new(
+            TypeKind::Reference(luanext_parser::ast::types::TypeReference {
+                name: luanext_parser::ast::Spanned::new(e_id, make_span()),
+                type_arguments: None,
+                span: make_span(),
+            }),
+            make_span(),
+        );
+        let never = Type::new(TypeKind::Primitive(PrimitiveType::Never), make_span());
+
+        // "onClick" | "onHover" | "focus" distributes member by member
+        let check = Type::new(
+            TypeKind::Union(arena.alloc_slice_fill_iter([
+                literal("onClick"),
+                literal("onHover"),
+                literal("focus"),
+            ])),
+            make_span(),
+        );
+        let conditional = ConditionalType {
+            check_type: arena.alloc(check),
+            extends_type: arena.alloc(pattern),
+            true_type: arena.alloc(e_ref),
+            false_type: arena.alloc(never),
+            span: make_span(),
+        };
+
+        let type_env = TypeEnvironment::new();
+        let result = evaluate_conditional_type(&arena, &conditional, &type_env).unwrap();
+        match &result.kind {
+            TypeKind::Union(types) => {
+                assert_eq!(types.len(), 3);
+                assert!(
+                    matches!(&types[0].kind, TypeKind::Literal(Literal::String(s)) if s == "Click")
+                );
+                assert!(
+                    matches!(&types[1].kind, TypeKind::Literal(Literal::String(s)) if s == "Hover")
+                );
+                assert!(matches!(
+                    types[2].kind,
+                    TypeKind::Primitive(PrimitiveType::Never)
+                ));
+            }
+            _ => panic!("Expected union type"),
+        }
+    }
+
+    #[test]
+    fn test_template_literal_expansion_limit() {
+        use luanext_parser::ast::types::{TemplateLiteralType, TemplateLiteralTypePart as Part};
+        let arena = Bump::new();
+        let interner = luanext_parser::string_interner::StringInterner::new();
+
+        // 100 * 100 * 100 combinations exceed the limit before any are generated
+        let digits = Type::new(
+            TypeKind::Union(arena.alloc_slice_fill_iter((0..100).map(|i| {
+                Type::new(
+                    TypeKind::Literal(Literal::String(i.to_string())),
+                    make_span(),
+                )
+            }))),
+            make_span(),
+        );
+        let digits: &Type = arena.alloc(digits);
+        let template = TemplateLiteralType {
+            parts: template_parts(
+                &arena,
+                vec![Part::Type(digits), Part::Type(digits), Part::Type(digits)],
+            ),
+            span: make_span(),
+        };
+
+        let type_env = TypeEnvironment::new();
+        let err =
+            evaluate_template_literal_type(&arena, &template, &type_env, &interner).unwrap_err();
+        assert!(err.contains("exceeds the limit"));
+    }
+
+    #[test]
+    fn test_brand_marks_and_unwraps_base_type() {
+        let arena = Bump::new();
+        let interner = luanext_parser::string_interner::StringInterner::new();
+        let string_type = Type::new(TypeKind::Primitive(PrimitiveType::String), make_span());
+        let tag = Type::new(
+            TypeKind::Literal(Literal::String("UserId".to_string())),
+            make_span(),
+        );
+
+        let branded = brand(&arena, &[string_type, tag], make_span(), &interner).unwrap();
+        assert_eq!(brand_tag(&branded, &interner).as_deref(), Some("UserId"));
+
+        let base = brand_base(&arena, &branded, &interner).unwrap();
+        assert!(matches!(
+            base.kind,
+            TypeKind::Primitive(PrimitiveType::String)
+        ));
+
+        // Readonly keeps the brand
+        let frozen = readonly(&arena, &[branded], make_span()).unwrap();
+        assert_eq!(brand_tag(&frozen, &interner).as_deref(), Some("UserId"));
+
+        // A declared brand shows its tag, not its identity
+        let string_type = Type::new(TypeKind::Primitive(PrimitiveType::String), make_span());
+        let marker = brand_marker(&arena, "Id", Some("users::UserId"), make_span(), &interner);
+        let declared = brand(&arena, &[string_type, marker], make_span(), &interner).unwrap();
+        assert_eq!(brand_tag(&declared, &interner).as_deref(), Some("Id"));
+
+        let number_type = Type::new(TypeKind::Primitive(PrimitiveType::Number), make_span());
+        assert!(brand(
+            &arena,
+            &[number_type.clone(), number_type],
+            make_span(),
+            &interner
+        )
+        .is_err());
+    }
 }
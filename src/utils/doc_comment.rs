@@ -0,0 +1,200 @@
+//! Documentation comments attached to declarations
+//!
+//! The parser drops comments, so doc blocks are recovered from the source
+//! text: the run of `--` comment lines directly above a declaration is its
+//! documentation when it contains a `---` line, as in the stdlib files:
+//!
+//! ```lua
+//! ---
+//! -- Extracts a substring.
+//! -- @param s The source string
+//! -- @returns The substring
+//! export function sub(s: string, i: number, j?: number): string
+//! ```
+//!
+//! LuaLS-style `---@tag` lines are read the same way.
+
+/// A `@param` entry of a doc comment
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DocParam {
+    pub name: String,
+    pub description: String,
+}
+
+/// The `@deprecated` tag of a doc comment
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Deprecation {
+    /// The text after the tag, usually naming the replacement
+    pub message: Option<String>,
+}
+
+impl Deprecation {
+    /// The warning for a reference to the deprecated `name`
+    pub fn warning(&self, name: &str) -> String {
+        match &self.message {
+            Some(message) => format!("'{}' is deprecated: {}", name, message),
+            None => format!("'{}' is deprecated", name),
+        }
+    }
+}
+
+/// A parsed documentation comment
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct DocComment {
+    /// The text before the first tag, with lines joined by newlines
+    pub summary: String,
+    /// `@param name description` entries, in order
+    pub params: Vec<DocParam>,
+    /// `@returns description` (or `@return`)
+    pub returns: Option<String>,
+    /// `@deprecated [message]`
+    pub deprecated: Option<Deprecation>,
+    /// Any other `@tag text` entries, in order
+    pub tags: Vec<(String, String)>,
+}
+
+impl DocComment {
+    /// Parse the text of a doc block, one entry per comment line with the
+    /// `--`/`---` prefix removed
+    pub fn parse<'s>(lines: impl IntoIterator<Item = &'s str>) -> Self {
+        let mut doc = DocComment::default();
+        let mut summary = Vec::new();
+
+        for line in lines {
+            let line = line.trim();
+            let Some(tagged) = line.strip_prefix('@') else {
+                if doc.params.is_empty() && doc.returns.is_none() && doc.tags.is_empty() {
+                    summary.push(line);
+                }
+                continue;
+            };
+            let (tag, text) = tagged
+                .split_once(char::is_whitespace)
+                .map_or((tagged, ""), |(tag, text)| (tag, text.trim()));
+            match tag {
+                "param" => {
+                    let (name, description) = text
+                        .split_once(char::is_whitespace)
+                        .map_or((text, ""), |(name, description)| (name, description.trim()));
+                    doc.params.push(DocParam {
+                        name: name.to_string(),
+                        description: description.to_string(),
+                    });
+                }
+                "return" | "returns" => doc.returns = Some(text.to_string()),
+                "deprecated" => {
+                    doc.deprecated = Some(Deprecation {
+                        message: (!text.is_empty()).then(|| text.to_string()),
+                    })
+                }
+                _ => doc.tags.push((tag.to_string(), text.to_string())),
+            }
+        }
+
+        doc.summary = summary.join("\n").trim().to_string();
+        doc
+    }
+
+    /// The description of parameter `name`, if documented
+    pub fn param(&self, name: &str) -> Option<&str> {
+        self.params
+            .iter()
+            .find(|param| param.name == name)
+            .map(|param| param.description.as_str())
+    }
+}
+
+/// Find the doc comment directly above the declaration starting at byte
+/// `offset` of `source`. Decorator lines between the comment and the
+/// declaration are skipped.
+pub fn doc_comment_before(source: &str, offset: usize) -> Option<DocComment> {
+    let line_start = source
+        .get(..offset)?
+        .rfind('\n')
+        .map_or(0, |newline| newline + 1);
+
+    let mut block = Vec::new();
+    let mut has_marker = false;
+    for line in source[..line_start].lines().rev() {
+        let line = line.trim();
+        if block.is_empty() && line.starts_with('@') {
+            continue;
+        }
+        let Some(comment) = line.strip_prefix("--") else {
+            break;
+        };
+        // `--[[` starts a block comment, which is not a doc block
+        if comment.starts_with("[[") || comment.starts_with("[=") {
+            break;
+        }
+        match comment.strip_prefix('-') {
+            Some(text) => {
+                has_marker = true;
+                // A bare `---` only opens the block
+                if !text.trim().is_empty() {
+                    block.push(text);
+                }
+            }
+            None => block.push(comment),
+        }
+    }
+
+    if !has_marker {
+        return None;
+    }
+    block.reverse();
+    Some(DocComment::parse(block))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_stdlib_style_block() {
+        let source = "\
+declare namespace string {
+  ---
+  -- Extracts a substring.
+  -- @param s The source string
+  -- @param i Starting index
+  -- @returns The substring
+  export function sub(s: string, i: number): string
+}";
+        let offset = source.find("export").unwrap();
+        let doc = doc_comment_before(source, offset).unwrap();
+        assert_eq!(doc.summary, "Extracts a substring.");
+        assert_eq!(doc.param("s"), Some("The source string"));
+        assert_eq!(doc.param("i"), Some("Starting index"));
+        assert_eq!(doc.returns.as_deref(), Some("The substring"));
+        assert!(doc.deprecated.is_none());
+    }
+
+    #[test]
+    fn test_luals_style_tags() {
+        let source = "\
+--- Sums its arguments
+---@param a number
+---@deprecated use add instead
+---@since 1.2
+@readonly
+function sum(a: number): number";
+        let doc = doc_comment_before(source, source.find("function").unwrap()).unwrap();
+        assert_eq!(doc.summary, "Sums its arguments");
+        assert_eq!(doc.param("a"), Some(""));
+        assert_eq!(
+            doc.deprecated.unwrap().message.as_deref(),
+            Some("use add instead")
+        );
+        assert_eq!(doc.tags, vec![("since".to_string(), "1.2".to_string())]);
+    }
+
+    #[test]
+    fn test_plain_comments_are_not_docs() {
+        let source = "-- just a note\nlocal x = 1\n---\n-- Docs\n\nlocal y = 2";
+        assert!(doc_comment_before(source, source.find("local x").unwrap()).is_none());
+        // A blank line detaches the block
+        assert!(doc_comment_before(source, source.find("local y").unwrap()).is_none());
+        assert!(doc_comment_before(source, 0).is_none());
+    }
+}
@@ -1,3 +1,4 @@
+pub mod doc_comment;
 pub mod fuzzy;
 pub mod symbol_table;
 pub mod type_formatter;
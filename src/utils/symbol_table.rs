@@ -1,7 +1,9 @@
+use crate::utils::doc_comment::DocComment;
 use luanext_parser::ast::types::Type;
 use luanext_parser::span::Span;
 use rustc_hash::FxHashMap;
 use serde::{Deserialize, Serialize};
+use std::sync::Arc;
 
 /// Kind of symbol (variable, function, class, etc.)
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
@@ -26,6 +28,11 @@ pub struct Symbol<'arena> {
     pub span: Span,
     pub is_exported: bool,
     pub references: Vec<Span>,
+    /// The doc comment written above the declaration
+    pub doc: Option<Arc<DocComment>>,
+    /// The doc comments of its members (class, interface and enum members,
+    /// namespace functions), by member name
+    pub member_docs: FxHashMap<String, Arc<DocComment>>,
 }
 
 impl<'arena> Symbol<'arena> {
@@ -37,6 +44,8 @@ impl<'arena> Symbol<'arena> {
             span,
             is_exported: false,
             references: Vec::new(),
+            doc: None,
+            member_docs: FxHashMap::default(),
         }
     }
 
@@ -94,7 +103,7 @@ impl<'arena> Default for Scope<'arena> {
 }
 
 /// Symbol table managing scopes
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct SymbolTable<'arena> {
     current_scope: Scope<'arena>,
     scope_stack: Vec<Scope<'arena>>,
@@ -169,6 +178,66 @@ impl<'arena> SymbolTable<'arena> {
         false
     }
 
+    /// Replace the type of a symbol, e.g. once a function's body has been checked
+    /// Returns true if the symbol was found and updated
+    pub fn set_type(&mut self, name: &str, typ: Type<'arena>) -> bool {
+        // Try current scope first
+        if let Some(symbol) = self.current_scope.symbols.get_mut(name) {
+            symbol.typ = typ;
+            return true;
+        }
+
+        // Walk the scope stack (most recent first)
+        for scope in self.scope_stack.iter_mut().rev() {
+            if let Some(symbol) = scope.symbols.get_mut(name) {
+                symbol.typ = typ;
+                return true;
+            }
+        }
+
+        false
+    }
+
+    /// Attach a doc comment to a symbol
+    /// Returns true if the symbol was found and updated
+    pub fn set_doc(&mut self, name: &str, doc: Arc<DocComment>) -> bool {
+        // Try current scope first
+        if let Some(symbol) = self.current_scope.symbols.get_mut(name) {
+            symbol.doc = Some(doc);
+            return true;
+        }
+
+        // Walk the scope stack (most recent first)
+        for scope in self.scope_stack.iter_mut().rev() {
+            if let Some(symbol) = scope.symbols.get_mut(name) {
+                symbol.doc = Some(doc);
+                return true;
+            }
+        }
+
+        false
+    }
+
+    /// Attach a doc comment to the member `member` of a symbol
+    /// Returns true if the symbol was found and updated
+    pub fn set_member_doc(&mut self, name: &str, member: &str, doc: Arc<DocComment>) -> bool {
+        // Try current scope first
+        if let Some(symbol) = self.current_scope.symbols.get_mut(name) {
+            symbol.member_docs.insert(member.to_string(), doc);
+            return true;
+        }
+
+        // Walk the scope stack (most recent first)
+        for scope in self.scope_stack.iter_mut().rev() {
+            if let Some(symbol) = scope.symbols.get_mut(name) {
+                symbol.member_docs.insert(member.to_string(), doc);
+                return true;
+            }
+        }
+
+        false
+    }
+
     /// Get the current scope
     pub fn current_scope(&self) -> &Scope<'arena> {
         &self.current_scope
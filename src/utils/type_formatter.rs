@@ -5,7 +5,7 @@
 //! TypeScript-like type representations.
 
 use luanext_parser::ast::{
-    expression::Literal,
+    expression::{Expression, ExpressionKind, Literal},
     pattern::Pattern,
     statement::IndexKeyType,
     types::{FunctionType, ObjectType, ObjectTypeMember, PrimitiveType, Type, TypeKind},
@@ -18,11 +18,16 @@ use luanext_parser::string_interner::StringInterner;
 /// - `Primitive(String)` → `"string"`
 /// - `Union([String, Number])` → `"string | number"`
 /// - `Function(...)` → `"(string, number) => boolean"`
+/// - `Brand<string, "UserId">` → `"UserId"`
 ///
 /// # Arguments
 /// * `ty` - The type to format
 /// * `interner` - String interner for resolving identifiers
 pub fn format_type_for_error(ty: &Type, interner: &StringInterner) -> String {
+    // Branded types are shown by their brand name
+    if let Some(tag) = crate::types::utility_types::brand_tag(ty, interner) {
+        return tag;
+    }
     format_type_kind(&ty.kind, interner)
 }
 
@@ -68,7 +73,7 @@ fn format_type_kind(kind: &TypeKind, interner: &StringInterner) -> String {
         }
         TypeKind::Function(func) => format_function_type(func, interner),
         TypeKind::Literal(lit) => format_literal(lit),
-        TypeKind::TypeQuery(expr) => format!("typeof {:?}", expr), // Simplified
+        TypeKind::TypeQuery(expr) => format!("typeof {}", format_query_expression(expr, interner)),
         TypeKind::KeyOf(ty) => {
             format!("keyof {}", format_type_for_error(ty, interner))
         }
@@ -233,6 +238,47 @@ fn format_function_type(func: &FunctionType, interner: &StringInterner) -> Strin
     format!("({}) => {}", params.join(", "), ret)
 }
 
+/// Format the operand of a `typeof` query (identifiers, member chains and calls)
+fn format_query_expression(expr: &Expression, interner: &StringInterner) -> String {
+    let format_args = |args: &[luanext_parser::ast::expression::Argument]| {
+        args.iter()
+            .map(|arg| format_query_expression(&arg.value, interner))
+            .collect::<Vec<_>>()
+            .join(", ")
+    };
+
+    match &expr.kind {
+        ExpressionKind::Identifier(name) => interner.resolve(*name).to_string(),
+        ExpressionKind::Literal(lit) => format_literal(lit),
+        ExpressionKind::Member(object, member) => format!(
+            "{}.{}",
+            format_query_expression(object, interner),
+            interner.resolve(member.node)
+        ),
+        ExpressionKind::Index(object, index) => format!(
+            "{}[{}]",
+            format_query_expression(object, interner),
+            format_query_expression(index, interner)
+        ),
+        ExpressionKind::Call(callee, args, _) => format!(
+            "{}({})",
+            format_query_expression(callee, interner),
+            format_args(args)
+        ),
+        ExpressionKind::MethodCall(object, method, args, _) => format!(
+            "{}:{}({})",
+            format_query_expression(object, interner),
+            interner.resolve(method.node),
+            format_args(args)
+        ),
+        ExpressionKind::Parenthesized(inner) => {
+            format!("({})", format_query_expression(inner, interner))
+        }
+        ExpressionKind::SelfKeyword => "self".to_string(),
+        _ => "...".to_string(),
+    }
+}
+
 fn format_literal(lit: &Literal) -> String {
     match lit {
         Literal::String(s) => format!("\"{}\"", s),
@@ -311,6 +357,35 @@ mod tests {
         assert_eq!(format_type_for_error(&ty, &interner), "true");
     }
 
+    #[test]
+    fn test_format_type_query() {
+        let interner = StringInterner::default();
+        let config_id = interner.get_or_intern("config");
+        let db_id = interner.get_or_intern("db");
+
+        let config = Expression {
+            kind: ExpressionKind::Identifier(config_id),
+            span: make_span(),
+            annotated_type: None,
+            receiver_class: None,
+        };
+        let member = Expression {
+            kind: ExpressionKind::Member(
+                &config,
+                Spanned {
+                    node: db_id,
+                    span: make_span(),
+                },
+            ),
+            span: make_span(),
+            annotated_type: None,
+            receiver_class: None,
+        };
+
+        let ty = Type::new(TypeKind::TypeQuery(&member), make_span());
+        assert_eq!(format_type_for_error(&ty, &interner), "typeof config.db");
+    }
+
     #[test]
     fn test_format_reference_type() {
         let interner = StringInterner::default();
@@ -330,4 +405,30 @@ mod tests {
 
         assert_eq!(format_type_for_error(&ty, &interner), "User");
     }
+
+    #[test]
+    fn test_format_branded_type() {
+        let interner = StringInterner::default();
+        let marker_id = interner.get_or_intern("brand UserId");
+
+        let types = vec![
+            Type::new(TypeKind::Primitive(PrimitiveType::String), make_span()),
+            Type::new(
+                TypeKind::Reference(TypeReference {
+                    name: Spanned {
+                        node: marker_id,
+                        span: make_span(),
+                    },
+                    type_arguments: None,
+                    span: make_span(),
+                }),
+                make_span(),
+            ),
+        ];
+
+        let leaked_types = Box::leak(types.into_boxed_slice());
+        let ty = Type::new(TypeKind::Intersection(leaked_types), make_span());
+
+        assert_eq!(format_type_for_error(&ty, &interner), "UserId");
+    }
 }
@@ -90,6 +90,14 @@ pub trait AccessControlVisitor<'arena>: TypeCheckVisitor {
     /// Get class members
     fn get_class_members(&self, class_name: &str) -> Option<&Vec<ClassMemberInfo<'arena>>>;
 
+    /// Replace the kind (and so the type) of a registered member of the same kind
+    fn update_member_kind(
+        &mut self,
+        class_name: &str,
+        member_name: &str,
+        kind: ClassMemberKind<'arena>,
+    );
+
     /// Get parent class name
     fn get_parent_class(&self, class_name: &str) -> Option<String>;
 
@@ -107,7 +115,7 @@ pub trait AccessControlVisitor<'arena>: TypeCheckVisitor {
 }
 
 /// Default implementation of access control
-#[derive(Default)]
+#[derive(Default, Clone)]
 pub struct AccessControl<'arena> {
     class_members: FxHashMap<String, Vec<ClassMemberInfo<'arena>>>,
     final_classes: FxHashMap<String, bool>,
@@ -356,6 +364,22 @@ impl<'arena> AccessControlVisitor<'arena> for AccessControl<'arena> {
         self.class_members.get(class_name)
     }
 
+    fn update_member_kind(
+        &mut self,
+        class_name: &str,
+        member_name: &str,
+        kind: ClassMemberKind<'arena>,
+    ) {
+        if let Some(member) = self.class_members.get_mut(class_name).and_then(|members| {
+            members.iter_mut().find(|m| {
+                m.name == member_name
+                    && std::mem::discriminant(&m.kind) == std::mem::discriminant(&kind)
+            })
+        }) {
+            member.kind = kind;
+        }
+    }
+
     fn get_parent_class(&self, class_name: &str) -> Option<String> {
         self.class_parents.get(class_name).cloned().flatten()
     }
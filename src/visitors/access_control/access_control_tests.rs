@@ -1214,4 +1214,40 @@ mod tests {
             "Child should access parent's protected member"
         );
     }
+
+    #[test]
+    fn test_update_member_kind_replaces_matching_kind() {
+        let mut access_control = AccessControl::new();
+        access_control.register_class("Config", None);
+        access_control
+            .register_member("Config", create_test_member("port", AccessModifier::Public));
+
+        access_control.update_member_kind(
+            "Config",
+            "port",
+            ClassMemberKind::Property {
+                type_annotation: Type::new(
+                    TypeKind::Primitive(PrimitiveType::String),
+                    Span::default(),
+                ),
+            },
+        );
+        // A different kind with the same name is left alone
+        access_control.update_member_kind(
+            "Config",
+            "port",
+            ClassMemberKind::Method {
+                parameters: vec![],
+                return_type: None,
+                is_abstract: false,
+            },
+        );
+
+        let members = access_control.get_class_members("Config").unwrap();
+        assert!(matches!(
+            &members[0].kind,
+            ClassMemberKind::Property { type_annotation }
+                if matches!(type_annotation.kind, TypeKind::Primitive(PrimitiveType::String))
+        ));
+    }
 }
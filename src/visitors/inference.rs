@@ -1,21 +1,33 @@
-use super::super::visitors::{AccessControl, AccessControlVisitor, ClassMemberKind};
+use super::super::visitors::{
+    AccessControl, AccessControlVisitor, ClassMemberInfo, ClassMemberKind,
+};
 use super::TypeCheckVisitor;
+use crate::cli::config::LuaVersion;
 use crate::cli::diagnostics::DiagnosticHandler;
 use crate::core::type_compat::TypeCompatibility;
 use crate::core::type_environment::TypeEnvironment;
-use crate::types::generics::instantiate_type;
+use crate::helpers::lua_pattern::{self, CaptureKind};
+use crate::helpers::{format_string, target_features, type_utilities};
+use crate::module_resolver::{ModuleId, ModuleRegistry, ModuleResolver, TypedDependency};
+use crate::phases::module_phase::{self, LazyTypeCheckCallback};
+use crate::types::coroutine::{self, CoroutineSignature};
+use crate::types::ffi::{self, CDeclarations, CType};
+use crate::types::generics::{instantiate_type, substitute_self_type, SELF_TYPE_NAME};
+use crate::types::reflection;
+use crate::utils::doc_comment::Deprecation;
 use crate::utils::symbol_table::{Symbol, SymbolKind, SymbolTable};
 use crate::TypeCheckError;
 use luanext_parser::ast::expression::*;
 use luanext_parser::ast::pattern::{ArrayPatternElement, Pattern, PatternWithDefault};
-use luanext_parser::ast::statement::{Block, OperatorKind, Statement};
+use luanext_parser::ast::statement::{Block, OperatorKind, Parameter, Statement};
 use luanext_parser::ast::types::*;
 use luanext_parser::prelude::{
     Argument, MatchArm, MatchArmBody, MatchExpression, PropertySignature,
 };
 use luanext_parser::span::Span;
-use luanext_parser::string_interner::StringInterner;
-use rustc_hash::FxHashMap;
+use luanext_parser::string_interner::{StringId, StringInterner};
+use rustc_hash::{FxHashMap, FxHashSet};
+use std::cell::RefCell;
 use std::sync::Arc;
 use tracing::{debug, error, instrument, span, Level};
 
@@ -128,6 +140,28 @@ pub struct InferenceContext<'a, 'arena> {
     pub diagnostic_handler: &'a Arc<dyn DiagnosticHandler>,
     pub class_type_params:
         &'a FxHashMap<String, Vec<luanext_parser::ast::statement::TypeParameter<'arena>>>,
+    /// Target Lua version (with `Auto` already resolved), which selects
+    /// version-specific operator semantics
+    pub target: LuaVersion,
+    /// C declarations from `ffi.cdef` calls, used to type LuaJIT cdata
+    pub c_declarations: &'a CDeclarations,
+    /// Signature of the coroutine whose body is being checked, for `coroutine.yield`
+    pub coroutine: Option<&'a CoroutineSignature<'arena>>,
+    /// Spans (`start`, `end`) of the deprecated references already reported,
+    /// so an expression inferred more than once is reported once
+    pub reported_deprecations: &'a RefCell<FxHashSet<(usize, usize)>>,
+    /// Module resolution state for typing `require` calls, when checking a
+    /// module of a multi-module compilation
+    pub module_registry: Option<&'a Arc<ModuleRegistry>>,
+    pub module_resolver: Option<&'a Arc<ModuleResolver>>,
+    pub current_module_id: Option<&'a ModuleId>,
+    /// Type-checks a required module that was not checked yet
+    pub lazy_callback: Option<&'a dyn LazyTypeCheckCallback>,
+    /// Dependencies of the `require` calls inferred, which the checker adds
+    /// to its module dependencies
+    pub required_modules: &'a RefCell<Vec<TypedDependency>>,
+    /// Spans of the `require` calls of plain Lua files already reported
+    pub reported_plain_lua_requires: &'a RefCell<FxHashSet<(usize, usize)>>,
 }
 
 pub struct TypeInferrer<'a, 'arena> {
@@ -140,6 +174,24 @@ pub struct TypeInferrer<'a, 'arena> {
     diagnostic_handler: &'a Arc<dyn DiagnosticHandler>,
     class_type_params:
         &'a FxHashMap<String, Vec<luanext_parser::ast::statement::TypeParameter<'arena>>>,
+    /// Receiver type for a function expression being assigned into an open table,
+    /// used to type its leading `self` parameter
+    method_receiver: Option<Type<'arena>>,
+    /// Contextual `self` type for methods of the next object literal (`ThisType<T>`)
+    object_receiver: Option<Type<'arena>>,
+    /// Infer literals as `as const` would: array literals become tuples and
+    /// object literal properties become readonly, keeping literal types
+    const_context: bool,
+    target: LuaVersion,
+    c_declarations: &'a CDeclarations,
+    coroutine: Option<&'a CoroutineSignature<'arena>>,
+    reported_deprecations: &'a RefCell<FxHashSet<(usize, usize)>>,
+    module_registry: Option<&'a Arc<ModuleRegistry>>,
+    module_resolver: Option<&'a Arc<ModuleResolver>>,
+    current_module_id: Option<&'a ModuleId>,
+    lazy_callback: Option<&'a dyn LazyTypeCheckCallback>,
+    required_modules: &'a RefCell<Vec<TypedDependency>>,
+    reported_plain_lua_requires: &'a RefCell<FxHashSet<(usize, usize)>>,
 }
 
 impl<'a, 'arena> TypeInferrer<'a, 'arena> {
@@ -159,7 +211,92 @@ impl<'a, 'arena> TypeInferrer<'a, 'arena> {
             interner: ctx.interner,
             diagnostic_handler: ctx.diagnostic_handler,
             class_type_params: ctx.class_type_params,
+            method_receiver: None,
+            object_receiver: None,
+            const_context: false,
+            target: ctx.target,
+            c_declarations: ctx.c_declarations,
+            coroutine: ctx.coroutine,
+            reported_deprecations: ctx.reported_deprecations,
+            module_registry: ctx.module_registry,
+            module_resolver: ctx.module_resolver,
+            current_module_id: ctx.current_module_id,
+            lazy_callback: ctx.lazy_callback,
+            required_modules: ctx.required_modules,
+            reported_plain_lua_requires: ctx.reported_plain_lua_requires,
+        }
+    }
+
+    /// Type `self` in the methods of the next inferred object literal as `receiver`
+    pub fn with_object_receiver(mut self, receiver: Type<'arena>) -> Self {
+        self.object_receiver = Some(receiver);
+        self
+    }
+
+    /// Infer the next expression's literals in a const context, as if asserted `as const`
+    pub fn with_const_context(mut self) -> Self {
+        self.const_context = true;
+        self
+    }
+
+    /// Record a reference to the symbol `name`, warning when it is deprecated
+    fn note_reference(&mut self, name: &str, span: Span) {
+        let Some(symbol) = self.symbol_table.lookup(name) else {
+            return;
+        };
+        let doc = symbol.doc.clone();
+        let recorded = symbol.references.contains(&span);
+        if !recorded {
+            self.symbol_table.add_reference(name, span);
+        }
+        if let Some(deprecation) = doc.as_ref().and_then(|doc| doc.deprecated.as_ref()) {
+            self.report_deprecated(name, deprecation, span);
+        }
+    }
+
+    /// Warn when the member `member` of `object` is deprecated. The owner of
+    /// the member is the class or interface the object's type names, or the
+    /// namespace, class or enum the object itself names.
+    fn check_member_deprecation(
+        &self,
+        object: &Expression<'arena>,
+        obj_type: &Type<'arena>,
+        member: &str,
+        span: Span,
+    ) {
+        let owners = [
+            match &obj_type.kind {
+                TypeKind::Reference(type_ref) => Some(self.interner.resolve(type_ref.name.node)),
+                _ => None,
+            },
+            match &object.kind {
+                ExpressionKind::Identifier(name) => Some(self.interner.resolve(*name)),
+                _ => None,
+            },
+        ];
+        for owner in owners.into_iter().flatten() {
+            if let Some(deprecation) = self
+                .symbol_table
+                .lookup(&owner)
+                .and_then(|symbol| symbol.member_docs.get(member))
+                .and_then(|doc| doc.deprecated.as_ref())
+            {
+                self.report_deprecated(&format!("{}.{}", owner, member), deprecation, span);
+                return;
+            }
+        }
+    }
+
+    /// Report a reference to the deprecated `name`, once per span
+    fn report_deprecated(&self, name: &str, deprecation: &Deprecation, span: Span) {
+        use crate::cli::diagnostics::{error_codes::DEPRECATED, Diagnostic};
+
+        let key = (span.start as usize, span.end as usize);
+        if !self.reported_deprecations.borrow_mut().insert(key) {
+            return;
         }
+        self.diagnostic_handler
+            .report(Diagnostic::warning(span, deprecation.warning(name)).with_code(DEPRECATED));
     }
 }
 
@@ -189,6 +326,7 @@ impl<'a, 'arena> TypeInferenceVisitor<'arena> for TypeInferrer<'a, 'arena> {
             ExpressionKind::Identifier(name) => {
                 let name_str = self.interner.resolve(*name);
                 debug!(name = %name_str, "Inferring identifier type");
+                self.note_reference(&name_str, span);
 
                 // Check for narrowed type first
                 if let Some(narrowed_type) = self.narrowing_context.get_narrowed_type(*name) {
@@ -200,6 +338,10 @@ impl<'a, 'arena> TypeInferenceVisitor<'arena> for TypeInferrer<'a, 'arena> {
                 if let Some(symbol) = self.symbol_table.lookup(&name_str) {
                     debug!(name = %name_str, type = ?symbol.typ, "Found in symbol table");
                     Ok(symbol.typ.clone())
+                } else if let Some(message) =
+                    target_features::unavailable_global(&name_str, self.target)
+                {
+                    Err(TypeCheckError::new(message, span))
                 } else {
                     error!(name = %name_str, "Undefined variable");
 
@@ -247,18 +389,85 @@ impl<'a, 'arena> TypeInferenceVisitor<'arena> for TypeInferrer<'a, 'arena> {
                     }
                 }
 
+                // `ffi.new`, `ffi.cast` and `ffi.typeof` with a C type name yield typed cdata
+                if let Some(cdata) = self.infer_ffi_call(callee, args, span)? {
+                    return Ok(cdata);
+                }
+
+                // `pcall` and `xpcall` return either the results of the call or its error
+                if let Some(result) = self.infer_protected_call(callee, args, span)? {
+                    return Ok(result);
+                }
+
+                // `coroutine.create`, `wrap`, `resume` and `yield` track the coroutine's types
+                if let Some(result) = self.infer_coroutine_call(callee, args, span)? {
+                    return Ok(result);
+                }
+
+                // `Reflect.getFields(obj)` and friends describe the static type of `obj`
+                if let Some(result) = self.infer_reflect_call(callee, args, span)? {
+                    return Ok(result);
+                }
+
+                // `string.format` and the pattern functions read their literal
+                // format string or pattern
+                if let Some(function) = self.library_function(callee, "string") {
+                    if let Some((subject, rest)) = args.split_first() {
+                        if let Some(result) =
+                            self.infer_string_call(&function, &subject.value, rest, span)?
+                        {
+                            return Ok(result);
+                        }
+                    }
+                }
+
                 let callee_type = self.infer_expression(callee)?;
 
                 // Note: We can no longer mutate stored_type_args since AST is arena-allocated.
                 // Type argument inference results are used directly rather than stored back.
 
-                self.infer_call(&callee_type, args, span)
+                // `setmetatable(t, mt)` yields `t` extended with the members of `mt.__index`
+                if let ExpressionKind::Identifier(name_id) = &callee.kind {
+                    if self.interner.resolve(*name_id) == "setmetatable" {
+                        if let Some(instance) = self.infer_setmetatable(args, span)? {
+                            return Ok(instance);
+                        }
+                    }
+                }
+
+                // LuaJIT's built-in modules are also loaded with `require`
+                if let Some(module) = self.builtin_module(callee, args) {
+                    return Ok(module);
+                }
+
+                // `require("pkg.mod")` of a module of the compilation yields its exports
+                if let Some(module) = self.required_module(callee, args, span) {
+                    return Ok(module);
+                }
+
+                let return_type = self.infer_call(&callee_type, args, span)?;
+                Ok(self.expand_c_library(return_type, span))
             }
 
             ExpressionKind::MethodCall(object, method, args, _) => {
                 let obj_type = self.infer_expression(object)?;
                 let method_name = self.interner.resolve(method.node);
-                let method_type = self.infer_method(&obj_type, &method_name, args, span)?;
+
+                // `("%d"):format(n)` is `string.format("%d", n)`, `s:match(p)` is
+                // `string.match(s, p)`
+                if matches!(
+                    obj_type.kind,
+                    TypeKind::Primitive(PrimitiveType::String)
+                        | TypeKind::Literal(Literal::String(_))
+                ) {
+                    if let Some(result) =
+                        self.infer_string_call(&method_name, object, args, span)?
+                    {
+                        return Ok(result);
+                    }
+                }
+                self.check_member_deprecation(object, &obj_type, &method_name, method.span);
+                let method_type = self.infer_method_call(&obj_type, &method_name, args, span)?;
 
                 // Note: receiver_class and annotated_type are no longer mutated
                 // since AST is arena-allocated (immutable). The type information
@@ -269,7 +478,14 @@ impl<'a, 'arena> TypeInferenceVisitor<'arena> for TypeInferrer<'a, 'arena> {
             ExpressionKind::Member(object, member) => {
                 let obj_type = self.infer_expression(object)?;
                 let member_name = self.interner.resolve(member.node);
-                self.infer_member(&obj_type, &member_name, span)
+                if ffi::FFI_KEYWORD_MEMBERS.contains(&member_name.as_str())
+                    && ffi::is_ffi_namespace(&obj_type, self.interner)
+                {
+                    return Ok(Type::new(TypeKind::Primitive(PrimitiveType::Unknown), span));
+                }
+                self.check_member_deprecation(object, &obj_type, &member_name, member.span);
+                let member_type = self.infer_member(&obj_type, &member_name, span)?;
+                Ok(self.expand_c_library(member_type, span))
             }
 
             ExpressionKind::Index(object, index) => {
@@ -294,8 +510,29 @@ impl<'a, 'arena> TypeInferenceVisitor<'arena> for TypeInferrer<'a, 'arena> {
                     _ => None,
                 };
 
+                // `M.f = function(self, ...)` on an open table declares a method
+                // whose `self` is typed as the table itself
+                let open_table_target = self.open_table_member_target(target)?;
+                if let Some((_, obj_type, _)) = &open_table_target {
+                    if matches!(value.kind, ExpressionKind::Function(_)) {
+                        self.method_receiver = Some(obj_type.clone());
+                    }
+                }
+
                 // For new globals, infer RHS type first, then declare with that type
                 let value_type = self.infer_expression(value)?;
+                self.method_receiver = None;
+
+                // Whatever a test narrowed the variable to no longer holds
+                if let ExpressionKind::Identifier(name) = &target.kind {
+                    self.narrowing_context.invalidate(*name);
+                }
+
+                if let Some((table_ref, _, member_name)) = open_table_target {
+                    if self.extend_open_table(&table_ref, &member_name, &value_type, span) {
+                        return Ok(value_type);
+                    }
+                }
 
                 let target_type = match &target.kind {
                     ExpressionKind::Member(object, member) => {
@@ -356,6 +593,7 @@ impl<'a, 'arena> TypeInferenceVisitor<'arena> for TypeInferrer<'a, 'arena> {
             ExpressionKind::OptionalMember(object, member) => {
                 let obj_type = self.infer_expression(object)?;
                 let member_name = self.interner.resolve(member.node);
+                self.check_member_deprecation(object, &obj_type, &member_name, member.span);
                 let member_type = self.infer_member(&obj_type, &member_name, span)?;
                 self.make_optional(member_type, span)
             }
@@ -379,11 +617,18 @@ impl<'a, 'arena> TypeInferenceVisitor<'arena> for TypeInferrer<'a, 'arena> {
             ExpressionKind::OptionalMethodCall(object, method, args, _) => {
                 let obj_type = self.infer_expression(object)?;
                 let method_name = self.interner.resolve(method.node);
-                let method_type = self.infer_method(&obj_type, &method_name, args, span)?;
+                self.check_member_deprecation(object, &obj_type, &method_name, method.span);
+                let method_type = self.infer_method_call(&obj_type, &method_name, args, span)?;
                 self.make_optional(method_type, span)
             }
 
             ExpressionKind::Array(elements) => {
+                if self.const_context {
+                    if let Some(tuple) = self.infer_const_tuple(elements, span)? {
+                        return Ok(tuple);
+                    }
+                }
+
                 if elements.is_empty() {
                     // Empty array has unknown element type
                     return Ok(Type::new(
@@ -473,6 +718,8 @@ impl<'a, 'arena> TypeInferenceVisitor<'arena> for TypeInferrer<'a, 'arena> {
             }
 
             ExpressionKind::Object(props) => {
+                let receiver = self.object_receiver.take();
+
                 // Infer object type from properties
                 let mut members = Vec::new();
 
@@ -483,12 +730,22 @@ impl<'a, 'arena> TypeInferenceVisitor<'arena> for TypeInferrer<'a, 'arena> {
                             value,
                             span: prop_span,
                         } => {
+                            // Methods see the contextual `self` type, if any
+                            if matches!(value.kind, ExpressionKind::Function(_)) {
+                                self.method_receiver = receiver.clone();
+                            }
+
                             // Infer the type of the value
-                            let value_type = self.infer_expression(value)?;
+                            let value_type = if self.const_context {
+                                self.infer_const_element(value)?
+                            } else {
+                                self.infer_expression(value)?
+                            };
+                            self.method_receiver = None;
 
                             // Create a property signature
                             let prop_sig = PropertySignature {
-                                is_readonly: false,
+                                is_readonly: self.const_context,
                                 name: key.clone(),
                                 is_optional: false,
                                 type_annotation: value_type,
@@ -560,15 +817,23 @@ impl<'a, 'arena> TypeInferenceVisitor<'arena> for TypeInferrer<'a, 'arena> {
             }
 
             ExpressionKind::Function(func_expr) => {
+                let receiver = self.method_receiver.take();
+
                 // Enter a new scope for the function expression
                 self.symbol_table.enter_scope();
 
                 // Register parameters in the scope
-                for param in func_expr.parameters.iter() {
+                for (index, param) in func_expr.parameters.iter().enumerate() {
                     if let Pattern::Identifier(ident) = &param.pattern {
                         let param_type = if let Some(type_ann) = &param.type_annotation {
                             // Use the declared type
                             type_ann.clone()
+                        } else if let Some(receiver) = receiver
+                            .as_ref()
+                            .filter(|_| index == 0 && self.interner.resolve(ident.node) == "self")
+                        {
+                            // Method on an open table - `self` is the table
+                            receiver.clone()
                         } else {
                             // No type annotation - use unknown
                             Type::new(TypeKind::Primitive(PrimitiveType::Unknown), param.span)
@@ -809,14 +1074,36 @@ impl<'a, 'arena> TypeInferenceVisitor<'arena> for TypeInferrer<'a, 'arena> {
             }
 
             ExpressionKind::TypeAssertion(expr, asserted_type) => {
+                // `expr as const` keeps literal types: arrays become tuples and
+                // object properties become readonly
+                if matches!(&asserted_type.kind, TypeKind::Reference(type_ref)
+                    if type_ref.type_arguments.is_none()
+                        && self.interner.resolve(type_ref.name.node) == "const")
+                {
+                    let saved = std::mem::replace(&mut self.const_context, true);
+                    let result = self.infer_expression(expr);
+                    self.const_context = saved;
+                    return result;
+                }
+
                 // Type assertions (expr as Type) override the inferred type
                 // First, infer the expression type to ensure it's valid
                 let expr_type = self.infer_expression(expr)?;
 
-                // Check if the assertion is valid (expression type is compatible with asserted type)
-                if !TypeCompatibility::is_assignable(&expr_type, asserted_type)
-                    && !TypeCompatibility::is_assignable(asserted_type, &expr_type)
-                {
+                // Check if the assertion is valid (expression type is compatible with asserted type).
+                // Casts are how branded values are created and unwrapped, so a brand
+                // is compared by its underlying type.
+                let compatible = |a: &Type<'arena>, b: &Type<'arena>| {
+                    TypeCompatibility::is_assignable(a, b) || TypeCompatibility::is_assignable(b, a)
+                };
+                let expr_base = self.brand_base_of(&expr_type);
+                let asserted_base = self.brand_base_of(asserted_type);
+                let via_brand = (expr_base.is_some() || asserted_base.is_some())
+                    && compatible(
+                        expr_base.as_ref().unwrap_or(&expr_type),
+                        asserted_base.as_ref().unwrap_or(asserted_type),
+                    );
+                if !compatible(&expr_type, asserted_type) && !via_brand {
                     self.diagnostic_handler.error(
                         span,
                         &format!(
@@ -839,6 +1126,10 @@ impl<'a, 'arena> TypeInferenceVisitor<'arena> for TypeInferrer<'a, 'arena> {
         right: &Type<'arena>,
         span: Span,
     ) -> Result<Type<'arena>, TypeCheckError> {
+        if let Some(message) = target_features::unsupported_binary_operator(op, self.target) {
+            self.report_unsupported_feature(span, message);
+        }
+
         match op {
             BinaryOp::Add
             | BinaryOp::Subtract
@@ -853,16 +1144,8 @@ impl<'a, 'arena> TypeInferenceVisitor<'arena> for TypeInferrer<'a, 'arena> {
                 }
 
                 // Check that both operands are numbers
-                let left_is_number = matches!(
-                    left.kind,
-                    TypeKind::Primitive(PrimitiveType::Number)
-                        | TypeKind::Literal(Literal::Number(_))
-                );
-                let right_is_number = matches!(
-                    right.kind,
-                    TypeKind::Primitive(PrimitiveType::Number)
-                        | TypeKind::Literal(Literal::Number(_))
-                );
+                let left_is_number = type_utilities::is_numeric_type(left);
+                let right_is_number = type_utilities::is_numeric_type(right);
 
                 if !left_is_number {
                     self.diagnostic_handler.error(
@@ -883,7 +1166,11 @@ impl<'a, 'arena> TypeInferenceVisitor<'arena> for TypeInferrer<'a, 'arena> {
                     );
                 }
 
-                Ok(Type::new(TypeKind::Primitive(PrimitiveType::Number), span))
+                // On Lua 5.3+, integer operands stay integers except under `/` and `^`
+                let integer_result = !matches!(op, BinaryOp::Divide | BinaryOp::Power)
+                    && type_utilities::is_integer_type(left)
+                    && type_utilities::is_integer_type(right);
+                Ok(self.numeric_type(integer_result, span))
             }
             BinaryOp::Concatenate => {
                 Ok(Type::new(TypeKind::Primitive(PrimitiveType::String), span))
@@ -905,23 +1192,26 @@ impl<'a, 'arena> TypeInferenceVisitor<'arena> for TypeInferrer<'a, 'arena> {
             | BinaryOp::BitwiseOr
             | BinaryOp::BitwiseXor
             | BinaryOp::ShiftLeft
-            | BinaryOp::ShiftRight => {
-                Ok(Type::new(TypeKind::Primitive(PrimitiveType::Number), span))
-            }
+            | BinaryOp::ShiftRight => Ok(self.numeric_type(true, span)),
         }
     }
 
     fn infer_unary_op(
         &self,
         op: UnaryOp,
-        _operand: &Type<'arena>,
+        operand: &Type<'arena>,
         span: Span,
     ) -> Result<Type<'arena>, TypeCheckError> {
+        if let Some(message) = target_features::unsupported_unary_operator(op, self.target) {
+            self.report_unsupported_feature(span, message);
+        }
+
         match op {
-            UnaryOp::Negate => Ok(Type::new(TypeKind::Primitive(PrimitiveType::Number), span)),
+            UnaryOp::Negate => {
+                Ok(self.numeric_type(type_utilities::is_integer_type(operand), span))
+            }
             UnaryOp::Not => Ok(Type::new(TypeKind::Primitive(PrimitiveType::Boolean), span)),
-            UnaryOp::Length => Ok(Type::new(TypeKind::Primitive(PrimitiveType::Number), span)),
-            UnaryOp::BitwiseNot => Ok(Type::new(TypeKind::Primitive(PrimitiveType::Number), span)),
+            UnaryOp::Length | UnaryOp::BitwiseNot => Ok(self.numeric_type(true, span)),
         }
     }
 
@@ -936,101 +1226,35 @@ impl<'a, 'arena> TypeInferenceVisitor<'arena> for TypeInferrer<'a, 'arena> {
 
         match &callee_type.kind {
             TypeKind::Function(func_type) => {
-                // Check argument count
-                let actual_args = args.len();
-                debug!(actual_args, "Checking function call argument count");
-
-                // Count required parameters (non-optional, non-rest, no default value)
-                let required_params = func_type
-                    .parameters
-                    .iter()
-                    .filter(|p| !p.is_rest && !p.is_optional && p.default.is_none())
-                    .count();
-
-                // Check if the last parameter is a rest parameter
-                let has_rest_param = func_type
-                    .parameters
-                    .last()
-                    .map(|p| p.is_rest)
-                    .unwrap_or(false);
-
-                // Count optional parameters (includes parameters with default values)
-                let optional_params = func_type
-                    .parameters
+                let arg_types = self.infer_argument_types(args);
+                self.check_call_signature(func_type, args, &arg_types, span)
+            }
+            // Overloaded declarations are an intersection of their signatures
+            TypeKind::Intersection(members) => {
+                let signatures: Vec<&FunctionType<'arena>> = members
                     .iter()
-                    .filter(|p| (p.is_optional || p.default.is_some()) && !p.is_rest)
-                    .count();
-
-                let max_params = if has_rest_param {
-                    usize::MAX
-                } else {
-                    required_params + optional_params
+                    .filter_map(|member| match &member.kind {
+                        TypeKind::Function(func_type) => Some(func_type),
+                        _ => None,
+                    })
+                    .collect();
+                let Some(last) = signatures.last().copied() else {
+                    return Ok(Type::new(TypeKind::Primitive(PrimitiveType::Unknown), span));
                 };
-
-                // Check minimum required arguments
-                if actual_args < required_params {
-                    error!(
-                        expected_min = required_params,
-                        actual = actual_args,
-                        "Too few arguments"
-                    );
-                    return Err(TypeCheckError::new(
-                        format!(
-                            "Function expects at least {} arguments but received {}",
-                            required_params, actual_args
-                        ),
-                        span,
-                    ));
-                }
-
-                // Check maximum allowed arguments (unless rest parameter)
-                if !has_rest_param && actual_args > max_params {
-                    error!(
-                        expected_max = max_params,
-                        actual = actual_args,
-                        "Too many arguments"
-                    );
-                    return Err(TypeCheckError::new(
-                        format!(
-                            "Function expects at most {} arguments but received {}",
-                            max_params, actual_args
-                        ),
-                        span,
-                    ));
+                if signatures.len() != members.len() {
+                    return Ok(Type::new(TypeKind::Primitive(PrimitiveType::Unknown), span));
                 }
 
-                // Check argument types match parameter types
-                for (i, arg) in args.iter().enumerate() {
-                    if i < func_type.parameters.len() {
-                        let param = &func_type.parameters[i];
-
-                        // Infer the argument type
-                        if let Ok(arg_type) = self.infer_expression(&arg.value) {
-                            if let Some(param_type) = &param.type_annotation {
-                                // Check if argument type is assignable to parameter type
-                                // Use is_assignable_with_env to properly resolve type aliases
-                                if !TypeCompatibility::is_assignable_with_env(
-                                    &arg_type,
-                                    param_type,
-                                    self.type_env,
-                                    self.interner,
-                                ) {
-                                    self.diagnostic_handler.error(
-                                        arg.value.span,
-                                        &format!(
-                                            "Type mismatch in function call: argument {} has type '{:?}' which is not assignable to parameter type '{:?}'",
-                                            i + 1,
-                                            arg_type.kind,
-                                            param_type.kind
-                                        ),
-                                    );
-                                }
-                            }
-                        }
-                    }
-                }
-
-                Ok((*func_type.return_type).clone())
+                // The first signature that accepts the arguments is used; when
+                // none does, the last one (by convention the most general)
+                // reports the mismatch
+                let arg_types = self.infer_argument_types(args);
+                let selected = signatures
+                    .iter()
+                    .copied()
+                    .find(|signature| self.accepts_arguments(signature, &arg_types))
+                    .unwrap_or(last);
+                self.check_call_signature(selected, args, &arg_types, span)
             }
             _ => {
                 // Non-function called - return unknown
@@ -1046,96 +1270,16 @@ impl<'a, 'arena> TypeInferenceVisitor<'arena> for TypeInferrer<'a, 'arena> {
         _args: &[Argument<'arena>],
         span: Span,
     ) -> Result<Type<'arena>, TypeCheckError> {
-        // Look up the method in the object type
-        match &obj_type.kind {
-            TypeKind::Object(obj) => {
-                for member in obj.members.iter() {
-                    if let ObjectTypeMember::Method(method) = member {
-                        if self.interner.resolve(method.name.node) == method_name {
-                            // Return the return type of the method
-                            return Ok(method.return_type.clone());
-                        }
-                    }
-                }
-                // Method not found - return unknown
-                Ok(Type::new(TypeKind::Primitive(PrimitiveType::Unknown), span))
-            }
-            TypeKind::Reference(type_ref) => {
-                let type_name = self.interner.resolve(type_ref.name.node);
-
-                // Handle generic class instantiation — substitute type params in return types
-                let type_args = type_ref.type_arguments.as_ref();
-                let class_params = self.class_type_params.get(&type_name);
-
-                if let Some(class_members) = self.access_control.get_class_members(&type_name) {
-                    // Try exact name match first
-                    for member in class_members {
-                        if member.name == method_name {
-                            match &member.kind {
-                                ClassMemberKind::Method { return_type, .. } => {
-                                    let rt = return_type.clone().unwrap_or_else(|| {
-                                        Type::new(TypeKind::Primitive(PrimitiveType::Unknown), span)
-                                    });
-                                    if let (Some(args), Some(params)) = (type_args, class_params) {
-                                        let instantiated =
-                                            instantiate_type(self.arena, &rt, params, args)
-                                                .map_err(|e| TypeCheckError::new(e, span))?;
-                                        return Ok(instantiated);
-                                    }
-                                    return Ok(rt);
-                                }
-                                ClassMemberKind::Getter { return_type } => {
-                                    if let (Some(args), Some(params)) = (type_args, class_params) {
-                                        let instantiated =
-                                            instantiate_type(self.arena, return_type, params, args)
-                                                .map_err(|e| TypeCheckError::new(e, span))?;
-                                        return Ok(instantiated);
-                                    }
-                                    return Ok(return_type.clone());
-                                }
-                                ClassMemberKind::Setter { .. } => {
-                                    return Ok(Type::new(
-                                        TypeKind::Primitive(PrimitiveType::Void),
-                                        span,
-                                    ));
-                                }
-                                _ => {}
-                            }
-                        }
-                    }
-                    // Codegen maps get_X() → getter X, set_X() → setter X
-                    if let Some(stripped) = method_name.strip_prefix("get_") {
-                        for member in class_members {
-                            if member.name == stripped {
-                                if let ClassMemberKind::Getter { return_type } = &member.kind {
-                                    if let (Some(args), Some(params)) = (type_args, class_params) {
-                                        let instantiated =
-                                            instantiate_type(self.arena, return_type, params, args)
-                                                .map_err(|e| TypeCheckError::new(e, span))?;
-                                        return Ok(instantiated);
-                                    }
-                                    return Ok(return_type.clone());
-                                }
-                            }
-                        }
-                    }
-                    if let Some(stripped) = method_name.strip_prefix("set_") {
-                        for member in class_members {
-                            if member.name == stripped {
-                                if let ClassMemberKind::Setter { .. } = &member.kind {
-                                    return Ok(Type::new(
-                                        TypeKind::Primitive(PrimitiveType::Void),
-                                        span,
-                                    ));
-                                }
-                            }
-                        }
-                    }
-                }
-                Ok(Type::new(TypeKind::Primitive(PrimitiveType::Unknown), span))
-            }
-            _ => Ok(Type::new(TypeKind::Primitive(PrimitiveType::Unknown), span)),
-        }
+        let return_type = self.lookup_method_return_type(obj_type, method_name, span)?;
+
+        // Polymorphic `Self` in a method signature refers to the receiver type
+        let self_name = self.interner.intern(SELF_TYPE_NAME);
+        Ok(substitute_self_type(
+            self.arena,
+            &return_type,
+            self_name,
+            obj_type,
+        ))
     }
 
     fn infer_member(
@@ -1196,6 +1340,16 @@ impl<'a, 'arena> TypeInferenceVisitor<'arena> for TypeInferrer<'a, 'arena> {
                     };
                 let member = effective_member;
 
+                // Open tables gain fields by assignment anywhere in the program, so
+                // reading a field that has not been seen yet is not an error
+                if let Some(resolved) = self.type_env.open_table(type_ref) {
+                    return Ok(self
+                        .infer_member(resolved, member, span)
+                        .unwrap_or_else(|_| {
+                            Type::new(TypeKind::Primitive(PrimitiveType::Unknown), span)
+                        }));
+                }
+
                 // Try to resolve the type reference to get the actual type
                 // Use lookup_type to check both type aliases and interfaces
                 if let Some(resolved) = self.type_env.lookup_type(&type_name) {
@@ -1263,10 +1417,7 @@ impl<'a, 'arena> TypeInferenceVisitor<'arena> for TypeInferrer<'a, 'arena> {
                         }
                         ObjectTypeMember::Method(method) => {
                             if method.name.node == member_id {
-                                return Ok(Type::new(
-                                    TypeKind::Primitive(PrimitiveType::Unknown),
-                                    span,
-                                ));
+                                return Ok(self.method_member_type(obj, member_id, span));
                             }
                         }
                         _ => {}
@@ -1305,6 +1456,20 @@ impl<'a, 'arena> TypeInferenceVisitor<'arena> for TypeInferrer<'a, 'arena> {
                 // For nullable types, look up member on the inner type
                 self.infer_member(inner, member, span)
             }
+            TypeKind::Intersection(types) => {
+                // The member comes from the first part that has it
+                let mut last_error = None;
+                for typ in types.iter() {
+                    match self.infer_member(typ, member, span) {
+                        Ok(member_type) => return Ok(member_type),
+                        Err(e) => last_error = Some(e),
+                    }
+                }
+                match last_error {
+                    Some(e) => Err(e),
+                    None => Ok(Type::new(TypeKind::Primitive(PrimitiveType::Unknown), span)),
+                }
+            }
             _ => {
                 // Non-object member access - return unknown
                 Ok(Type::new(TypeKind::Primitive(PrimitiveType::Unknown), span))
@@ -1730,6 +1895,1552 @@ impl<'a, 'arena> TypeInferenceVisitor<'arena> for TypeInferrer<'a, 'arena> {
 }
 
 impl<'a, 'arena> TypeInferrer<'a, 'arena> {
+    /// If `target` is a field of an open table (`M.field`), return the
+    /// reference naming the table, the table type and the field name.
+    fn open_table_member_target(
+        &mut self,
+        target: &Expression<'arena>,
+    ) -> Result<Option<(TypeReference<'arena>, Type<'arena>, String)>, TypeCheckError> {
+        let ExpressionKind::Member(object, member) = &target.kind else {
+            return Ok(None);
+        };
+        let obj_type = self.infer_expression(object)?;
+        if let TypeKind::Reference(type_ref) = &obj_type.kind {
+            if self.type_env.open_table(type_ref).is_some() {
+                let member_name = self.interner.resolve(member.node);
+                return Ok(Some((type_ref.clone(), obj_type.clone(), member_name)));
+            }
+        }
+        Ok(None)
+    }
+
+    /// Add `member` to the shape of an open table if it is not declared yet.
+    ///
+    /// Returns `false` when the member already exists, in which case the
+    /// assignment is checked against the existing field type.
+    fn extend_open_table(
+        &mut self,
+        table_ref: &TypeReference<'arena>,
+        member: &str,
+        value_type: &Type<'arena>,
+        span: Span,
+    ) -> bool {
+        let member_id = self.interner.intern(member);
+        let (members, shape_span) = match self.type_env.open_table(table_ref) {
+            Some(Type {
+                kind: TypeKind::Object(obj),
+                span: shape_span,
+                ..
+            }) => {
+                let exists = obj.members.iter().any(|m| match m {
+                    ObjectTypeMember::Property(prop) => prop.name.node == member_id,
+                    ObjectTypeMember::Method(method) => method.name.node == member_id,
+                    _ => false,
+                });
+                if exists {
+                    return false;
+                }
+                (obj.members.to_vec(), *shape_span)
+            }
+            _ => return false,
+        };
+
+        let mut members = members;
+        members.push(ObjectTypeMember::Property(PropertySignature {
+            is_readonly: false,
+            name: luanext_parser::ast::Spanned::new(member_id, span),
+            is_optional: false,
+            type_annotation: crate::helpers::type_utilities::widen_type(value_type.clone()),
+            span,
+        }));
+        let members = self.arena.alloc_slice_fill_iter(members);
+        self.type_env.register_open_table(
+            &table_ref.name,
+            Type::new(
+                TypeKind::Object(ObjectType {
+                    members,
+                    span: shape_span,
+                }),
+                shape_span,
+            ),
+        );
+        true
+    }
+
+    /// Infer an array literal in a const context as a tuple of its element types.
+    /// Returns `None` when the literal has spreads, whose length is unknown.
+    fn infer_const_tuple(
+        &mut self,
+        elements: &[ArrayElement<'arena>],
+        span: Span,
+    ) -> Result<Option<Type<'arena>>, TypeCheckError> {
+        let mut element_types = Vec::with_capacity(elements.len());
+        for elem in elements.iter() {
+            match elem {
+                ArrayElement::Expression(expr) => {
+                    element_types.push(self.infer_const_element(expr)?)
+                }
+                ArrayElement::Spread(_) => return Ok(None),
+            }
+        }
+        Ok(Some(Type::new(
+            TypeKind::Tuple(self.arena.alloc_slice_fill_iter(element_types)),
+            span,
+        )))
+    }
+
+    /// Infer an element of a literal in a const context. The context applies to
+    /// nested literals only, not to the operands of calls or other expressions.
+    fn infer_const_element(
+        &mut self,
+        expr: &Expression<'arena>,
+    ) -> Result<Type<'arena>, TypeCheckError> {
+        match &expr.kind {
+            ExpressionKind::Array(_)
+            | ExpressionKind::Object(_)
+            | ExpressionKind::Literal(_)
+            | ExpressionKind::Parenthesized(_) => self.infer_expression(expr),
+            _ => {
+                let saved = std::mem::replace(&mut self.const_context, false);
+                let result = self.infer_expression(expr);
+                self.const_context = saved;
+                result
+            }
+        }
+    }
+
+    /// The function named by a `library.function` callee, when `library` is
+    /// the stdlib namespace of that name rather than a shadowing local
+    fn library_function(&self, callee: &Expression<'arena>, library: &str) -> Option<String> {
+        let ExpressionKind::Member(object, member) = &callee.kind else {
+            return None;
+        };
+        let ExpressionKind::Identifier(name) = &object.kind else {
+            return None;
+        };
+        let is_library = self.interner.resolve(*name) == library
+            && self
+                .symbol_table
+                .lookup(library)
+                .is_some_and(|symbol| symbol.kind == SymbolKind::Const);
+        is_library.then(|| self.interner.resolve(member.node))
+    }
+
+    /// Type a `string` library call made on `subject`, either as
+    /// `string.f(subject, ...)` or `subject:f(...)`. Returns `None` when the
+    /// stdlib declaration types the call as well as a literal argument could.
+    fn infer_string_call(
+        &mut self,
+        function: &str,
+        subject: &Expression<'arena>,
+        args: &[Argument<'arena>],
+        span: Span,
+    ) -> Result<Option<Type<'arena>>, TypeCheckError> {
+        use crate::cli::diagnostics::{error_codes, Diagnostic};
+
+        if function == "format" {
+            self.check_format_arguments(subject, args)?;
+            return Ok(Some(Type::new(
+                TypeKind::Primitive(PrimitiveType::String),
+                span,
+            )));
+        }
+        if !matches!(function, "match" | "gmatch" | "find" | "gsub") {
+            return Ok(None);
+        }
+        let Some((pattern, rest)) = args.split_first() else {
+            return Ok(None);
+        };
+        let ExpressionKind::Literal(Literal::String(literal)) = &pattern.value.kind else {
+            return Ok(None);
+        };
+
+        self.infer_expression(subject)?;
+        // `string.find(s, p, init, true)` matches `p` literally
+        let plain = function == "find"
+            && matches!(
+                rest.get(1).map(|arg| &arg.value.kind),
+                Some(ExpressionKind::Literal(Literal::Boolean(true)))
+            );
+        let captures = if plain {
+            Vec::new()
+        } else {
+            match lua_pattern::parse_pattern(literal) {
+                Ok(captures) => captures,
+                Err(message) => {
+                    self.diagnostic_handler.report(Diagnostic::error_with_code(
+                        pattern.value.span,
+                        error_codes::INVALID_PATTERN,
+                        format!("Invalid pattern: {}", message),
+                    ));
+                    for arg in rest {
+                        self.infer_expression(&arg.value)?;
+                    }
+                    return Ok(Some(Type::new(
+                        TypeKind::Primitive(PrimitiveType::Unknown),
+                        span,
+                    )));
+                }
+            }
+        };
+
+        if function == "gsub" {
+            if let Some((replacement, rest)) = rest.split_first() {
+                self.check_pattern_replacement(&replacement.value, &captures)?;
+                for arg in rest {
+                    self.infer_expression(&arg.value)?;
+                }
+            }
+            let elements = self.arena.alloc_slice_fill_iter([
+                Type::new(TypeKind::Primitive(PrimitiveType::String), span),
+                self.numeric_type(true, span),
+            ]);
+            return Ok(Some(Type::new(TypeKind::Tuple(elements), span)));
+        }
+        for arg in rest {
+            self.infer_expression(&arg.value)?;
+        }
+
+        // Without captures a match produces the whole matched substring
+        let mut values: Vec<Type<'arena>> = if captures.is_empty() && function != "find" {
+            vec![Type::new(TypeKind::Primitive(PrimitiveType::String), span)]
+        } else {
+            captures
+                .iter()
+                .map(|capture| self.capture_type(*capture, span))
+                .collect()
+        };
+
+        let result = match function {
+            "gmatch" => {
+                // The iterator ends the loop with `nil`, so inside a generic
+                // `for` its values are never nil
+                let return_type = self.pack_pattern_values(values, span);
+                Type::new(
+                    TypeKind::Function(FunctionType {
+                        type_parameters: None,
+                        parameters: &[],
+                        return_type: self.arena.alloc(return_type),
+                        throws: None,
+                        span,
+                    }),
+                    span,
+                )
+            }
+            _ => {
+                if function == "find" {
+                    let position = self.numeric_type(true, span);
+                    values.insert(0, position.clone());
+                    values.insert(0, position);
+                }
+                let values = values
+                    .into_iter()
+                    .map(|value| self.make_optional(value, span))
+                    .collect::<Result<Vec<_>, _>>()?;
+                self.pack_pattern_values(values, span)
+            }
+        };
+        Ok(Some(result))
+    }
+
+    /// The type of a value captured by a pattern
+    fn capture_type(&self, capture: CaptureKind, span: Span) -> Type<'arena> {
+        match capture {
+            CaptureKind::Substring => Type::new(TypeKind::Primitive(PrimitiveType::String), span),
+            CaptureKind::Position => self.numeric_type(true, span),
+        }
+    }
+
+    /// A single value as itself, several as a tuple
+    fn pack_pattern_values(&self, mut values: Vec<Type<'arena>>, span: Span) -> Type<'arena> {
+        if values.len() == 1 {
+            return values.remove(0);
+        }
+        Type::new(
+            TypeKind::Tuple(self.arena.alloc_slice_fill_iter(values)),
+            span,
+        )
+    }
+
+    /// Check a `gsub` replacement against the pattern's captures: `%n` in a
+    /// literal string must name a capture, and a function may take at most
+    /// one parameter per capture (the whole match when there are none)
+    fn check_pattern_replacement(
+        &mut self,
+        replacement: &Expression<'arena>,
+        captures: &[CaptureKind],
+    ) -> Result<(), TypeCheckError> {
+        use crate::cli::diagnostics::{error_codes, Diagnostic};
+
+        if let ExpressionKind::Literal(Literal::String(literal)) = &replacement.kind {
+            if let Err(message) =
+                lua_pattern::check_replacement(literal, captures.len(), self.target)
+            {
+                self.diagnostic_handler.report(Diagnostic::error_with_code(
+                    replacement.span,
+                    error_codes::INVALID_PATTERN,
+                    format!("Invalid replacement: {}", message),
+                ));
+            }
+            return Ok(());
+        }
+
+        let replacement_type = self.infer_expression(replacement)?;
+        let TypeKind::Function(func) = &replacement_type.kind else {
+            return Ok(());
+        };
+        let capture_types: Vec<Type<'arena>> = if captures.is_empty() {
+            vec![Type::new(
+                TypeKind::Primitive(PrimitiveType::String),
+                replacement.span,
+            )]
+        } else {
+            captures
+                .iter()
+                .map(|capture| self.capture_type(*capture, replacement.span))
+                .collect()
+        };
+
+        let fixed_params = func.parameters.iter().filter(|p| !p.is_rest).count();
+        if fixed_params > capture_types.len() {
+            self.diagnostic_handler.report(Diagnostic::error_with_code(
+                replacement.span,
+                error_codes::WRONG_ARG_COUNT,
+                format!(
+                    "Replacement function takes {} parameters but the pattern produces {} captures",
+                    fixed_params,
+                    capture_types.len()
+                ),
+            ));
+        }
+        for (param, capture_type) in func.parameters.iter().zip(&capture_types) {
+            let Some(param_type) = param.type_annotation.as_ref().filter(|_| !param.is_rest) else {
+                continue;
+            };
+            if !TypeCompatibility::is_assignable_with_env(
+                capture_type,
+                param_type,
+                self.type_env,
+                self.interner,
+            ) {
+                self.diagnostic_handler.report(Diagnostic::error_with_code(
+                    param.span,
+                    error_codes::TYPE_MISMATCH,
+                    format!(
+                        "Replacement parameter of type '{:?}' cannot accept a capture of type '{:?}'",
+                        param_type.kind, capture_type.kind
+                    ),
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    /// Infer the type of each argument of a call, `None` where it fails
+    fn infer_argument_types(&mut self, args: &[Argument<'arena>]) -> Vec<Option<Type<'arena>>> {
+        args.iter()
+            .map(|arg| self.infer_expression(&arg.value).ok())
+            .collect()
+    }
+
+    /// The type an argument at `index` must have, with a rest parameter's
+    /// array annotation unwrapped to its element type
+    fn argument_parameter_type<'p>(
+        parameters: &'p [Parameter<'arena>],
+        index: usize,
+    ) -> Option<&'p Type<'arena>> {
+        let param = parameters
+            .get(index)
+            .filter(|param| !param.is_rest)
+            .or_else(|| parameters.last().filter(|param| param.is_rest))?;
+        let param_type = param.type_annotation.as_ref()?;
+        match &param_type.kind {
+            TypeKind::Array(element) if param.is_rest => Some(&**element),
+            _ => Some(param_type),
+        }
+    }
+
+    /// Whether a call with arguments of `arg_types` matches `signature`, used
+    /// to pick among overloads
+    fn accepts_arguments(
+        &self,
+        signature: &FunctionType<'arena>,
+        arg_types: &[Option<Type<'arena>>],
+    ) -> bool {
+        let params = signature.parameters;
+        let required = params
+            .iter()
+            .filter(|p| !p.is_rest && !p.is_optional && p.default.is_none())
+            .count();
+        let has_rest = params.last().is_some_and(|p| p.is_rest);
+        if arg_types.len() < required || (!has_rest && arg_types.len() > params.len()) {
+            return false;
+        }
+        arg_types.iter().enumerate().all(|(i, arg_type)| {
+            match (arg_type, Self::argument_parameter_type(params, i)) {
+                (Some(arg_type), Some(param_type)) => TypeCompatibility::is_assignable_with_env(
+                    arg_type,
+                    param_type,
+                    self.type_env,
+                    self.interner,
+                ),
+                _ => true,
+            }
+        })
+    }
+
+    /// Check a call's arguments against `func_type` and return its result
+    fn check_call_signature(
+        &mut self,
+        func_type: &FunctionType<'arena>,
+        args: &[Argument<'arena>],
+        arg_types: &[Option<Type<'arena>>],
+        span: Span,
+    ) -> Result<Type<'arena>, TypeCheckError> {
+        // Check argument count
+        let actual_args = args.len();
+        debug!(actual_args, "Checking function call argument count");
+
+        // Count required parameters (non-optional, non-rest, no default value)
+        let required_params = func_type
+            .parameters
+            .iter()
+            .filter(|p| !p.is_rest && !p.is_optional && p.default.is_none())
+            .count();
+
+        // Check if the last parameter is a rest parameter
+        let has_rest_param = func_type
+            .parameters
+            .last()
+            .map(|p| p.is_rest)
+            .unwrap_or(false);
+
+        // Count optional parameters (includes parameters with default values)
+        let optional_params = func_type
+            .parameters
+            .iter()
+            .filter(|p| (p.is_optional || p.default.is_some()) && !p.is_rest)
+            .count();
+
+        let max_params = if has_rest_param {
+            usize::MAX
+        } else {
+            required_params + optional_params
+        };
+
+        // Check minimum required arguments
+        if actual_args < required_params {
+            error!(
+                expected_min = required_params,
+                actual = actual_args,
+                "Too few arguments"
+            );
+            return Err(TypeCheckError::new(
+                format!(
+                    "Function expects at least {} arguments but received {}",
+                    required_params, actual_args
+                ),
+                span,
+            ));
+        }
+
+        // Check maximum allowed arguments (unless rest parameter)
+        if !has_rest_param && actual_args > max_params {
+            error!(
+                expected_max = max_params,
+                actual = actual_args,
+                "Too many arguments"
+            );
+            return Err(TypeCheckError::new(
+                format!(
+                    "Function expects at most {} arguments but received {}",
+                    max_params, actual_args
+                ),
+                span,
+            ));
+        }
+
+        // Check argument types match parameter types
+        for (i, (arg, arg_type)) in args.iter().zip(arg_types).enumerate() {
+            let (Some(arg_type), Some(param_type)) = (
+                arg_type,
+                Self::argument_parameter_type(func_type.parameters, i),
+            ) else {
+                continue;
+            };
+            // Check if argument type is assignable to parameter type
+            // Use is_assignable_with_env to properly resolve type aliases
+            if !TypeCompatibility::is_assignable_with_env(
+                arg_type,
+                param_type,
+                self.type_env,
+                self.interner,
+            ) {
+                self.diagnostic_handler.error(
+                    arg.value.span,
+                    &format!(
+                        "Type mismatch in function call: argument {} has type '{:?}' which is not assignable to parameter type '{:?}'",
+                        i + 1,
+                        arg_type.kind,
+                        param_type.kind
+                    ),
+                );
+            }
+        }
+
+        // A `FormatString` parameter checks the arguments after it
+        if let Some(index) = func_type
+            .parameters
+            .iter()
+            .position(|param| self.is_format_string_parameter(param))
+        {
+            if let Some(format) = args.get(index) {
+                self.check_format_argument_types(
+                    &format.value,
+                    &args[index + 1..],
+                    arg_types.get(index + 1..).unwrap_or_default(),
+                );
+            }
+        }
+
+        Ok((*func_type.return_type).clone())
+    }
+
+    /// Whether a parameter is declared as a `FormatString`
+    fn is_format_string_parameter(&self, param: &Parameter<'arena>) -> bool {
+        matches!(
+            &param.type_annotation,
+            Some(Type { kind: TypeKind::Reference(type_ref), .. })
+                if self.interner.resolve(type_ref.name.node) == "FormatString"
+        )
+    }
+
+    /// Infer the arguments of a `string.format` call and check them against
+    /// its format string
+    fn check_format_arguments(
+        &mut self,
+        format: &Expression<'arena>,
+        args: &[Argument<'arena>],
+    ) -> Result<(), TypeCheckError> {
+        if !matches!(format.kind, ExpressionKind::Literal(Literal::String(_))) {
+            self.infer_expression(format)?;
+        }
+        let arg_types = args
+            .iter()
+            .map(|arg| self.infer_expression(&arg.value).map(Some))
+            .collect::<Result<Vec<_>, _>>()?;
+        self.check_format_argument_types(format, args, &arg_types);
+        Ok(())
+    }
+
+    /// Check the already inferred arguments of a `string.format`-style call
+    /// against the conversions of a literal format string. A non-literal
+    /// format string leaves the arguments unchecked.
+    fn check_format_argument_types(
+        &self,
+        format: &Expression<'arena>,
+        args: &[Argument<'arena>],
+        arg_types: &[Option<Type<'arena>>],
+    ) {
+        use crate::cli::diagnostics::{error_codes, Diagnostic};
+
+        let ExpressionKind::Literal(Literal::String(literal)) = &format.kind else {
+            return;
+        };
+
+        let conversions = match format_string::parse_format(literal, self.target) {
+            Ok(conversions) => conversions,
+            Err(message) => {
+                self.diagnostic_handler.report(Diagnostic::error_with_code(
+                    format.span,
+                    error_codes::INVALID_FORMAT_STRING,
+                    format!("Invalid format string: {}", message),
+                ));
+                return;
+            }
+        };
+
+        // A trailing call may expand to several values
+        let open_ended = args.last().is_some_and(|arg| {
+            matches!(
+                arg.value.kind,
+                ExpressionKind::Call(..) | ExpressionKind::MethodCall(..)
+            )
+        });
+        if args.len() > conversions.len() || (args.len() < conversions.len() && !open_ended) {
+            self.diagnostic_handler.report(Diagnostic::error_with_code(
+                format.span,
+                error_codes::WRONG_ARG_COUNT,
+                format!(
+                    "Format string expects {} arguments but received {}",
+                    conversions.len(),
+                    args.len()
+                ),
+            ));
+        }
+
+        for ((arg, arg_type), conversion) in args.iter().zip(arg_types).zip(&conversions) {
+            let Some(arg_type) = arg_type else {
+                continue;
+            };
+            let Some(expected) =
+                conversion
+                    .argument
+                    .expected_type(self.arena, self.target, arg.value.span)
+            else {
+                continue;
+            };
+            if !TypeCompatibility::is_assignable_with_env(
+                arg_type,
+                &expected,
+                self.type_env,
+                self.interner,
+            ) {
+                self.diagnostic_handler.report(Diagnostic::error_with_code(
+                    arg.value.span,
+                    error_codes::TYPE_MISMATCH,
+                    format!(
+                        "Argument for '%{}' has type '{:?}' which is not assignable to '{:?}'",
+                        conversion.specifier, arg_type.kind, expected.kind
+                    ),
+                ));
+            }
+        }
+    }
+
+    /// Type `pcall(f, ...)` and `xpcall(f, handler, ...)` as the union of a
+    /// success tuple `[true, ...R]` and a failure tuple `[false, E]`, checking
+    /// the arguments against `f`. `E` is `f`'s `throws` type, or `string`; for
+    /// `xpcall` it is whatever the message handler returns.
+    fn infer_protected_call(
+        &mut self,
+        callee: &Expression<'arena>,
+        args: &[Argument<'arena>],
+        span: Span,
+    ) -> Result<Option<Type<'arena>>, TypeCheckError> {
+        let ExpressionKind::Identifier(name) = &callee.kind else {
+            return Ok(None);
+        };
+        let name = self.interner.resolve(*name);
+        let handlers = match name.as_str() {
+            "pcall" => 0,
+            "xpcall" => 1,
+            _ => return Ok(None),
+        };
+        let is_builtin = self
+            .symbol_table
+            .lookup(&name)
+            .is_some_and(|symbol| symbol.kind == SymbolKind::Function);
+        if !is_builtin || args.len() < 1 + handlers {
+            return Ok(None);
+        }
+
+        let func_type = self.infer_expression(&args[0].value)?;
+        let TypeKind::Function(func) = &func_type.kind else {
+            return Ok(None);
+        };
+        let return_type = self.infer_call(&func_type, &args[1 + handlers..], span)?;
+
+        let mut error_type = match func.throws {
+            Some(throws) if !throws.is_empty() => {
+                type_utilities::canonicalize_union(throws.to_vec(), span, self.arena)
+            }
+            _ => Type::new(TypeKind::Primitive(PrimitiveType::String), span),
+        };
+        if handlers == 1 {
+            error_type = self.check_message_handler(&args[1].value, &error_type)?;
+        }
+
+        let boolean = |value| Type::new(TypeKind::Literal(Literal::Boolean(value)), span);
+        let mut success = vec![boolean(true)];
+        match &return_type.kind {
+            TypeKind::Tuple(values) => success.extend(values.iter().cloned()),
+            TypeKind::Primitive(PrimitiveType::Void) => {}
+            _ => success.push(return_type),
+        }
+        let failure = [boolean(false), error_type];
+        let outcomes = self.arena.alloc_slice_fill_iter([
+            Type::new(
+                TypeKind::Tuple(self.arena.alloc_slice_fill_iter(success)),
+                span,
+            ),
+            Type::new(
+                TypeKind::Tuple(self.arena.alloc_slice_fill_iter(failure)),
+                span,
+            ),
+        ]);
+        Ok(Some(Type::new(TypeKind::Union(outcomes), span)))
+    }
+
+    /// Check an `xpcall` message handler against the error it receives and
+    /// return the type of what it produces in place of the error
+    fn check_message_handler(
+        &mut self,
+        handler: &Expression<'arena>,
+        error_type: &Type<'arena>,
+    ) -> Result<Type<'arena>, TypeCheckError> {
+        use crate::cli::diagnostics::{error_codes, Diagnostic};
+
+        let handler_type = self.infer_expression(handler)?;
+        let TypeKind::Function(func) = &handler_type.kind else {
+            return Ok(Type::new(
+                TypeKind::Primitive(PrimitiveType::Unknown),
+                handler.span,
+            ));
+        };
+
+        let required = func
+            .parameters
+            .iter()
+            .filter(|p| !p.is_rest && !p.is_optional && p.default.is_none())
+            .count();
+        if required > 1 {
+            self.diagnostic_handler.report(Diagnostic::error_with_code(
+                handler.span,
+                error_codes::WRONG_ARG_COUNT,
+                format!(
+                    "Message handler is called with the error only but requires {} arguments",
+                    required
+                ),
+            ));
+        }
+        if let Some(param_type) = func
+            .parameters
+            .first()
+            .filter(|p| !p.is_rest)
+            .and_then(|p| p.type_annotation.as_ref())
+        {
+            if !TypeCompatibility::is_assignable_with_env(
+                error_type,
+                param_type,
+                self.type_env,
+                self.interner,
+            ) {
+                self.diagnostic_handler.report(Diagnostic::error_with_code(
+                    handler.span,
+                    error_codes::TYPE_MISMATCH,
+                    format!(
+                        "Message handler parameter of type '{:?}' cannot accept the error of type '{:?}'",
+                        param_type.kind, error_type.kind
+                    ),
+                ));
+            }
+        }
+        Ok((*func.return_type).clone())
+    }
+
+    /// Type `Reflect.getFields`, `getOwnFields`, `getMethods` and
+    /// `getOwnMethods` from the static type of their argument: each
+    /// descriptor's `name` is a member name of that type. Returns `None` when
+    /// the argument is not a class, interface or object type.
+    fn infer_reflect_call(
+        &mut self,
+        callee: &Expression<'arena>,
+        args: &[Argument<'arena>],
+        span: Span,
+    ) -> Result<Option<Type<'arena>>, TypeCheckError> {
+        let Some(function) = self.library_function(callee, reflection::REFLECT_NAMESPACE) else {
+            return Ok(None);
+        };
+        let (methods, own_only) = match function.as_str() {
+            "getFields" => (false, false),
+            "getOwnFields" => (false, true),
+            "getMethods" => (true, false),
+            "getOwnMethods" => (true, true),
+            _ => return Ok(None),
+        };
+        let [subject] = args else {
+            return Ok(None);
+        };
+
+        let subject_type = self.infer_expression(&subject.value)?;
+        let names = match &subject_type.kind {
+            TypeKind::Reference(type_ref) => reflection::member_names(
+                &self.interner.resolve(type_ref.name.node),
+                methods,
+                own_only,
+                self.type_env,
+                self.access_control,
+                self.interner,
+            ),
+            TypeKind::Object(obj) => {
+                let mut names: Vec<String> = Vec::new();
+                for member in obj.members.iter() {
+                    let name = match member {
+                        ObjectTypeMember::Property(prop)
+                            if !methods
+                                && !matches!(prop.type_annotation.kind, TypeKind::Function(_)) =>
+                        {
+                            self.interner.resolve(prop.name.node)
+                        }
+                        ObjectTypeMember::Property(prop)
+                            if methods
+                                && matches!(prop.type_annotation.kind, TypeKind::Function(_)) =>
+                        {
+                            self.interner.resolve(prop.name.node)
+                        }
+                        ObjectTypeMember::Method(method) if methods => {
+                            self.interner.resolve(method.name.node)
+                        }
+                        _ => continue,
+                    };
+                    if !names.contains(&name) {
+                        names.push(name);
+                    }
+                }
+                Some(names)
+            }
+            _ => None,
+        };
+        let Some(names) = names else {
+            return Ok(None);
+        };
+
+        // The descriptor of one member: `{ name: "a" | "b", typeName: string, isStatic: boolean }`
+        let mut literals: Vec<Type<'arena>> = names
+            .into_iter()
+            .map(|name| Type::new(TypeKind::Literal(Literal::String(name)), span))
+            .collect();
+        let name_type = match literals.len() {
+            0 => Type::new(TypeKind::Primitive(PrimitiveType::Never), span),
+            1 => literals.pop().unwrap(),
+            _ => Type::new(
+                TypeKind::Union(self.arena.alloc_slice_fill_iter(literals)),
+                span,
+            ),
+        };
+        let property = |name: &str, type_annotation: Type<'arena>| {
+            ObjectTypeMember::Property(PropertySignature {
+                is_readonly: true,
+                name: luanext_parser::ast::Spanned::new(self.interner.intern(name), span),
+                is_optional: false,
+                type_annotation,
+                span,
+            })
+        };
+        let members = [
+            property("name", name_type),
+            property(
+                "typeName",
+                Type::new(TypeKind::Primitive(PrimitiveType::String), span),
+            ),
+            property(
+                "isStatic",
+                Type::new(TypeKind::Primitive(PrimitiveType::Boolean), span),
+            ),
+        ];
+        let descriptor = Type::new(
+            TypeKind::Object(ObjectType {
+                members: self.arena.alloc_slice_fill_iter(members),
+                span,
+            }),
+            span,
+        );
+        Ok(Some(Type::new(
+            TypeKind::Array(self.arena.alloc(descriptor)),
+            span,
+        )))
+    }
+
+    /// Type calls into the `coroutine` library that involve a typed coroutine.
+    /// Returns `None` for calls the stdlib declarations already describe.
+    fn infer_coroutine_call(
+        &mut self,
+        callee: &Expression<'arena>,
+        args: &[Argument<'arena>],
+        span: Span,
+    ) -> Result<Option<Type<'arena>>, TypeCheckError> {
+        let Some(function) = self.library_function(callee, "coroutine") else {
+            return Ok(None);
+        };
+        match function.as_str() {
+            "create" | "wrap" => {
+                let [body] = args else {
+                    return Ok(None);
+                };
+                let body_type = self.infer_expression(&body.value)?;
+                let TypeKind::Function(func) = &body_type.kind else {
+                    return Ok(None);
+                };
+                let signature = CoroutineSignature::for_body(
+                    self.arena,
+                    func.parameters,
+                    (*func.return_type).clone(),
+                    span,
+                );
+                if function == "create" {
+                    return Ok(Some(signature.to_type(self.arena, self.interner, span)));
+                }
+                // `wrap` returns a function that resumes the coroutine and
+                // returns what it yields or, at the end, returns
+                let output = signature.output_type(self.arena, span);
+                Ok(Some(Type::new(
+                    TypeKind::Function(FunctionType {
+                        type_parameters: None,
+                        parameters: func.parameters,
+                        return_type: self.arena.alloc(output),
+                        throws: None,
+                        span,
+                    }),
+                    span,
+                )))
+            }
+            "resume" => {
+                let Some((co, values)) = args.split_first() else {
+                    return Ok(None);
+                };
+                let co_type = self.infer_expression(&co.value)?;
+                let Some(signature) = CoroutineSignature::from_type(&co_type, self.interner) else {
+                    return Ok(None);
+                };
+                self.check_coroutine_values(&signature.resume_type, values, "coroutine.resume")?;
+                Ok(Some(signature.resume_result(self.arena, span)))
+            }
+            "yield" => {
+                let Some(signature) = self.coroutine else {
+                    return Ok(None);
+                };
+                self.check_coroutine_values(&signature.yield_type, args, "coroutine.yield")?;
+                Ok(Some(signature.resume_type.clone()))
+            }
+            _ => Ok(None),
+        }
+    }
+
+    /// Check the values passed to `coroutine.resume` or `coroutine.yield`
+    /// against the packed type the coroutine expects
+    fn check_coroutine_values(
+        &mut self,
+        expected: &Type<'arena>,
+        args: &[Argument<'arena>],
+        function: &str,
+    ) -> Result<(), TypeCheckError> {
+        let Some(expected_values) = coroutine::unpack_values(expected) else {
+            for arg in args {
+                self.infer_expression(&arg.value)?;
+            }
+            return Ok(());
+        };
+        if args.len() > expected_values.len() {
+            return Err(TypeCheckError::new(
+                format!(
+                    "{} expects at most {} values for this coroutine but received {}",
+                    function,
+                    expected_values.len(),
+                    args.len()
+                ),
+                args[expected_values.len()].value.span,
+            ));
+        }
+        for (arg, expected_type) in args.iter().zip(expected_values) {
+            let arg_type = self.infer_expression(&arg.value)?;
+            if !TypeCompatibility::is_assignable_with_env(
+                &arg_type,
+                expected_type,
+                self.type_env,
+                self.interner,
+            ) {
+                self.diagnostic_handler.error(
+                    arg.value.span,
+                    &format!(
+                        "Value passed to {} has type '{:?}' which is not assignable to '{:?}'",
+                        function, arg_type.kind, expected_type.kind
+                    ),
+                );
+            }
+        }
+        Ok(())
+    }
+
+    /// Replace the stdlib's `CLibrary` placeholder (`ffi.C`, `ffi.load(...)`)
+    /// with the functions and variables declared through `ffi.cdef`
+    fn expand_c_library(&self, typ: Type<'arena>, span: Span) -> Type<'arena> {
+        if ffi::is_c_library(&typ, self.interner) {
+            self.c_declarations
+                .library_type(self.arena, self.interner, span)
+        } else {
+            typ
+        }
+    }
+
+    /// The type of `require("ffi")`, `require("bit")` or `require("jit")`,
+    /// which load the modules LuaJIT declares as globals
+    fn builtin_module(
+        &self,
+        callee: &Expression<'arena>,
+        args: &[Argument<'arena>],
+    ) -> Option<Type<'arena>> {
+        let module = self.require_source(callee, args)?;
+        if !matches!(module, "ffi" | "bit" | "jit") {
+            return None;
+        }
+        self.symbol_table
+            .lookup(module)
+            .filter(|symbol| symbol.kind == SymbolKind::Const)
+            .map(|symbol| symbol.typ.clone())
+    }
+
+    /// The export type of the module a `require` call loads, resolved like an
+    /// import source, recording the module as a value dependency. Returns
+    /// `None` when the module is not part of the compilation.
+    fn required_module(
+        &self,
+        callee: &Expression<'arena>,
+        args: &[Argument<'arena>],
+        span: Span,
+    ) -> Option<Type<'arena>> {
+        let source = self.require_source(callee, args)?;
+        module_phase::resolve_require_type(
+            source,
+            span,
+            &mut self.required_modules.borrow_mut(),
+            self.module_registry,
+            self.module_resolver,
+            self.current_module_id,
+            self.lazy_callback,
+            self.arena,
+            self.interner,
+            self.reported_plain_lua_requires,
+            self.diagnostic_handler,
+        )
+    }
+
+    /// The module name of a `require("name")` call with a string literal argument
+    fn require_source<'e>(
+        &self,
+        callee: &Expression<'arena>,
+        args: &'e [Argument<'arena>],
+    ) -> Option<&'e str> {
+        let ExpressionKind::Identifier(name) = &callee.kind else {
+            return None;
+        };
+        if self.interner.resolve(*name) != "require" || args.len() != 1 {
+            return None;
+        }
+        match &args[0].value.kind {
+            ExpressionKind::Literal(Literal::String(module)) => Some(module.as_str()),
+            _ => None,
+        }
+    }
+
+    /// Type `ffi.new`, `ffi.cast` and `ffi.typeof` calls whose first argument
+    /// is a C type name literal. Returns `None` for any other call.
+    fn infer_ffi_call(
+        &mut self,
+        callee: &Expression<'arena>,
+        args: &[Argument<'arena>],
+        span: Span,
+    ) -> Result<Option<Type<'arena>>, TypeCheckError> {
+        let ExpressionKind::Member(object, member) = &callee.kind else {
+            return Ok(None);
+        };
+        let function = self.interner.resolve(member.node);
+        if !matches!(function.as_str(), "new" | "cast" | "typeof") {
+            return Ok(None);
+        }
+        let Some((type_arg, init)) = args.split_first() else {
+            return Ok(None);
+        };
+        let ExpressionKind::Literal(Literal::String(type_name)) = &type_arg.value.kind else {
+            return Ok(None);
+        };
+        let object_type = self.infer_expression(object)?;
+        if !ffi::is_ffi_namespace(&object_type, self.interner) {
+            return Ok(None);
+        }
+
+        let type_span = type_arg.value.span;
+        let ctype = self
+            .c_declarations
+            .parse_type_name(type_name)
+            .map_err(|e| {
+                TypeCheckError::new(format!("Invalid C type '{}': {}", type_name, e), type_span)
+            })?;
+        if let Some(tag) = self.c_declarations.undefined_record(&ctype) {
+            return Err(TypeCheckError::new(
+                format!(
+                    "C type '{}' has no definition; declare its fields with ffi.cdef",
+                    tag
+                ),
+                type_span,
+            ));
+        }
+
+        match function.as_str() {
+            "typeof" => Ok(Some(self.c_declarations.ctype_constructor(
+                &ctype,
+                self.arena,
+                self.interner,
+                span,
+            ))),
+            "new" => {
+                self.check_cdata_initializers(&ctype, init)?;
+                Ok(Some(self.c_declarations.cdata_type(
+                    &ctype,
+                    self.arena,
+                    self.interner,
+                    span,
+                )))
+            }
+            _ => {
+                for arg in init {
+                    self.infer_expression(&arg.value)?;
+                }
+                Ok(Some(self.c_declarations.cdata_type(
+                    &ctype,
+                    self.arena,
+                    self.interner,
+                    span,
+                )))
+            }
+        }
+    }
+
+    /// Check the positional initializers of `ffi.new` against the fields of a struct
+    fn check_cdata_initializers(
+        &mut self,
+        ctype: &CType,
+        init: &[Argument<'arena>],
+    ) -> Result<(), TypeCheckError> {
+        let declarations = self.c_declarations;
+        let fields = match ctype {
+            CType::Record(tag) => declarations.record_fields(tag),
+            _ => None,
+        };
+        // A single table initializes fields by name or position, which LuaJIT
+        // checks at runtime; arrays and scalars take any initializer
+        let table_initializer = matches!(
+            init,
+            [single] if matches!(single.value.kind, ExpressionKind::Object(_) | ExpressionKind::Array(_))
+        );
+        let Some(fields) = fields.filter(|_| !table_initializer) else {
+            for arg in init {
+                self.infer_expression(&arg.value)?;
+            }
+            return Ok(());
+        };
+
+        if init.len() > fields.len() {
+            return Err(TypeCheckError::new(
+                format!(
+                    "Too many initializers: C type has {} fields but {} values were given",
+                    fields.len(),
+                    init.len()
+                ),
+                init[fields.len()].value.span,
+            ));
+        }
+        for (arg, field) in init.iter().zip(fields) {
+            let arg_type = self.infer_expression(&arg.value)?;
+            let field_type =
+                declarations.cdata_type(&field.ctype, self.arena, self.interner, arg.value.span);
+            if !TypeCompatibility::is_assignable_with_env(
+                &arg_type,
+                &field_type,
+                self.type_env,
+                self.interner,
+            ) {
+                self.diagnostic_handler.error(
+                    arg.value.span,
+                    &format!(
+                        "Initializer for field '{}' has type '{:?}' which is not assignable to '{:?}'",
+                        field.name, arg_type.kind, field_type.kind
+                    ),
+                );
+            }
+        }
+        Ok(())
+    }
+
+    /// Report a construct that does not exist on the configured Lua target
+    fn report_unsupported_feature(&self, span: Span, message: String) {
+        use crate::cli::diagnostics::{error_codes, Diagnostic};
+
+        self.diagnostic_handler.report(Diagnostic::error_with_code(
+            span,
+            error_codes::UNSUPPORTED_FEATURE,
+            message,
+        ));
+    }
+
+    /// The result type of a numeric operation: `integer` when the operation
+    /// yields an integer and the target has an integer subtype (Lua 5.3+),
+    /// `number` otherwise
+    fn numeric_type(&self, integer: bool, span: Span) -> Type<'arena> {
+        let prim = if integer && self.target.has_integer_subtype() {
+            PrimitiveType::Integer
+        } else {
+            PrimitiveType::Number
+        };
+        Type::new(TypeKind::Primitive(prim), span)
+    }
+
+    /// The underlying type of a branded type or of an alias naming one
+    fn brand_base_of(&self, typ: &Type<'arena>) -> Option<Type<'arena>> {
+        let resolved = match &typ.kind {
+            TypeKind::Reference(type_ref) if type_ref.type_arguments.is_none() => self
+                .type_env
+                .lookup_type(&self.interner.resolve(type_ref.name.node))?,
+            _ => typ,
+        };
+        crate::types::utility_types::brand_base(self.arena, resolved, self.interner)
+    }
+
+    /// Model `setmetatable(t, mt)` for Lua-style classes.
+    ///
+    /// When `mt.__index` is an open table the instance is typed as `t` intersected
+    /// with that table, so methods added to the table later in the program still
+    /// resolve on instances while the table itself does not gain the fields of
+    /// `t`. When `mt.__index` is a plain object type, the result is `t` extended
+    /// with its members. Returns `None` when the metatable has no usable
+    /// `__index`.
+    fn infer_setmetatable(
+        &mut self,
+        args: &[Argument<'arena>],
+        span: Span,
+    ) -> Result<Option<Type<'arena>>, TypeCheckError> {
+        let [table_arg, metatable_arg, ..] = args else {
+            return Ok(None);
+        };
+        let table_type = self.infer_expression(&table_arg.value)?;
+        let metatable_type = self.infer_expression(&metatable_arg.value)?;
+        let Ok(index_type) = self.infer_member(&metatable_type, "__index", span) else {
+            return Ok(None);
+        };
+
+        let table_members: &[ObjectTypeMember<'arena>] = match &table_type.kind {
+            TypeKind::Object(obj) => obj.members,
+            _ => &[],
+        };
+
+        match &index_type.kind {
+            TypeKind::Reference(type_ref) => {
+                if self.type_env.open_table(type_ref).is_none() {
+                    return Ok(None);
+                }
+                // The instance's own fields come first, so they shadow the table's
+                let parts = self
+                    .arena
+                    .alloc_slice_fill_iter([table_type.clone(), index_type.clone()]);
+                Ok(Some(Type::new(TypeKind::Intersection(parts), span)))
+            }
+            TypeKind::Object(index_obj) => {
+                let mut members = table_members.to_vec();
+                for member in index_obj.members.iter() {
+                    let name = match member {
+                        ObjectTypeMember::Property(prop) => prop.name.node,
+                        ObjectTypeMember::Method(method) => method.name.node,
+                        _ => continue,
+                    };
+                    let shadowed = table_members.iter().any(|m| match m {
+                        ObjectTypeMember::Property(prop) => prop.name.node == name,
+                        ObjectTypeMember::Method(method) => method.name.node == name,
+                        _ => false,
+                    });
+                    if !shadowed {
+                        members.push(member.clone());
+                    }
+                }
+                let members = self.arena.alloc_slice_fill_iter(members);
+                Ok(Some(Type::new(
+                    TypeKind::Object(ObjectType { members, span }),
+                    span,
+                )))
+            }
+            _ => Ok(None),
+        }
+    }
+
+    /// Type a method call, resolving an overloaded method by its arguments
+    fn infer_method_call(
+        &mut self,
+        obj_type: &Type<'arena>,
+        method_name: &str,
+        args: &[Argument<'arena>],
+        span: Span,
+    ) -> Result<Type<'arena>, TypeCheckError> {
+        let Some(overloads) = self.method_overloads(obj_type, method_name) else {
+            return self.infer_method(obj_type, method_name, args, span);
+        };
+        let return_type = self.infer_call(&overloads, args, span)?;
+        let self_name = self.interner.intern(SELF_TYPE_NAME);
+        Ok(substitute_self_type(
+            self.arena,
+            &return_type,
+            self_name,
+            obj_type,
+        ))
+    }
+
+    /// The type of a method member read as a value: its function type, or the
+    /// intersection of its signatures when it is overloaded. Generic methods
+    /// are not instantiated at calls, so they stay `unknown`.
+    fn method_member_type(
+        &self,
+        obj: &ObjectType<'arena>,
+        name: StringId,
+        span: Span,
+    ) -> Type<'arena> {
+        let methods: Vec<&MethodSignature<'arena>> = obj
+            .members
+            .iter()
+            .filter_map(|member| match member {
+                ObjectTypeMember::Method(method) if method.name.node == name => Some(method),
+                _ => None,
+            })
+            .collect();
+        if methods
+            .iter()
+            .any(|method| method.type_parameters.is_some())
+        {
+            return Type::new(TypeKind::Primitive(PrimitiveType::Unknown), span);
+        }
+
+        let mut signatures: Vec<Type<'arena>> = methods
+            .into_iter()
+            .map(|method| {
+                self.signature_type(method.parameters, method.return_type.clone(), method.span)
+            })
+            .collect();
+        if signatures.len() == 1 {
+            signatures.pop().unwrap()
+        } else {
+            Type::new(
+                TypeKind::Intersection(self.arena.alloc_slice_fill_iter(signatures)),
+                span,
+            )
+        }
+    }
+
+    /// A function type for a method signature
+    fn signature_type(
+        &self,
+        parameters: &'arena [Parameter<'arena>],
+        return_type: Type<'arena>,
+        span: Span,
+    ) -> Type<'arena> {
+        Type::new(
+            TypeKind::Function(FunctionType {
+                type_parameters: None,
+                parameters,
+                return_type: self.arena.alloc(return_type),
+                throws: None,
+                span,
+            }),
+            span,
+        )
+    }
+
+    /// The signatures of an overloaded method of `obj_type`, as an
+    /// intersection to call. `None` unless the method has several
+    /// non-generic signatures.
+    fn method_overloads(&self, obj_type: &Type<'arena>, method_name: &str) -> Option<Type<'arena>> {
+        let span = obj_type.span;
+        let overloads = match &obj_type.kind {
+            TypeKind::Object(obj) => {
+                self.method_member_type(obj, self.interner.intern(method_name), span)
+            }
+            // Type arguments are only applied by `lookup_method_return_type`
+            TypeKind::Reference(type_ref) if type_ref.type_arguments.is_none() => {
+                let mut class_name = self.interner.resolve(type_ref.name.node);
+                let signatures = loop {
+                    let signatures: Vec<Type<'arena>> = self
+                        .access_control
+                        .get_class_members(&class_name)
+                        .into_iter()
+                        .flatten()
+                        .filter(|member| member.name == method_name)
+                        .filter_map(|member| match &member.kind {
+                            ClassMemberKind::Method {
+                                parameters,
+                                return_type,
+                                ..
+                            } => Some(self.signature_type(
+                                self.arena.alloc_slice_clone(parameters),
+                                return_type.clone().unwrap_or_else(|| {
+                                    Type::new(TypeKind::Primitive(PrimitiveType::Unknown), span)
+                                }),
+                                span,
+                            )),
+                            _ => None,
+                        })
+                        .collect();
+                    if !signatures.is_empty() {
+                        break signatures;
+                    }
+                    match self.access_control.get_parent_class(&class_name) {
+                        Some(parent) => class_name = parent,
+                        None => return None,
+                    }
+                };
+                Type::new(
+                    TypeKind::Intersection(self.arena.alloc_slice_fill_iter(signatures)),
+                    span,
+                )
+            }
+            _ => return None,
+        };
+        match &overloads.kind {
+            TypeKind::Intersection(signatures) if signatures.len() > 1 => Some(overloads),
+            _ => None,
+        }
+    }
+
+    /// Look up the declared return type of a method on an object or class type.
+    /// Class methods are searched up the inheritance chain so inherited methods
+    /// resolve on derived receivers. `Self` is left unsubstituted for the caller.
+    fn lookup_method_return_type(
+        &self,
+        obj_type: &Type<'arena>,
+        method_name: &str,
+        span: Span,
+    ) -> Result<Type<'arena>, TypeCheckError> {
+        match &obj_type.kind {
+            TypeKind::Object(obj) => {
+                for member in obj.members.iter() {
+                    match member {
+                        ObjectTypeMember::Method(method)
+                            if self.interner.resolve(method.name.node) == method_name =>
+                        {
+                            // Return the return type of the method
+                            return Ok(method.return_type.clone());
+                        }
+                        // Lua-style methods are function-valued fields
+                        ObjectTypeMember::Property(prop)
+                            if self.interner.resolve(prop.name.node) == method_name =>
+                        {
+                            if let TypeKind::Function(func) = &prop.type_annotation.kind {
+                                return Ok((*func.return_type).clone());
+                            }
+                        }
+                        _ => {}
+                    }
+                }
+                // Method not found - return unknown
+                Ok(Type::new(TypeKind::Primitive(PrimitiveType::Unknown), span))
+            }
+            TypeKind::Reference(type_ref) => {
+                let mut class_name = self.interner.resolve(type_ref.name.node);
+
+                // Open tables carry their methods in their current shape rather
+                // than in a class declaration
+                if let Some(resolved) = self.type_env.open_table(type_ref) {
+                    return self.lookup_method_return_type(resolved, method_name, span);
+                }
+
+                // Type arguments only apply to the receiver's own class; inherited
+                // members are returned as declared on the ancestor
+                let mut type_args = type_ref.type_arguments;
+
+                loop {
+                    let class_params = self.class_type_params.get(&class_name);
+                    if let Some(class_members) = self.access_control.get_class_members(&class_name)
+                    {
+                        if let Some(found) = self.find_class_method_return_type(
+                            class_members,
+                            method_name,
+                            type_args,
+                            class_params,
+                            span,
+                        )? {
+                            return Ok(found);
+                        }
+                    }
+
+                    match self.access_control.get_parent_class(&class_name) {
+                        Some(parent) => {
+                            class_name = parent;
+                            type_args = None;
+                        }
+                        None => break,
+                    }
+                }
+                Ok(Type::new(TypeKind::Primitive(PrimitiveType::Unknown), span))
+            }
+            // The method comes from the first part that has it
+            TypeKind::Intersection(types) => {
+                for typ in types.iter() {
+                    let return_type = self.lookup_method_return_type(typ, method_name, span)?;
+                    if !matches!(
+                        return_type.kind,
+                        TypeKind::Primitive(PrimitiveType::Unknown)
+                    ) {
+                        return Ok(return_type);
+                    }
+                }
+                Ok(Type::new(TypeKind::Primitive(PrimitiveType::Unknown), span))
+            }
+            _ => Ok(Type::new(TypeKind::Primitive(PrimitiveType::Unknown), span)),
+        }
+    }
+
+    /// Find a method (or getter/setter accessed through `get_X`/`set_X`) among the
+    /// members of a single class, instantiating generic class type parameters.
+    fn find_class_method_return_type(
+        &self,
+        class_members: &[ClassMemberInfo<'arena>],
+        method_name: &str,
+        type_args: Option<&'arena [Type<'arena>]>,
+        class_params: Option<&Vec<luanext_parser::ast::statement::TypeParameter<'arena>>>,
+        span: Span,
+    ) -> Result<Option<Type<'arena>>, TypeCheckError> {
+        let instantiate = |typ: &Type<'arena>| -> Result<Type<'arena>, TypeCheckError> {
+            if let (Some(args), Some(params)) = (type_args, class_params) {
+                instantiate_type(self.arena, typ, params, args)
+                    .map_err(|e| TypeCheckError::new(e, span))
+            } else {
+                Ok(typ.clone())
+            }
+        };
+
+        // Try exact name match first
+        for member in class_members {
+            if member.name == method_name {
+                match &member.kind {
+                    ClassMemberKind::Method { return_type, .. } => {
+                        let rt = return_type.clone().unwrap_or_else(|| {
+                            Type::new(TypeKind::Primitive(PrimitiveType::Unknown), span)
+                        });
+                        return instantiate(&rt).map(Some);
+                    }
+                    ClassMemberKind::Getter { return_type } => {
+                        return instantiate(return_type).map(Some);
+                    }
+                    ClassMemberKind::Setter { .. } => {
+                        return Ok(Some(Type::new(
+                            TypeKind::Primitive(PrimitiveType::Void),
+                            span,
+                        )));
+                    }
+                    _ => {}
+                }
+            }
+        }
+        // Codegen maps get_X() → getter X, set_X() → setter X
+        if let Some(stripped) = method_name.strip_prefix("get_") {
+            for member in class_members {
+                if member.name == stripped {
+                    if let ClassMemberKind::Getter { return_type } = &member.kind {
+                        return instantiate(return_type).map(Some);
+                    }
+                }
+            }
+        }
+        if let Some(stripped) = method_name.strip_prefix("set_") {
+            for member in class_members {
+                if member.name == stripped {
+                    if let ClassMemberKind::Setter { .. } = &member.kind {
+                        return Ok(Some(Type::new(
+                            TypeKind::Primitive(PrimitiveType::Void),
+                            span,
+                        )));
+                    }
+                }
+            }
+        }
+        Ok(None)
+    }
+
     /// Check if a type contains unresolved type parameters (References that aren't
     /// known types, interfaces, or classes). Used to guard the access_control fallback
     /// in infer_member so we don't return raw type annotations with unsubstituted
@@ -1739,6 +3450,7 @@ impl<'a, 'arena> TypeInferrer<'a, 'arena> {
             TypeKind::Reference(type_ref) => {
                 let name = self.interner.resolve(type_ref.name.node);
                 self.type_env.lookup_type(&name).is_none()
+                    && self.type_env.open_table(type_ref).is_none()
                     && self.access_control.get_class_members(&name).is_none()
             }
             TypeKind::Union(types) | TypeKind::Intersection(types) | TypeKind::Tuple(types) => {
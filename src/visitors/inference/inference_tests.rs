@@ -31,6 +31,17 @@ mod tests {
             interner,
             diagnostic_handler,
             class_type_params,
+            // Numeric results are plain `number`s unless a test opts into 5.3+
+            target: crate::cli::config::LuaVersion::Lua51,
+            c_declarations: Box::leak(Box::new(crate::types::ffi::CDeclarations::new())),
+            coroutine: None,
+            reported_deprecations: Box::leak(Box::new(Default::default())),
+            module_registry: None,
+            module_resolver: None,
+            current_module_id: None,
+            lazy_callback: None,
+            required_modules: Box::leak(Box::new(Default::default())),
+            reported_plain_lua_requires: Box::leak(Box::new(Default::default())),
         }));
         TypeInferrer::new(arena, symbol_table, type_env, narrowing_context, ctx)
     }
@@ -2438,4 +2449,80 @@ mod tests {
             TypeKind::Primitive(PrimitiveType::Number)
         ));
     }
+
+    #[test]
+    fn test_infer_integer_arithmetic_on_lua54() {
+        let arena = Bump::new();
+        let interner = StringInterner::new();
+        let mut symbol_table = SymbolTable::new();
+        let mut type_env = TypeEnvironment::new();
+        let mut narrowing_context = NarrowingContext::new();
+        let access_control = AccessControl::new();
+
+        let diagnostic_handler: Arc<dyn DiagnosticHandler> =
+            Arc::new(CollectingDiagnosticHandler::new());
+
+        let mut inferrer = create_test_inferrer(
+            &arena,
+            &mut symbol_table,
+            &mut type_env,
+            &mut narrowing_context,
+            &access_control,
+            &interner,
+            &diagnostic_handler,
+        );
+        inferrer.target = crate::cli::config::LuaVersion::Lua54;
+
+        let literal = |lit: Literal| {
+            &*arena.alloc(Expression {
+                kind: ExpressionKind::Literal(lit),
+                span: Span::default(),
+                annotated_type: None,
+                receiver_class: None,
+            })
+        };
+        let mut infer_op = |op: BinaryOp, left: Literal, right: Literal| {
+            let expr = Expression {
+                kind: ExpressionKind::Binary(op, literal(left), literal(right)),
+                span: Span::default(),
+                annotated_type: None,
+                receiver_class: None,
+            };
+            inferrer.infer_expression(&expr).unwrap().kind
+        };
+
+        assert!(matches!(
+            infer_op(
+                BinaryOp::IntegerDivide,
+                Literal::Integer(7),
+                Literal::Integer(2)
+            ),
+            TypeKind::Primitive(PrimitiveType::Integer)
+        ));
+        assert!(matches!(
+            infer_op(BinaryOp::Add, Literal::Integer(1), Literal::Integer(2)),
+            TypeKind::Primitive(PrimitiveType::Integer)
+        ));
+        assert!(matches!(
+            infer_op(
+                BinaryOp::BitwiseAnd,
+                Literal::Number(1.5),
+                Literal::Integer(2)
+            ),
+            TypeKind::Primitive(PrimitiveType::Integer)
+        ));
+        assert!(matches!(
+            infer_op(BinaryOp::Divide, Literal::Integer(4), Literal::Integer(2)),
+            TypeKind::Primitive(PrimitiveType::Number)
+        ));
+        assert!(matches!(
+            infer_op(BinaryOp::Add, Literal::Number(1.5), Literal::Integer(2)),
+            TypeKind::Primitive(PrimitiveType::Number)
+        ));
+        // `3.0` is a float even though it has no fractional part
+        assert!(matches!(
+            infer_op(BinaryOp::Add, Literal::Number(3.0), Literal::Integer(2)),
+            TypeKind::Primitive(PrimitiveType::Number)
+        ));
+    }
 }
@@ -1,5 +1,6 @@
 use luanext_parser::ast::expression::{BinaryOp, Expression, ExpressionKind, Literal, UnaryOp};
 use luanext_parser::ast::types::{PrimitiveType, Type, TypeKind};
+use luanext_parser::span::Span;
 use luanext_parser::string_interner::StringId;
 use rustc_hash::FxHashMap;
 
@@ -29,11 +30,18 @@ pub trait NarrowingVisitor<'arena> {
     fn get_context_mut(&mut self) -> &mut NarrowingContext<'arena>;
 }
 
+/// Types a variable takes depending on whether a discriminant variable is truthy
+pub type CorrelatedType<'arena> = (StringId, Type<'arena>, Type<'arena>);
+
 /// Type narrowing context - tracks refined types for variables in the current scope
 #[derive(Debug, Clone)]
 pub struct NarrowingContext<'arena> {
     /// Map from variable name to narrowed type
     narrowed_types: FxHashMap<StringId, Type<'arena>>,
+    /// Variables narrowed by testing another one, e.g. `value` in
+    /// `local [ok, value] = pcall(f)` narrowed by `if ok then`, with the span
+    /// of the declaration that binds them
+    correlated_types: FxHashMap<StringId, (Span, Vec<CorrelatedType<'arena>>)>,
 }
 
 impl<'arena> Default for NarrowingContext<'arena> {
@@ -46,6 +54,7 @@ impl<'arena> NarrowingContext<'arena> {
     pub fn new() -> Self {
         Self {
             narrowed_types: FxHashMap::default(),
+            correlated_types: FxHashMap::default(),
         }
     }
 
@@ -64,6 +73,60 @@ impl<'arena> NarrowingContext<'arena> {
         self.narrowed_types.remove(&name);
     }
 
+    /// Record the types other variables take when `discriminant` is truthy or
+    /// falsy, for the variables bound by the declaration at `declaration`
+    pub fn set_correlated_types(
+        &mut self,
+        discriminant: StringId,
+        declaration: Span,
+        correlated: Vec<CorrelatedType<'arena>>,
+    ) {
+        self.correlated_types
+            .insert(discriminant, (declaration, correlated));
+    }
+
+    /// The variables narrowed by testing `discriminant`
+    pub fn correlated_types(&self, discriminant: StringId) -> &[CorrelatedType<'arena>] {
+        self.correlated_types
+            .get(&discriminant)
+            .map_or(&[], |(_, correlated)| correlated.as_slice())
+    }
+
+    /// Forget what is known about a variable that was assigned: its narrowed
+    /// type and every correlation it takes part in
+    pub fn invalidate(&mut self, name: StringId) {
+        self.narrowed_types.remove(&name);
+        self.correlated_types.remove(&name);
+        for (_, correlated) in self.correlated_types.values_mut() {
+            correlated.retain(|(dependent, _, _)| *dependent != name);
+        }
+    }
+
+    /// Keep only the correlations whose variables still refer to the
+    /// declaration that bound them, as decided by `is_declared(name, span)`
+    pub fn retain_correlations(&mut self, is_declared: impl Fn(StringId, Span) -> bool) {
+        self.correlated_types
+            .retain(|discriminant, (declaration, _)| is_declared(*discriminant, *declaration));
+        for (declaration, correlated) in self.correlated_types.values_mut() {
+            correlated.retain(|(dependent, _, _)| is_declared(*dependent, *declaration));
+        }
+    }
+
+    /// Drop the narrowed types and correlations that a branch ending in
+    /// `branch_end` invalidated by assigning their variables
+    pub fn drop_invalidated(&mut self, branch_end: &Self) {
+        self.narrowed_types
+            .retain(|name, _| branch_end.narrowed_types.contains_key(name));
+        self.correlated_types
+            .retain(|discriminant, _| branch_end.correlated_types.contains_key(discriminant));
+        for (discriminant, (_, correlated)) in self.correlated_types.iter_mut() {
+            let remaining = branch_end.correlated_types(*discriminant);
+            correlated.retain(|(dependent, _, _)| {
+                remaining.iter().any(|(other, _, _)| other == dependent)
+            });
+        }
+    }
+
     /// Merge two narrowing contexts (for branch join points)
     pub fn merge(then_ctx: &Self, else_ctx: &Self) -> Self {
         // For now, we only keep types that are the same in both branches
@@ -77,6 +140,11 @@ impl<'arena> NarrowingContext<'arena> {
                 }
             }
         }
+        for (name, correlated) in &then_ctx.correlated_types {
+            if else_ctx.correlated_types.contains_key(name) {
+                merged.correlated_types.insert(*name, correlated.clone());
+            }
+        }
 
         merged
     }
@@ -154,6 +222,16 @@ pub fn narrow_type_from_condition<'arena>(
                         }
                     }
                 }
+            } else if let Some((var_name, subtype)) = extract_math_type_check(interner, left, right)
+            {
+                narrow_to_number_subtype(
+                    arena,
+                    var_name,
+                    &subtype,
+                    original_types,
+                    &mut then_ctx,
+                    &mut else_ctx,
+                );
             } else {
                 // Check for x == nil equality narrowing
                 if let Some((var_name, is_nil)) = extract_nil_check(interner, left, right) {
@@ -188,6 +266,17 @@ pub fn narrow_type_from_condition<'arena>(
                         }
                     }
                 }
+            } else if let Some((var_name, subtype)) = extract_math_type_check(interner, left, right)
+            {
+                // Flip the narrowing for != operator
+                narrow_to_number_subtype(
+                    arena,
+                    var_name,
+                    &subtype,
+                    original_types,
+                    &mut else_ctx,
+                    &mut then_ctx,
+                );
             } else {
                 // x != nil
                 if let Some((var_name, is_nil)) = extract_nil_check(interner, left, right) {
@@ -244,6 +333,29 @@ pub fn narrow_type_from_condition<'arena>(
             return (merged_then, right_else);
         }
 
+        // Reflect.isInstance(x, ClassName) narrows like `x instanceof ClassName`
+        ExpressionKind::Call(function, arguments, _)
+            if is_reflect_is_instance(interner, function) =>
+        {
+            if let [object, class] = arguments {
+                if let (
+                    ExpressionKind::Identifier(var_name),
+                    ExpressionKind::Identifier(class_name),
+                ) = (&object.value.kind, &class.value.kind)
+                {
+                    narrow_to_class(
+                        arena,
+                        *var_name,
+                        *class_name,
+                        condition.span,
+                        original_types,
+                        &mut then_ctx,
+                        &mut else_ctx,
+                    );
+                }
+            }
+        }
+
         // Type guard function call: isString(x)
         ExpressionKind::Call(function, arguments, _) => {
             if let Some((var_name, narrowed_type)) =
@@ -265,30 +377,25 @@ pub fn narrow_type_from_condition<'arena>(
         ExpressionKind::Binary(BinaryOp::Instanceof, left, right) => {
             if let ExpressionKind::Identifier(var_name) = &left.kind {
                 if let ExpressionKind::Identifier(class_name) = &right.kind {
-                    // In then branch: narrow to the class type
-                    // For now, create a reference to the class type
-                    let class_type = Type::new(
-                        TypeKind::Reference(luanext_parser::ast::types::TypeReference {
-                            name: luanext_parser::ast::Ident::new(*class_name, condition.span),
-                            type_arguments: None,
-                            span: condition.span,
-                        }),
+                    narrow_to_class(
+                        arena,
+                        *var_name,
+                        *class_name,
                         condition.span,
+                        original_types,
+                        &mut then_ctx,
+                        &mut else_ctx,
                     );
-                    then_ctx.set_narrowed_type(*var_name, class_type.clone());
-
-                    // In else branch: exclude the class type
-                    if let Some(original) = original_types.get(var_name) {
-                        if let Some(else_type) = exclude_type(arena, original, &class_type) {
-                            else_ctx.set_narrowed_type(*var_name, else_type);
-                        }
-                    }
                 }
             }
         }
 
         // Truthiness check: if x then ...
         ExpressionKind::Identifier(name) => {
+            for (dependent, when_truthy, when_falsy) in base_ctx.correlated_types(*name) {
+                then_ctx.set_narrowed_type(*dependent, when_truthy.clone());
+                else_ctx.set_narrowed_type(*dependent, when_falsy.clone());
+            }
             if let Some(original) = original_types.get(name) {
                 // In then branch: x is truthy (non-nil, non-false)
                 if let Some(truthy_type) = make_truthy_type(arena, original) {
@@ -310,6 +417,85 @@ pub fn narrow_type_from_condition<'arena>(
     (then_ctx, else_ctx)
 }
 
+/// Narrow `var_name` to the class `class_name` in the then branch and exclude
+/// the class in the else branch
+fn narrow_to_class<'arena>(
+    arena: &'arena bumpalo::Bump,
+    var_name: StringId,
+    class_name: StringId,
+    span: Span,
+    original_types: &FxHashMap<StringId, Type<'arena>>,
+    then_ctx: &mut NarrowingContext<'arena>,
+    else_ctx: &mut NarrowingContext<'arena>,
+) {
+    // In then branch: narrow to the class type
+    // For now, create a reference to the class type
+    let class_type = Type::new(
+        TypeKind::Reference(luanext_parser::ast::types::TypeReference {
+            name: luanext_parser::ast::Ident::new(class_name, span),
+            type_arguments: None,
+            span,
+        }),
+        span,
+    );
+    then_ctx.set_narrowed_type(var_name, class_type.clone());
+
+    // In else branch: exclude the class type
+    if let Some(original) = original_types.get(&var_name) {
+        if let Some(else_type) = exclude_type(arena, original, &class_type) {
+            else_ctx.set_narrowed_type(var_name, else_type);
+        }
+    }
+}
+
+/// Narrow `var_name` after `math.type(x) == subtype`
+///
+/// `"integer"` narrows to `integer` and removes `integer` in the else branch.
+/// `"float"` narrows to `number`; the else branch is left alone because
+/// there is no separate float type to remove.
+fn narrow_to_number_subtype<'arena>(
+    arena: &'arena bumpalo::Bump,
+    var_name: StringId,
+    subtype: &str,
+    original_types: &FxHashMap<StringId, Type<'arena>>,
+    then_ctx: &mut NarrowingContext<'arena>,
+    else_ctx: &mut NarrowingContext<'arena>,
+) {
+    let span = Span::new(0, 0, 0, 0);
+    match subtype {
+        "integer" => {
+            let integer = Type::new(TypeKind::Primitive(PrimitiveType::Integer), span);
+            then_ctx.set_narrowed_type(var_name, integer.clone());
+
+            if let Some(original) = original_types.get(&var_name) {
+                if let Some(else_type) = exclude_type(arena, original, &integer) {
+                    else_ctx.set_narrowed_type(var_name, else_type);
+                }
+            }
+        }
+        "float" => {
+            then_ctx.set_narrowed_type(
+                var_name,
+                Type::new(TypeKind::Primitive(PrimitiveType::Number), span),
+            );
+        }
+        _ => {}
+    }
+}
+
+/// Whether `function` is `Reflect.isInstance`
+fn is_reflect_is_instance(
+    interner: &luanext_parser::string_interner::StringInterner,
+    function: &Expression<'_>,
+) -> bool {
+    let ExpressionKind::Member(object, member) = &function.kind else {
+        return false;
+    };
+    matches!(&object.kind, ExpressionKind::Identifier(name)
+        if interner.resolve(*name) == crate::types::reflection::REFLECT_NAMESPACE)
+        && interner.resolve(member.node) == "isInstance"
+}
+
 /// Extract typeof check: typeof x == "string" -> Some((x, "string"))
 fn extract_typeof_check<'arena>(
     interner: &luanext_parser::string_interner::StringInterner,
@@ -345,6 +531,35 @@ fn extract_typeof_check<'arena>(
     None
 }
 
+/// Extract math.type check: math.type(x) == "integer" -> Some((x, "integer"))
+fn extract_math_type_check<'arena>(
+    interner: &luanext_parser::string_interner::StringInterner,
+    left: &Expression<'arena>,
+    right: &Expression<'arena>,
+) -> Option<(StringId, String)> {
+    let (call, subtype) = match (&left.kind, &right.kind) {
+        (_, ExpressionKind::Literal(Literal::String(subtype))) => (left, subtype),
+        (ExpressionKind::Literal(Literal::String(subtype)), _) => (right, subtype),
+        _ => return None,
+    };
+    let ExpressionKind::Call(function, arguments, _) = &call.kind else {
+        return None;
+    };
+    let ExpressionKind::Member(object, member) = &function.kind else {
+        return None;
+    };
+    let is_math_type = matches!(&object.kind, ExpressionKind::Identifier(name)
+        if interner.resolve(*name) == "math")
+        && interner.resolve(member.node) == "type";
+    match arguments {
+        [argument] if is_math_type => match &argument.value.kind {
+            ExpressionKind::Identifier(var_name) => Some((*var_name, subtype.clone())),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
 /// Extract type guard function call: isString(x) -> Some((x, string))
 /// Type guards are functions with return type `param is Type`
 fn extract_type_guard_call<'arena>(
@@ -1008,6 +1223,62 @@ mod tests {
         assert!(is_nil);
     }
 
+    #[test]
+    fn test_math_type_narrowing() {
+        let arena = bumpalo::Bump::new();
+        let interner = luanext_parser::string_interner::StringInterner::new();
+        let x_id = interner.intern("x");
+        let expr = |kind| Expression {
+            kind,
+            span: make_span(),
+            annotated_type: None,
+            receiver_class: None,
+        };
+
+        // math.type(x) == "integer"
+        let math = arena.alloc(expr(ExpressionKind::Identifier(interner.intern("math"))));
+        let function = arena.alloc(expr(ExpressionKind::Member(
+            math,
+            luanext_parser::ast::Ident::new(interner.intern("type"), make_span()),
+        )));
+        let arguments = arena.alloc_slice_clone(&[luanext_parser::ast::expression::Argument {
+            value: expr(ExpressionKind::Identifier(x_id)),
+            is_spread: false,
+            span: make_span(),
+        }]);
+        let call = arena.alloc(expr(ExpressionKind::Call(function, arguments, None)));
+        let integer = arena.alloc(expr(ExpressionKind::Literal(Literal::String(
+            "integer".to_string(),
+        ))));
+        let condition = expr(ExpressionKind::Binary(BinaryOp::Equal, call, integer));
+
+        let original = Type::new(
+            TypeKind::Union(arena.alloc_slice_clone(&[
+                Type::new(TypeKind::Primitive(PrimitiveType::Integer), make_span()),
+                Type::new(TypeKind::Primitive(PrimitiveType::String), make_span()),
+            ])),
+            make_span(),
+        );
+        let mut original_types = FxHashMap::default();
+        original_types.insert(x_id, original);
+
+        let (then_ctx, else_ctx) = narrow_type_from_condition(
+            &arena,
+            &condition,
+            &NarrowingContext::new(),
+            &original_types,
+            &interner,
+        );
+        assert!(matches!(
+            then_ctx.get_narrowed_type(x_id).map(|t| &t.kind),
+            Some(TypeKind::Primitive(PrimitiveType::Integer))
+        ));
+        assert!(matches!(
+            else_ctx.get_narrowed_type(x_id).map(|t| &t.kind),
+            Some(TypeKind::Primitive(PrimitiveType::String))
+        ));
+    }
+
     #[test]
     fn test_extract_nil_check_reversed() {
         let interner = luanext_parser::string_interner::StringInterner::new();
@@ -1053,4 +1324,73 @@ mod tests {
         let result = extract_nil_check(&interner, &left, &right);
         assert!(result.is_none());
     }
+
+    #[test]
+    fn test_correlated_types_follow_discriminant() {
+        let arena = bumpalo::Bump::new();
+        let interner = luanext_parser::string_interner::StringInterner::new();
+        let ok_id = interner.intern("ok");
+        let value_id = interner.intern("value");
+        let number_type = Type::new(TypeKind::Primitive(PrimitiveType::Number), make_span());
+        let string_type = Type::new(TypeKind::Primitive(PrimitiveType::String), make_span());
+
+        let mut base_ctx = NarrowingContext::new();
+        base_ctx.set_correlated_types(
+            ok_id,
+            make_span(),
+            vec![(value_id, number_type.clone(), string_type.clone())],
+        );
+
+        // if ok then
+        let condition = Expression {
+            kind: ExpressionKind::Identifier(ok_id),
+            span: make_span(),
+            annotated_type: None,
+            receiver_class: None,
+        };
+        let (then_ctx, else_ctx) = narrow_type_from_condition(
+            &arena,
+            &condition,
+            &base_ctx,
+            &FxHashMap::default(),
+            &interner,
+        );
+
+        assert!(types_equal(
+            then_ctx.get_narrowed_type(value_id).unwrap(),
+            &number_type
+        ));
+        assert!(types_equal(
+            else_ctx.get_narrowed_type(value_id).unwrap(),
+            &string_type
+        ));
+    }
+
+    #[test]
+    fn test_assignment_invalidates_correlations() {
+        let interner = luanext_parser::string_interner::StringInterner::new();
+        let ok_id = interner.intern("ok");
+        let value_id = interner.intern("value");
+        let number_type = Type::new(TypeKind::Primitive(PrimitiveType::Number), make_span());
+        let string_type = Type::new(TypeKind::Primitive(PrimitiveType::String), make_span());
+        let correlated = vec![(value_id, number_type, string_type)];
+
+        let mut ctx = NarrowingContext::new();
+        ctx.set_correlated_types(ok_id, make_span(), correlated.clone());
+        ctx.invalidate(value_id);
+        assert!(ctx.correlated_types(ok_id).is_empty());
+
+        ctx.set_correlated_types(ok_id, make_span(), correlated.clone());
+        let branch_end = {
+            let mut branch = ctx.clone_for_branch();
+            branch.invalidate(ok_id);
+            branch
+        };
+        ctx.drop_invalidated(&branch_end);
+        assert!(ctx.correlated_types(ok_id).is_empty());
+
+        ctx.set_correlated_types(ok_id, make_span(), correlated);
+        ctx.retain_correlations(|name, _| name != ok_id);
+        assert!(ctx.correlated_types(ok_id).is_empty());
+    }
 }